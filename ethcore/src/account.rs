@@ -231,6 +231,12 @@ impl Account {
 		self.filth = Filth::Dirty;
 	}
 
+	/// Set the nonce of the account.
+	pub fn set_nonce(&mut self, nonce: U256) {
+		self.nonce = nonce;
+		self.filth = Filth::Dirty;
+	}
+
 	/// Increment the nonce of the account by one.
 	pub fn add_balance(&mut self, x: &U256) {
 		self.balance = self.balance + *x;
@@ -0,0 +1,306 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Append-only store of ancient block bodies and receipts.
+//!
+//! Bodies and receipts of old canonical blocks are never modified, so they are moved
+//! out of the database into flat files. Each kind of data is kept in a table of
+//! two files:
+//! 	[name].dat: concatenated entries
+//! 	[name].idx: end offset of every entry in the data file (8 bytes little-endian)
+//!
+//! Entries are indexed by block number, starting at genesis. Blocks missing from the
+//! database, e.g. those preceding a restored snapshot, are kept as empty entries.
+
+use std::cmp;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use util::{Bytes, Mutex};
+use header::BlockNumber;
+
+const OFFSET_SIZE: u64 = 8;
+
+fn encode_offset(offset: u64) -> [u8; 8] {
+	let mut bytes = [0u8; 8];
+	for (i, byte) in bytes.iter_mut().enumerate() {
+		*byte = (offset >> (8 * i)) as u8;
+	}
+	bytes
+}
+
+fn decode_offset(bytes: &[u8; 8]) -> u64 {
+	bytes.iter().rev().fold(0u64, |acc, byte| (acc << 8) + *byte as u64)
+}
+
+/// Copies first `len` bytes of a file.
+fn copy_prefix(from: &Path, to: &Path, len: u64) -> io::Result<()> {
+	let source = try!(File::open(from));
+	let mut target = try!(File::create(to));
+	if try!(io::copy(&mut source.take(len), &mut target)) != len {
+		return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("{} is shorter than expected", from.display())));
+	}
+	target.sync_all()
+}
+
+/// Single append-only table.
+struct Table {
+	data: File,
+	index: File,
+	len: u64,
+	data_len: u64,
+}
+
+impl Table {
+	/// Opens or creates the table, dropping entries which were not completely written.
+	fn open(dir: &Path, name: &str) -> io::Result<Table> {
+		let options = {
+			let mut options = OpenOptions::new();
+			options.read(true).write(true).create(true);
+			options
+		};
+		let data = try!(options.open(dir.join(format!("{}.dat", name))));
+		let index = try!(options.open(dir.join(format!("{}.idx", name))));
+
+		let mut table = Table {
+			len: try!(index.metadata()).len() / OFFSET_SIZE,
+			data_len: try!(data.metadata()).len(),
+			data: data,
+			index: index,
+		};
+
+		// index entries pointing past the end of data refer to unfinished writes.
+		let mut len = table.len;
+		while len > 0 && try!(table.offset(len)) > table.data_len {
+			len -= 1;
+		}
+		try!(table.truncate(len));
+		Ok(table)
+	}
+
+	/// End offset of the entry `len - 1`.
+	fn offset(&mut self, len: u64) -> io::Result<u64> {
+		if len == 0 {
+			return Ok(0);
+		}
+		let mut bytes = [0u8; 8];
+		try!(self.index.seek(SeekFrom::Start((len - 1) * OFFSET_SIZE)));
+		try!(self.index.read_exact(&mut bytes));
+		Ok(decode_offset(&bytes))
+	}
+
+	fn get(&mut self, number: u64) -> io::Result<Option<Bytes>> {
+		if number >= self.len {
+			return Ok(None);
+		}
+		let start = try!(self.offset(number));
+		let end = try!(self.offset(number + 1));
+		let mut bytes = vec![0u8; (end - start) as usize];
+		try!(self.data.seek(SeekFrom::Start(start)));
+		try!(self.data.read_exact(&mut bytes));
+		Ok(Some(bytes))
+	}
+
+	fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+		let end = self.data_len + bytes.len() as u64;
+		try!(self.data.seek(SeekFrom::Start(self.data_len)));
+		try!(self.data.write_all(bytes));
+		try!(self.index.seek(SeekFrom::Start(self.len * OFFSET_SIZE)));
+		try!(self.index.write_all(&encode_offset(end)));
+		self.data_len = end;
+		self.len += 1;
+		Ok(())
+	}
+
+	fn truncate(&mut self, len: u64) -> io::Result<()> {
+		let data_len = try!(self.offset(len));
+		try!(self.index.set_len(len * OFFSET_SIZE));
+		try!(self.data.set_len(data_len));
+		self.len = len;
+		self.data_len = data_len;
+		Ok(())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		try!(self.data.sync_all());
+		self.index.sync_all()
+	}
+}
+
+/// Append-only store of bodies and receipts of ancient canonical blocks.
+pub struct AncientStore {
+	path: PathBuf,
+	// All locks must be captured in the order declared here.
+	bodies: Mutex<Table>,
+	receipts: Mutex<Table>,
+}
+
+impl AncientStore {
+	/// Opens the store at given directory, creating it if needed.
+	pub fn open(path: &Path) -> io::Result<AncientStore> {
+		try!(fs::create_dir_all(path));
+		let mut bodies = try!(Table::open(path, "bodies"));
+		let mut receipts = try!(Table::open(path, "receipts"));
+
+		// blocks are appended to both tables, an interrupted append may leave them uneven.
+		let len = cmp::min(bodies.len, receipts.len);
+		try!(bodies.truncate(len));
+		try!(receipts.truncate(len));
+
+		Ok(AncientStore {
+			path: path.to_path_buf(),
+			bodies: Mutex::new(bodies),
+			receipts: Mutex::new(receipts),
+		})
+	}
+
+	/// Number of blocks in the store. These are all blocks with lower numbers.
+	pub fn len(&self) -> BlockNumber {
+		self.bodies.lock().len
+	}
+
+	/// Returns compressed body of the block with given number.
+	/// Blocks which were missing from the database have no body here either.
+	pub fn body(&self, number: BlockNumber) -> Option<Bytes> {
+		self.bodies.lock().get(number)
+			.expect("Ancient store i/o error. Some issue with disk?")
+			.and_then(|bytes| if bytes.is_empty() { None } else { Some(bytes) })
+	}
+
+	/// Returns encoded receipts of the block with given number.
+	/// Blocks which had no receipts stored have none here either.
+	pub fn receipts(&self, number: BlockNumber) -> Option<Bytes> {
+		self.receipts.lock().get(number)
+			.expect("Ancient store i/o error. Some issue with disk?")
+			.and_then(|bytes| if bytes.is_empty() { None } else { Some(bytes) })
+	}
+
+	/// Appends the block with given number, which must be equal to the length of the store.
+	/// Data is not guaranteed to be on disk until `flush` is called.
+	pub fn append(&self, number: BlockNumber, body: &[u8], receipts: Option<&[u8]>) -> io::Result<()> {
+		let mut bodies = self.bodies.lock();
+		let mut receipts_table = self.receipts.lock();
+		assert_eq!(number, bodies.len, "ancient blocks are appended in order");
+		try!(receipts_table.append(receipts.unwrap_or(&[])));
+		bodies.append(body)
+	}
+
+	/// Flushes appended blocks to disk.
+	pub fn flush(&self) -> io::Result<()> {
+		try!(self.bodies.lock().flush());
+		self.receipts.lock().flush()
+	}
+
+	/// Backs up the first `len` blocks into given directory by hard-linking the files of the store.
+	/// Since the files are shared, the backup also contains blocks appended later and must not be
+	/// opened while this store is in use. Files are copied up to `len` blocks when they cannot be linked,
+	/// e.g. across file systems.
+	pub fn backup(&self, path: &Path, len: BlockNumber) -> io::Result<()> {
+		let tables = {
+			let mut bodies = self.bodies.lock();
+			let mut receipts = self.receipts.lock();
+			if len > bodies.len {
+				return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Only {} ancient blocks are stored", bodies.len)));
+			}
+			[("bodies", try!(bodies.offset(len))), ("receipts", try!(receipts.offset(len)))]
+		};
+
+		// appends never modify existing entries, so the files can be read without holding the locks.
+		try!(fs::create_dir_all(path));
+		for &(name, data_len) in &tables {
+			let files = [(format!("{}.idx", name), len * OFFSET_SIZE), (format!("{}.dat", name), data_len)];
+			for &(ref file, file_len) in &files {
+				let (from, to) = (self.path.join(file), path.join(file));
+				if fs::hard_link(&from, &to).is_err() {
+					try!(copy_prefix(&from, &to, file_len));
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::OpenOptions;
+	use std::io::Write;
+	use devtools::RandomTempPath;
+	use super::AncientStore;
+
+	#[test]
+	fn appends_and_reads_blocks() {
+		let path = RandomTempPath::create_dir();
+		let store = AncientStore::open(path.as_path()).unwrap();
+		assert_eq!(store.len(), 0);
+		assert_eq!(store.body(0), None);
+
+		store.append(0, b"genesis", None).unwrap();
+		store.append(1, b"first", Some(b"receipts")).unwrap();
+		store.flush().unwrap();
+
+		assert_eq!(store.len(), 2);
+		assert_eq!(store.body(0), Some(b"genesis".to_vec()));
+		assert_eq!(store.receipts(0), None);
+		assert_eq!(store.body(1), Some(b"first".to_vec()));
+		assert_eq!(store.receipts(1), Some(b"receipts".to_vec()));
+		assert_eq!(store.body(2), None);
+	}
+
+	#[test]
+	fn drops_partially_written_blocks_on_open() {
+		let path = RandomTempPath::create_dir();
+		{
+			let store = AncientStore::open(path.as_path()).unwrap();
+			store.append(0, b"genesis", None).unwrap();
+			store.append(1, b"first", Some(b"receipts")).unwrap();
+			store.flush().unwrap();
+		}
+
+		// receipts of the next block were written, but its body was not.
+		{
+			let mut index = OpenOptions::new().append(true).open(path.as_path().join("receipts.idx")).unwrap();
+			index.write_all(&[0xff; 8]).unwrap();
+			let mut data = OpenOptions::new().append(true).open(path.as_path().join("bodies.dat")).unwrap();
+			data.write_all(b"seco").unwrap();
+		}
+
+		let store = AncientStore::open(path.as_path()).unwrap();
+		assert_eq!(store.len(), 2);
+		assert_eq!(store.body(1), Some(b"first".to_vec()));
+		store.append(2, b"second", None).unwrap();
+		assert_eq!(store.body(2), Some(b"second".to_vec()));
+	}
+
+	#[test]
+	fn backs_up_blocks() {
+		let path = RandomTempPath::create_dir();
+		let store = AncientStore::open(path.as_path()).unwrap();
+		store.append(0, b"genesis", None).unwrap();
+		store.append(1, b"first", Some(b"receipts")).unwrap();
+		store.append(2, b"second", None).unwrap();
+
+		let backup_path = RandomTempPath::create_dir();
+		store.backup(backup_path.as_path(), 2).unwrap();
+		assert!(store.backup(backup_path.as_path(), 4).is_err());
+
+		// files are linked, so the backup shares later blocks.
+		let backup = AncientStore::open(backup_path.as_path()).unwrap();
+		assert_eq!(backup.len(), 3);
+		assert_eq!(backup.body(1), Some(b"first".to_vec()));
+		assert_eq!(backup.receipts(1), Some(b"receipts".to_vec()));
+	}
+}
@@ -28,13 +28,13 @@ use blockchain::block_info::{BlockInfo, BlockLocation, BranchBecomingCanonChainD
 use blockchain::best_block::BestBlock;
 use types::tree_route::TreeRoute;
 use blockchain::update::ExtrasUpdate;
-use blockchain::{CacheSize, ImportRoute, Config};
-use db::{Writable, Readable, CacheUpdatePolicy};
+use blockchain::{AncientStore, CacheSize, ImportRoute, Config};
+use db::{Key, Writable, Readable, CacheUpdatePolicy};
 use client::{DB_COL_EXTRA, DB_COL_HEADERS, DB_COL_BODIES};
 use cache_manager::CacheManager;
 
-const LOG_BLOOMS_LEVELS: usize = 3;
-const LOG_BLOOMS_ELEMENTS_PER_INDEX: usize = 16;
+pub const LOG_BLOOMS_LEVELS: usize = 3;
+pub const LOG_BLOOMS_ELEMENTS_PER_INDEX: usize = 16;
 
 /// Interface for querying blocks by hash and by number.
 pub trait BlockProvider {
@@ -146,6 +146,7 @@ pub struct BlockChain {
 	blooms_config: bc::Config,
 
 	best_block: RwLock<BestBlock>,
+	history_start: RwLock<BlockNumber>,
 
 	// block cache
 	block_headers: RwLock<HashMap<H256, Bytes>>,
@@ -158,7 +159,10 @@ pub struct BlockChain {
 	blocks_blooms: RwLock<HashMap<LogGroupPosition, BloomGroup>>,
 	block_receipts: RwLock<HashMap<H256, BlockReceipts>>,
 
-	db: Arc<Database>,
+	db: Arc<KeyValueDB>,
+	ancient: Option<AncientStore>,
+	ancient_depth: u64,
+	history_blocks: Option<u64>,
 
 	cache_man: RwLock<CacheManager<CacheID>>,
 
@@ -248,7 +252,7 @@ impl BlockProvider for BlockChain {
 
 		self.note_used(CacheID::BlockBody(hash.clone()));
 
-		match opt {
+		match opt.or_else(|| self.ancient_number(hash).and_then(|n| self.ancient.as_ref().and_then(|a| a.body(n)))) {
 			Some(b) => {
 				let bytes: Bytes = UntrustedRlp::new(&b).decompress(RlpType::Blocks).to_vec();
 				let mut write = self.block_bodies.write();
@@ -280,7 +284,11 @@ impl BlockProvider for BlockChain {
 	/// Get receipts of block with given hash.
 	fn block_receipts(&self, hash: &H256) -> Option<BlockReceipts> {
 		self.note_used(CacheID::BlockReceipts(hash.clone()));
-		self.db.read_with_cache(DB_COL_EXTRA, &self.block_receipts, hash)
+		self.db.read_with_cache(DB_COL_EXTRA, &self.block_receipts, hash).or_else(|| {
+			self.ancient_number(hash)
+				.and_then(|n| self.ancient.as_ref().and_then(|a| a.receipts(n)))
+				.map(|bytes| decode(&bytes))
+		})
 	}
 
 	/// Returns numbers of blocks containing given bloom.
@@ -314,7 +322,7 @@ impl<'a> Iterator for AncestryIter<'a> {
 
 impl BlockChain {
 	/// Create new instance of blockchain from given Genesis
-	pub fn new(config: Config, genesis: &[u8], db: Arc<Database>) -> BlockChain {
+	pub fn new(config: Config, genesis: &[u8], db: Arc<KeyValueDB>) -> BlockChain {
 		// 400 is the avarage size of the key
 		let cache_man = CacheManager::new(config.pref_cache_size, config.max_cache_size, 400);
 
@@ -324,6 +332,7 @@ impl BlockChain {
 				elements_per_index: LOG_BLOOMS_ELEMENTS_PER_INDEX,
 			},
 			best_block: RwLock::new(BestBlock::default()),
+			history_start: RwLock::new(0),
 			block_headers: RwLock::new(HashMap::new()),
 			block_bodies: RwLock::new(HashMap::new()),
 			block_details: RwLock::new(HashMap::new()),
@@ -332,6 +341,9 @@ impl BlockChain {
 			blocks_blooms: RwLock::new(HashMap::new()),
 			block_receipts: RwLock::new(HashMap::new()),
 			db: db.clone(),
+			ancient: None,
+			ancient_depth: config.ancient_depth,
+			history_blocks: config.history_blocks,
 			cache_man: RwLock::new(cache_man),
 			pending_best_block: RwLock::new(None),
 			pending_block_hashes: RwLock::new(HashMap::new()),
@@ -357,7 +369,7 @@ impl BlockChain {
 					children: vec![]
 				};
 
-				let batch = DBTransaction::new(&db);
+				let batch = DBTransaction::new(&*db);
 				batch.put(DB_COL_HEADERS, &hash, block.header_rlp().as_raw()).unwrap();
 				batch.put(DB_COL_BODIES, &hash, &Self::block_to_body(genesis)).unwrap();
 
@@ -369,6 +381,10 @@ impl BlockChain {
 			}
 		};
 
+		if let Some(start) = bc.db.get(DB_COL_EXTRA, b"history").unwrap() {
+			*bc.history_start.write() = decode(&start);
+		}
+
 		{
 			// Fetch best block details
 			let best_block_number = bc.block_number(&best_block_hash).unwrap();
@@ -388,16 +404,231 @@ impl BlockChain {
 		bc
 	}
 
+	/// Sets the store for bodies and receipts of ancient blocks.
+	/// It must contain only canonical blocks of this chain.
+	pub fn set_ancient_store(&mut self, store: AncientStore) {
+		self.ancient = Some(store);
+	}
+
+	/// Number of the first block which body and receipts are kept in the database.
+	/// All earlier canonical blocks have been moved to the ancient store.
+	pub fn ancient_blocks(&self) -> BlockNumber {
+		self.ancient.as_ref().map_or(0, |a| a.len())
+	}
+
+	/// Returns the ancient store, if there is one.
+	pub fn ancient_store(&self) -> Option<&AncientStore> {
+		self.ancient.as_ref()
+	}
+
+	/// Returns the number of given block if its data may be found in the ancient store.
+	fn ancient_number(&self, hash: &H256) -> Option<BlockNumber> {
+		let len = self.ancient_blocks();
+		self.block_number(hash)
+			.and_then(|n| if n < len && self.block_hash(n).as_ref() == Some(hash) { Some(n) } else { None })
+	}
+
+	/// Moves bodies and receipts of at most `max_blocks` canonical blocks older than
+	/// the ancient depth from the database to the ancient store.
+	///
+	/// Returns the number of moved blocks.
+	pub fn freeze(&self, max_blocks: u64) -> u64 {
+		let ancient = match self.ancient {
+			Some(ref ancient) => ancient,
+			None => return 0,
+		};
+
+		let best = self.best_block_number();
+		if best < self.ancient_depth {
+			return 0;
+		}
+		let start = ancient.len();
+		let end = cmp::min(best - self.ancient_depth, start + max_blocks);
+
+		let mut moved = Vec::new();
+		let mut appended = 0;
+		for number in start..end {
+			let hash = self.block_hash(number);
+			let body = match hash {
+				Some(ref hash) => self.db.get(DB_COL_BODIES, hash).expect("Low level database error. Some issue with disk?"),
+				None => None,
+			};
+			let result = match (hash, body) {
+				(Some(hash), Some(body)) => {
+					let receipts_key = Key::<BlockReceipts>::key(&hash);
+					let receipts = self.db.get(DB_COL_EXTRA, &receipts_key).expect("Low level database error. Some issue with disk?");
+					let result = ancient.append(number, &body, receipts.as_ref().map(|r| &**r));
+					moved.push((hash, receipts_key));
+					result
+				},
+				// gap in the history, e.g. before a restored snapshot, is recorded as an empty entry.
+				_ => ancient.append(number, &[], None),
+			};
+			if let Err(e) = result {
+				warn!("Failed to move block #{} to the ancient store: {}", number, e);
+				break;
+			}
+			appended += 1;
+		}
+
+		if appended == 0 {
+			return 0;
+		}
+
+		// data may be removed from the database only once it is safely on disk.
+		if let Err(e) = ancient.flush() {
+			warn!("Failed to flush the ancient store: {}", e);
+			return 0;
+		}
+
+		let batch = DBTransaction::new(&*self.db);
+		for &(ref hash, ref receipts_key) in &moved {
+			batch.delete(DB_COL_BODIES, hash).unwrap();
+			batch.delete(DB_COL_EXTRA, receipts_key).unwrap();
+		}
+		batch.put(DB_COL_EXTRA, b"ancient", &encode(&(start + appended))).unwrap();
+		self.db.write(batch).expect("Low level database error. Some issue with disk?");
+		appended
+	}
+
+	/// Number of blocks which have been moved to the ancient store.
+	/// Their bodies and receipts are not available without the store.
+	pub fn frozen_blocks(&self) -> BlockNumber {
+		self.db.get(DB_COL_EXTRA, b"ancient").expect("Low level database error. Some issue with disk?")
+			.map_or(0, |len| decode(&len))
+	}
+
+	/// Number of the first canonical block which body and receipts have not been pruned.
+	pub fn history_start(&self) -> BlockNumber {
+		*self.history_start.read()
+	}
+
+	/// Deletes bodies and receipts of at most `max_blocks` canonical blocks older than
+	/// the configured history. Headers, details and transaction addresses are kept.
+	///
+	/// Returns the number of pruned blocks.
+	pub fn prune_history(&self, max_blocks: u64) -> u64 {
+		let history = match self.history_blocks {
+			Some(history) => history,
+			None => return 0,
+		};
+
+		let best = self.best_block_number();
+		if best < history {
+			return 0;
+		}
+		let start = self.history_start();
+		let end = cmp::min(best - history, start + max_blocks);
+		if start >= end {
+			return 0;
+		}
+
+		// blocks before a restored snapshot are not in the database.
+		let hashes: Vec<H256> = (start..end).filter_map(|number| self.block_hash(number)).collect();
+		let batch = DBTransaction::new(&*self.db);
+		for hash in &hashes {
+			batch.delete(DB_COL_BODIES, hash).unwrap();
+			batch.delete(DB_COL_EXTRA, &Key::<BlockReceipts>::key(hash)).unwrap();
+		}
+		batch.put(DB_COL_EXTRA, b"history", &encode(&end)).unwrap();
+		self.db.write(batch).expect("Low level database error. Some issue with disk?");
+
+		*self.history_start.write() = end;
+		let mut bodies = self.block_bodies.write();
+		let mut receipts = self.block_receipts.write();
+		for hash in &hashes {
+			bodies.remove(hash);
+			receipts.remove(hash);
+		}
+		end - start
+	}
+
 	/// Returns true if the given parent block has given child
 	/// (though not necessarily a part of the canon chain).
 	fn is_known_child(&self, parent: &H256, hash: &H256) -> bool {
 		self.db.read_with_cache(DB_COL_EXTRA, &self.block_details, parent).map_or(false, |d| d.children.contains(hash))
 	}
 
+	/// Reverts the canonical chain to the block with given number, which becomes the best block.
+	/// All later canonical blocks and branches forking from them are removed.
+	/// Changes are written to `batch`, the chain should not be used until it is written.
+	///
+	/// Returns hashes of the removed blocks, canonical ones first.
+	pub fn revert(&self, batch: &DBTransaction, number: BlockNumber) -> Vec<H256> {
+		let best_number = self.best_block_number();
+		if number >= best_number {
+			return vec![];
+		}
+
+		let hash = self.block_hash(number).expect("blocks up to the best one are canonical; qed");
+		let canon: Vec<H256> = (number + 1..best_number + 1)
+			.map(|n| self.block_hash(n).expect("blocks up to the best one are canonical; qed"))
+			.collect();
+
+		for (n, block_hash) in (number + 1..).zip(canon.iter()) {
+			batch.delete(DB_COL_EXTRA, &n.key()).unwrap();
+			if let Some(body) = self.block_body(block_hash) {
+				for tx_hash in BodyView::new(&body).transaction_hashes() {
+					batch.delete(DB_COL_EXTRA, &Key::<TransactionAddress>::key(&tx_hash)).unwrap();
+				}
+			}
+		}
+
+		// non-canonical children of removed blocks are not reachable anymore.
+		let canon_set: HashSet<H256> = canon.iter().cloned().collect();
+		let mut removed = canon.clone();
+		let mut pending: Vec<H256> = canon.iter()
+			.filter_map(|h| self.block_details(h))
+			.flat_map(|details| details.children.into_iter())
+			.filter(|child| !canon_set.contains(child))
+			.collect();
+		while let Some(h) = pending.pop() {
+			if let Some(details) = self.block_details(&h) {
+				pending.extend(details.children);
+			}
+			removed.push(h);
+		}
+
+		for h in &removed {
+			batch.delete(DB_COL_HEADERS, h).unwrap();
+			batch.delete(DB_COL_BODIES, h).unwrap();
+			batch.delete(DB_COL_EXTRA, &Key::<BlockDetails>::key(h)).unwrap();
+			batch.delete(DB_COL_EXTRA, &Key::<BlockReceipts>::key(h)).unwrap();
+		}
+
+		let range = (number + 1) as bc::Number..best_number as bc::Number;
+		let chain = bc::group::BloomGroupChain::new(self.blooms_config, self);
+		for (k, v) in chain.replace(&range, vec![]) {
+			batch.write(DB_COL_EXTRA, &LogGroupPosition::from(k), &BloomGroup::from(v));
+		}
+
+		let mut details = self.block_details(&hash).expect("canonical block has details; qed");
+		details.children.retain(|child| child != &canon[0]);
+		batch.write(DB_COL_EXTRA, &hash, &details);
+		batch.put(DB_COL_EXTRA, b"best", &hash).unwrap();
+
+		let block = self.block(&hash).expect("canonical block is known; qed");
+		*self.best_block.write() = BestBlock {
+			number: number,
+			total_difficulty: details.total_difficulty,
+			hash: hash,
+			block: block,
+		};
+
+		self.block_headers.write().clear();
+		self.block_bodies.write().clear();
+		self.block_details.write().clear();
+		self.block_hashes.write().clear();
+		self.transaction_addresses.write().clear();
+		self.blocks_blooms.write().clear();
+		self.block_receipts.write().clear();
+
+		removed
+	}
+
 	/// Rewind to a previous block
 	#[cfg(test)]
 	fn rewind(&self) -> Option<H256> {
-		use db::Key;
 		let batch = self.db.transaction();
 		// track back to the best block we have in the blocks database
 		if let Some(best_block_hash) = self.db.get(DB_COL_EXTRA, b"best").unwrap() {
@@ -1039,11 +1270,11 @@ mod tests {
 	use std::str::FromStr;
 	use std::sync::Arc;
 	use rustc_serialize::hex::FromHex;
-	use util::{Database, DatabaseConfig};
+	use util::{Database, DatabaseConfig, DBTransaction, U256};
 	use util::hash::*;
 	use util::sha3::Hashable;
 	use receipt::Receipt;
-	use blockchain::{BlockProvider, BlockChain, Config, ImportRoute};
+	use blockchain::{AncientStore, BlockProvider, BlockChain, Config, ImportRoute};
 	use tests::helpers::*;
 	use devtools::*;
 	use blockchain::generator::{ChainGenerator, ChainIterator, BlockFinalizer};
@@ -1540,4 +1771,126 @@ mod tests {
 		assert_eq!(bc.rewind(), Some(genesis_hash.clone()));
 		assert_eq!(bc.rewind(), None);
 	}
+
+	#[test]
+	fn moves_ancient_blocks_to_ancient_store() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let temp = RandomTempPath::new();
+		let ancient_path = RandomTempPath::create_dir();
+		let config = || Config { ancient_depth: 3, ..Config::default() };
+		let mut hashes = vec![BlockView::new(&genesis).header_view().sha3()];
+
+		{
+			let db = new_db(temp.as_str());
+			let mut bc = BlockChain::new(config(), &genesis, db.clone());
+			bc.set_ancient_store(AncientStore::open(ancient_path.as_path()).unwrap());
+
+			for number in 1u64..11 {
+				let block = canon_chain.generate(&mut finalizer).unwrap();
+				hashes.push(BlockView::new(&block).header_view().sha3());
+				let receipt = Receipt::new(H256::default(), U256::from(number), vec![]);
+				insert_block(&db, &bc, &block, vec![receipt]);
+			}
+
+			assert_eq!(bc.freeze(5), 5);
+			assert_eq!(bc.freeze(100), 2);
+			assert_eq!(bc.freeze(100), 0);
+			assert_eq!(bc.ancient_blocks(), 7);
+			assert_eq!(bc.frozen_blocks(), 7);
+			assert!(db.get(client::DB_COL_BODIES, &hashes[6]).unwrap().is_none());
+			assert!(db.get(client::DB_COL_BODIES, &hashes[7]).unwrap().is_some());
+		}
+
+		let db = new_db(temp.as_str());
+		let mut bc = BlockChain::new(config(), &genesis, db.clone());
+		bc.set_ancient_store(AncientStore::open(ancient_path.as_path()).unwrap());
+		assert_eq!(bc.ancient_blocks(), 7);
+		for (number, hash) in hashes.iter().enumerate() {
+			assert!(bc.block(hash).is_some());
+			match number {
+				0 => assert!(bc.block_receipts(hash).is_none()),
+				n => assert_eq!(bc.block_receipts(hash).unwrap().receipts[0].gas_used, U256::from(n as u64)),
+			}
+		}
+	}
+
+	#[test]
+	fn records_history_gaps_in_ancient_store() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let temp = RandomTempPath::new();
+		let ancient_path = RandomTempPath::create_dir();
+		let db = new_db(temp.as_str());
+		let mut bc = BlockChain::new(Config { ancient_depth: 3, ..Config::default() }, &genesis, db.clone());
+		bc.set_ancient_store(AncientStore::open(ancient_path.as_path()).unwrap());
+
+		let mut hashes = vec![BlockView::new(&genesis).header_view().sha3()];
+		for _ in 1u64..11 {
+			let block = canon_chain.generate(&mut finalizer).unwrap();
+			hashes.push(BlockView::new(&block).header_view().sha3());
+			insert_block(&db, &bc, &block, vec![]);
+		}
+
+		// bodies of blocks before a restored snapshot are missing.
+		let batch = DBTransaction::new(&*db);
+		for hash in &hashes[..3] {
+			batch.delete(client::DB_COL_BODIES, hash).unwrap();
+		}
+		db.write(batch).unwrap();
+
+		assert_eq!(bc.freeze(100), 7);
+		assert_eq!(bc.frozen_blocks(), 7);
+		assert!(bc.block(&hashes[2]).is_none());
+		assert!(bc.block(&hashes[3]).is_some());
+		assert!(db.get(client::DB_COL_BODIES, &hashes[3]).unwrap().is_none());
+	}
+
+	#[test]
+	fn prunes_bodies_and_receipts_of_old_blocks() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let temp = RandomTempPath::new();
+		let config = || Config { history_blocks: Some(3), ..Config::default() };
+		let mut hashes = vec![BlockView::new(&genesis).header_view().sha3()];
+
+		{
+			let db = new_db(temp.as_str());
+			let bc = BlockChain::new(config(), &genesis, db.clone());
+			for number in 1u64..11 {
+				let block = canon_chain.generate(&mut finalizer).unwrap();
+				hashes.push(BlockView::new(&block).header_view().sha3());
+				let receipt = Receipt::new(H256::default(), U256::from(number), vec![]);
+				insert_block(&db, &bc, &block, vec![receipt]);
+				// populate the caches.
+				assert!(bc.block(&hashes[number as usize]).is_some());
+			}
+
+			assert_eq!(bc.prune_history(5), 5);
+			assert_eq!(bc.prune_history(100), 2);
+			assert_eq!(bc.prune_history(100), 0);
+			assert_eq!(bc.history_start(), 7);
+			assert!(bc.block_body(&hashes[6]).is_none());
+			assert!(bc.block_receipts(&hashes[6]).is_none());
+		}
+
+		let db = new_db(temp.as_str());
+		let bc = BlockChain::new(config(), &genesis, db.clone());
+		assert_eq!(bc.history_start(), 7);
+		for hash in &hashes[..7] {
+			assert!(bc.block_header(hash).is_some());
+			assert!(bc.block(hash).is_none());
+			assert!(bc.block_receipts(hash).is_none());
+		}
+		for hash in &hashes[7..] {
+			assert!(bc.block(hash).is_some());
+			assert!(bc.block_receipts(hash).is_some());
+		}
+	}
 }
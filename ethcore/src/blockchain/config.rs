@@ -16,6 +16,10 @@
 
 //! Blockchain configuration.
 
+/// Minimal number of recent blocks which bodies and receipts must be kept
+/// when history is pruned. Reorganisations must never reach pruned blocks.
+pub const MIN_HISTORY_BLOCKS: u64 = 128;
+
 /// Blockchain configuration.
 #[derive(Debug, PartialEq)]
 pub struct Config {
@@ -25,6 +29,12 @@ pub struct Config {
 	pub max_cache_size: usize,
 	/// Backing db cache_size
 	pub db_cache_size: Option<usize>,
+	/// Number of recent blocks which bodies and receipts are kept in the database
+	/// when the ancient store is used.
+	pub ancient_depth: u64,
+	/// Number of recent blocks which bodies and receipts are kept.
+	/// Only headers of older blocks are kept if set. Should not be lower than `MIN_HISTORY_BLOCKS`.
+	pub history_blocks: Option<u64>,
 }
 
 impl Default for Config {
@@ -33,6 +43,8 @@ impl Default for Config {
 			pref_cache_size: 1 << 14,
 			max_cache_size: 1 << 20,
 			db_cache_size: None,
+			ancient_depth: 90000,
+			history_blocks: None,
 		}
 	}
 }
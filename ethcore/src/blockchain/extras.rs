@@ -36,8 +36,13 @@ pub enum ExtrasIndex {
 	BlocksBlooms = 3,
 	/// Block receipts index
 	BlockReceipts = 4,
+	/// Local transactions index
+	LocalTransactions = 5,
 }
 
+/// Key of the local transactions of the miner.
+pub const LOCAL_TRANSACTIONS_KEY: [u8; 1] = [ExtrasIndex::LocalTransactions as u8];
+
 fn with_index(hash: &H256, i: ExtrasIndex) -> H264 {
 	let mut result = H264::default();
 	result[0] = i as u8;
@@ -0,0 +1,462 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline inspection and repair of the blockchain database.
+//!
+//! These functions work directly on the database of a stopped node and never panic
+//! on corrupted entries. The canonical chain is always found by following parent hashes
+//! of the headers back from the best block, so it does not depend on any of the indexes
+//! which are checked or rebuilt.
+
+use std::fmt;
+use bloomchain as bc;
+use util::*;
+use util::migration::Progress;
+use header::{Header, BlockNumber};
+use receipt::Receipt;
+use blooms::{Bloom, BloomGroup};
+use db::{Key, Writable};
+use trace::FlatBlockTraces;
+use client::{DB_COL_EXTRA, DB_COL_HEADERS, DB_COL_BODIES, DB_COL_TRACE};
+use super::extras::{BlockDetails, BlockReceipts, TransactionAddress, LogGroupPosition};
+use super::blockchain::{LOG_BLOOMS_LEVELS, LOG_BLOOMS_ELEMENTS_PER_INDEX};
+use super::AncientStore;
+
+const DB_ERROR: &'static str = "Low level database error. Some issue with disk?";
+
+/// Database entry of the blockchain.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Entry {
+	/// Hash of the best block.
+	BestBlock,
+	/// Number of the first block which body and receipts have not been pruned.
+	HistoryStart,
+	/// Header of the block with given hash.
+	Header(H256),
+	/// Body of the block with given hash.
+	Body(H256),
+	/// Details of the block with given hash.
+	Details(H256),
+	/// Hash of the canonical block with given number.
+	BlockHash(BlockNumber),
+	/// Address of the transaction with given hash.
+	TransactionAddress(H256),
+	/// Receipts of the block with given hash.
+	Receipts(H256),
+	/// Group of blooms at given level and index.
+	Blooms(usize, usize),
+	/// Whether tracing is enabled.
+	Tracing,
+	/// Range of blocks which traces are missing.
+	MissingTraces,
+	/// Traces of the block with given hash.
+	Traces(H256),
+}
+
+impl fmt::Display for Entry {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Entry::BestBlock => write!(f, "best block hash"),
+			Entry::HistoryStart => write!(f, "start of block history"),
+			Entry::Header(ref hash) => write!(f, "header of block {}", hash),
+			Entry::Body(ref hash) => write!(f, "body of block {}", hash),
+			Entry::Details(ref hash) => write!(f, "details of block {}", hash),
+			Entry::BlockHash(number) => write!(f, "hash of block #{}", number),
+			Entry::TransactionAddress(ref hash) => write!(f, "address of transaction {}", hash),
+			Entry::Receipts(ref hash) => write!(f, "receipts of block {}", hash),
+			Entry::Blooms(level, index) => write!(f, "blooms group {} at level {}", index, level),
+			Entry::Tracing => write!(f, "tracing switch"),
+			Entry::MissingTraces => write!(f, "range of missing traces"),
+			Entry::Traces(ref hash) => write!(f, "traces of block {}", hash),
+		}
+	}
+}
+
+/// Problem found in the blockchain database.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Fault {
+	/// Entry is missing.
+	Missing(Entry),
+	/// Entry cannot be decoded or does not match the canonical chain.
+	Invalid(Entry),
+	/// State root of the best block is missing from the state database.
+	MissingState(H256),
+}
+
+impl fmt::Display for Fault {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Fault::Missing(ref entry) => write!(f, "Missing {}", entry),
+			Fault::Invalid(ref entry) => write!(f, "Invalid {}", entry),
+			Fault::MissingState(ref root) => write!(f, "Missing state root {} of the best block", root),
+		}
+	}
+}
+
+/// Result of the database verification.
+#[derive(Debug)]
+pub struct Report {
+	/// Number of the best block.
+	pub best_block: BlockNumber,
+	/// Problems found.
+	pub faults: Vec<Fault>,
+}
+
+/// Number and total size of entries in a database column.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ColumnStats {
+	/// Number of keys.
+	pub keys: u64,
+	/// Total size of the keys in bytes.
+	pub key_bytes: u64,
+	/// Total size of the values in bytes.
+	pub value_bytes: u64,
+}
+
+/// Rebuilds blooms groups of consecutive canonical blocks starting from genesis.
+///
+/// All groups touched by a span of `elements_per_index ^ levels` blocks are unaffected
+/// by the following blocks, so they are taken out after every span is complete.
+struct BloomsBuilder {
+	config: bc::Config,
+	groups: HashMap<bc::group::GroupPosition, BloomGroup>,
+}
+
+impl bc::group::BloomGroupDatabase for BloomsBuilder {
+	fn blooms_at(&self, position: &bc::group::GroupPosition) -> Option<bc::group::BloomGroup> {
+		self.groups.get(position).cloned().map(Into::into)
+	}
+}
+
+impl BloomsBuilder {
+	fn new() -> Self {
+		BloomsBuilder {
+			config: bc::Config {
+				levels: LOG_BLOOMS_LEVELS,
+				elements_per_index: LOG_BLOOMS_ELEMENTS_PER_INDEX,
+			},
+			groups: HashMap::new(),
+		}
+	}
+
+	/// Adds bloom of the next block. Returns all complete groups once a span is finished.
+	fn push(&mut self, number: BlockNumber, bloom: &H2048, last: bool) -> Option<HashMap<bc::group::GroupPosition, BloomGroup>> {
+		let changes = {
+			let chain = bc::group::BloomGroupChain::new(self.config, &*self);
+			chain.insert(number as bc::Number, Bloom::from(bloom.clone()).into())
+		};
+		self.groups.extend(changes.into_iter().map(|(position, group)| (position, From::from(group))));
+
+		let span = self.config.elements_per_index.pow(self.config.levels as u32) as BlockNumber;
+		if last || number % span == span - 1 {
+			Some(mem::replace(&mut self.groups, HashMap::new()))
+		} else {
+			None
+		}
+	}
+}
+
+fn read_block(db: &Database, col: Option<u32>, hash: &H256) -> Option<Bytes> {
+	db.get(col, hash).expect(DB_ERROR).map(|bytes| UntrustedRlp::new(&bytes).decompress(RlpType::Blocks).to_vec())
+}
+
+fn read_header(db: &Database, hash: &H256) -> Result<Header, Fault> {
+	let bytes = try!(read_block(db, DB_COL_HEADERS, hash).ok_or_else(|| Fault::Missing(Entry::Header(hash.clone()))));
+	UntrustedRlp::new(&bytes).as_val().map_err(|_| Fault::Invalid(Entry::Header(hash.clone())))
+}
+
+/// Returns the data of a canonical block from the ancient store, if it has been moved there.
+fn read_ancient<F>(ancient: Option<&AncientStore>, number: BlockNumber, f: F) -> Option<Bytes>
+	where F: FnOnce(&AncientStore, BlockNumber) -> Option<Bytes> {
+	ancient.and_then(|ancient| if number < ancient.len() { f(ancient, number) } else { None })
+}
+
+/// Returns hashes of all transactions in the body of given canonical block.
+fn read_transaction_hashes(db: &Database, ancient: Option<&AncientStore>, header: &Header, hash: &H256) -> Result<Vec<H256>, Fault> {
+	let bytes = try!(read_block(db, DB_COL_BODIES, hash)
+		.or_else(|| read_ancient(ancient, header.number(), AncientStore::body).map(|b| UntrustedRlp::new(&b).decompress(RlpType::Blocks).to_vec()))
+		.ok_or_else(|| Fault::Missing(Entry::Body(hash.clone()))));
+	let transactions: Vec<Bytes> = try!(UntrustedRlp::new(&bytes).at(0)
+		.map(|txs| txs.iter().map(|tx| tx.as_raw().to_vec()).collect())
+		.map_err(|_| Fault::Invalid(Entry::Body(hash.clone()))));
+
+	let hashes = transactions.iter().map(|tx| tx.sha3()).collect();
+	if &ordered_trie_root(transactions) != header.transactions_root() {
+		return Err(Fault::Invalid(Entry::Body(hash.clone())));
+	}
+	Ok(hashes)
+}
+
+fn read_extras<T, R>(db: &Database, key: &Key<T, Target = R>, entry: Entry) -> Result<T, Fault> where T: Decodable, R: Deref<Target = [u8]> {
+	match db.get(DB_COL_EXTRA, &key.key()).expect(DB_ERROR) {
+		Some(bytes) => UntrustedRlp::new(&bytes).as_val().map_err(|_| Fault::Invalid(entry)),
+		None => Err(Fault::Missing(entry)),
+	}
+}
+
+/// Returns receipts of given canonical block.
+fn read_receipts(db: &Database, ancient: Option<&AncientStore>, number: BlockNumber, hash: &H256) -> Result<BlockReceipts, Fault> {
+	match read_ancient(ancient, number, AncientStore::receipts) {
+		Some(bytes) => UntrustedRlp::new(&bytes).as_val().map_err(|_| Fault::Invalid(Entry::Receipts(hash.clone()))),
+		None => read_extras::<BlockReceipts, _>(db, hash, Entry::Receipts(hash.clone())),
+	}
+}
+
+/// Returns the number of the first block which body and receipts have not been pruned.
+fn history_start(db: &Database) -> Result<BlockNumber, Fault> {
+	match db.get(DB_COL_EXTRA, b"history").expect(DB_ERROR) {
+		Some(bytes) => UntrustedRlp::new(&bytes).as_val().map_err(|_| Fault::Invalid(Entry::HistoryStart)),
+		None => Ok(0),
+	}
+}
+
+/// Returns whether tracing is enabled and the inclusive range of blocks which traces have not been built yet.
+fn tracing(db: &Database) -> Result<(bool, Option<(BlockNumber, BlockNumber)>), Fault> {
+	let enabled = match db.get(DB_COL_TRACE, b"enabled").expect(DB_ERROR) {
+		Some(ref value) if &value[..] == &[0x1] => true,
+		Some(ref value) if &value[..] == &[0x0] => false,
+		Some(_) => return Err(Fault::Invalid(Entry::Tracing)),
+		None => false,
+	};
+	let missing = match db.get(DB_COL_TRACE, b"missing").expect(DB_ERROR) {
+		Some(bytes) => {
+			let rlp = UntrustedRlp::new(&bytes);
+			match (rlp.val_at(0), rlp.val_at(1)) {
+				(Ok(from), Ok(to)) => Some((from, to)),
+				_ => return Err(Fault::Invalid(Entry::MissingTraces)),
+			}
+		},
+		None => None,
+	};
+	Ok((enabled, missing))
+}
+
+/// Walks the canonical chain back from the best block to genesis.
+/// Returns the hashes of canonical blocks ordered by number.
+fn canon_chain(db: &Database) -> Result<Vec<H256>, Fault> {
+	let mut hash = match db.get(DB_COL_EXTRA, b"best").expect(DB_ERROR) {
+		Some(ref best) if best.len() == 32 => H256::from_slice(best),
+		Some(_) => return Err(Fault::Invalid(Entry::BestBlock)),
+		None => return Err(Fault::Missing(Entry::BestBlock)),
+	};
+
+	let mut hashes = Vec::new();
+	let mut expected = None;
+	loop {
+		let header = try!(read_header(db, &hash));
+		if expected.map_or(false, |number| number != header.number()) {
+			return Err(Fault::Invalid(Entry::Header(hash)));
+		}
+		hashes.push(hash);
+		if header.number() == 0 {
+			break;
+		}
+		expected = Some(header.number() - 1);
+		hash = header.parent_hash().clone();
+	}
+
+	hashes.reverse();
+	Ok(hashes)
+}
+
+/// Verifies the blockchain database.
+///
+/// Walks the canonical chain and checks details, block hashes, transaction addresses,
+/// receipts, blooms and, if tracing is enabled, traces of every block, then checks that the state root
+/// of the best block is present in `state`. Verification stops early only if the chain of headers is broken.
+/// Bodies and receipts of ancient blocks are read from `ancient` if given. Transaction addresses
+/// and receipts of blocks which history has been pruned are not checked, neither are traces
+/// which have not been built yet.
+pub fn verify(db: &Database, ancient: Option<&AncientStore>, state: &HashDB) -> Report {
+	let hashes = match canon_chain(db) {
+		Ok(hashes) => hashes,
+		Err(fault) => return Report { best_block: 0, faults: vec![fault] },
+	};
+
+	let best_block = hashes.len() as BlockNumber - 1;
+	let mut faults = Vec::new();
+	let history_start = match history_start(db) {
+		Ok(start) => start,
+		Err(fault) => {
+			faults.push(fault);
+			0
+		}
+	};
+	let (tracing, missing_traces) = match tracing(db) {
+		Ok(tracing) => tracing,
+		Err(fault) => {
+			faults.push(fault);
+			(false, None)
+		}
+	};
+	let mut blooms = BloomsBuilder::new();
+	let mut total_difficulty = U256::zero();
+	let mut progress = Progress::default();
+
+	for (number, hash) in hashes.iter().enumerate() {
+		let number = number as BlockNumber;
+		let header = read_header(db, hash).expect("header has been read while walking the chain; qed");
+		total_difficulty = total_difficulty + *header.difficulty();
+
+		match read_extras::<H256, _>(db, &number, Entry::BlockHash(number)) {
+			Ok(ref h) if h == hash => {},
+			Ok(_) => faults.push(Fault::Invalid(Entry::BlockHash(number))),
+			Err(fault) => faults.push(fault),
+		}
+
+		match read_extras::<BlockDetails, _>(db, hash, Entry::Details(hash.clone())) {
+			Ok(ref details) if details.number == number && &details.parent == header.parent_hash() && details.total_difficulty == total_difficulty => {},
+			Ok(_) => faults.push(Fault::Invalid(Entry::Details(hash.clone()))),
+			Err(fault) => faults.push(fault),
+		}
+
+		// bodies and receipts of blocks before the history start have been pruned
+		if number >= history_start {
+			match read_transaction_hashes(db, ancient, &header, hash) {
+				Ok(transactions) => for (index, tx_hash) in transactions.into_iter().enumerate() {
+					match read_extras::<TransactionAddress, _>(db, &tx_hash, Entry::TransactionAddress(tx_hash.clone())) {
+						Ok(ref address) if &address.block_hash == hash && address.index == index => {},
+						Ok(_) => faults.push(Fault::Invalid(Entry::TransactionAddress(tx_hash))),
+						Err(fault) => faults.push(fault),
+					}
+				},
+				Err(fault) => faults.push(fault),
+			}
+		}
+
+		// genesis receipts are never stored
+		if number != 0 && number >= history_start {
+			match read_receipts(db, ancient, number, hash) {
+				Ok(ref receipts) if &receipts_root(&receipts.receipts) == header.receipts_root() => {},
+				Ok(_) => faults.push(Fault::Invalid(Entry::Receipts(hash.clone()))),
+				Err(fault) => faults.push(fault),
+			}
+		}
+
+		// genesis is never traced
+		let traces_missing = missing_traces.map_or(false, |(from, to)| number >= from && number <= to);
+		if tracing && number != 0 && !traces_missing {
+			let entry = Entry::Traces(hash.clone());
+			match db.get(DB_COL_TRACE, &Key::<FlatBlockTraces>::key(hash)).expect(DB_ERROR) {
+				Some(bytes) => if UntrustedRlp::new(&bytes).as_val::<FlatBlockTraces>().is_err() {
+					faults.push(Fault::Invalid(entry));
+				},
+				None => faults.push(Fault::Missing(entry)),
+			}
+		}
+
+		if let Some(groups) = blooms.push(number, &header.log_bloom, number == best_block) {
+			for (position, group) in groups {
+				let entry = Entry::Blooms(position.level, position.index);
+				match db.get(DB_COL_EXTRA, &LogGroupPosition::from(position).key()).expect(DB_ERROR) {
+					Some(ref stored) if &stored[..] == &encode(&group)[..] => {},
+					Some(_) => faults.push(Fault::Invalid(entry)),
+					None => faults.push(Fault::Missing(entry)),
+				}
+			}
+		}
+
+		progress.tick();
+	}
+
+	if db.get(DB_COL_EXTRA, &(best_block + 1).key()).expect(DB_ERROR).is_some() {
+		faults.push(Fault::Invalid(Entry::BlockHash(best_block + 1)));
+	}
+
+	let best = read_header(db, &hashes[best_block as usize]).expect("header has been read while walking the chain; qed");
+	if !state.contains(best.state_root()) {
+		faults.push(Fault::MissingState(best.state_root().clone()));
+	}
+
+	Report {
+		best_block: best_block,
+		faults: faults,
+	}
+}
+
+fn receipts_root(receipts: &[Receipt]) -> H256 {
+	ordered_trie_root(receipts.iter().map(|r| r.rlp_bytes().to_vec()).collect())
+}
+
+/// Rebuilds block hashes, transaction addresses and blooms of the canonical chain
+/// from the headers and bodies of its blocks. Block hashes above the best block are removed.
+///
+/// Bodies of ancient blocks are read from `ancient` if given. Transaction addresses of blocks
+/// which history has been pruned are left untouched.
+///
+/// Returns the number of the best block or the fault which makes the repair impossible.
+pub fn repair(db: &Database, ancient: Option<&AncientStore>) -> Result<BlockNumber, Fault> {
+	let hashes = try!(canon_chain(db));
+	let best_block = hashes.len() as BlockNumber - 1;
+	let history_start = try!(history_start(db));
+	let mut blooms = BloomsBuilder::new();
+	let mut batch = DBTransaction::new(db);
+	let mut progress = Progress::default();
+
+	for (number, hash) in hashes.iter().enumerate() {
+		let number = number as BlockNumber;
+		let header = try!(read_header(db, hash));
+		let transactions = if number >= history_start {
+			try!(read_transaction_hashes(db, ancient, &header, hash))
+		} else {
+			Vec::new()
+		};
+
+		batch.write(DB_COL_EXTRA, &number, hash);
+		for (index, tx_hash) in transactions.into_iter().enumerate() {
+			batch.write(DB_COL_EXTRA, &tx_hash, &TransactionAddress {
+				block_hash: hash.clone(),
+				index: index,
+			});
+		}
+
+		if let Some(groups) = blooms.push(number, &header.log_bloom, number == best_block) {
+			for (position, group) in groups {
+				batch.write(DB_COL_EXTRA, &LogGroupPosition::from(position), &group);
+			}
+			db.write(mem::replace(&mut batch, DBTransaction::new(db))).expect(DB_ERROR);
+		}
+
+		progress.tick();
+	}
+
+	let mut stale = best_block + 1;
+	while db.get(DB_COL_EXTRA, &stale.key()).expect(DB_ERROR).is_some() {
+		batch.delete(DB_COL_EXTRA, &stale.key()).expect(DB_ERROR);
+		stale += 1;
+	}
+	db.write(batch).expect(DB_ERROR);
+
+	Ok(best_block)
+}
+
+/// Counts keys and their sizes in every column of the database.
+pub fn stats(db: &Database, columns: Option<u32>) -> Vec<(Option<u32>, ColumnStats)> {
+	let columns: Vec<Option<u32>> = match columns {
+		Some(columns) => (0..columns).map(Some).collect(),
+		None => vec![None],
+	};
+
+	columns.into_iter().map(|col| {
+		let stats = db.iter(col).fold(ColumnStats::default(), |mut stats, (key, value)| {
+			stats.keys += 1;
+			stats.key_bytes += key.len() as u64;
+			stats.value_bytes += value.len() as u64;
+			stats
+		});
+		(col, stats)
+	}).collect()
+}
@@ -16,6 +16,7 @@
 
 //! Blockchain database.
 
+mod ancient;
 mod best_block;
 mod block_info;
 pub mod blockchain;
@@ -23,13 +24,15 @@ mod cache;
 mod config;
 pub mod extras;
 mod import_route;
+pub mod inspect;
 mod update;
 
 #[cfg(test)]
 pub mod generator;
 
+pub use self::ancient::AncientStore;
 pub use self::blockchain::{BlockProvider, BlockChain};
 pub use self::cache::CacheSize;
-pub use self::config::Config;
+pub use self::config::{Config, MIN_HISTORY_BLOCKS};
 pub use types::tree_route::TreeRoute;
 pub use self::import_route::ImportRoute;
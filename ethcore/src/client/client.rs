@@ -17,12 +17,17 @@ use std::collections::{HashSet, HashMap, VecDeque};
 use std::sync::{Arc, Weak};
 use std::path::{Path};
 use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::mem;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
 use std::time::{Instant};
 use time::precise_time_ns;
 
 // util
-use util::{journaldb, rlp, Bytes, View, PerfTimer, Itertools, Mutex, RwLock};
+use util::{journaldb, rlp, Bytes, View, PerfTimer, Itertools, Mutex, RwLock, BaseDataError, UtilError, HashDB, Mismatch};
+use util::trie::{TrieDB, Trie};
+use util::triehash::ordered_trie_root;
 use util::journaldb::JournalDB;
 use util::rlp::{UntrustedRlp};
 use util::numbers::*;
@@ -33,7 +38,7 @@ use util::kvdb::*;
 use io::*;
 use views::{BlockView, HeaderView, BodyView};
 use error::{ImportError, ExecutionError, CallError, BlockError, ImportResult};
-use header::BlockNumber;
+use header::{Header, BlockNumber};
 use state::State;
 use spec::Spec;
 use basic_types::Seal;
@@ -48,14 +53,19 @@ use blockchain::extras::TransactionAddress;
 use types::filter::Filter;
 use log_entry::LocalizedLogEntry;
 use block_queue::{BlockQueue, BlockQueueInfo};
-use blockchain::{BlockChain, BlockProvider, TreeRoute, ImportRoute};
+use blockchain::{AncientStore, BlockChain, BlockProvider, TreeRoute, ImportRoute};
 use client::{BlockID, TransactionID, UncleID, TraceId, ClientConfig, BlockChainClient, MiningBlockChainClient,
-	TraceFilter, CallAnalytics, BlockImportError, Mode, ChainNotify};
+	TraceFilter, CallAnalytics, BlockImportError, Mode, ChainNotify, DatabaseBackup, DatabaseBackend};
 use client::Error as ClientError;
 use env_info::EnvInfo;
 use executive::{Executive, Executed, TransactOptions, contract_address};
-use receipt::LocalizedReceipt;
+use receipt::{Receipt, LocalizedReceipt};
+use pod_account::{self, PodAccount};
+use account_db::AccountDB;
+use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
+use trace::{StructLogger, StructLogOptions, StructLogTrace};
 use trace;
 use trace::FlatTransactionTraces;
 use evm::Factory as EvmFactory;
@@ -70,6 +80,8 @@ pub use blockchain::CacheSize as BlockChainCacheSize;
 
 const MAX_TX_QUEUE_SIZE: usize = 4096;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
+const MAX_BLOCKS_TO_FREEZE: u64 = 1000;
+const MAX_BLOCKS_TO_PRUNE: u64 = 1000;
 
 impl fmt::Display for BlockChainInfo {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -120,7 +132,7 @@ pub struct Client {
 	chain: Arc<BlockChain>,
 	tracedb: Arc<TraceDB<BlockChain>>,
 	engine: Arc<Engine>,
-	db: Arc<Database>,
+	db: Arc<KeyValueDB>,
 	state_db: Mutex<Box<JournalDB>>,
 	block_queue: BlockQueue,
 	report: RwLock<ClientReport>,
@@ -136,9 +148,15 @@ pub struct Client {
 	notify: RwLock<Vec<Weak<ChainNotify>>>,
 	queue_transactions: AtomicUsize,
 	last_hashes: RwLock<VecDeque<H256>>,
+	history: u64,
 }
 
-const HISTORY: u64 = 1200;
+/// Default number of recent blocks whose state is kept by pruned databases.
+pub const HISTORY: u64 = 1200;
+/// Minimal number of recent states kept, so that short reorganisations can be handled.
+pub const MIN_HISTORY: u64 = 8;
+/// Key of the pruning history the state database is committed with.
+pub const PRUNING_HISTORY_KEY: &'static [u8] = b"pruning_history";
 
 // database columns
 /// Column for State
@@ -177,13 +195,26 @@ impl Client {
 		db_config.compaction = config.db_compaction.compaction_profile();
 		db_config.wal = config.db_wal;
 
-		let db = Arc::new(try!(Database::open(&db_config, &path.to_str().unwrap()).map_err(ClientError::Database)));
-		let chain = Arc::new(BlockChain::new(config.blockchain, &gb, db.clone()));
+		let db: Arc<KeyValueDB> = match config.db_backend {
+			DatabaseBackend::RocksDB => Arc::new(try!(Database::open(&db_config, &path.to_str().unwrap()).map_err(ClientError::Database))),
+			DatabaseBackend::InMemory => Arc::new(in_memory(DB_NO_OF_COLUMNS)),
+		};
+		let mut chain = BlockChain::new(config.blockchain, &gb, db.clone());
+		if let Some(ref ancient_path) = config.ancient_path {
+			let ancient = try!(AncientStore::open(ancient_path).map_err(|e| ClientError::Database(format!("{}", e))));
+			chain.set_ancient_store(ancient);
+		}
+		let frozen = chain.frozen_blocks();
+		if chain.ancient_blocks() < frozen {
+			return Err(ClientError::Database(format!("Bodies and receipts of blocks before #{} have been moved to an ancient store, which is missing. Run with the --ancient-path and --pruning used before.", frozen)));
+		}
+		let chain = Arc::new(chain);
 		let tracedb = Arc::new(try!(TraceDB::new(config.tracing, db.clone(), chain.clone())));
 
+		let trie_factory = TrieFactory::new(config.trie_spec);
 		let mut state_db = journaldb::new(db.clone(), config.pruning, DB_COL_STATE);
-		if state_db.is_empty() && try!(spec.ensure_db_good(state_db.as_hashdb_mut())) {
-			let batch = DBTransaction::new(&db);
+		if state_db.is_empty() && try!(spec.ensure_db_good(state_db.as_hashdb_mut(), &trie_factory)) {
+			let batch = DBTransaction::new(&*db);
 			try!(state_db.commit(&batch, 0, &spec.genesis_header().hash(), None));
 			try!(db.write(batch).map_err(ClientError::Database));
 		}
@@ -198,6 +229,29 @@ impl Client {
 		let panic_handler = PanicHandler::new_in_arc();
 		panic_handler.forward_from(&block_queue);
 
+		let history = if config.history < MIN_HISTORY {
+			warn!("Pruning history of {} blocks is too short, keeping {} blocks instead.", config.history, MIN_HISTORY);
+			MIN_HISTORY
+		} else {
+			config.history
+		};
+
+		// eras between a lower history and the one already used would never be canonicalised.
+		let is_pruned = state_db.is_pruned();
+		let history = match try!(db.get(DB_COL_EXTRA, PRUNING_HISTORY_KEY).map_err(ClientError::Database)) {
+			Some(ref stored) if is_pruned && rlp::decode::<u64>(stored) > history => {
+				let stored = rlp::decode(stored);
+				warn!("Pruning history of an existing database cannot be lowered, keeping {} blocks instead of {}.", stored, history);
+				stored
+			},
+			_ => history,
+		};
+		if is_pruned {
+			let batch = DBTransaction::new(&*db);
+			try!(batch.put(DB_COL_EXTRA, PRUNING_HISTORY_KEY, &rlp::encode(&history)).map_err(ClientError::Database));
+			try!(db.write(batch).map_err(ClientError::Database));
+		}
+
 		let awake = match config.mode { Mode::Dark(..) => false, _ => true };
 		let client = Client {
 			sleep_state: Mutex::new(SleepState::new(awake)),
@@ -214,14 +268,17 @@ impl Client {
 			panic_handler: panic_handler,
 			verifier: verification::new(config.verifier_type),
 			vm_factory: Arc::new(EvmFactory::new(config.vm_type)),
-			trie_factory: TrieFactory::new(config.trie_spec),
+			trie_factory: trie_factory,
 			miner: miner,
 			io_channel: message_channel,
 			notify: RwLock::new(Vec::new()),
 			queue_transactions: AtomicUsize::new(0),
 			last_hashes: RwLock::new(VecDeque::new()),
+			history: history,
 		};
-		Ok(Arc::new(client))
+		let client = Arc::new(client);
+		client.miner.restore_local_transactions(&*client, client.db.clone());
+		Ok(client)
 	}
 
 	/// Adds an actor to be notified on certain events
@@ -245,6 +302,89 @@ impl Client {
 		}
 	}
 
+	/// Returns state and environment for a non-persistent call of `t` on top of given block.
+	/// The sender is given enough balance to pay for the call.
+	fn call_environment(&self, t: &SignedTransaction, block: BlockID, state_override: &StateOverride) -> Result<(State, EnvInfo), CallError> {
+		let header = try!(self.block_header(block).ok_or(CallError::StatePruned));
+		let view = HeaderView::new(&header);
+		let last_hashes = self.build_last_hashes(view.hash());
+		let env_info = EnvInfo {
+			number: view.number(),
+			author: view.author(),
+			timestamp: view.timestamp(),
+			difficulty: view.difficulty(),
+			last_hashes: last_hashes,
+			gas_used: U256::zero(),
+			gas_limit: U256::max_value(),
+		};
+		// that's just a copy of the state.
+		let mut state = try!(self.state_at(block).ok_or(CallError::StatePruned));
+		state.apply_override(state_override);
+
+		let sender = try!(t.sender().map_err(|e| {
+			let message = format!("Transaction malformed: {:?}", e);
+			ExecutionError::TransactionMalformed(message)
+		}));
+		let balance = state.balance(&sender);
+		let needed_balance = t.value + t.gas * t.gas_price;
+		if balance < needed_balance {
+			// give the sender a sufficient balance
+			state.add_balance(&sender, &(needed_balance - balance));
+		}
+
+		Ok((state, env_info))
+	}
+
+	/// Returns state and environment right before given transaction was executed, together with the transaction.
+	fn replay_environment(&self, id: TransactionID) -> Result<(State, EnvInfo, SignedTransaction), CallError> {
+		let address = try!(self.transaction_address(id).ok_or(CallError::TransactionNotFound));
+		let header_data = try!(self.block_header(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
+		let body_data = try!(self.block_body(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
+		let mut state = try!(self.state_at_beginning(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
+		let mut txs = BodyView::new(&body_data).transactions();
+
+		if address.index >= txs.len() {
+			return Err(CallError::TransactionNotFound);
+		}
+
+		let mut env_info = self.block_env_info(&HeaderView::new(&header_data));
+		for t in txs.iter().take(address.index) {
+			match Executive::new(&mut state, &env_info, &*self.engine, &self.vm_factory).transact(t, Default::default()) {
+				Ok(x) => { env_info.gas_used = env_info.gas_used + x.gas_used; }
+				Err(ee) => { return Err(CallError::Execution(ee)) }
+			}
+		}
+
+		let t = txs.swap_remove(address.index);
+		Ok((state, env_info, t))
+	}
+
+	/// Returns environment of the given block before any of its transactions were executed.
+	fn block_env_info(&self, view: &HeaderView) -> EnvInfo {
+		EnvInfo {
+			number: view.number(),
+			author: view.author(),
+			timestamp: view.timestamp(),
+			difficulty: view.difficulty(),
+			last_hashes: self.build_last_hashes(view.hash()),
+			gas_used: U256::zero(),
+			gas_limit: view.gas_limit(),
+		}
+	}
+
+	/// Executes `t` on top of given state recording every executed instruction.
+	fn struct_log(&self, state: &mut State, env_info: &EnvInfo, t: &SignedTransaction, options: StructLogOptions) -> Result<StructLogTrace, CallError> {
+		let vm_tracer = StructLogger::toplevel(options);
+		let logs = vm_tracer.logs();
+		let executed = try!(Executive::new(state, env_info, &*self.engine, &self.vm_factory).transact_with_tracer(t, false, trace::NoopTracer, vm_tracer));
+		let logs = mem::replace(&mut *logs.lock(), Vec::new());
+
+		Ok(StructLogTrace {
+			executed: executed,
+			logs: logs,
+		})
+	}
+
 	fn build_last_hashes(&self, parent_hash: H256) -> Arc<LastHashes> {
 		{
 			let hashes = self.last_hashes.read();
@@ -276,7 +416,7 @@ impl Client {
 
 		// Check the block isn't so old we won't be able to enact it.
 		let best_block_number = self.chain.best_block_number();
-		if best_block_number >= HISTORY && header.number() <= best_block_number - HISTORY {
+		if best_block_number >= self.history && header.number() <= best_block_number - self.history {
 			warn!(target: "client", "Block import failed for #{} ({})\nBlock is ancient (current best block: #{}).", header.number(), header.hash(), best_block_number);
 			return Err(());
 		}
@@ -420,8 +560,8 @@ impl Client {
 		let number = block.header().number();
 		let parent = block.header().parent_hash().clone();
 		// Are we committing an era?
-		let ancient = if number >= HISTORY {
-			let n = number - HISTORY;
+		let ancient = if number >= self.history {
+			let n = number - self.history;
 			Some((n, self.chain.block_hash(n).unwrap()))
 		} else {
 			None
@@ -436,7 +576,7 @@ impl Client {
 
 		//let traces = From::from(block.traces().clone().unwrap_or_else(Vec::new));
 
-		let batch = DBTransaction::new(&self.db);
+		let batch = DBTransaction::new(&*self.db);
 		// CHECK! I *think* this is fine, even if the state_root is equal to another
 		// already-imported block of the same number.
 		// TODO: Prove it with a test.
@@ -498,7 +638,7 @@ impl Client {
 			let db = self.state_db.lock().boxed_clone();
 
 			// early exit for pruned blocks
-			if db.is_pruned() && self.chain.best_block_number() >= block_number + HISTORY {
+			if db.is_pruned() && self.chain.best_block_number() >= block_number + self.history {
 				return None;
 			}
 
@@ -548,6 +688,11 @@ impl Client {
 	/// Tick the client.
 	// TODO: manage by real events.
 	pub fn tick(&self) {
+		// freezing and pruning history wait for the next tick when blocks are being imported.
+		if let Some(_import_lock) = self.import_lock.try_lock() {
+			self.chain.freeze(MAX_BLOCKS_TO_FREEZE);
+			self.chain.prune_history(MAX_BLOCKS_TO_PRUNE);
+		}
 		self.chain.collect_garbage();
 		self.block_queue.collect_garbage();
 		self.tracedb.collect_garbage();
@@ -611,6 +756,253 @@ impl Client {
 		Ok(())
 	}
 
+	/// Skips building missing traces of blocks preceding `from`.
+	pub fn skip_missing_traces(&self, from: BlockNumber) {
+		let batch = DBTransaction::new(&*self.db);
+		self.tracedb.skip_missing_traces(&batch, from);
+		self.db.write(batch).expect("DB write failed.");
+	}
+
+	/// Reverts the chain to the block with given number, which becomes the best block.
+	/// All later canonical blocks and branches forking from them are removed together with their traces.
+	/// Fails if the state of the block has already been pruned.
+	///
+	/// Returns hashes of the removed blocks, canonical ones first.
+	pub fn revert(&self, number: BlockNumber) -> Result<Vec<H256>, ClientError> {
+		let _import_lock = self.import_lock.lock();
+		let best = self.chain.best_block_number();
+		if number >= best {
+			return Ok(vec![]);
+		}
+
+		if number < self.chain.ancient_blocks() {
+			return Err(ClientError::Util(UtilError::SimpleString(format!("Block #{} has been moved to the ancient store.", number))));
+		}
+		if number < self.chain.history_start() {
+			return Err(ClientError::Util(UtilError::SimpleString(format!("Body of block #{} has been pruned.", number))));
+		}
+
+		let hash = self.chain.block_hash(number).expect("blocks up to the best one are canonical; qed");
+		let header = self.chain.block_header(&hash).expect("canonical block is known; qed");
+		let mut state_db = self.state_db.lock();
+		if !state_db.contains(header.state_root()) {
+			return Err(ClientError::Util(BaseDataError::PrunedEra(number).into()));
+		}
+
+		let batch = DBTransaction::new(&*self.db);
+		try!(state_db.revert(&batch, number));
+		let removed = self.chain.revert(&batch, number);
+		self.tracedb.revert(&batch, number, best, &removed);
+		try!(self.db.write(batch).map_err(ClientError::Database));
+		self.last_hashes.write().clear();
+
+		Ok(removed)
+	}
+
+	/// Copies the database into given directory while blocks keep being imported.
+	/// The directory receives the database in `db`, the ancient store in `ancient` if there is one
+	/// and the best block the backup corresponds to in `backup.json`.
+	/// The directory must be empty or not exist yet.
+	///
+	/// The database is copied key by key from a snapshot, so the backup takes time and space
+	/// proportional to the database. RocksDB checkpoints would only hard-link the SST files, but
+	/// our RocksDB bindings expose neither checkpoints nor a way to stop compactions from deleting
+	/// the files while they are being linked.
+	pub fn backup(&self, path: &Path) -> Result<DatabaseBackup, ClientError> {
+		let io_error = |e: ::std::io::Error| ClientError::Database(format!("{}", e));
+		if fs::read_dir(path).ok().map_or(false, |mut entries| entries.next().is_some()) {
+			return Err(ClientError::Database(format!("Backup target {} is not empty", path.display())));
+		}
+
+		// imports are paused only until all columns have been captured.
+		let (snapshot, ancient_blocks, backup) = {
+			let _import_lock = self.import_lock.lock();
+			try!(self.db.flush().map_err(ClientError::Database));
+			let backup = DatabaseBackup {
+				number: self.chain.best_block_number(),
+				hash: self.chain.best_block_hash(),
+			};
+			(self.db.snapshot(), self.chain.ancient_blocks(), backup)
+		};
+
+		try!(fs::create_dir_all(path).map_err(&io_error));
+		try!(snapshot.write_to(&append_path(path, "db")).map_err(ClientError::Database));
+		if let Some(ancient) = self.chain.ancient_store() {
+			try!(ancient.backup(&path.join("ancient"), ancient_blocks).map_err(&io_error));
+		}
+
+		let mut file = try!(File::create(path.join("backup.json")).map_err(&io_error));
+		try!(writeln!(file, "{{\"number\": {}, \"hash\": \"0x{}\"}}", backup.number, backup.hash.hex()).map_err(&io_error));
+		info!(target: "client", "Database backup of block #{} ({}) written to {}", backup.number, backup.hash, path.display());
+		Ok(backup)
+	}
+
+	/// Passes every account in the final state of the given block to `f`, ordered by address hash.
+	/// Code and storage are only read when requested.
+	///
+	/// Addresses and storage keys are recovered from their hashes, so the state must have been
+	/// built with a fat database and kept by the archive pruning method.
+	pub fn for_each_account<F>(&self, id: BlockID, code: bool, storage: bool, mut f: F) -> Result<(), ClientError>
+		where F: FnMut(Address, PodAccount) {
+		let header = match self.block_header(id) {
+			Some(header) => header,
+			None => return Err(ClientError::Util(UtilError::SimpleString("Unknown block.".into()))),
+		};
+
+		let db = self.state_db.lock().boxed_clone();
+		if db.is_pruned() {
+			return Err(ClientError::Util(UtilError::SimpleString("Accounts can only be enumerated in an archive database.".into())));
+		}
+
+		let root = HeaderView::new(&header).state_root();
+		let db = db.as_hashdb();
+		let trie = try!(TrieDB::new(db, &root));
+		for (hash, rlp) in trie.iter() {
+			let hash = H256::from_slice(&hash);
+			let address = match db.get_aux(&hash) {
+				Some(address) => Address::from_slice(&address),
+				None => return Err(ClientError::Util(BaseDataError::MissingPreimage(hash).into())),
+			};
+
+			let account = rlp::Rlp::new(rlp);
+			let account_db = AccountDB::from_hash(db, hash);
+			let mut pod = PodAccount {
+				nonce: account.val_at(0),
+				balance: account.val_at(1),
+				code: None,
+				storage: Default::default(),
+			};
+
+			if code {
+				let code_hash: H256 = account.val_at(3);
+				pod.code = if code_hash == SHA3_EMPTY {
+					Some(vec![])
+				} else {
+					account_db.get(&code_hash).map(|c| c.to_vec())
+				};
+			}
+
+			if storage {
+				let storage_root: H256 = account.val_at(2);
+				let storage_trie = try!(TrieDB::new(&account_db, &storage_root));
+				for (key, value) in storage_trie.iter() {
+					let key = H256::from_slice(&key);
+					let key = match account_db.get_aux(&key) {
+						Some(key) => H256::from_slice(&key),
+						None => return Err(ClientError::Util(BaseDataError::MissingPreimage(key).into())),
+					};
+					pod.storage.insert(key, H256::from(rlp::decode::<U256>(value)));
+				}
+			}
+
+			f(address, pod);
+		}
+
+		Ok(())
+	}
+
+	/// Imports a block together with its receipts without executing it.
+	///
+	/// Only blocks filling a gap in the canonical chain below the best block are accepted,
+	/// e.g. the history preceding blocks restored from a snapshot. The block is checked against
+	/// its parent and the receipts against the header, but transactions are not executed and
+	/// no state is built, so the block must come from a trusted source.
+	pub fn import_trusted_block(&self, bytes: Bytes, receipts: Vec<Receipt>) -> Result<H256, BlockImportError> {
+		let header: Header = try!(UntrustedRlp::new(&bytes).val_at(0).map_err(|e| BlockImportError::Other(format!("Invalid block: {}", e))));
+		let hash = header.hash();
+		let number = header.number();
+
+		let _import_lock = self.import_lock.lock();
+		if self.chain.is_known(&hash) {
+			return Err(BlockImportError::Import(ImportError::AlreadyInChain));
+		}
+		if number >= self.chain.best_block_number() || self.chain.block_hash(number).is_some() {
+			return Err(BlockImportError::Other(format!("Block #{} does not fill a gap in the canonical chain.", number)));
+		}
+		if self.chain.block_hash(number - 1) != Some(header.parent_hash().clone()) {
+			return Err(BlockImportError::Block(BlockError::UnknownParent(header.parent_hash().clone())));
+		}
+		// the block must also be the parent of the canonical block above it, if there is one.
+		let child = self.chain.block_hash(number + 1);
+		if let Some(ref child) = child {
+			let child_header = self.chain.block_header(child).expect("canonical blocks have headers; qed");
+			if child_header.parent_hash() != &hash {
+				return Err(BlockImportError::Other(format!("Block #{} is not the parent of canonical block {}.", number, child)));
+			}
+		}
+
+		try!(verification::verify_block_basic(&header, &bytes, &*self.engine));
+		try!(verification::verify_block_family(&header, &bytes, &*self.engine, &*self.chain));
+		let receipts_root = ordered_trie_root(receipts.iter().map(|r| rlp::encode(r).to_vec()).collect());
+		if &receipts_root != header.receipts_root() {
+			return Err(BlockImportError::Block(BlockError::InvalidReceiptsRoot(Mismatch { expected: header.receipts_root().clone(), found: receipts_root })));
+		}
+
+		self.chain.insert_snapshot_block(&bytes, receipts, None, false);
+		self.chain.commit();
+
+		// connect the block to the rest of the chain once the gap is closed.
+		if let Some(child) = child {
+			self.chain.add_child(hash, child);
+		}
+
+		Ok(hash)
+	}
+
+	/// Builds traces of up to `max_blocks` blocks missing from the trace database
+	/// by re-enacting them on top of their parent's state.
+	/// Blocks which bodies have been pruned are skipped.
+	/// Returns the range of blocks which traces are still missing.
+	pub fn build_missing_traces(&self, max_blocks: usize) -> Result<Option<(BlockNumber, BlockNumber)>, ::error::Error> {
+		for _ in 0..max_blocks {
+			// the lock is released between blocks, so that the import of new blocks is not stalled.
+			let _import_lock = self.import_lock.lock();
+			let number = match self.tracedb.missing_traces() {
+				Some((from, _)) => from,
+				None => break,
+			};
+
+			let history_start = self.chain.history_start();
+			if number < history_start {
+				warn!(target: "trace", "Traces of blocks #{}..#{} cannot be built: their bodies have been pruned.", number, history_start - 1);
+				self.skip_missing_traces(history_start);
+				continue;
+			}
+
+			let hash = self.chain.block_hash(number).expect("missing traces are within the canonical chain; qed");
+			let block = match self.chain.block(&hash) {
+				Some(block) => block,
+				None => return Err(ClientError::Trace(trace::Error::HistoryPruned(number)).into()),
+			};
+			let parent_hash = BlockView::new(&block).header_view().parent_hash();
+			let parent = self.chain.block_header(&parent_hash).expect("parent of a known block is known; qed");
+			if !self.state_db.lock().contains(parent.state_root()) {
+				return Err(ClientError::Trace(trace::Error::StatePruned(number)).into());
+			}
+
+			let last_hashes = self.build_last_hashes(parent_hash);
+			let db = self.state_db.lock().boxed_clone();
+			let enacted = try!(enact_bytes(&block, &*self.engine, true, db, &parent, last_hashes, &self.vm_factory, self.trie_factory.clone()));
+			let traces: Vec<FlatTransactionTraces> = enacted.traces().clone()
+				.expect("block is enacted with tracing; qed")
+				.into_iter()
+				.map(Into::into)
+				.collect();
+
+			let batch = DBTransaction::new(&*self.db);
+			self.tracedb.import_missing(&batch, TraceImportRequest {
+				traces: traces.into(),
+				block_hash: hash.clone(),
+				block_number: number,
+				enacted: vec![hash],
+				retracted: 0,
+			});
+			self.db.write(batch).expect("DB write failed.");
+		}
+
+		Ok(self.tracedb.missing_traces())
+	}
+
 	fn block_hash(chain: &BlockChain, id: BlockID) -> Option<H256> {
 		match id {
 			BlockID::Hash(hash) => Some(hash),
@@ -655,33 +1047,10 @@ impl Client {
 }
 
 impl BlockChainClient for Client {
-	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics) -> Result<Executed, CallError> {
-		let header = try!(self.block_header(block).ok_or(CallError::StatePruned));
-		let view = HeaderView::new(&header);
-		let last_hashes = self.build_last_hashes(view.hash());
-		let env_info = EnvInfo {
-			number: view.number(),
-			author: view.author(),
-			timestamp: view.timestamp(),
-			difficulty: view.difficulty(),
-			last_hashes: last_hashes,
-			gas_used: U256::zero(),
-			gas_limit: U256::max_value(),
-		};
-		// that's just a copy of the state.
-		let mut state = try!(self.state_at(block).ok_or(CallError::StatePruned));
+	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics, state_override: &StateOverride) -> Result<Executed, CallError> {
+		let (mut state, env_info) = try!(self.call_environment(t, block, state_override));
 		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
 
-		let sender = try!(t.sender().map_err(|e| {
-			let message = format!("Transaction malformed: {:?}", e);
-			ExecutionError::TransactionMalformed(message)
-		}));
-		let balance = state.balance(&sender);
-		let needed_balance = t.value + t.gas * t.gas_price;
-		if balance < needed_balance {
-			// give the sender a sufficient balance
-			state.add_balance(&sender, &(needed_balance - balance));
-		}
 		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
 		let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, &self.vm_factory).transact(t, options));
 
@@ -692,41 +1061,106 @@ impl BlockChainClient for Client {
 	}
 
 	fn replay(&self, id: TransactionID, analytics: CallAnalytics) -> Result<Executed, CallError> {
-		let address = try!(self.transaction_address(id).ok_or(CallError::TransactionNotFound));
-		let header_data = try!(self.block_header(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
-		let body_data = try!(self.block_body(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
-		let mut state = try!(self.state_at_beginning(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
+		let (mut state, env_info, t) = try!(self.replay_environment(id));
+		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, &self.vm_factory).transact(&t, options));
+		ret.state_diff = original_state.map(|original| state.diff_from(original));
+
+		Ok(ret)
+	}
+
+	fn replay_block_transactions(&self, block: BlockID, analytics: CallAnalytics) -> Result<Vec<Executed>, CallError> {
+		let header_data = try!(self.block_header(block).ok_or(CallError::StatePruned));
+		let body_data = try!(self.block_body(block).ok_or(CallError::StatePruned));
+		let mut state = try!(self.state_at_beginning(block).ok_or(CallError::StatePruned));
 		let txs = BodyView::new(&body_data).transactions();
 
-		if address.index >= txs.len() {
-			return Err(CallError::TransactionNotFound);
+		let mut env_info = self.block_env_info(&HeaderView::new(&header_data));
+		let mut results = Vec::with_capacity(txs.len());
+		for t in &txs {
+			let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+			let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+			let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, &self.vm_factory).transact(t, options));
+			ret.state_diff = original_state.map(|original| state.diff_from(original));
+			env_info.gas_used = env_info.gas_used + ret.gas_used;
+			results.push(ret);
 		}
 
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
-		let view = HeaderView::new(&header_data);
-		let last_hashes = self.build_last_hashes(view.hash());
-		let mut env_info = EnvInfo {
-			number: view.number(),
-			author: view.author(),
-			timestamp: view.timestamp(),
-			difficulty: view.difficulty(),
-			last_hashes: last_hashes,
-			gas_used: U256::zero(),
-			gas_limit: view.gas_limit(),
+		Ok(results)
+	}
+
+	fn debug_call(&self, t: &SignedTransaction, block: BlockID, options: StructLogOptions) -> Result<StructLogTrace, CallError> {
+		let (mut state, env_info) = try!(self.call_environment(t, block, &StateOverride::default()));
+		self.struct_log(&mut state, &env_info, t, options)
+	}
+
+	fn debug_replay(&self, id: TransactionID, options: StructLogOptions) -> Result<StructLogTrace, CallError> {
+		let (mut state, env_info, t) = try!(self.replay_environment(id));
+		self.struct_log(&mut state, &env_info, &t, options)
+	}
+
+	fn state_diff(&self, from: BlockID, to: BlockID, accounts: Vec<Address>) -> Option<StateDiff> {
+		let pre = match self.state_at(from) {
+			Some(state) => state,
+			None => return None,
+		};
+		let post = match self.state_at(to) {
+			Some(state) => state,
+			None => return None,
 		};
-		for t in txs.iter().take(address.index) {
-			match Executive::new(&mut state, &env_info, &*self.engine, &self.vm_factory).transact(t, Default::default()) {
-				Ok(x) => { env_info.gas_used = env_info.gas_used + x.gas_used; }
-				Err(ee) => { return Err(CallError::Execution(ee)) }
-			}
-		}
-		let t = &txs[address.index];
 
-		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
-		let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, &self.vm_factory).transact(t, options));
-		ret.state_diff = original_state.map(|original| state.diff_from(original));
+		let raw = accounts.into_iter()
+			.filter_map(|address| {
+				let pre_account = pre.pod_account(&address);
+				let post_account = post.pod_account(&address);
+				pod_account::diff_pod(pre_account.as_ref(), post_account.as_ref()).map(|diff| (address, diff))
+			})
+			.collect();
 
-		Ok(ret)
+		Some(StateDiff { raw: raw })
+	}
+
+	fn history_start(&self) -> BlockNumber {
+		self.chain.history_start()
+	}
+
+	fn missing_traces(&self) -> Option<(BlockNumber, BlockNumber)> {
+		self.tracedb.missing_traces()
+	}
+
+	fn transaction_block(&self, id: TransactionID) -> Option<H256> {
+		self.transaction_address(id).map(|address| address.block_hash)
+	}
+
+	fn backup_database(&self, path: String) -> Result<DatabaseBackup, String> {
+		self.backup(Path::new(&path)).map_err(|e| format!("{}", e))
+	}
+
+	fn oldest_state(&self) -> Option<BlockNumber> {
+		let db = self.state_db.lock().boxed_clone();
+		let has_state = |number| self.chain.block_hash(number)
+			.and_then(|hash| self.chain.block_header(&hash))
+			.map_or(false, |header| db.contains(header.state_root()));
+
+		let best = self.chain.best_block_number();
+		if !has_state(best) {
+			return None;
+		}
+
+		// states are available for a continuous range of blocks ending with the best one.
+		let mut lowest = if db.is_pruned() && best >= self.history { best + 1 - self.history } else { 0 };
+		let mut highest = best;
+		while lowest < highest {
+			let middle = lowest + (highest - lowest) / 2;
+			if has_state(middle) {
+				highest = middle;
+			} else {
+				lowest = middle + 1;
+			}
+		}
+		Some(lowest)
 	}
 
 	fn keep_alive(&self) {
@@ -945,6 +1379,7 @@ impl BlockChainClient for Client {
 					})
 					.collect::<Vec<LocalizedLogEntry>>()
 			})
+			.take(filter.limit.unwrap_or(usize::max_value()))
 			.collect()
 	}
 
@@ -952,14 +1387,18 @@ impl BlockChainClient for Client {
 		let start = self.block_number(filter.range.start);
 		let end = self.block_number(filter.range.end);
 
+		let after = filter.after.unwrap_or(0);
+		let count = filter.count;
+
 		if start.is_some() && end.is_some() {
 			let filter = trace::Filter {
 				range: start.unwrap() as usize..end.unwrap() as usize,
 				from_address: From::from(filter.from_address),
 				to_address: From::from(filter.to_address),
+				action: filter.action,
 			};
 
-			let traces = self.tracedb.filter(&filter);
+			let traces = self.tracedb.filter(&filter, after, count);
 			Some(traces)
 		} else {
 			None
@@ -1011,6 +1450,10 @@ impl BlockChainClient for Client {
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		self.miner.pending_transactions()
 	}
+
+	fn local_transactions_to_rebroadcast(&self) -> Vec<SignedTransaction> {
+		self.miner.local_transactions_to_rebroadcast(self.chain.best_block_number())
+	}
 }
 
 impl MiningBlockChainClient for Client {
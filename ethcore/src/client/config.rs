@@ -15,14 +15,16 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str::FromStr;
+use std::path::PathBuf;
 pub use std::time::Duration;
 pub use block_queue::BlockQueueConfig;
-pub use blockchain::Config as BlockChainConfig;
+pub use blockchain::{Config as BlockChainConfig, MIN_HISTORY_BLOCKS};
 pub use trace::{Config as TraceConfig, Switch};
 pub use evm::VMType;
 pub use verification::VerifierType;
 use util::{journaldb, CompactionProfile};
 use util::trie::TrieSpec;
+use super::client::HISTORY;
 
 /// Client state db compaction profile
 #[derive(Debug, PartialEq)]
@@ -61,6 +63,21 @@ impl FromStr for DatabaseCompactionProfile {
 	}
 }
 
+/// Storage of the client database.
+#[derive(Debug, PartialEq)]
+pub enum DatabaseBackend {
+	/// RocksDB database at the client path.
+	RocksDB,
+	/// Database kept in memory. Nothing is written to disk and all data is lost when the client is dropped.
+	InMemory,
+}
+
+impl Default for DatabaseBackend {
+	fn default() -> Self {
+		DatabaseBackend::RocksDB
+	}
+}
+
 /// Operating mode for the client.
 #[derive(Debug, Eq, PartialEq)]
 pub enum Mode {
@@ -81,7 +98,7 @@ impl Default for Mode {
 }
 
 /// Client configuration. Includes configs for all sub-systems.
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq)]
 pub struct ClientConfig {
 	/// Block queue configuration.
 	pub queue: BlockQueueConfig,
@@ -103,15 +120,43 @@ pub struct ClientConfig {
 	pub db_compaction: DatabaseCompactionProfile,
 	/// Should db have WAL enabled?
 	pub db_wal: bool, 
+	/// Storage of the database.
+	pub db_backend: DatabaseBackend,
 	/// Operating mode
 	pub mode: Mode,
 	/// Type of block verifier used by client.
 	pub verifier_type: VerifierType,
+	/// Number of recent states kept when the state is pruned.
+	pub history: u64,
+	/// Directory of the ancient block store, if old bodies and receipts should be moved there.
+	pub ancient_path: Option<PathBuf>,
+}
+
+impl Default for ClientConfig {
+	fn default() -> Self {
+		ClientConfig {
+			queue: Default::default(),
+			blockchain: Default::default(),
+			tracing: Default::default(),
+			vm_type: Default::default(),
+			trie_spec: Default::default(),
+			pruning: Default::default(),
+			name: Default::default(),
+			db_cache_size: Default::default(),
+			db_compaction: Default::default(),
+			db_wal: Default::default(),
+			db_backend: Default::default(),
+			mode: Default::default(),
+			verifier_type: Default::default(),
+			history: HISTORY,
+			ancient_path: None,
+		}
+	}
 }
 
 #[cfg(test)]
 mod test {
-	use super::{DatabaseCompactionProfile, Mode};
+	use super::{DatabaseCompactionProfile, DatabaseBackend, Mode};
 
 	#[test]
 	fn test_default_compaction_profile() {
@@ -125,6 +170,11 @@ mod test {
 		assert_eq!(DatabaseCompactionProfile::HDD, "hdd".parse().unwrap());
 	}
 
+	#[test]
+	fn test_default_database_backend() {
+		assert_eq!(DatabaseBackend::default(), DatabaseBackend::RocksDB);
+	}
+
 	#[test]
 	fn test_mode_default() {
 		assert_eq!(Mode::default(), Mode::Active);
@@ -23,9 +23,10 @@ use transaction::{Transaction, LocalizedTransaction, SignedTransaction, Action};
 use blockchain::TreeRoute;
 use client::{BlockChainClient, MiningBlockChainClient, BlockChainInfo, BlockStatus, BlockID,
 	TransactionID, UncleID, TraceId, TraceFilter, LastHashes, CallAnalytics,
-	BlockImportError};
+	BlockImportError, DatabaseBackup};
 use header::{Header as BlockHeader, BlockNumber};
 use filter::Filter;
+use views::BlockView;
 use log_entry::LocalizedLogEntry;
 use receipt::{Receipt, LocalizedReceipt};
 use blockchain::extras::BlockReceipts;
@@ -38,7 +39,9 @@ use block_queue::BlockQueueInfo;
 use block::{OpenBlock, SealedBlock};
 use executive::Executed;
 use error::CallError;
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, StructLogOptions, StructLogTrace};
+use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 
 /// Test client.
 pub struct TestBlockChainClient {
@@ -72,6 +75,10 @@ pub struct TestBlockChainClient {
 	pub spec: Spec,
 	/// VM Factory
 	pub vm_factory: EvmFactory,
+	/// Number of the first block with body, only headers of older blocks are available.
+	pub history_start: RwLock<BlockNumber>,
+	/// Range of blocks which traces are missing.
+	pub missing_traces: RwLock<Option<(BlockNumber, BlockNumber)>>,
 }
 
 #[derive(Clone)]
@@ -113,6 +120,8 @@ impl TestBlockChainClient {
 			miner: Arc::new(Miner::with_spec(&spec)),
 			spec: spec,
 			vm_factory: EvmFactory::new(VMType::Interpreter),
+			history_start: RwLock::new(0),
+			missing_traces: RwLock::new(None),
 		};
 		client.add_blocks(1, EachBlockWith::Nothing); // add genesis block
 		client.genesis_hash = client.last_hash.read().clone();
@@ -124,6 +133,16 @@ impl TestBlockChainClient {
 		self.receipts.write().insert(id, receipt);
 	}
 
+	/// Set the number of the first block with body.
+	pub fn set_history_start(&self, number: BlockNumber) {
+		*self.history_start.write() = number;
+	}
+
+	/// Set the range of blocks which traces are missing.
+	pub fn set_missing_traces(&self, missing: Option<(BlockNumber, BlockNumber)>) {
+		*self.missing_traces.write() = missing;
+	}
+
 	/// Set the execution result.
 	pub fn set_execution_result(&self, result: Result<Executed, CallError>) {
 		*self.execution_result.write() = Some(result);
@@ -244,6 +263,15 @@ impl TestBlockChainClient {
 			BlockID::Latest | BlockID::Pending => self.numbers.read().get(&(self.numbers.read().len() - 1)).cloned()
 		}
 	}
+
+	/// Returns the block unless its body has been pruned.
+	fn with_body<'a>(&self, block: &'a Bytes) -> Option<&'a Bytes> {
+		if BlockView::new(block).header_view().number() < *self.history_start.read() {
+			None
+		} else {
+			Some(block)
+		}
+	}
 }
 
 pub fn get_temp_journal_db() -> GuardedTempResult<Box<JournalDB>> {
@@ -262,7 +290,7 @@ impl MiningBlockChainClient for TestBlockChainClient {
 		let genesis_header = self.spec.genesis_header();
 		let mut db_result = get_temp_journal_db();
 		let mut db = db_result.take();
-		self.spec.ensure_db_good(db.as_hashdb_mut()).unwrap();
+		self.spec.ensure_db_good(db.as_hashdb_mut(), &Default::default()).unwrap();
 
 		let last_hashes = vec![genesis_header.hash()];
 		let mut open_block = OpenBlock::new(
@@ -292,7 +320,7 @@ impl MiningBlockChainClient for TestBlockChainClient {
 }
 
 impl BlockChainClient for TestBlockChainClient {
-	fn call(&self, _t: &SignedTransaction, _block: BlockID, _analytics: CallAnalytics) -> Result<Executed, CallError> {
+	fn call(&self, _t: &SignedTransaction, _block: BlockID, _analytics: CallAnalytics, _state_override: &StateOverride) -> Result<Executed, CallError> {
 		self.execution_result.read().clone().unwrap()
 	}
 
@@ -300,6 +328,50 @@ impl BlockChainClient for TestBlockChainClient {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn replay_block_transactions(&self, _block: BlockID, _analytics: CallAnalytics) -> Result<Vec<Executed>, CallError> {
+		self.execution_result.read().clone().unwrap().map(|executed| vec![executed])
+	}
+
+	fn debug_call(&self, _t: &SignedTransaction, _block: BlockID, _options: StructLogOptions) -> Result<StructLogTrace, CallError> {
+		self.execution_result.read().clone().unwrap().map(|executed| StructLogTrace { executed: executed, logs: vec![] })
+	}
+
+	fn debug_replay(&self, _id: TransactionID, _options: StructLogOptions) -> Result<StructLogTrace, CallError> {
+		self.execution_result.read().clone().unwrap().map(|executed| StructLogTrace { executed: executed, logs: vec![] })
+	}
+
+	fn state_diff(&self, _from: BlockID, _to: BlockID, _accounts: Vec<Address>) -> Option<StateDiff> {
+		None
+	}
+
+	fn oldest_state(&self) -> Option<BlockNumber> {
+		Some(0)
+	}
+
+	fn history_start(&self) -> BlockNumber {
+		*self.history_start.read()
+	}
+
+	fn missing_traces(&self) -> Option<(BlockNumber, BlockNumber)> {
+		*self.missing_traces.read()
+	}
+
+	fn transaction_block(&self, id: TransactionID) -> Option<H256> {
+		match id {
+			TransactionID::Hash(hash) => self.blocks.read().iter()
+				.find(|&(_, block)| BlockView::new(block).transaction_hashes().contains(&hash))
+				.map(|(block_hash, _)| block_hash.clone()),
+			TransactionID::Location(id, _) => self.block_hash(id),
+		}
+	}
+
+	fn backup_database(&self, _path: String) -> Result<DatabaseBackup, String> {
+		Ok(DatabaseBackup {
+			number: self.chain_info().best_block_number,
+			hash: self.chain_info().best_block_hash,
+		})
+	}
+
 	fn block_total_difficulty(&self, _id: BlockID) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -376,7 +448,7 @@ impl BlockChainClient for TestBlockChainClient {
 	}
 
 	fn block_body(&self, id: BlockID) -> Option<Bytes> {
-		self.block_hash(id).and_then(|hash| self.blocks.read().get(&hash).map(|r| {
+		self.block_hash(id).and_then(|hash| self.blocks.read().get(&hash).and_then(|r| self.with_body(r)).map(|r| {
 			let mut stream = RlpStream::new_list(2);
 			stream.append_raw(Rlp::new(r).at(1).as_raw(), 1);
 			stream.append_raw(Rlp::new(r).at(2).as_raw(), 1);
@@ -385,7 +457,7 @@ impl BlockChainClient for TestBlockChainClient {
 	}
 
 	fn block(&self, id: BlockID) -> Option<Bytes> {
-		self.block_hash(id).and_then(|hash| self.blocks.read().get(&hash).cloned())
+		self.block_hash(id).and_then(|hash| self.blocks.read().get(&hash).and_then(|r| self.with_body(r)).cloned())
 	}
 
 	fn block_status(&self, id: BlockID) -> BlockStatus {
@@ -549,4 +621,8 @@ impl BlockChainClient for TestBlockChainClient {
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		self.miner.pending_transactions()
 	}
+
+	fn local_transactions_to_rebroadcast(&self) -> Vec<SignedTransaction> {
+		self.miner.local_transactions_to_rebroadcast(self.chain_info().best_block_number)
+	}
 }
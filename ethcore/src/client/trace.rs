@@ -24,4 +24,8 @@ impl TraceDatabaseExtras for BlockChain {
 			})
 			.map(|tx| tx.hash())
 	}
+
+	fn best_block_number(&self) -> BlockNumber {
+		BlockChain::best_block_number(self)
+	}
 }
@@ -28,19 +28,22 @@ use filter::Filter;
 use views::{BlockView};
 use error::{ImportResult, CallError};
 use receipt::LocalizedReceipt;
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, StructLogOptions, StructLogTrace};
 use evm::Factory as EvmFactory;
 use types::ids::*;
 use types::trace_filter::Filter as TraceFilter;
 use executive::Executed;
 use env_info::LastHashes;
 use types::call_analytics::CallAnalytics;
+use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 use block_import_error::BlockImportError;
 use std::mem;
 use std::collections::VecDeque;
 use ipc::{IpcConfig, BinaryConvertError};
 use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
+use types::database_backup::DatabaseBackup;
 
 #[derive(Ipc)]
 #[ipc(client_ident="RemoteClient")]
@@ -150,16 +153,51 @@ pub trait BlockChainClient : Sync + Send {
 	/// Returns numbers of blocks containing given bloom.
 	fn blocks_with_bloom(&self, bloom: &H2048, from_block: BlockID, to_block: BlockID) -> Option<Vec<BlockNumber>>;
 
-	/// Returns logs matching given filter.
+	/// Returns logs matching given filter, in block order.
+	/// At most `filter.limit` entries are returned.
 	fn logs(&self, filter: Filter) -> Vec<LocalizedLogEntry>;
 
 	/// Makes a non-persistent transaction call.
-	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics) -> Result<Executed, CallError>;
+	/// `state_override` is applied to a temporary copy of the state before the call is executed.
+	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics, state_override: &StateOverride) -> Result<Executed, CallError>;
 
 	/// Replays a given transaction for inspection.
 	fn replay(&self, t: TransactionID, analytics: CallAnalytics) -> Result<Executed, CallError>;
 
-	/// Returns traces matching given filter.
+	/// Re-executes all transactions of a given block on top of its parent's state.
+	fn replay_block_transactions(&self, block: BlockID, analytics: CallAnalytics) -> Result<Vec<Executed>, CallError>;
+
+	/// Makes a non-persistent transaction call recording every executed instruction.
+	fn debug_call(&self, t: &SignedTransaction, block: BlockID, options: StructLogOptions) -> Result<StructLogTrace, CallError>;
+
+	/// Replays a given transaction recording every executed instruction.
+	fn debug_replay(&self, t: TransactionID, options: StructLogOptions) -> Result<StructLogTrace, CallError>;
+
+	/// Returns differences of given accounts between the states at the end of two blocks.
+	/// Returns `None` if either of the states is not available.
+	fn state_diff(&self, from: BlockID, to: BlockID, accounts: Vec<Address>) -> Option<StateDiff>;
+
+	/// Returns the number of the oldest block whose body and receipts are still available.
+	/// Only headers of older blocks are kept.
+	fn history_start(&self) -> BlockNumber;
+
+	/// Returns the inclusive range of blocks which traces are missing from the trace database.
+	/// Traces of those blocks cannot be queried until they are built.
+	fn missing_traces(&self) -> Option<(BlockNumber, BlockNumber)>;
+
+	/// Get the hash of the block containing given transaction.
+	fn transaction_block(&self, id: TransactionID) -> Option<H256>;
+
+	/// Copies the database into given directory without stopping block import.
+	/// Returns the best block the backup corresponds to.
+	fn backup_database(&self, path: String) -> Result<DatabaseBackup, String>;
+
+	/// Returns the number of the oldest block whose state is still available.
+	/// Returns `None` if the state of the best block is missing.
+	fn oldest_state(&self) -> Option<BlockNumber>;
+
+	/// Returns traces matching given filter, in block order.
+	/// Pagination is controlled by `filter.after` and `filter.count`.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
 	/// Returns trace with given id.
@@ -180,6 +218,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// list all transactions
 	fn pending_transactions(&self) -> Vec<SignedTransaction>;
 
+	/// Local transactions which were not mined for a while and should be broadcast to all peers again.
+	fn local_transactions_to_rebroadcast(&self) -> Vec<SignedTransaction>;
+
 	/// Get the gas price distribution.
 	fn gas_price_statistics(&self, sample_size: usize, distribution_size: usize) -> Result<Vec<U256>, ()> {
 		let mut h = self.chain_info().best_block_hash;
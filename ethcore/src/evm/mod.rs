@@ -36,3 +36,9 @@ pub use self::ext::{Ext, ContractCreateResult, MessageCallResult};
 pub use self::factory::{Factory, VMType};
 pub use self::schedule::Schedule;
 pub use types::executed::CallType;
+
+/// Returns the name of given instruction and the number of stack items it takes and pushes.
+pub fn instruction_info(instruction: u8) -> (&'static str, usize, usize) {
+	let info = &instructions::INSTRUCTIONS[instruction as usize];
+	(info.name, info.args, info.ret)
+}
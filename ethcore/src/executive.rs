@@ -492,6 +492,7 @@ mod tests {
 	use trace::trace;
 	use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer};
 	use trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff, VMTracer, NoopVMTracer, ExecutiveVMTracer};
+	use trace::{StructLogger, StructLogOptions};
 	use types::executed::CallType;
 
 	#[test]
@@ -711,6 +712,115 @@ mod tests {
 		assert_eq!(vm_tracer.drain().unwrap(), expected_vm_trace);
 	}
 
+	#[test]
+	fn test_struct_logger() {
+		// instructions are reported to vm tracers by the interpreter only.
+		let factory = Factory::new(VMType::Interpreter);
+		// 60 02 - push 2
+		// 60 03 - push 3
+		// 01 - add
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 05 - push 5
+		// 60 01 - push 1
+		// 55 - sstore
+		// 00 - stop
+		let code = "6002600301600052600560015500".from_hex().unwrap();
+
+		let sender = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let address = contract_address(&sender, &U256::zero());
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.code_address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(code);
+		params.call_type = CallType::Call;
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let info = EnvInfo::default();
+		let engine = TestEngine::new(5);
+		let mut substate = Substate::new();
+		let mut vm_tracer = StructLogger::toplevel(StructLogOptions::default());
+		let logs = vm_tracer.logs();
+
+		{
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			let output = BytesRef::Fixed(&mut[0u8;0]);
+			ex.call(params, &mut substate, output, &mut NoopTracer, &mut vm_tracer).unwrap();
+		}
+
+		let logs = logs.lock();
+		assert_eq!(logs.len(), 9);
+		assert_eq!(logs.iter().map(|l| l.pc).collect::<Vec<_>>(), vec![0, 2, 4, 5, 7, 8, 10, 12, 13]);
+		assert_eq!(logs.iter().map(|l| l.gas).collect::<Vec<_>>(), vec_into![100000, 99997, 99994, 99991, 99988, 99982, 99979, 99976, 79976]);
+		assert!(logs.iter().all(|l| l.depth == 1));
+
+		// add
+		assert_eq!(logs[2].stack, Some(vec_into![2, 3]));
+		// mstore
+		assert_eq!(logs[4].stack, Some(vec_into![5, 0]));
+		assert_eq!(logs[4].memory, Some(vec![]));
+		// push 5
+		assert_eq!(logs[5].stack, Some(vec![]));
+		let mut memory = vec![0u8; 32];
+		memory[31] = 5;
+		assert_eq!(logs[5].memory, Some(memory));
+		// sstore
+		assert_eq!(logs[7].gas_cost, U256::from(20000));
+		let mut storage = BTreeMap::new();
+		storage.insert(H256::from(&U256::from(1)), H256::from(&U256::from(5)));
+		assert_eq!(logs[7].storage, Some(storage));
+	}
+
+	#[test]
+	fn test_struct_logger_options() {
+		// instructions are reported to vm tracers by the interpreter only.
+		let factory = Factory::new(VMType::Interpreter);
+		let code = "6002600301600052600560015500".from_hex().unwrap();
+
+		let sender = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let address = contract_address(&sender, &U256::zero());
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.code_address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(code);
+		params.call_type = CallType::Call;
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let info = EnvInfo::default();
+		let engine = TestEngine::new(5);
+		let mut substate = Substate::new();
+		let mut vm_tracer = StructLogger::toplevel(StructLogOptions {
+			disable_memory: true,
+			disable_stack: false,
+			disable_storage: true,
+			max_steps: Some(2),
+			max_size: None,
+			opcodes: vec![0x60],
+		});
+		let logs = vm_tracer.logs();
+
+		{
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			let output = BytesRef::Fixed(&mut[0u8;0]);
+			ex.call(params, &mut substate, output, &mut NoopTracer, &mut vm_tracer).unwrap();
+		}
+
+		let logs = logs.lock();
+		assert_eq!(logs.len(), 2);
+		assert_eq!(logs[0].pc, 0);
+		assert_eq!(logs[0].gas, U256::from(100000));
+		assert_eq!(logs[1].pc, 2);
+		assert_eq!(logs[1].stack, Some(vec_into![2]));
+		assert_eq!(logs[1].memory, None);
+		assert_eq!(logs[1].storage, None);
+	}
+
 	evm_test!{test_create_contract: test_create_contract_jit, test_create_contract_int}
 	fn test_create_contract(factory: Factory) {
 		// code:
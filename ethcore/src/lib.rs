@@ -126,7 +126,7 @@ mod db;
 mod common;
 mod basic_types;
 mod env_info;
-mod pod_account;
+pub mod pod_account;
 mod state;
 mod account;
 mod account_db;
@@ -146,3 +146,4 @@ mod json_tests;
 
 pub use types::*;
 pub use executive::contract_address;
+pub use blockchain::{inspect, AncientStore};
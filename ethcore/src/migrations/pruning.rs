@@ -0,0 +1,436 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This migration converts a database between pruning algorithms.
+//!
+//! It is executed with `Manager::execute_into`. All columns but the state are copied verbatim.
+//! The state is rebuilt from the oldest block whose state should be kept: the whole state of that block is written
+//! directly to the backing database and every following block is committed through
+//! the target journal database, just like the client would do on import.
+//! The changes of every block are found by walking the state tries of the block and of its parent
+//! side by side, skipping subtries which did not change.
+
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::Arc;
+
+use util::{Bytes, H256, Mutex, HashDB, AsHashDB, MemoryDB, NibbleSlice, Rlp, View, SHA3_NULL_RLP, SHA3_EMPTY, encode};
+use util::journaldb::{self, Algorithm, JournalDB};
+use util::kvdb::{Database, DBTransaction};
+use util::migration::{Config, Error, Migration, Progress};
+use util::trie::node::Node;
+
+use account_db::combine_key;
+use blockchain::{BlockChain, BlockProvider, Config as BlockChainConfig};
+use client::{DB_COL_STATE, DB_COL_EXTRA, DB_NO_OF_COLUMNS, PRUNING_HISTORY_KEY};
+
+/// Reference to a trie node: either by hash or inlined into its parent.
+#[derive(PartialEq)]
+enum NodeRef {
+	Hash(H256),
+	Inline(Bytes),
+}
+
+impl NodeRef {
+	// reference to the root node of a trie. `None` if the trie is empty.
+	fn root(root: &H256) -> Option<NodeRef> {
+		if root == &SHA3_NULL_RLP {
+			None
+		} else {
+			Some(NodeRef::Hash(root.clone()))
+		}
+	}
+
+	// decode a child reference from the raw rlp of a node item. `None` if there is no child.
+	fn from_raw(raw: &[u8]) -> Option<NodeRef> {
+		let rlp = Rlp::new(raw);
+		if rlp.is_data() && rlp.size() == 32 {
+			Some(NodeRef::Hash(rlp.as_val()))
+		} else if rlp.is_list() {
+			Some(NodeRef::Inline(raw.to_vec()))
+		} else {
+			None
+		}
+	}
+}
+
+/// Receives nodes inserted into or removed from the state.
+trait StateChanges {
+	/// Called with the database key and value of an inserted node.
+	fn inserted(&mut self, key: H256, value: &[u8]) -> Result<(), Error>;
+	/// Called with the database key of a removed node.
+	fn removed(&mut self, key: H256);
+}
+
+impl StateChanges for MemoryDB {
+	fn inserted(&mut self, key: H256, value: &[u8]) -> Result<(), Error> {
+		self.emplace(key, value.to_vec());
+		Ok(())
+	}
+
+	fn removed(&mut self, key: H256) {
+		self.remove(&key);
+	}
+}
+
+/// Writes the nodes of a whole state straight into the backing database.
+struct StateWriter<'a> {
+	db: &'a Database,
+	batch: DBTransaction,
+	batch_size: usize,
+	pending: usize,
+	progress: Progress,
+}
+
+impl<'a> StateWriter<'a> {
+	fn new(db: &'a Database, config: &Config) -> Self {
+		StateWriter {
+			db: db,
+			batch: DBTransaction::new(db),
+			batch_size: config.batch_size,
+			pending: 0,
+			progress: Progress::default(),
+		}
+	}
+
+	fn commit(&mut self) -> Result<(), Error> {
+		let batch = mem::replace(&mut self.batch, DBTransaction::new(self.db));
+		self.pending = 0;
+		self.db.write(batch).map_err(Error::Custom)
+	}
+}
+
+impl<'a> StateChanges for StateWriter<'a> {
+	fn inserted(&mut self, key: H256, value: &[u8]) -> Result<(), Error> {
+		self.progress.tick();
+		try!(self.batch.put(DB_COL_STATE, &key, value).map_err(Error::Custom));
+		self.pending += 1;
+		if self.pending == self.batch_size {
+			try!(self.commit());
+		}
+		Ok(())
+	}
+
+	fn removed(&mut self, _key: H256) {}
+}
+
+/// Walks two versions of the state trie and reports the nodes which differ.
+struct StateWalker<'a> {
+	db: &'a HashDB,
+}
+
+impl<'a> StateWalker<'a> {
+	// report all nodes of the state at `new` and not at `old`, and the other way round.
+	fn diff(&self, old: &H256, new: &H256, changes: &mut StateChanges) -> Result<(), Error> {
+		self.diff_trie(NodeRef::root(old), NodeRef::root(new), Vec::new(), None, changes)
+	}
+
+	// load the node and report it as inserted or removed.
+	// storage trie nodes and code are stored under keys combined with the account's address hash.
+	fn load(&self, node: &NodeRef, owner: Option<&H256>, inserted: bool, changes: &mut StateChanges) -> Result<Bytes, Error> {
+		match *node {
+			NodeRef::Inline(ref data) => Ok(data.clone()),
+			NodeRef::Hash(ref hash) => {
+				let key = owner.map_or_else(|| hash.clone(), |owner| combine_key(owner, hash));
+				let data = try!(self.db.get(&key).map(|d| d.to_vec())
+					.ok_or_else(|| Error::Custom(format!("State node {} is missing from the database.", hash))));
+
+				if inserted {
+					try!(changes.inserted(key, &data));
+				} else {
+					changes.removed(key);
+				}
+				Ok(data)
+			}
+		}
+	}
+
+	// walk two nodes placed at the same path in the trie.
+	fn diff_trie(&self, old: Option<NodeRef>, new: Option<NodeRef>, path: Vec<u8>, owner: Option<&H256>, changes: &mut StateChanges) -> Result<(), Error> {
+		if old == new {
+			return Ok(());
+		}
+
+		let mut children: BTreeMap<Vec<u8>, (Option<NodeRef>, Option<NodeRef>)> = BTreeMap::new();
+		let mut values: BTreeMap<Vec<u8>, (Option<Bytes>, Option<Bytes>)> = BTreeMap::new();
+
+		if let Some(old) = old {
+			let data = try!(self.load(&old, owner, false, changes));
+			let (node_children, node_values) = expand(&data, &path);
+			for (child_path, child) in node_children {
+				children.entry(child_path).or_insert((None, None)).0 = Some(child);
+			}
+			for (value_path, value) in node_values {
+				values.entry(value_path).or_insert((None, None)).0 = Some(value);
+			}
+		}
+
+		if let Some(new) = new {
+			let data = try!(self.load(&new, owner, true, changes));
+			let (node_children, node_values) = expand(&data, &path);
+			for (child_path, child) in node_children {
+				children.entry(child_path).or_insert((None, None)).1 = Some(child);
+			}
+			for (value_path, value) in node_values {
+				values.entry(value_path).or_insert((None, None)).1 = Some(value);
+			}
+		}
+
+		for (child_path, (old, new)) in children {
+			try!(self.diff_trie(old, new, child_path, owner, changes));
+		}
+
+		// only values of the account trie refer to further data.
+		if owner.is_none() {
+			for (value_path, (old, new)) in values {
+				try!(self.diff_account(old, new, &value_path, changes));
+			}
+		}
+
+		Ok(())
+	}
+
+	// walk the storage and code of an account.
+	fn diff_account(&self, old: Option<Bytes>, new: Option<Bytes>, path: &[u8], changes: &mut StateChanges) -> Result<(), Error> {
+		if old == new {
+			return Ok(());
+		}
+
+		if path.len() != 64 {
+			return Err(Error::Custom(format!("Invalid account path in the state trie: {:?}.", path)));
+		}
+
+		let address_hash = H256::from_slice(&path.chunks(2).map(|n| n[0] << 4 | n[1]).collect::<Vec<u8>>());
+		let (old_storage, old_code) = account_roots(old.as_ref());
+		let (new_storage, new_code) = account_roots(new.as_ref());
+
+		if old_storage != new_storage {
+			try!(self.diff_trie(NodeRef::root(&old_storage), NodeRef::root(&new_storage), Vec::new(), Some(&address_hash), changes));
+		}
+
+		if old_code != new_code {
+			if old_code != SHA3_EMPTY {
+				try!(self.load(&NodeRef::Hash(old_code), Some(&address_hash), false, changes));
+			}
+			if new_code != SHA3_EMPTY {
+				try!(self.load(&NodeRef::Hash(new_code), Some(&address_hash), true, changes));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+// storage root and code hash of an account, if any.
+fn account_roots(account: Option<&Bytes>) -> (H256, H256) {
+	match account {
+		Some(account) => {
+			let rlp = Rlp::new(account);
+			(rlp.val_at(2), rlp.val_at(3))
+		},
+		None => (SHA3_NULL_RLP, SHA3_EMPTY),
+	}
+}
+
+// path of the node below `path` reached through `partial` key.
+fn extend(path: &[u8], partial: &NibbleSlice) -> Vec<u8> {
+	let mut p = path.to_vec();
+	p.extend((0..partial.len()).map(|i| partial.at(i)));
+	p
+}
+
+// children and values of a node, by their full nibble path.
+fn expand(data: &[u8], path: &[u8]) -> (Vec<(Vec<u8>, NodeRef)>, Vec<(Vec<u8>, Bytes)>) {
+	let mut children = Vec::new();
+	let mut values = Vec::new();
+	match Node::decoded(data) {
+		Node::Empty => {},
+		Node::Leaf(partial, value) => values.push((extend(path, &partial), value.to_vec())),
+		Node::Extension(partial, child) => {
+			if let Some(child) = NodeRef::from_raw(child) {
+				children.push((extend(path, &partial), child));
+			}
+		},
+		Node::Branch(nodes, value) => {
+			for (i, child) in nodes.iter().enumerate() {
+				if let Some(child) = NodeRef::from_raw(child) {
+					let mut p = path.to_vec();
+					p.push(i as u8);
+					children.push((p, child));
+				}
+			}
+			if let Some(value) = value {
+				values.push((path.to_vec(), value.to_vec()));
+			}
+		},
+	}
+	(children, values)
+}
+
+/// Conversion of a database to another pruning algorithm.
+pub struct ToPruning {
+	from: Algorithm,
+	to: Algorithm,
+	history: u64,
+	genesis: Bytes,
+	first: Arc<Mutex<Option<u64>>>,
+}
+
+impl ToPruning {
+	/// Creates a migration from the `from` algorithm to `to`.
+	/// When `to` prunes the state, only the state of the last `history` blocks is kept.
+	/// Only stable algorithms are supported as the target.
+	pub fn new(from: Algorithm, to: Algorithm, history: u64, genesis: Bytes) -> Result<Self, Error> {
+		if !to.is_stable() || from == to {
+			return Err(Error::MigrationImpossible);
+		}
+
+		Ok(ToPruning {
+			from: from,
+			to: to,
+			history: history,
+			genesis: genesis,
+			first: Arc::new(Mutex::new(None)),
+		})
+	}
+
+	/// Returns a handle to the number of the first block which has its state in the new database,
+	/// set once the state is migrated. Older states are not available in the source database
+	/// or are pruned by the target algorithm.
+	pub fn first_block(&self) -> Arc<Mutex<Option<u64>>> {
+		self.first.clone()
+	}
+
+	// rebuild the state of the `source` database in `dest`.
+	// interrupted migration is resumed when given the same destination again.
+	fn migrate_state(&self, source: Arc<Database>, dest: Arc<Database>, config: &Config) -> Result<u64, Error> {
+		let chain = BlockChain::new(BlockChainConfig::default(), &self.genesis, source.clone());
+		let source_state = journaldb::new(source.clone(), self.from, DB_COL_STATE);
+		let mut dest_state = journaldb::new(dest.clone(), self.to, DB_COL_STATE);
+
+		let block = |n| chain.block_hash(n)
+			.and_then(|hash| chain.block_header(&hash).map(|header| (hash, header.state_root().clone())))
+			.ok_or_else(|| Error::Custom(format!("Block #{} is missing from the database.", n)));
+		let has_state = |root: &H256| root == &SHA3_NULL_RLP || source_state.contains(root);
+
+		let best = chain.best_block_number();
+		let lowest = if self.to == Algorithm::Archive { 0 } else { best.saturating_sub(self.history) };
+		let mut first = best;
+		if !has_state(&try!(block(best)).1) {
+			return Err(Error::Custom(format!("State of the best block #{} is missing from the database.", best)));
+		}
+		while first > lowest && has_state(&try!(block(first - 1)).1) {
+			first -= 1;
+		}
+
+		let walker = StateWalker { db: source_state.as_hashdb() };
+		let start = match dest_state.latest_era() {
+			Some(era) if era >= first && era <= best => era + 1,
+			Some(_) => return Err(Error::Custom("Destination database contains an unrelated state.".into())),
+			None => {
+				flushln!("Copying state of block #{}", first);
+				let (hash, root) = try!(block(first));
+				let mut writer = StateWriter::new(&dest, config);
+				try!(walker.diff(&SHA3_NULL_RLP, &root, &mut writer));
+				try!(writer.commit());
+
+				// empty commit marks the state of the first block as complete.
+				let batch = DBTransaction::new(&*dest);
+				try!(dest_state.commit(&batch, first, &hash, None).map_err(|e| Error::Custom(format!("{}", e))));
+				try!(dest.write(batch).map_err(Error::Custom));
+				flushln!("");
+				first + 1
+			},
+		};
+
+		for number in start..(best + 1) {
+			let (hash, root) = try!(block(number));
+			let (_, parent_root) = try!(block(number - 1));
+
+			let mut changes = MemoryDB::new();
+			try!(walker.diff(&parent_root, &root, &mut changes));
+			for (key, (value, rc)) in changes.drain() {
+				if rc > 0 {
+					dest_state.emplace(key, value);
+				} else if rc < 0 {
+					dest_state.remove(&key);
+				}
+			}
+
+			let ancient = if number >= first + self.history {
+				let n = number - self.history;
+				Some((n, try!(block(n)).0))
+			} else {
+				None
+			};
+
+			let batch = DBTransaction::new(&*dest);
+			try!(dest_state.commit(&batch, number, &hash, ancient).map_err(|e| Error::Custom(format!("{}", e))));
+			try!(dest.write(batch).map_err(Error::Custom));
+
+			if number % 1000 == 0 || number == best {
+				flushln!("Rebuilt state of block #{} of #{}", number, best);
+			}
+		}
+
+		Ok(first)
+	}
+}
+
+impl Migration for ToPruning {
+	fn columns(&self) -> Option<u32> { DB_NO_OF_COLUMNS }
+
+	// the conversion works on the consolidated database and keeps its version.
+	fn version(&self) -> u32 { 9 }
+
+	fn migrate(&mut self, source: Arc<Database>, config: &Config, dest: Arc<Database>, col: Option<u32>) -> Result<(), Error> {
+		if col == DB_COL_STATE {
+			let first = try!(self.migrate_state(source, dest, config));
+			*self.first.lock() = Some(first);
+			Ok(())
+		} else {
+			try!(copy_column(&source, &dest, col, config));
+			if col == DB_COL_EXTRA {
+				// the client has to keep the history the state was rebuilt with.
+				let batch = DBTransaction::new(&*dest);
+				if self.to == Algorithm::Archive {
+					try!(batch.delete(DB_COL_EXTRA, PRUNING_HISTORY_KEY).map_err(Error::Custom));
+				} else {
+					try!(batch.put(DB_COL_EXTRA, PRUNING_HISTORY_KEY, &encode(&self.history)).map_err(Error::Custom));
+				}
+				try!(dest.write(batch).map_err(Error::Custom));
+			}
+			Ok(())
+		}
+	}
+}
+
+// copy all items of the column.
+fn copy_column(source: &Database, dest: &Database, col: Option<u32>, config: &Config) -> Result<(), Error> {
+	let mut progress = Progress::default();
+	let mut batch = DBTransaction::new(dest);
+	let mut pending = 0;
+	for (key, value) in source.iter(col) {
+		progress.tick();
+		try!(batch.put(col, &key, &value).map_err(Error::Custom));
+		pending += 1;
+		if pending == config.batch_size {
+			try!(dest.write(mem::replace(&mut batch, DBTransaction::new(dest))).map_err(Error::Custom));
+			pending = 0;
+		}
+	}
+	dest.write(batch).map_err(Error::Custom)
+}
@@ -18,6 +18,7 @@
 //! using an address' hash as opposed to the address itself.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use util::Bytes;
 use util::hash::{Address, FixedHash, H256};
@@ -149,7 +150,7 @@ impl OverlayRecentV7 {
 	// walk all journal entries in the database backwards.
 	// replace all possible inserted/deleted keys with their migrated counterparts
 	// and commit the altered entries.
-	fn migrate_journal(&self, source: &Database, mut batch: Batch, dest: &mut Database) -> Result<(), Error> {
+	fn migrate_journal(&self, source: &Database, mut batch: Batch, dest: &Database) -> Result<(), Error> {
 		if let Some(val) = try!(source.get(None, V7_LATEST_ERA_KEY).map_err(Error::Custom)) {
 			try!(batch.insert(V7_LATEST_ERA_KEY.into(), val.to_owned(), dest));
 
@@ -226,7 +227,7 @@ impl Migration for OverlayRecentV7 {
 	// walk all records in the database, attempting to migrate any possible and
 	// keeping records of those that we do. then migrate the journal using
 	// this information.
-	fn migrate(&mut self, source: &Database, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+	fn migrate(&mut self, source: Arc<Database>, config: &Config, dest: Arc<Database>, col: Option<u32>) -> Result<(), Error> {
 		let mut batch = Batch::new(config, col);
 
 		// check version metadata.
@@ -252,10 +253,10 @@ impl Migration for OverlayRecentV7 {
 				}
 			}
 
-			try!(batch.insert(key, value.into_vec(), dest));
+			try!(batch.insert(key, value.into_vec(), &dest));
 		}
 
-		try!(self.walk_journal(source));
-		self.migrate_journal(source, batch, dest)
+		try!(self.walk_journal(&source));
+		self.migrate_journal(&source, batch, &dest)
 	}
 }
@@ -17,6 +17,7 @@
 
 //! This migration consolidates all databases into single one using Column Families.
 
+use std::sync::Arc;
 use util::{Rlp, RlpStream, View, Stream};
 use util::kvdb::Database;
 use util::migration::{Batch, Config, Error, Migration, Progress};
@@ -55,28 +56,28 @@ impl Migration for ToV9 {
 
 	fn version(&self) -> u32 { 9 }
 
-	fn migrate(&mut self, source: &Database, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+	fn migrate(&mut self, source: Arc<Database>, config: &Config, dest: Arc<Database>, col: Option<u32>) -> Result<(), Error> {
 		let mut batch = Batch::new(config, self.column);
 
 		for (key, value) in source.iter(col) {
 			self.progress.tick();
 			match self.extract {
 				Extract::Header => {
-					try!(batch.insert(key.to_vec(), Rlp::new(&value).at(0).as_raw().to_vec(), dest))
+					try!(batch.insert(key.to_vec(), Rlp::new(&value).at(0).as_raw().to_vec(), &dest))
 				},
 				Extract::Body => {
 					let mut body = RlpStream::new_list(2);
 					let block_rlp = Rlp::new(&value);
 					body.append_raw(block_rlp.at(1).as_raw(), 1);
 					body.append_raw(block_rlp.at(2).as_raw(), 1);
-					try!(batch.insert(key.to_vec(), body.out(), dest))
+					try!(batch.insert(key.to_vec(), body.out(), &dest))
 				},
 				Extract::All => {
-					try!(batch.insert(key.to_vec(), value.to_vec(), dest))
+					try!(batch.insert(key.to_vec(), value.to_vec(), &dest))
 				}
 			}
 		}
 
-		batch.commit(dest)
+		batch.commit(&dest)
 	}
 }
@@ -0,0 +1,322 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracking of transactions submitted through this node.
+
+use std::collections::{BTreeMap, VecDeque};
+use util::Bytes;
+use util::hash::H256;
+use util::rlp::*;
+use transaction::SignedTransaction;
+use header::BlockNumber;
+
+/// Number of finished (mined, replaced, dropped or invalid) transactions kept in the list.
+const MAX_FINISHED: usize = 256;
+
+/// Status of local transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocalTransactionStatus {
+	/// Transaction is in the queue and can be included in block.
+	Pending,
+	/// Transaction is in the queue waiting for transactions with lower nonces.
+	Future,
+	/// Transaction was included in block.
+	Mined {
+		/// Number of the block.
+		block_number: BlockNumber,
+		/// Hash of the block.
+		block_hash: H256,
+	},
+	/// Transaction was replaced by another transaction with the same sender and nonce.
+	Replaced(H256),
+	/// Transaction was dropped from the queue (e.g. because the queue was full).
+	Dropped,
+	/// Transaction was rejected by the queue or found invalid while creating a block.
+	Invalid(String),
+}
+
+impl LocalTransactionStatus {
+	/// Returns true if transaction is still waiting in the queue.
+	pub fn is_queued(&self) -> bool {
+		match *self {
+			LocalTransactionStatus::Pending | LocalTransactionStatus::Future => true,
+			_ => false,
+		}
+	}
+}
+
+/// Local transaction with its current status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalTransaction {
+	/// The transaction.
+	pub transaction: SignedTransaction,
+	/// Current status.
+	pub status: LocalTransactionStatus,
+	/// Number of the best block when transaction was last submitted or broadcast.
+	pub last_broadcast: BlockNumber,
+}
+
+impl Encodable for LocalTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(4);
+		s.append(&self.transaction);
+		s.append(&self.last_broadcast);
+		match self.status {
+			LocalTransactionStatus::Pending => { s.append(&0u8).begin_list(0); },
+			LocalTransactionStatus::Future => { s.append(&1u8).begin_list(0); },
+			LocalTransactionStatus::Mined { ref block_number, ref block_hash } => {
+				s.append(&2u8).begin_list(2).append(block_number).append(block_hash);
+			},
+			LocalTransactionStatus::Replaced(ref by) => { s.append(&3u8).begin_list(1).append(by); },
+			LocalTransactionStatus::Dropped => { s.append(&4u8).begin_list(0); },
+			LocalTransactionStatus::Invalid(ref reason) => { s.append(&5u8).begin_list(1).append(reason); },
+		}
+	}
+}
+
+impl Decodable for LocalTransaction {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let d = decoder.as_rlp();
+		let details = try!(d.at(3));
+		let status = match try!(d.val_at::<u8>(2)) {
+			0 => LocalTransactionStatus::Pending,
+			1 => LocalTransactionStatus::Future,
+			2 => LocalTransactionStatus::Mined {
+				block_number: try!(details.val_at(0)),
+				block_hash: try!(details.val_at(1)),
+			},
+			3 => LocalTransactionStatus::Replaced(try!(details.val_at(0))),
+			4 => LocalTransactionStatus::Dropped,
+			5 => LocalTransactionStatus::Invalid(try!(details.val_at(0))),
+			_ => return Err(DecoderError::Custom("Unknown local transaction status")),
+		};
+		Ok(LocalTransaction {
+			transaction: try!(d.val_at(0)),
+			last_broadcast: try!(d.val_at(1)),
+			status: status,
+		})
+	}
+}
+
+/// Transactions submitted through this node, indexed by hash.
+///
+/// Transactions which are no longer in the queue are kept for inspection,
+/// but only `MAX_FINISHED` most recent ones.
+#[derive(Default)]
+pub struct LocalTransactionsList {
+	transactions: BTreeMap<H256, LocalTransaction>,
+	/// Hashes in order of insertion.
+	order: VecDeque<H256>,
+	/// Whether the list has changed since it was last saved.
+	changed: bool,
+}
+
+impl LocalTransactionsList {
+	/// Starts tracking given transaction (or updates its status if already tracked).
+	pub fn insert(&mut self, transaction: SignedTransaction, status: LocalTransactionStatus, best_block: BlockNumber) {
+		let hash = transaction.hash();
+		let previous = self.transactions.insert(hash, LocalTransaction {
+			transaction: transaction,
+			status: status,
+			last_broadcast: best_block,
+		});
+		if previous.is_none() {
+			self.order.push_back(hash);
+		}
+		self.changed = true;
+		self.prune();
+	}
+
+	/// Changes status of tracked transaction. Untracked transactions are ignored.
+	pub fn set_status(&mut self, hash: &H256, status: LocalTransactionStatus) {
+		if let Some(tx) = self.transactions.get_mut(hash) {
+			if tx.status != status {
+				tx.status = status;
+				self.changed = true;
+			}
+		}
+		self.prune();
+	}
+
+	/// Returns true if given transaction is tracked.
+	pub fn contains(&self, hash: &H256) -> bool {
+		self.transactions.contains_key(hash)
+	}
+
+	/// Returns true if no transactions are tracked.
+	pub fn is_empty(&self) -> bool {
+		self.transactions.is_empty()
+	}
+
+	/// Returns all tracked transactions.
+	pub fn all(&self) -> &BTreeMap<H256, LocalTransaction> {
+		&self.transactions
+	}
+
+	/// Returns transactions which are still queued and were last broadcast at least `blocks` blocks ago.
+	/// Marks returned transactions as broadcast at `best_block`.
+	pub fn take_stale(&mut self, best_block: BlockNumber, blocks: BlockNumber) -> Vec<SignedTransaction> {
+		let stale: Vec<_> = self.transactions.values_mut()
+			.filter(|tx| tx.status.is_queued() && tx.last_broadcast + blocks <= best_block)
+			.map(|tx| {
+				tx.last_broadcast = best_block;
+				tx.transaction.clone()
+			})
+			.collect();
+		self.changed = self.changed || !stale.is_empty();
+		stale
+	}
+
+	/// Returns true if the list has changed since the last call and resets the flag.
+	pub fn take_changed(&mut self) -> bool {
+		::std::mem::replace(&mut self.changed, false)
+	}
+
+	/// Serializes the list for persistence.
+	pub fn to_rlp(&self) -> Bytes {
+		let mut s = RlpStream::new_list(self.order.len());
+		for hash in &self.order {
+			s.append(&self.transactions[hash]);
+		}
+		s.out()
+	}
+
+	/// Deserializes the list saved with `to_rlp`.
+	pub fn from_rlp(bytes: &[u8]) -> Result<Self, DecoderError> {
+		let mut list = LocalTransactionsList::default();
+		let transactions: Vec<LocalTransaction> = try!(UntrustedRlp::new(bytes).as_val());
+		for tx in transactions {
+			let hash = tx.transaction.hash();
+			list.order.push_back(hash);
+			list.transactions.insert(hash, tx);
+		}
+		Ok(list)
+	}
+
+	/// Forgets the oldest finished transactions above the limit.
+	fn prune(&mut self) {
+		let finished = self.transactions.values().filter(|tx| !tx.status.is_queued()).count();
+		if finished <= MAX_FINISHED {
+			return;
+		}
+
+		self.changed = true;
+		let mut to_remove = finished - MAX_FINISHED;
+		let transactions = &mut self.transactions;
+		self.order.retain(|hash| {
+			if to_remove > 0 && !transactions[hash].status.is_queued() {
+				transactions.remove(hash);
+				to_remove -= 1;
+				false
+			} else {
+				true
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::numbers::U256;
+	use util::hash::H256;
+	use util::crypto::KeyPair;
+	use transaction::{Transaction, Action, SignedTransaction};
+	use super::{LocalTransactionsList, LocalTransactionStatus, MAX_FINISHED};
+
+	fn new_tx(nonce: usize) -> SignedTransaction {
+		let keypair = KeyPair::create().unwrap();
+		Transaction {
+			action: Action::Create,
+			value: U256::from(100),
+			data: vec![],
+			gas: U256::from(100_000),
+			gas_price: U256::one(),
+			nonce: nonce.into(),
+		}.sign(keypair.secret())
+	}
+
+	#[test]
+	fn should_roundtrip_through_rlp() {
+		// given
+		let mut list = LocalTransactionsList::default();
+		let (tx1, tx2, tx3) = (new_tx(0), new_tx(1), new_tx(2));
+		list.insert(tx1.clone(), LocalTransactionStatus::Pending, 5);
+		list.insert(tx2.clone(), LocalTransactionStatus::Mined { block_number: 6, block_hash: H256::from(6) }, 5);
+		list.insert(tx3.clone(), LocalTransactionStatus::Invalid("Insufficient balance".into()), 5);
+
+		// when
+		let restored = LocalTransactionsList::from_rlp(&list.to_rlp()).unwrap();
+
+		// then
+		assert_eq!(restored.all(), list.all());
+	}
+
+	#[test]
+	fn should_return_stale_transactions_once() {
+		// given
+		let mut list = LocalTransactionsList::default();
+		let tx = new_tx(0);
+		list.insert(tx.clone(), LocalTransactionStatus::Pending, 10);
+
+		// when
+		let early = list.take_stale(12, 5);
+		let stale = list.take_stale(15, 5);
+		let again = list.take_stale(16, 5);
+
+		// then
+		assert!(early.is_empty());
+		assert_eq!(stale, vec![tx]);
+		assert!(again.is_empty());
+	}
+
+	#[test]
+	fn should_track_changes() {
+		// given
+		let mut list = LocalTransactionsList::default();
+		let tx = new_tx(0);
+
+		// when
+		list.insert(tx.clone(), LocalTransactionStatus::Pending, 10);
+		let inserted = list.take_changed();
+		list.set_status(&tx.hash(), LocalTransactionStatus::Pending);
+		let same_status = list.take_changed();
+		list.set_status(&tx.hash(), LocalTransactionStatus::Dropped);
+		let new_status = list.take_changed();
+
+		// then
+		assert!(inserted);
+		assert!(!same_status);
+		assert!(new_status);
+		assert!(!list.take_changed());
+	}
+
+	#[test]
+	fn should_keep_limited_number_of_finished_transactions() {
+		// given
+		let mut list = LocalTransactionsList::default();
+		let pending = new_tx(0);
+		list.insert(pending.clone(), LocalTransactionStatus::Pending, 0);
+
+		// when
+		for i in 0..MAX_FINISHED + 1 {
+			list.insert(new_tx(i), LocalTransactionStatus::Dropped, 0);
+		}
+
+		// then
+		assert_eq!(list.all().len(), MAX_FINISHED + 1);
+		assert!(list.contains(&pending.hash()));
+	}
+}
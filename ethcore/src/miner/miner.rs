@@ -22,14 +22,19 @@ use util::using_queue::{UsingQueue, GetAction};
 use account_provider::AccountProvider;
 use views::{BlockView, HeaderView};
 use state::State;
-use client::{MiningBlockChainClient, Executive, Executed, EnvInfo, TransactOptions, BlockID, CallAnalytics};
+use client::{MiningBlockChainClient, Executive, Executed, EnvInfo, TransactOptions, BlockID, CallAnalytics, StateOverride};
 use block::{ClosedBlock, IsBlock, Block};
 use error::*;
 use transaction::SignedTransaction;
 use receipt::Receipt;
 use spec::Spec;
 use engines::Engine;
-use miner::{MinerService, MinerStatus, TransactionQueue, AccountDetails, TransactionOrigin};
+use miner::{MinerService, MinerStatus, TransactionQueue, AccountDetails, TransactionOrigin, QueuedTransaction,
+	QueuedTransactionState, LocalTransactionStatus};
+use miner::local_transactions::LocalTransactionsList;
+use client::DB_COL_EXTRA;
+use blockchain::extras::LOCAL_TRANSACTIONS_KEY;
+use header::BlockNumber;
 use miner::work_notify::WorkPoster;
 use client::TransactionImportResult;
 use miner::price_info::PriceInfo;
@@ -69,6 +74,8 @@ pub struct MinerOptions {
 	pub work_queue_size: usize,
 	/// Can we submit two different solutions for the same block and expect both to result in an import?
 	pub enable_resubmission: bool,
+	/// Number of blocks after which a local transaction that is still not mined is broadcast to all peers again.
+	pub tx_rebroadcast_blocks: u64,
 }
 
 impl Default for MinerOptions {
@@ -84,6 +91,7 @@ impl Default for MinerOptions {
 			reseal_min_period: Duration::from_secs(2),
 			work_queue_size: 20,
 			enable_resubmission: true,
+			tx_rebroadcast_blocks: 10,
 		}
 	}
 }
@@ -167,6 +175,7 @@ struct SealingWork {
 pub struct Miner {
 	// NOTE [ToDr]  When locking always lock in this order!
 	transaction_queue: Arc<Mutex<TransactionQueue>>,
+	local_transactions: Mutex<LocalTransactionsList>,
 	sealing_work: Mutex<SealingWork>,
 
 	// for sealing...
@@ -182,6 +191,7 @@ pub struct Miner {
 	accounts: Option<Arc<AccountProvider>>,
 	work_poster: Option<WorkPoster>,
 	gas_pricer: Mutex<GasPricer>,
+	local_transactions_db: RwLock<Option<Arc<KeyValueDB>>>,
 }
 
 impl Miner {
@@ -189,6 +199,7 @@ impl Miner {
 	pub fn with_spec(spec: &Spec) -> Miner {
 		Miner {
 			transaction_queue: Arc::new(Mutex::new(TransactionQueue::new())),
+			local_transactions: Mutex::new(LocalTransactionsList::default()),
 			options: Default::default(),
 			next_allowed_reseal: Mutex::new(Instant::now()),
 			sealing_block_last_request: Mutex::new(0),
@@ -200,6 +211,7 @@ impl Miner {
 			engine: spec.engine.clone(),
 			work_poster: None,
 			gas_pricer: Mutex::new(GasPricer::new_fixed(20_000_000_000u64.into())),
+			local_transactions_db: RwLock::new(None),
 		}
 	}
 
@@ -209,6 +221,7 @@ impl Miner {
 		let txq = Arc::new(Mutex::new(TransactionQueue::with_limits(options.tx_queue_size, options.tx_gas_limit)));
 		Arc::new(Miner {
 			transaction_queue: txq,
+			local_transactions: Mutex::new(LocalTransactionsList::default()),
 			next_allowed_reseal: Mutex::new(Instant::now()),
 			sealing_block_last_request: Mutex::new(0),
 			sealing_work: Mutex::new(SealingWork{queue: UsingQueue::new(options.work_queue_size), enabled: options.force_sealing || !options.new_work_notify.is_empty()}),
@@ -220,9 +233,96 @@ impl Miner {
 			engine: spec.engine.clone(),
 			work_poster: work_poster,
 			gas_pricer: Mutex::new(gas_pricer),
+			local_transactions_db: RwLock::new(None),
 		})
 	}
 
+	/// Sets the database used to persist local transactions and re-imports transactions
+	/// which were still queued when the node was stopped.
+	pub fn restore_local_transactions(&self, chain: &MiningBlockChainClient, db: Arc<KeyValueDB>) {
+		let saved = match db.get(DB_COL_EXTRA, &LOCAL_TRANSACTIONS_KEY) {
+			Ok(Some(bytes)) => LocalTransactionsList::from_rlp(&bytes).unwrap_or_else(|e| {
+				warn!(target: "miner", "Error decoding saved local transactions: {:?}", e);
+				LocalTransactionsList::default()
+			}),
+			Ok(None) => LocalTransactionsList::default(),
+			Err(e) => {
+				warn!(target: "miner", "Error reading saved local transactions: {}", e);
+				LocalTransactionsList::default()
+			},
+		};
+		*self.local_transactions_db.write() = Some(db);
+
+		let to_import = saved.all().values()
+			.filter(|tx| tx.status.is_queued())
+			.map(|tx| tx.transaction.clone())
+			.collect::<Vec<_>>();
+		*self.local_transactions.lock() = saved;
+		if to_import.is_empty() {
+			return;
+		}
+
+		info!(target: "miner", "Re-importing {} local transactions.", to_import.len());
+		let best_block = chain.chain_info().best_block_number;
+		{
+			let mut transaction_queue = self.transaction_queue.lock();
+			let results = self.add_transactions_to_queue(chain, to_import.clone(), TransactionOrigin::Local, &mut transaction_queue);
+			let mut local_transactions = self.local_transactions.lock();
+			for (tx, result) in to_import.into_iter().zip(results.iter()) {
+				local_transactions.insert(tx, local_status(result), best_block);
+			}
+		}
+		self.save_local_transactions();
+	}
+
+	/// Returns local transactions which were not mined within `tx_rebroadcast_blocks`
+	/// since they were last broadcast and marks them as broadcast now.
+	pub fn local_transactions_to_rebroadcast(&self, best_block: BlockNumber) -> Vec<SignedTransaction> {
+		self.local_transactions.lock().take_stale(best_block, self.options.tx_rebroadcast_blocks)
+	}
+
+	/// Updates status of queued local transactions according to the queue content.
+	/// Returns `true` if any status has changed.
+	fn update_local_transactions(&self, transaction_queue: &TransactionQueue) -> bool {
+		let mut local_transactions = self.local_transactions.lock();
+		let queued = local_transactions.all().iter()
+			.filter(|&(_, tx)| tx.status.is_queued())
+			.map(|(hash, tx)| (*hash, tx.transaction.sender(), tx.transaction.nonce, tx.status.clone()))
+			.collect::<Vec<_>>();
+
+		let mut changed = false;
+		for (hash, sender, nonce, old_status) in queued {
+			let status = match transaction_queue.transaction_state(&hash) {
+				Some(QueuedTransactionState::Current) => LocalTransactionStatus::Pending,
+				Some(QueuedTransactionState::Future) => LocalTransactionStatus::Future,
+				None => match sender.ok().and_then(|sender| transaction_queue.find_by_sender_nonce(&sender, &nonce)) {
+					Some(replacement) => LocalTransactionStatus::Replaced(replacement),
+					None => LocalTransactionStatus::Dropped,
+				},
+			};
+			if status != old_status {
+				local_transactions.set_status(&hash, status);
+				changed = true;
+			}
+		}
+		changed
+	}
+
+	/// Writes local transactions to the database (if set), unless they have not changed since last written.
+	fn save_local_transactions(&self) {
+		if let Some(ref db) = *self.local_transactions_db.read() {
+			let mut local_transactions = self.local_transactions.lock();
+			if !local_transactions.take_changed() {
+				return;
+			}
+			let batch = DBTransaction::new(&**db);
+			let _ = batch.put(DB_COL_EXTRA, &LOCAL_TRANSACTIONS_KEY, &local_transactions.to_rlp());
+			if let Err(e) = db.write(batch) {
+				warn!(target: "miner", "Error saving local transactions: {}", e);
+			}
+		}
+	}
+
 	fn forced_sealing(&self) -> bool {
 		self.options.force_sealing || !self.options.new_work_notify.is_empty()
 	}
@@ -285,7 +385,7 @@ impl Miner {
 			(transactions, open_block, last_work_hash)
 		};
 
-		let mut invalid_transactions = HashSet::new();
+		let mut invalid_transactions = HashMap::new();
 		let block_number = open_block.block().fields().header.number();
 		// TODO: push new uncles, too.
 		for tx in transactions {
@@ -307,7 +407,7 @@ impl Miner {
 				// already have transaction - ignore
 				Err(Error::Transaction(TransactionError::AlreadyImported)) => {},
 				Err(e) => {
-					invalid_transactions.insert(hash);
+					invalid_transactions.insert(hash, format!("{}", e));
 					debug!(target: "miner",
 						   "Error adding transaction to block: number={}. transaction_hash={:?}, Error: {:?}",
 						   block_number, hash, e);
@@ -323,11 +423,16 @@ impl Miner {
 			balance: chain.latest_balance(a),
 		};
 
-		{
-			let mut queue = self.transaction_queue.lock();
-			for hash in invalid_transactions.into_iter() {
-				queue.remove_invalid(&hash, &fetch_account);
+		if !invalid_transactions.is_empty() {
+			{
+				let mut queue = self.transaction_queue.lock();
+				let mut local_transactions = self.local_transactions.lock();
+				for (hash, reason) in invalid_transactions.into_iter() {
+					queue.remove_invalid(&hash, &fetch_account);
+					local_transactions.set_status(&hash, LocalTransactionStatus::Invalid(reason));
+				}
 			}
+			self.save_local_transactions();
 		}
 
 		if !block.transactions().is_empty() {
@@ -459,7 +564,7 @@ impl MinerService for Miner {
 		}
 	}
 
-	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
+	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics, state_override: &StateOverride) -> Result<Executed, CallError> {
 		let sealing_work = self.sealing_work.lock();
 		match sealing_work.queue.peek_last_ref() {
 			Some(work) => {
@@ -479,6 +584,7 @@ impl MinerService for Miner {
 				};
 				// that's just a copy of the state.
 				let mut state = block.state().clone();
+				state.apply_override(state_override);
 				let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
 
 				let sender = try!(t.sender().map_err(|e| {
@@ -500,7 +606,7 @@ impl MinerService for Miner {
 				Ok(ret)
 			},
 			None => {
-				chain.call(t, BlockID::Latest, analytics)
+				chain.call(t, BlockID::Latest, analytics, state_override)
 			}
 		}
 	}
@@ -603,12 +709,16 @@ impl MinerService for Miner {
 		transactions: Vec<SignedTransaction>
 	) -> Vec<Result<TransactionImportResult, Error>> {
 
-		let results = {
+		let (results, local_changed) = {
 			let mut transaction_queue = self.transaction_queue.lock();
-			self.add_transactions_to_queue(
+			let results = self.add_transactions_to_queue(
 				chain, transactions, TransactionOrigin::External, &mut transaction_queue
-			)
+			);
+			(results, self.update_local_transactions(&transaction_queue))
 		};
+		if local_changed {
+			self.save_local_transactions();
+		}
 
 		if !results.is_empty() && self.options.reseal_on_external_tx &&	self.tx_reseal_allowed() {
 			// --------------------------------------------------------------------------
@@ -633,8 +743,11 @@ impl MinerService for Miner {
 			// Be sure to release the lock before we call enable_and_prepare_sealing
 			let mut transaction_queue = self.transaction_queue.lock();
 			let import = self.add_transactions_to_queue(
-				chain, vec![transaction], TransactionOrigin::Local, &mut transaction_queue
+				chain, vec![transaction.clone()], TransactionOrigin::Local, &mut transaction_queue
 			).pop().unwrap();
+			// other local transactions might have been replaced or dropped
+			self.update_local_transactions(&transaction_queue);
+			self.local_transactions.lock().insert(transaction, local_status(&import), chain.chain_info().best_block_number);
 
 			match import {
 				Ok(ref res) => {
@@ -649,6 +762,7 @@ impl MinerService for Miner {
 			}
 			import
 		};
+		self.save_local_transactions();
 
 		// --------------------------------------------------------------------------
 		// | NOTE Code below requires transaction_queue and sealing_work locks.     |
@@ -733,6 +847,43 @@ impl MinerService for Miner {
 		self.transaction_queue.lock().last_nonce(address)
 	}
 
+	fn queued_transactions(&self) -> Vec<QueuedTransaction> {
+		self.transaction_queue.lock().queued_transactions()
+	}
+
+	fn transaction_rejection(&self, hash: &H256) -> Option<String> {
+		self.transaction_queue.lock().rejection(hash)
+	}
+
+	fn remove_local_transaction(&self, chain: &MiningBlockChainClient, hash: &H256) -> bool {
+		let fetch_account = |a: &Address| AccountDetails {
+			nonce: chain.latest_nonce(a),
+			balance: chain.latest_balance(a),
+		};
+		let removed = {
+			let mut transaction_queue = self.transaction_queue.lock();
+			let removed = transaction_queue.remove_local(hash, &fetch_account);
+			self.update_local_transactions(&transaction_queue);
+			removed
+		};
+		if removed {
+			self.save_local_transactions();
+			// the pending block may contain the removed transaction.
+			self.update_sealing(chain);
+		}
+		removed
+	}
+
+	fn local_transactions(&self) -> BTreeMap<H256, LocalTransactionStatus> {
+		self.local_transactions.lock().all().iter()
+			.map(|(hash, tx)| (*hash, tx.status.clone()))
+			.collect()
+	}
+
+	fn prioritise_local_transaction(&self, hash: &H256) -> bool {
+		self.transaction_queue.lock().prioritise_local(hash)
+	}
+
 	fn update_sealing(&self, chain: &MiningBlockChainClient) {
 		trace!(target: "miner", "update_sealing");
 		let requires_reseal = {
@@ -865,6 +1016,39 @@ impl MinerService for Miner {
 			});
 		}
 
+		// Finally update status of local transactions
+		let has_local = !self.local_transactions.lock().is_empty();
+		if has_local {
+			{
+				let mut local_transactions = self.local_transactions.lock();
+				for hash in retracted {
+					let block = chain.block(BlockID::Hash(*hash)).expect("Expected in-chain blocks.");
+					for tx_hash in BlockView::new(&block).transaction_hashes() {
+						if local_transactions.contains(&tx_hash) {
+							// will be corrected below according to the queue
+							local_transactions.set_status(&tx_hash, LocalTransactionStatus::Pending);
+						}
+					}
+				}
+				for hash in enacted {
+					let block = chain.block(BlockID::Hash(*hash)).expect("Expected in-chain blocks.");
+					let block = BlockView::new(&block);
+					let block_number = block.header_view().number();
+					for tx_hash in block.transaction_hashes() {
+						local_transactions.set_status(&tx_hash, LocalTransactionStatus::Mined {
+							block_number: block_number,
+							block_hash: *hash,
+						});
+					}
+				}
+			}
+			{
+				let transaction_queue = self.transaction_queue.lock();
+				self.update_local_transactions(&transaction_queue);
+			}
+			self.save_local_transactions();
+		}
+
 		if enacted.len() > 0 {
 			// --------------------------------------------------------------------------
 			// | NOTE Code below requires transaction_queue and sealing_work locks.     |
@@ -875,6 +1059,15 @@ impl MinerService for Miner {
 	}
 }
 
+/// Status of local transaction after import to the queue.
+fn local_status(result: &Result<TransactionImportResult, Error>) -> LocalTransactionStatus {
+	match *result {
+		Ok(TransactionImportResult::Current) => LocalTransactionStatus::Pending,
+		Ok(TransactionImportResult::Future) => LocalTransactionStatus::Future,
+		Err(ref e) => LocalTransactionStatus::Invalid(format!("{}", e)),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -882,8 +1075,10 @@ mod tests {
 	use super::super::MinerService;
 	use super::*;
 	use util::*;
-	use client::{TestBlockChainClient, EachBlockWith};
+	use client::{TestBlockChainClient, EachBlockWith, DB_NO_OF_COLUMNS};
 	use client::{TransactionImportResult};
+	use miner::LocalTransactionStatus;
+	use devtools::RandomTempPath;
 	use types::transaction::{Transaction, Action};
 	use block::*;
 	use spec::Spec;
@@ -933,6 +1128,7 @@ mod tests {
 				pending_set: PendingSet::AlwaysSealing,
 				work_queue_size: 5,
 				enable_resubmission: true,
+				tx_rebroadcast_blocks: 10,
 			},
 			GasPricer::new_fixed(0u64.into()),
 			&Spec::new_test(),
@@ -970,6 +1166,37 @@ mod tests {
 		assert_eq!(miner.enable_and_prepare_sealing(&client), false);
 	}
 
+	#[test]
+	fn should_restore_local_transactions_from_database() {
+		// given
+		let temp = RandomTempPath::new();
+		let db = Arc::new(Database::open(&DatabaseConfig::with_columns(DB_NO_OF_COLUMNS), temp.as_str()).unwrap());
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		miner.restore_local_transactions(&client, db.clone());
+		let transaction = {
+			let keypair = KeyPair::create().unwrap();
+			Transaction {
+				action: Action::Create,
+				value: U256::zero(),
+				data: "3331600055".from_hex().unwrap(),
+				gas: U256::from(100_000),
+				gas_price: U256::zero(),
+				nonce: U256::zero(),
+			}.sign(keypair.secret())
+		};
+		let hash = transaction.hash();
+		miner.import_own_transaction(&client, transaction).unwrap();
+
+		// when
+		let restarted = self::miner();
+		restarted.restore_local_transactions(&client, db);
+
+		// then
+		assert_eq!(restarted.local_transactions().get(&hash), Some(&LocalTransactionStatus::Pending));
+		assert_eq!(restarted.all_transactions().len(), 1);
+	}
+
 	#[test]
 	fn should_import_external_transaction() {
 		// given
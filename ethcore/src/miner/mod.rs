@@ -44,17 +44,19 @@
 mod miner;
 mod external;
 mod transaction_queue;
+mod local_transactions;
 mod work_notify;
 mod price_info;
 
-pub use self::transaction_queue::{TransactionQueue, AccountDetails, TransactionOrigin};
+pub use self::transaction_queue::{TransactionQueue, AccountDetails, TransactionOrigin, QueuedTransaction, QueuedTransactionState};
 pub use self::miner::{Miner, MinerOptions, PendingSet, GasPricer, GasPriceCalibratorOptions};
 pub use self::external::{ExternalMiner, ExternalMinerService};
+pub use self::local_transactions::{LocalTransaction, LocalTransactionStatus};
 pub use client::TransactionImportResult;
 
 use std::collections::BTreeMap;
 use util::{H256, U256, Address, Bytes};
-use client::{MiningBlockChainClient, Executed, CallAnalytics};
+use client::{MiningBlockChainClient, Executed, CallAnalytics, StateOverride};
 use block::ClosedBlock;
 use receipt::Receipt;
 use error::{Error, CallError};
@@ -149,6 +151,22 @@ pub trait MinerService : Send + Sync {
 	/// Returns highest transaction nonce for given address.
 	fn last_nonce(&self, address: &Address) -> Option<U256>;
 
+	/// Get a list of all transactions in the queue (both current and future) with their queue details.
+	fn queued_transactions(&self) -> Vec<QueuedTransaction>;
+
+	/// Returns reason of recent rejection or removal of given transaction from the queue.
+	fn transaction_rejection(&self, hash: &H256) -> Option<String>;
+
+	/// Removes local transaction from the queue. Returns `false` if there is no such local transaction.
+	fn remove_local_transaction(&self, chain: &MiningBlockChainClient, hash: &H256) -> bool;
+
+	/// Moves local transaction ahead of transactions with the same nonce height.
+	/// Returns `false` if there is no such local transaction.
+	fn prioritise_local_transaction(&self, hash: &H256) -> bool;
+
+	/// Returns status of transactions submitted through this node.
+	fn local_transactions(&self) -> BTreeMap<H256, LocalTransactionStatus>;
+
 	/// Is it currently sealing?
 	fn is_sealing(&self) -> bool;
 
@@ -162,7 +180,7 @@ pub trait MinerService : Send + Sync {
 	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> U256;
 
 	/// Call into contract code using pending state.
-	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError>;
+	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics, state_override: &StateOverride) -> Result<Executed, CallError>;
 
 	/// Get storage value in pending state.
 	fn storage_at(&self, chain: &MiningBlockChainClient, address: &Address, position: &H256) -> H256;
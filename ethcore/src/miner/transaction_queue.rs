@@ -84,13 +84,14 @@
 use std::default::Default;
 use std::cmp::{Ordering};
 use std::cmp;
-use std::collections::{HashMap, BTreeSet};
+use std::collections::{HashMap, BTreeSet, VecDeque};
 use util::numbers::{Uint, U256};
 use util::hash::{Address, H256};
 use util::table::Table;
 use transaction::*;
 use error::{Error, TransactionError};
 use client::TransactionImportResult;
+use time::get_time;
 
 /// Transaction origin
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -135,6 +136,8 @@ struct TransactionOrder {
 	hash: H256,
 	/// Origin of the transaction
 	origin: TransactionOrigin,
+	/// Transaction was prioritised by the node operator
+	prioritised: bool,
 }
 
 
@@ -145,6 +148,7 @@ impl TransactionOrder {
 			gas_price: tx.transaction.gas_price,
 			hash: tx.hash(),
 			origin: tx.origin,
+			prioritised: tx.prioritised,
 		}
 	}
 
@@ -179,6 +183,11 @@ impl Ord for TransactionOrder {
 			return self.origin.cmp(&b.origin);
 		}
 
+		// Then prioritised local transactions
+		if self.prioritised != b.prioritised {
+			return b.prioritised.cmp(&self.prioritised);
+		}
+
 		// Then compare gas_prices
 		let a_gas = self.gas_price;
 		let b_gas = b.gas_price;
@@ -197,6 +206,10 @@ struct VerifiedTransaction {
 	transaction: SignedTransaction,
 	/// transaction origin
 	origin: TransactionOrigin,
+	/// Unix timestamp of insertion to the queue
+	insertion_time: u64,
+	/// Transaction was prioritised by the node operator
+	prioritised: bool,
 }
 
 impl VerifiedTransaction {
@@ -205,6 +218,8 @@ impl VerifiedTransaction {
 		Ok(VerifiedTransaction {
 			transaction: transaction,
 			origin: origin,
+			insertion_time: get_time().sec as u64,
+			prioritised: false,
 		})
 	}
 
@@ -289,6 +304,19 @@ impl TransactionSet {
 		None
 	}
 
+	/// Re-inserts transaction with given `(sender, nonce)` with priority flag set.
+	/// Returns `false` if there is no such transaction in this set.
+	fn prioritise(&mut self, sender: &Address, nonce: &U256) -> bool {
+		match self.drop(sender, nonce) {
+			Some(mut order) => {
+				order.prioritised = true;
+				self.insert(*sender, *nonce, order);
+				true
+			},
+			None => false,
+		}
+	}
+
 	/// Drop all transactions.
 	fn clear(&mut self) {
 		self.by_priority.clear();
@@ -311,6 +339,30 @@ pub struct TransactionQueueStatus {
 	pub future: usize,
 }
 
+/// State of transaction in the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuedTransactionState {
+	/// Transaction is ready to be included in block
+	Current,
+	/// Transaction is waiting for transactions with lower nonces
+	Future,
+}
+
+/// Transaction in the queue with its queue details
+#[derive(Debug, Clone)]
+pub struct QueuedTransaction {
+	/// Transaction
+	pub transaction: SignedTransaction,
+	/// Transaction origin
+	pub origin: TransactionOrigin,
+	/// Unix timestamp of insertion to the queue
+	pub insertion_time: u64,
+	/// Transaction was prioritised by the node operator
+	pub prioritised: bool,
+	/// Queue the transaction is in
+	pub state: QueuedTransactionState,
+}
+
 /// Details of account
 pub struct AccountDetails {
 	/// Most recent account nonce
@@ -323,6 +375,9 @@ pub struct AccountDetails {
 /// Transactions with `gas > (gas_limit + gas_limit * Factor(in percents))` are not imported to the queue.
 const GAS_LIMIT_HYSTERESIS: usize = 10; // %
 
+/// Number of most recent rejection reasons kept by the queue.
+const REJECTIONS_LIMIT: usize = 1024;
+
 /// `TransactionQueue` implementation
 pub struct TransactionQueue {
 	/// Gas Price threshold for transactions that can be imported to this queue (defaults to 0)
@@ -339,6 +394,10 @@ pub struct TransactionQueue {
 	by_hash: HashMap<H256, VerifiedTransaction>,
 	/// Last nonce of transaction in current (to quickly check next expected transaction)
 	last_nonces: HashMap<Address, U256>,
+	/// Reasons of recently rejected transactions
+	rejections: HashMap<H256, String>,
+	/// Hashes of recently rejected transactions, oldest first
+	rejections_order: VecDeque<H256>,
 }
 
 impl Default for TransactionQueue {
@@ -375,6 +434,8 @@ impl TransactionQueue {
 			future: future,
 			by_hash: HashMap::new(),
 			last_nonces: HashMap::new(),
+			rejections: HashMap::new(),
+			rejections_order: VecDeque::new(),
 		}
 	}
 
@@ -430,6 +491,16 @@ impl TransactionQueue {
 
 	/// Add signed transaction to queue to be verified and imported
 	pub fn add<T>(&mut self, tx: SignedTransaction, fetch_account: &T, origin: TransactionOrigin) -> Result<TransactionImportResult, Error>
+	where T: Fn(&Address) -> AccountDetails {
+		let hash = tx.hash();
+		let result = self.verify_and_import(tx, fetch_account, origin);
+		if let Err(ref e) = result {
+			self.note_rejection(hash, format!("{}", e));
+		}
+		result
+	}
+
+	fn verify_and_import<T>(&mut self, tx: SignedTransaction, fetch_account: &T, origin: TransactionOrigin) -> Result<TransactionImportResult, Error>
 	where T: Fn(&Address) -> AccountDetails {
 
 		trace!(target: "txqueue", "Importing: {:?}", tx.hash());
@@ -546,6 +617,48 @@ impl TransactionQueue {
 		}
 	}
 
+	/// Removes local transaction identified by hash from queue.
+	/// Returns `false` if there is no such transaction or it was not submitted locally.
+	pub fn remove_local<T>(&mut self, transaction_hash: &H256, fetch_account: &T) -> bool
+		where T: Fn(&Address) -> AccountDetails {
+
+		match self.by_hash.get(transaction_hash) {
+			Some(tx) if tx.origin == TransactionOrigin::Local => {},
+			_ => return false,
+		}
+		self.remove_invalid(transaction_hash, fetch_account);
+		self.note_rejection(*transaction_hash, "Removed from the queue by the node operator".into());
+		true
+	}
+
+	/// Moves local transaction identified by hash ahead of other transactions with the same nonce height.
+	/// Returns `false` if there is no such transaction or it was not submitted locally.
+	pub fn prioritise_local(&mut self, transaction_hash: &H256) -> bool {
+		let (sender, nonce) = match self.by_hash.get_mut(transaction_hash) {
+			Some(tx) if tx.origin == TransactionOrigin::Local => {
+				tx.prioritised = true;
+				(tx.sender(), tx.nonce())
+			},
+			_ => return false,
+		};
+		self.current.prioritise(&sender, &nonce) || self.future.prioritise(&sender, &nonce)
+	}
+
+	/// Returns reason of recent rejection or removal of given transaction (if any).
+	pub fn rejection(&self, transaction_hash: &H256) -> Option<String> {
+		self.rejections.get(transaction_hash).cloned()
+	}
+
+	fn note_rejection(&mut self, hash: H256, reason: String) {
+		if self.rejections.insert(hash, reason).is_none() {
+			self.rejections_order.push_back(hash);
+		}
+		while self.rejections_order.len() > REJECTIONS_LIMIT {
+			let oldest = self.rejections_order.pop_front().expect("Length is greater than limit; qed");
+			self.rejections.remove(&oldest);
+		}
+	}
+
 	/// Update height of all transactions in future transactions set.
 	fn update_future(&mut self, sender: &Address, current_nonce: U256) {
 		// We need to drain all transactions for current sender from future and reinsert them with updated height
@@ -595,6 +708,24 @@ impl TransactionQueue {
 			.collect()
 	}
 
+	/// Returns all transactions in the queue (both `current` and `future`) ordered by priority.
+	pub fn queued_transactions(&self) -> Vec<QueuedTransaction> {
+		let current = self.current.by_priority.iter().map(|t| (t, QueuedTransactionState::Current));
+		let future = self.future.by_priority.iter().map(|t| (t, QueuedTransactionState::Future));
+		current.chain(future)
+			.map(|(t, state)| {
+				let tx = self.by_hash.get(&t.hash).expect("All transactions in `current` and `future` are always included in `by_hash`");
+				QueuedTransaction {
+					transaction: tx.transaction.clone(),
+					origin: tx.origin,
+					insertion_time: tx.insertion_time,
+					prioritised: tx.prioritised,
+					state: state,
+				}
+			})
+			.collect()
+	}
+
 	/// Returns hashes of all transactions from current, ordered by priority.
 	pub fn pending_hashes(&self) -> Vec<H256> {
 		self.current.by_priority
@@ -613,6 +744,23 @@ impl TransactionQueue {
 		match self.by_hash.get(hash) { Some(transaction_ref) => Some(transaction_ref.transaction.clone()), None => None }
 	}
 
+	/// Returns the queue given transaction is in (if any).
+	pub fn transaction_state(&self, hash: &H256) -> Option<QueuedTransactionState> {
+		self.by_hash.get(hash).map(|tx| {
+			match self.current.by_address.get(&tx.sender(), &tx.nonce()) {
+				Some(_) => QueuedTransactionState::Current,
+				None => QueuedTransactionState::Future,
+			}
+		})
+	}
+
+	/// Returns hash of transaction from given sender with given nonce (if any is in the queue).
+	pub fn find_by_sender_nonce(&self, sender: &Address, nonce: &U256) -> Option<H256> {
+		self.current.by_address.get(sender, nonce)
+			.or_else(|| self.future.by_address.get(sender, nonce))
+			.map(|order| order.hash)
+	}
+
 	/// Removes all elements (in any state) from the queue
 	pub fn clear(&mut self) {
 		self.current.clear();
@@ -1668,4 +1816,69 @@ mod test {
 		assert_eq!(txq.current.by_priority.len(), 3);
 	}
 
+
+	#[test]
+	fn should_list_queued_transactions_with_state() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let (tx1, tx2) = new_txs(U256::from(2));
+		txq.add(tx1.clone(), &default_nonce, TransactionOrigin::Local).unwrap();
+		txq.add(tx2.clone(), &default_nonce, TransactionOrigin::External).unwrap();
+
+		// when
+		let queued = txq.queued_transactions();
+
+		// then
+		assert_eq!(queued.len(), 2);
+		assert_eq!(queued[0].transaction.hash(), tx1.hash());
+		assert_eq!(queued[0].origin, TransactionOrigin::Local);
+		assert_eq!(queued[0].state, QueuedTransactionState::Current);
+		assert_eq!(queued[1].transaction.hash(), tx2.hash());
+		assert_eq!(queued[1].state, QueuedTransactionState::Future);
+	}
+
+	#[test]
+	fn should_remember_rejection_reason() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let tx = new_tx();
+		let hash = tx.hash();
+		let last_nonce = tx.nonce + U256::one();
+		let fetch_last_nonce = |_a: &Address| AccountDetails{ nonce: last_nonce, balance: !U256::zero() };
+
+		// when
+		let res = txq.add(tx, &fetch_last_nonce, TransactionOrigin::External);
+
+		// then
+		assert!(res.is_err());
+		assert!(txq.rejection(&hash).is_some());
+		assert_eq!(txq.rejection(&H256::zero()), None);
+	}
+
+	#[test]
+	fn should_remove_and_prioritise_only_local_transactions() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let (cheap, _) = new_similar_txs();
+		let (_, expensive) = new_similar_txs();
+		let external = new_tx();
+		txq.add(cheap.clone(), &default_nonce, TransactionOrigin::Local).unwrap();
+		txq.add(expensive.clone(), &default_nonce, TransactionOrigin::Local).unwrap();
+		txq.add(external.clone(), &default_nonce, TransactionOrigin::External).unwrap();
+		assert_eq!(txq.top_transactions()[0].hash(), expensive.hash());
+
+		// when
+		let external_prioritised = txq.prioritise_local(&external.hash());
+		let local_prioritised = txq.prioritise_local(&cheap.hash());
+
+		// then
+		assert!(!external_prioritised);
+		assert!(local_prioritised);
+		assert_eq!(txq.top_transactions()[0].hash(), cheap.hash());
+		assert!(txq.queued_transactions()[0].prioritised);
+		assert!(!txq.remove_local(&external.hash(), &default_nonce));
+		assert!(txq.remove_local(&cheap.hash(), &default_nonce));
+		assert_eq!(txq.status().pending, 2);
+		assert!(txq.rejection(&cheap.hash()).is_some());
+	}
 }
@@ -229,17 +229,17 @@ impl Spec {
 	}
 
 	/// Ensure that the given state DB has the trie nodes in for the genesis state.
-	pub fn ensure_db_good(&self, db: &mut HashDB) -> Result<bool, Box<TrieError>> {
+	pub fn ensure_db_good(&self, db: &mut HashDB, factory: &TrieFactory) -> Result<bool, Box<TrieError>> {
 		if !db.contains(&self.state_root()) {
 			let mut root = H256::new();
 			{
-				let mut t = SecTrieDBMut::new(db, &mut root);
+				let mut t = factory.create(db, &mut root);
 				for (address, account) in self.genesis_state.get().iter() {
 					try!(t.insert(address.as_slice(), &account.rlp()));
 				}
 			}
 			for (address, account) in self.genesis_state.get().iter() {
-				account.insert_additional(&mut AccountDBMut::new(db, address));
+				account.insert_additional(&mut AccountDBMut::new(db, address), factory);
 			}
 			assert!(db.contains(&self.state_root()));
 			Ok(true)
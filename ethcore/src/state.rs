@@ -25,6 +25,7 @@ use trace::FlatTrace;
 use pod_account::*;
 use pod_state::{self, PodState};
 use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 
 /// Used to return information about an `State::apply` operation.
 pub struct ApplyOutcome {
@@ -213,6 +214,11 @@ impl State {
 		self.require(a, false).inc_nonce()
 	}
 
+	/// Set the nonce of account `a`.
+	pub fn set_nonce(&mut self, a: &Address, nonce: U256) {
+		self.require(a, false).set_nonce(nonce)
+	}
+
 	/// Mutate storage of account `a` so that it is `value` for `key`.
 	pub fn set_storage(&mut self, a: &Address, key: H256, value: H256) {
 		self.require(a, false).set_storage(key, value)
@@ -229,6 +235,29 @@ impl State {
 		self.require_or_from(a, true, || Account::new_contract(0.into(), self.account_start_nonce), |_|{}).reset_code(code);
 	}
 
+	/// Override balance, nonce, code and storage of accounts as given in `state_override`.
+	pub fn apply_override(&mut self, state_override: &StateOverride) {
+		for (address, account) in &state_override.accounts {
+			if let Some(balance) = account.balance {
+				let current = self.balance(address);
+				if balance > current {
+					self.add_balance(address, &(balance - current));
+				} else {
+					self.sub_balance(address, &(current - balance));
+				}
+			}
+			if let Some(nonce) = account.nonce {
+				self.set_nonce(address, nonce);
+			}
+			if let Some(ref code) = account.code {
+				self.reset_code(address, code.clone());
+			}
+			for (key, value) in &account.storage {
+				self.set_storage(address, key.clone(), value.clone());
+			}
+		}
+	}
+
 	/// Execute a given transaction.
 	/// This will change the state accordingly.
 	pub fn apply(&mut self, env_info: &EnvInfo, engine: &Engine, vm_factory: &EvmFactory, t: &SignedTransaction, tracing: bool) -> ApplyResult {
@@ -319,6 +348,24 @@ impl State {
 		}))
 	}
 
+	/// Populate a `PodAccount` of account `a` with all of its storage read from the trie.
+	/// Storage keys are the original ones only if the state is backed by a fat database,
+	/// otherwise they are the hashes of the keys.
+	pub fn pod_account(&self, a: &Address) -> Option<PodAccount> {
+		self.ensure_cached(a, true, |acc| acc.as_ref().map(|acc| {
+			let mut pod = PodAccount::from_account(acc);
+			if let Some(storage_root) = acc.storage_root() {
+				let account_db = AccountDB::new(self.db.as_hashdb(), a);
+				let trie = self.trie_factory.readonly(&account_db, storage_root).expect(SEC_TRIE_DB_UNWRAP_STR);
+				for (key, value) in trie.iter() {
+					let value: U256 = decode(value);
+					pod.storage.entry(H256::from_slice(&key)).or_insert_with(|| value.into());
+				}
+			}
+			pod
+		}))
+	}
+
 	fn query_pod(&mut self, query: &PodState) {
 		for (ref address, ref pod_account) in query.get() {
 			self.ensure_cached(address, true, |a| {
@@ -431,6 +478,8 @@ use env_info::*;
 use spec::*;
 use transaction::*;
 use util::log::init_log;
+use util::trie::{TrieFactory, TrieSpec};
+use types::state_override::{StateOverride, AccountOverride};
 use trace::trace;
 use trace::FlatTrace;
 use types::executed::CallType;
@@ -1388,6 +1437,59 @@ fn remove_from_database() {
 	assert_eq!(state.nonce(&a), U256::from(0u64));
 }
 
+#[test]
+fn pod_account_from_database() {
+	let a = Address::zero();
+	let temp = RandomTempPath::new();
+	let trie_factory = TrieFactory::new(TrieSpec::Fat);
+	let (root, db) = {
+		let mut state = State::new(get_temp_journal_db_in(temp.as_path()), U256::from(0u8), trie_factory.clone());
+		state.add_balance(&a, &U256::from(69u64));
+		state.set_storage(&a, H256::from(&U256::from(1u64)), H256::from(&U256::from(2u64)));
+		state.init_code(&a, vec![0x60, 0x00]);
+		state.commit().unwrap();
+		state.drop()
+	};
+
+	let state = State::from_existing(db, root, U256::from(0u8), trie_factory).unwrap();
+	let pod = state.pod_account(&a).unwrap();
+	assert_eq!(pod.balance, U256::from(69u64));
+	assert_eq!(pod.code, Some(vec![0x60, 0x00]));
+	assert_eq!(pod.storage, map![H256::from(&U256::from(1u64)) => H256::from(&U256::from(2u64))]);
+	assert_eq!(state.pod_account(&address_from_u64(1u64)), None);
+}
+
+#[test]
+fn apply_state_override() {
+	let mut state_result = get_temp_state();
+	let mut state = state_result.reference_mut();
+	let a = Address::zero();
+	let b = address_from_u64(1u64);
+	state.add_balance(&a, &U256::from(69u64));
+	state.add_balance(&b, &U256::from(1u64));
+	state.commit().unwrap();
+
+	let mut accounts = BTreeMap::new();
+	accounts.insert(a.clone(), AccountOverride {
+		balance: Some(U256::from(42u64)),
+		nonce: Some(U256::from(7u64)),
+		code: Some(vec![0x60, 0x00]),
+		storage: map![H256::from(&U256::from(1u64)) => H256::from(&U256::from(2u64))],
+	});
+	accounts.insert(b.clone(), AccountOverride {
+		balance: Some(U256::from(100u64)),
+		..Default::default()
+	});
+	state.apply_override(&StateOverride { accounts: accounts });
+
+	assert_eq!(state.balance(&a), U256::from(42u64));
+	assert_eq!(state.nonce(&a), U256::from(7u64));
+	assert_eq!(state.code(&a), Some(vec![0x60, 0x00]));
+	assert_eq!(state.storage_at(&a, &H256::from(&U256::from(1u64))), H256::from(&U256::from(2u64)));
+	assert_eq!(state.balance(&b), U256::from(100u64));
+	assert_eq!(state.nonce(&b), U256::from(0u64));
+}
+
 #[test]
 fn alter_balance() {
 	let mut state_result = get_temp_state();
@@ -15,12 +15,26 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use io::IoChannel;
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockID};
+use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, DatabaseBackend, BlockID, BlockImportError, CallAnalytics, DB_NO_OF_COLUMNS, DB_COL_STATE, DB_COL_HEADERS, DB_COL_BODIES, DB_COL_EXTRA, DB_COL_TRACE};
+use views::{HeaderView, BlockView};
+use types::account_diff::Diff;
 use block::IsBlock;
+use blockchain::extras::{TransactionAddress, BlockDetails, BlockReceipts};
+use receipt::Receipt;
+use error::{BlockError, ImportError};
+use db::Key;
+use inspect::{self, Fault, Entry};
 use tests::helpers::*;
 use common::*;
 use devtools::*;
 use miner::Miner;
+use migrations::ToPruning;
+use util::migration::Manager as MigrationManager;
+use spec::Spec;
+use util::journaldb::{self, Algorithm};
+use util::trie::TrieSpec;
+use pod_account::PodAccount;
+use pod_state::PodState;
 
 #[test]
 fn imports_from_empty() {
@@ -31,6 +45,186 @@ fn imports_from_empty() {
 	client.flush_queue();
 }
 
+#[test]
+fn imports_into_in_memory_database() {
+	let spec = get_test_spec();
+	let mut config = ClientConfig::default();
+	config.db_backend = DatabaseBackend::InMemory;
+	// the path is not used by the in-memory database.
+	let client = Client::new(config, &spec, Path::new(""), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+	for block in get_good_dummy_block_seq(4) {
+		client.import_block(block).unwrap();
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	assert_eq!(client.chain_info().best_block_number, 4);
+	assert!(client.block_body(BlockID::Number(3)).is_some());
+	assert!(client.state_at(BlockID::Latest).is_some());
+}
+
+#[test]
+fn keeps_configured_pruning_history() {
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let mut config = ClientConfig::default();
+	config.history = 8;
+	let client = Client::new(config, &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+	for block in get_good_dummy_block_seq(12) {
+		client.import_block(block).unwrap();
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	assert_eq!(client.chain_info().best_block_number, 12);
+	assert_eq!(client.oldest_state(), Some(5));
+	assert!(client.state_at(BlockID::Number(4)).is_none());
+	assert!(client.state_at(BlockID::Number(5)).is_some());
+}
+
+#[test]
+fn can_revert_chain() {
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let mut config = ClientConfig::default();
+	config.history = 8;
+	let client = Client::new(config, &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+	let blocks = get_good_dummy_block_seq(12);
+	for block in blocks.iter().cloned() {
+		client.import_block(block).unwrap();
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	// state of block #3 is already pruned.
+	assert!(client.revert(3).is_err());
+	assert_eq!(client.chain_info().best_block_number, 12);
+
+	assert_eq!(client.revert(8).unwrap().len(), 4);
+	assert_eq!(client.chain_info().best_block_number, 8);
+	assert!(client.block(BlockID::Number(9)).is_none());
+	assert!(client.state_at(BlockID::Latest).is_some());
+
+	// reverted blocks can be imported again.
+	for block in blocks.into_iter().skip(8) {
+		client.import_block(block).unwrap();
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert_eq!(client.chain_info().best_block_number, 12);
+}
+
+#[test]
+fn can_backup_database() {
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let client = Client::new(ClientConfig::default(), &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+	for block in get_good_dummy_block_seq(4) {
+		client.import_block(block).unwrap();
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	let backup_dir = RandomTempPath::create_dir();
+	let backup = client.backup(backup_dir.as_path()).unwrap();
+	assert_eq!(backup.number, 4);
+	assert_eq!(backup.hash, client.chain_info().best_block_hash);
+	assert!(backup_dir.as_path().join("backup.json").exists());
+	// the target must be empty.
+	assert!(client.backup(backup_dir.as_path()).is_err());
+
+	let restored = Client::new(ClientConfig::default(), &spec, &backup_dir.as_path().join("db"), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+	assert_eq!(restored.chain_info().best_block_hash, backup.hash);
+	assert!(restored.state_at(BlockID::Latest).is_some());
+}
+
+#[test]
+fn can_enumerate_accounts_of_fat_database() {
+	let mut storage = BTreeMap::new();
+	storage.insert(H256::from(1), H256::from(42));
+	let mut accounts = BTreeMap::new();
+	accounts.insert(Address::from(0x10), PodAccount::new(U256::from(100), U256::from(1), vec![0x60, 0x00], storage));
+	accounts.insert(Address::from(0x20), PodAccount::new(U256::from(5), U256::zero(), vec![], BTreeMap::new()));
+	let mut spec = get_test_spec();
+	spec.set_genesis_state(PodState::from(accounts.clone()));
+
+	// preimages are not kept unless the database is fat.
+	let dir = RandomTempPath::new();
+	let client = Client::new(ClientConfig::default(), &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+	assert!(client.for_each_account(BlockID::Latest, true, true, |_, _| {}).is_err());
+
+	let dir = RandomTempPath::new();
+	let mut config = ClientConfig::default();
+	config.pruning = Algorithm::Archive;
+	config.trie_spec = TrieSpec::Fat;
+	let client = Client::new(config, &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+
+	let mut exported = BTreeMap::new();
+	client.for_each_account(BlockID::Latest, true, true, |address, account| { exported.insert(address, account); }).unwrap();
+	assert_eq!(exported, accounts);
+
+	let mut exported = Vec::new();
+	client.for_each_account(BlockID::Latest, false, false, |_, account| exported.push(account)).unwrap();
+	assert!(exported.iter().all(|account| account.code.is_none() && account.storage.is_empty()));
+	assert_eq!(exported.len(), 2);
+}
+
+#[test]
+fn can_import_trusted_blocks_into_history_gap() {
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let blocks = get_good_dummy_block_seq(7);
+	{
+		let client = Client::new(ClientConfig::default(), &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+		for block in blocks.iter().take(6).cloned() {
+			client.import_block(block).unwrap();
+		}
+		client.flush_queue();
+		client.import_verified_blocks();
+	}
+
+	// remove block #3, as if the history was never synced.
+	let hash = BlockView::new(&blocks[2]).header_view().sha3();
+	{
+		let db = Database::open(&DatabaseConfig::with_columns(DB_NO_OF_COLUMNS), dir.as_path().to_str().unwrap()).unwrap();
+		let batch = db.transaction();
+		batch.delete(DB_COL_HEADERS, &hash).unwrap();
+		batch.delete(DB_COL_BODIES, &hash).unwrap();
+		batch.delete(DB_COL_EXTRA, &Key::<H256>::key(&3u64)).unwrap();
+		batch.delete(DB_COL_EXTRA, &Key::<BlockDetails>::key(&hash)).unwrap();
+		batch.delete(DB_COL_EXTRA, &Key::<BlockReceipts>::key(&hash)).unwrap();
+		db.write(batch).unwrap();
+	}
+
+	let client = Client::new(ClientConfig::default(), &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+	assert!(client.block(BlockID::Number(3)).is_none());
+
+	// blocks which do not fill a gap are rejected.
+	match client.import_trusted_block(blocks[1].clone(), vec![]) {
+		Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {},
+		e => panic!("Unexpected result: {:?}", e),
+	}
+	assert!(client.import_trusted_block(blocks[6].clone(), vec![]).is_err());
+
+	// a block with the right parent which is not the parent of block #4 is rejected.
+	let mut header = BlockView::new(&blocks[2]).header();
+	let timestamp = header.timestamp() + 1;
+	header.set_timestamp(timestamp);
+	assert!(client.import_trusted_block(create_test_block(&header), vec![]).is_err());
+	assert!(client.block(BlockID::Number(3)).is_none());
+
+	let receipts = vec![Receipt::new(H256::from(1), U256::from(21000), vec![])];
+	match client.import_trusted_block(blocks[2].clone(), receipts) {
+		Err(BlockImportError::Block(BlockError::InvalidReceiptsRoot(_))) => {},
+		e => panic!("Unexpected result: {:?}", e),
+	}
+
+	assert_eq!(client.import_trusted_block(blocks[2].clone(), vec![]).unwrap(), hash);
+	assert_eq!(client.block(BlockID::Number(3)), Some(blocks[2].clone()));
+	assert!(client.block_receipts(&hash).is_some());
+	assert_eq!(client.chain_info().best_block_number, 6);
+}
+
 #[test]
 fn returns_state_root_basic() {
 	let client_result = generate_dummy_client(6);
@@ -129,6 +323,40 @@ fn can_generate_gas_price_statistics() {
 	assert_eq!(s, vec_into![0, 1, 3, 5, 7, 9, 11, 13, 15]);
 }
 
+#[test]
+#[cfg_attr(feature="dev", allow(useless_vec))]
+fn can_replay_block_transactions() {
+	let client_result = generate_dummy_client_with_data(3, 2, &vec_into![1]);
+	let client = client_result.reference();
+	let analytics = CallAnalytics { transaction_tracing: true, vm_tracing: false, state_diffing: true };
+
+	let executed = client.replay_block_transactions(BlockID::Number(2), analytics).unwrap();
+	assert_eq!(executed.len(), 2);
+	assert!(executed.iter().all(|e| e.trace.len() == 1 && e.state_diff.is_some()));
+
+	let header = client.block_header(BlockID::Number(2)).unwrap();
+	assert_eq!(executed[1].cumulative_gas_used, HeaderView::new(&header).gas_used());
+
+	assert!(client.replay_block_transactions(BlockID::Number(0), analytics).is_err());
+}
+
+#[test]
+fn can_diff_state_between_blocks() {
+	let client_result = generate_dummy_client_with_data(3, 2, &vec_into![1]);
+	let client = client_result.reference();
+	let sender = KeyPair::from_secret("".sha3()).unwrap().address();
+	let unknown = Address::from(0xdead);
+
+	let diff = client.state_diff(BlockID::Number(1), BlockID::Number(3), vec![sender.clone(), unknown.clone()]).unwrap();
+	assert_eq!(diff.raw.len(), 1);
+	assert_eq!(diff.raw[&sender].nonce, Diff::Changed(2.into(), 6.into()));
+
+	let diff = client.state_diff(BlockID::Number(3), BlockID::Number(3), vec![sender.clone()]).unwrap();
+	assert!(diff.raw.is_empty());
+
+	assert!(client.state_diff(BlockID::Number(1), BlockID::Number(4), vec![sender]).is_none());
+}
+
 #[test]
 fn can_handle_long_fork() {
 	let client_result = generate_dummy_client(1200);
@@ -158,3 +386,102 @@ fn can_mine() {
 
 	assert_eq!(*b.block().header().parent_hash(), BlockView::new(&dummy_blocks[0]).header_view().sha3());
 }
+
+#[test]
+fn can_migrate_between_pruning_algorithms() {
+	fn nonces(client: &Client, address: &Address) -> Vec<Option<U256>> {
+		(0..4).map(|n| client.nonce(address, BlockID::Number(n))).collect()
+	}
+
+	let mut client_result = generate_dummy_client_with_data(3, 2, &vec_into![1]);
+	let address = KeyPair::from_secret("".sha3()).unwrap().address();
+	let expected = nonces(client_result.reference(), &address);
+	drop(client_result.result.take());
+
+	let spec = Spec::new_null();
+	let migrate = |from, to, history, source_path: &Path, dest_path: &Path| {
+		let migration = ToPruning::new(from, to, history, spec.genesis_block()).unwrap();
+		let first = migration.first_block();
+		let mut manager = MigrationManager::new(Default::default());
+		manager.add_migration(migration).unwrap();
+		manager.execute_into(source_path, DB_NO_OF_COLUMNS, dest_path).unwrap();
+		let first = *first.lock();
+		first.unwrap()
+	};
+	let open = |pruning, path: &Path| {
+		let mut config = ClientConfig::default();
+		config.pruning = pruning;
+		Client::new(config, &spec, path, Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap()
+	};
+
+	// all states are kept in archive.
+	let archive_path = RandomTempPath::new();
+	assert_eq!(migrate(Algorithm::OverlayRecent, Algorithm::Archive, 1200, client_result._temp.as_path().as_path(), archive_path.as_path().as_path()), 0);
+	assert_eq!(nonces(&*open(Algorithm::Archive, archive_path.as_path().as_path()), &address), expected);
+
+	// only recent states are kept when pruning.
+	let fast_path = RandomTempPath::new();
+	assert_eq!(migrate(Algorithm::Archive, Algorithm::OverlayRecent, 1, archive_path.as_path().as_path(), fast_path.as_path().as_path()), 2);
+	let client = open(Algorithm::OverlayRecent, fast_path.as_path().as_path());
+	assert_eq!(nonces(&*client, &address), vec![None, None, expected[2], expected[3]]);
+	assert_eq!(client.chain_info().best_block_number, 3);
+}
+
+#[test]
+fn can_verify_and_repair_database() {
+	let mut client_result = generate_dummy_client_with_data(3, 2, &vec_into![1]);
+	let block = client_result.reference().block(BlockID::Number(1)).unwrap();
+	let tx_hash = BlockView::new(&block).transaction_hashes()[0].clone();
+	let hashes: Vec<H256> = (1..4).map(|n| client_result.reference().block_hash(BlockID::Number(n)).unwrap()).collect();
+	drop(client_result.result.take());
+
+	let path = client_result._temp.as_path().as_path();
+	let db = Arc::new(Database::open(&DatabaseConfig::with_columns(DB_NO_OF_COLUMNS), path.to_str().unwrap()).unwrap());
+	let state = journaldb::new(db.clone(), Algorithm::OverlayRecent, DB_COL_STATE);
+
+	let report = inspect::verify(&db, None, state.as_hashdb());
+	assert_eq!(report.best_block, 3);
+	assert_eq!(report.faults, vec![]);
+
+	let batch = db.transaction();
+	batch.put(DB_COL_EXTRA, &Key::<H256>::key(&1u64), &encode(&H256::from(1))).unwrap();
+	batch.delete(DB_COL_EXTRA, &Key::<H256>::key(&2u64)).unwrap();
+	batch.delete(DB_COL_EXTRA, &Key::<TransactionAddress>::key(&tx_hash)).unwrap();
+	db.write(batch).unwrap();
+
+	assert_eq!(inspect::verify(&db, None, state.as_hashdb()).faults, vec![
+		Fault::Invalid(Entry::BlockHash(1)),
+		Fault::Missing(Entry::TransactionAddress(tx_hash)),
+		Fault::Missing(Entry::BlockHash(2)),
+	]);
+
+	assert_eq!(inspect::repair(&db, None), Ok(3));
+	assert_eq!(inspect::verify(&db, None, state.as_hashdb()).faults, vec![]);
+
+	let stats = inspect::stats(&db, DB_NO_OF_COLUMNS);
+	assert_eq!(stats.len(), 5);
+	// genesis and three imported blocks
+	assert_eq!(stats[1].1.keys, 4);
+
+	// bodies and receipts of blocks before the history start are not checked.
+	let hash = BlockView::new(&block).header_view().sha3();
+	let batch = db.transaction();
+	batch.delete(DB_COL_BODIES, &hash).unwrap();
+	batch.delete(DB_COL_EXTRA, &Key::<BlockReceipts>::key(&hash)).unwrap();
+	batch.put(DB_COL_EXTRA, b"history", &encode(&2u64)).unwrap();
+	db.write(batch).unwrap();
+
+	assert_eq!(inspect::verify(&db, None, state.as_hashdb()).faults, vec![]);
+	assert_eq!(inspect::repair(&db, None), Ok(3));
+
+	// traces are checked once tracing is enabled, unless they have not been built yet.
+	let batch = db.transaction();
+	batch.put(DB_COL_TRACE, b"enabled", &[0x1]).unwrap();
+	db.write(batch).unwrap();
+	assert_eq!(inspect::verify(&db, None, state.as_hashdb()).faults, hashes.iter().map(|h| Fault::Missing(Entry::Traces(h.clone()))).collect::<Vec<_>>());
+
+	let batch = db.transaction();
+	batch.put(DB_COL_TRACE, b"missing", &encode(&vec![1u64, 3])).unwrap();
+	db.write(batch).unwrap();
+	assert_eq!(inspect::verify(&db, None, state.as_hashdb()).faults, vec![]);
+}
@@ -138,7 +138,7 @@ pub fn generate_dummy_client_with_spec_and_data<F>(get_test_spec: F, block_numbe
 
 	let mut db_result = get_temp_journal_db();
 	let mut db = db_result.take();
-	test_spec.ensure_db_good(db.as_hashdb_mut()).unwrap();
+	test_spec.ensure_db_good(db.as_hashdb_mut(), &Default::default()).unwrap();
 	let vm_factory = Default::default();
 	let genesis_header = test_spec.genesis_header();
 
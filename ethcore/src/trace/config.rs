@@ -72,6 +72,9 @@ pub struct Config {
 	pub pref_cache_size: usize,
 	/// Max cache-size.
 	pub max_cache_size: usize,
+	/// Allows tracing to be turned on for a database synced without it.
+	/// Traces of already imported blocks are then marked as missing and have to be built.
+	pub build_missing: bool,
 }
 
 impl Default for Config {
@@ -84,6 +87,7 @@ impl Default for Config {
 			},
 			pref_cache_size: 15 * 1024 * 1024,
 			max_cache_size: 20 * 1024 * 1024,
+			build_missing: false,
 		}
 	}
 }
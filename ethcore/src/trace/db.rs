@@ -20,8 +20,10 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use bloomchain::{Number, Config as BloomConfig};
 use bloomchain::group::{BloomGroupDatabase, BloomGroupChain, GroupPosition, BloomGroup};
-use util::{H256, H264, Database, DBTransaction, RwLock, HeapSizeOf};
+use util::{H256, H264, KeyValueDB, DBTransaction, RwLock, HeapSizeOf};
+use util::rlp::{Rlp, RlpStream, Stream, View};
 use header::BlockNumber;
+use basic_types::LogBloom;
 use trace::{LocalizedTrace, Config, Switch, Filter, Database as TraceDatabase, ImportRequest, DatabaseExtras, Error};
 use db::{Key, Writable, Readable, CacheUpdatePolicy};
 use blooms;
@@ -30,6 +32,7 @@ use client::DB_COL_TRACE;
 use cache_manager::CacheManager;
 
 const TRACE_DB_VER: &'static [u8] = b"1.0";
+const MISSING_TRACES_KEY: &'static [u8] = b"missing";
 
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature="dev", allow(enum_variant_names))]
@@ -107,11 +110,13 @@ pub struct TraceDB<T> where T: DatabaseExtras {
 	blooms: RwLock<HashMap<TraceGroupPosition, blooms::BloomGroup>>,
 	cache_manager: RwLock<CacheManager<CacheID>>,
 	// db
-	tracesdb: Arc<Database>,
+	tracesdb: Arc<KeyValueDB>,
 	// config,
 	bloom_config: BloomConfig,
 	// tracing enabled
 	enabled: bool,
+	// range of blocks which traces still have to be built
+	missing: RwLock<Option<(BlockNumber, BlockNumber)>>,
 	// extras
 	extras: Arc<T>,
 }
@@ -126,7 +131,7 @@ impl<T> BloomGroupDatabase for TraceDB<T> where T: DatabaseExtras {
 
 impl<T> TraceDB<T> where T: DatabaseExtras {
 	/// Creates new instance of `TraceDB`.
-	pub fn new(config: Config, tracesdb: Arc<Database>, extras: Arc<T>) -> Result<Self, Error> {
+	pub fn new(config: Config, tracesdb: Arc<KeyValueDB>, extras: Arc<T>) -> Result<Self, Error> {
 		// check if in previously tracing was enabled
 		let old_tracing = match tracesdb.get(DB_COL_TRACE, b"enabled").unwrap() {
 			Some(ref value) if value as &[u8] == &[0x1] => Switch::On,
@@ -135,16 +140,31 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 			None => Switch::Auto,
 		};
 
-		let enabled = try!(old_tracing.turn_to(config.enabled));
+		let old_missing = tracesdb.get(DB_COL_TRACE, MISSING_TRACES_KEY).unwrap()
+			.map(|value| {
+				let rlp = Rlp::new(&value);
+				(rlp.val_at(0), rlp.val_at(1))
+			});
+
+		let (enabled, missing) = match (old_tracing, config.enabled) {
+			// tracing is turned on for a database synced without it,
+			// traces of all already imported blocks are missing
+			(Switch::Off, Switch::On) if config.build_missing => {
+				let best_block = extras.best_block_number();
+				(true, if best_block > 0 { Some((1, best_block)) } else { None })
+			},
+			(old, new) => if try!(old.turn_to(new)) { (true, old_missing) } else { (false, None) },
+		};
 
 		let encoded_tracing = match enabled {
 			true => [0x1],
 			false => [0x0]
 		};
 
-		let batch = DBTransaction::new(&tracesdb);
+		let batch = DBTransaction::new(&*tracesdb);
 		batch.put(DB_COL_TRACE, b"enabled", &encoded_tracing).unwrap();
 		batch.put(DB_COL_TRACE, b"version", TRACE_DB_VER).unwrap();
+		Self::write_missing(&batch, missing);
 		tracesdb.write(batch).unwrap();
 
 		let db = TraceDB {
@@ -154,12 +174,70 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 			tracesdb: tracesdb,
 			bloom_config: config.blooms,
 			enabled: enabled,
+			missing: RwLock::new(missing),
 			extras: extras,
 		};
 
 		Ok(db)
 	}
 
+	fn write_missing(batch: &DBTransaction, missing: Option<(BlockNumber, BlockNumber)>) {
+		match missing {
+			Some((from, to)) => {
+				let mut stream = RlpStream::new_list(2);
+				stream.append(&from).append(&to);
+				batch.put(DB_COL_TRACE, MISSING_TRACES_KEY, &stream.out()).unwrap();
+			},
+			None => batch.delete(DB_COL_TRACE, MISSING_TRACES_KEY).unwrap(),
+		}
+	}
+
+	fn set_missing(&self, batch: &DBTransaction, missing: Option<(BlockNumber, BlockNumber)>) {
+		let missing = missing.and_then(|(from, to)| if from > to { None } else { Some((from, to)) });
+		Self::write_missing(batch, missing);
+		*self.missing.write() = missing;
+	}
+
+	/// Returns the inclusive range of blocks which traces are missing from the database.
+	/// Those have to be built before their traces can be queried.
+	pub fn missing_traces(&self) -> Option<(BlockNumber, BlockNumber)> {
+		*self.missing.read()
+	}
+
+	/// Skips building traces of missing blocks preceding `from`.
+	pub fn skip_missing_traces(&self, batch: &DBTransaction, from: BlockNumber) {
+		let missing = self.missing_traces().map(|(old_from, to)| (::std::cmp::max(old_from, from), to));
+		self.set_missing(batch, missing);
+	}
+
+	/// Inserts traces of the first block missing from the database.
+	pub fn import_missing(&self, batch: &DBTransaction, request: ImportRequest) {
+		let (from, to) = self.missing_traces().expect("import_missing is called only when traces are missing; qed");
+		assert_eq!(from, request.block_number, "Missing traces have to be imported in order.");
+		self.insert(batch, request);
+		self.set_missing(batch, Some((from + 1, to)));
+	}
+
+	/// Removes traces of given blocks and trace blooms of canonical blocks after `number` up to `best`.
+	pub fn revert(&self, batch: &DBTransaction, number: BlockNumber, best: BlockNumber, removed: &[H256]) {
+		for hash in removed {
+			batch.delete(DB_COL_TRACE, &Key::<FlatBlockTraces>::key(hash)).unwrap();
+		}
+
+		if self.tracing_enabled() {
+			let chain = BloomGroupChain::new(self.bloom_config, self);
+			for (position, group) in chain.replace(&(number as Number + 1..best as Number), vec![]) {
+				batch.write(DB_COL_TRACE, &TraceGroupPosition::from(position), &blooms::BloomGroup::from(group));
+			}
+		}
+
+		let missing = self.missing_traces().map(|(from, to)| (from, ::std::cmp::min(to, number)));
+		self.set_missing(batch, missing);
+
+		self.traces.write().clear();
+		self.blooms.write().clear();
+	}
+
 	fn cache_size(&self) -> usize {
 		let traces = self.traces.read().heap_size_of_children();
 		let blooms = self.blooms.read().heap_size_of_children();
@@ -201,6 +279,63 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		self.traces(block_hash).map(Into::into)
 	}
 
+	fn insert(&self, batch: &DBTransaction, request: ImportRequest) {
+		// at first, let's insert new block traces
+		{
+			// note_used must be called before locking traces to avoid cache/traces deadlock on garbage collection
+			self.note_used(CacheID::Trace(request.block_hash.clone()));
+			let mut traces = self.traces.write();
+			// it's important to use overwrite here,
+			// cause this value might be queried by hash later
+			batch.write_with_cache(DB_COL_TRACE, traces.deref_mut(), request.block_hash, request.traces, CacheUpdatePolicy::Overwrite);
+		}
+
+		// now let's rebuild the blooms
+		{
+			let range_start = request.block_number as Number + 1 - request.enacted.len();
+			let range_end = range_start + request.retracted;
+			let replaced_range = range_start..range_end;
+			let mut missing = Vec::new();
+			let enacted_blooms = request.enacted
+				.iter()
+				.enumerate()
+				// side chain blocks imported before tracing was enabled have no traces.
+				// they are given an empty bloom and recorded as missing, so that their traces are built later.
+				.map(|(i, block_hash)| match self.traces(block_hash) {
+					Some(block_traces) => block_traces.bloom(),
+					None => {
+						missing.push((range_start + i) as BlockNumber);
+						LogBloom::default()
+					},
+				})
+				.map(blooms::Bloom::from)
+				.map(Into::into)
+				.collect();
+
+			if let (Some(first), Some(last)) = (missing.first().cloned(), missing.last().cloned()) {
+				warn!(target: "trace", "Traces of enacted blocks #{}..#{} are missing.", first, last);
+				let missing = match self.missing_traces() {
+					Some((from, to)) => (::std::cmp::min(from, first), ::std::cmp::max(to, last)),
+					None => (first, last),
+				};
+				self.set_missing(batch, Some(missing));
+			}
+
+			let chain = BloomGroupChain::new(self.bloom_config, self);
+			let trace_blooms = chain.replace(&replaced_range, enacted_blooms);
+			let blooms_to_insert = trace_blooms.into_iter()
+				.map(|p| (From::from(p.0), From::from(p.1)))
+				.collect::<HashMap<TraceGroupPosition, blooms::BloomGroup>>();
+
+			// note_used must be called before locking blooms to avoid cache/traces deadlock on garbage collection
+			for key in blooms_to_insert.keys() {
+				self.note_used(CacheID::Bloom(key.clone()));
+			}
+			let mut blooms = self.blooms.write();
+			batch.extend_with_cache(DB_COL_TRACE, blooms.deref_mut(), blooms_to_insert, CacheUpdatePolicy::Remove);
+		}
+	}
+
 	fn matching_block_traces(
 		&self,
 		filter: &Filter,
@@ -262,45 +397,7 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			return;
 		}
 
-		// at first, let's insert new block traces
-		{
-			// note_used must be called before locking traces to avoid cache/traces deadlock on garbage collection
-			self.note_used(CacheID::Trace(request.block_hash.clone()));
-			let mut traces = self.traces.write();
-			// it's important to use overwrite here,
-			// cause this value might be queried by hash later
-			batch.write_with_cache(DB_COL_TRACE, traces.deref_mut(), request.block_hash, request.traces, CacheUpdatePolicy::Overwrite);
-		}
-
-		// now let's rebuild the blooms
-		{
-			let range_start = request.block_number as Number + 1 - request.enacted.len();
-			let range_end = range_start + request.retracted;
-			let replaced_range = range_start..range_end;
-			let enacted_blooms = request.enacted
-				.iter()
-				// all traces are expected to be found here. That's why `expect` has been used
-				// instead of `filter_map`. If some traces haven't been found, it meens that
-				// traces database is corrupted or incomplete.
-				.map(|block_hash| self.traces(block_hash).expect("Traces database is incomplete."))
-				.map(|block_traces| block_traces.bloom())
-				.map(blooms::Bloom::from)
-				.map(Into::into)
-				.collect();
-
-			let chain = BloomGroupChain::new(self.bloom_config, self);
-			let trace_blooms = chain.replace(&replaced_range, enacted_blooms);
-			let blooms_to_insert = trace_blooms.into_iter()
-				.map(|p| (From::from(p.0), From::from(p.1)))
-				.collect::<HashMap<TraceGroupPosition, blooms::BloomGroup>>();
-
-			// note_used must be called before locking blooms to avoid cache/traces deadlock on garbage collection
-			for key in blooms_to_insert.keys() {
-				self.note_used(CacheID::Bloom(key.clone()));
-			}
-			let mut blooms = self.blooms.write();
-			batch.extend_with_cache(DB_COL_TRACE, blooms.deref_mut(), blooms_to_insert, CacheUpdatePolicy::Remove);
-		}
+		self.insert(batch, request);
 	}
 
 	fn trace(&self, block_number: BlockNumber, tx_position: usize, trace_position: Vec<usize>) -> Option<LocalizedTrace> {
@@ -383,9 +480,10 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			)
 	}
 
-	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace> {
+	fn filter(&self, filter: &Filter, after: usize, count: Option<usize>) -> Vec<LocalizedTrace> {
 		let chain = BloomGroupChain::new(self.bloom_config, self);
-		let numbers = chain.filter(filter);
+		let mut numbers = chain.filter(filter);
+		numbers.sort();
 		numbers.into_iter()
 			.flat_map(|n| {
 				let number = n as BlockNumber;
@@ -395,6 +493,8 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 					.expect("Expected to find a trace. Db is probably corrupted.");
 				self.matching_block_traces(filter, traces, hash, number)
 			})
+			.skip(after)
+			.take(count.unwrap_or(usize::max_value()))
 			.collect()
 	}
 }
@@ -423,6 +523,10 @@ mod tests {
 		fn transaction_hash(&self, _block_number: BlockNumber, _tx_position: usize) -> Option<H256> {
 			unimplemented!();
 		}
+
+		fn best_block_number(&self) -> BlockNumber {
+			unimplemented!();
+		}
 	}
 
 	#[derive(Clone)]
@@ -449,6 +553,10 @@ mod tests {
 			self.transaction_hashes.get(&block_number)
 				.and_then(|hashes| hashes.iter().cloned().nth(tx_position))
 		}
+
+		fn best_block_number(&self) -> BlockNumber {
+			self.block_hashes.keys().cloned().max().unwrap_or(0)
+		}
 	}
 
 	fn new_db(path: &str) -> Arc<Database> {
@@ -599,7 +707,7 @@ mod tests {
 
 		// import block 0
 		let request = create_simple_import_request(0, block_0.clone());
-		let batch = DBTransaction::new(&db);
+		let batch = DBTransaction::new(&*db);
 		tracedb.import(&batch, request);
 		db.write(batch).unwrap();
 
@@ -607,15 +715,16 @@ mod tests {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![Address::from(1)]),
 			to_address: AddressesFilter::from(vec![]),
+			action: Default::default(),
 		};
 
-		let traces = tracedb.filter(&filter);
+		let traces = tracedb.filter(&filter, 0, None);
 		assert_eq!(traces.len(), 1);
 		assert_eq!(traces[0], create_simple_localized_trace(0, block_0.clone(), tx_0.clone()));
 
 		// import block 1
 		let request = create_simple_import_request(1, block_1.clone());
-		let batch = DBTransaction::new(&db);
+		let batch = DBTransaction::new(&*db);
 		tracedb.import(&batch, request);
 		db.write(batch).unwrap();
 
@@ -623,13 +732,22 @@ mod tests {
 			range: (0..1),
 			from_address: AddressesFilter::from(vec![Address::from(1)]),
 			to_address: AddressesFilter::from(vec![]),
+			action: Default::default(),
 		};
 
-		let traces = tracedb.filter(&filter);
+		let traces = tracedb.filter(&filter, 0, None);
 		assert_eq!(traces.len(), 2);
 		assert_eq!(traces[0], create_simple_localized_trace(0, block_0.clone(), tx_0.clone()));
 		assert_eq!(traces[1], create_simple_localized_trace(1, block_1.clone(), tx_1.clone()));
 
+		let traces = tracedb.filter(&filter, 1, None);
+		assert_eq!(traces.len(), 1);
+		assert_eq!(traces[0], create_simple_localized_trace(1, block_1.clone(), tx_1.clone()));
+
+		let traces = tracedb.filter(&filter, 0, Some(1));
+		assert_eq!(traces.len(), 1);
+		assert_eq!(traces[0], create_simple_localized_trace(0, block_0.clone(), tx_0.clone()));
+
 		let traces = tracedb.block_traces(0).unwrap();
 		assert_eq!(traces.len(), 1);
 		assert_eq!(traces[0], create_simple_localized_trace(0, block_0.clone(), tx_0.clone()));
@@ -674,7 +792,7 @@ mod tests {
 
 			// import block 0
 			let request = create_simple_import_request(0, block_0.clone());
-			let batch = DBTransaction::new(&db);
+			let batch = DBTransaction::new(&*db);
 			tracedb.import(&batch, request);
 			db.write(batch).unwrap();
 		}
@@ -685,4 +803,118 @@ mod tests {
 			assert_eq!(traces.unwrap(), vec![create_simple_localized_trace(0, block_0, tx_0)]);
 		}
 	}
+
+	#[test]
+	fn test_building_missing_traces() {
+		let temp = RandomTempPath::new();
+		let db = new_db(temp.as_str());
+		let mut config = Config::default();
+		let mut extras = Extras::default();
+		let block_0 = H256::from(0xa1);
+		let block_1 = H256::from(0xa2);
+		let block_2 = H256::from(0xa3);
+		let tx_1 = H256::from(0xff);
+		let tx_2 = H256::from(0xaf);
+
+		extras.block_hashes.insert(0, block_0.clone());
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.block_hashes.insert(2, block_2.clone());
+		extras.transaction_hashes.insert(1, vec![tx_1.clone()]);
+		extras.transaction_hashes.insert(2, vec![tx_2.clone()]);
+
+		// synced without tracing
+		config.enabled = Switch::Off;
+		{
+			let tracedb = TraceDB::new(config.clone(), db.clone(), Arc::new(extras.clone())).unwrap();
+			assert_eq!(tracedb.tracing_enabled(), false);
+			assert_eq!(tracedb.missing_traces(), None);
+		}
+
+		config.enabled = Switch::On;
+		config.build_missing = true;
+		{
+			let tracedb = TraceDB::new(config.clone(), db.clone(), Arc::new(extras.clone())).unwrap();
+			assert_eq!(tracedb.tracing_enabled(), true);
+			assert_eq!(tracedb.missing_traces(), Some((1, 2)));
+
+			let batch = DBTransaction::new(&*db);
+			tracedb.import_missing(&batch, create_simple_import_request(1, block_1.clone()));
+			db.write(batch).unwrap();
+			assert_eq!(tracedb.missing_traces(), Some((2, 2)));
+		}
+
+		// building is resumed after reopening
+		config.build_missing = false;
+		{
+			let tracedb = TraceDB::new(config.clone(), db.clone(), Arc::new(extras.clone())).unwrap();
+			assert_eq!(tracedb.missing_traces(), Some((2, 2)));
+
+			let batch = DBTransaction::new(&*db);
+			tracedb.import_missing(&batch, create_simple_import_request(2, block_2.clone()));
+			db.write(batch).unwrap();
+			assert_eq!(tracedb.missing_traces(), None);
+
+			let filter = Filter {
+				range: (0..2),
+				from_address: AddressesFilter::from(vec![Address::from(1)]),
+				to_address: AddressesFilter::from(vec![]),
+				action: Default::default(),
+			};
+
+			let traces = tracedb.filter(&filter, 0, None);
+			assert_eq!(traces.len(), 2);
+			assert_eq!(traces[0], create_simple_localized_trace(1, block_1.clone(), tx_1.clone()));
+			assert_eq!(traces[1], create_simple_localized_trace(2, block_2.clone(), tx_2.clone()));
+		}
+	}
+
+	#[test]
+	fn test_enacting_blocks_without_traces() {
+		let temp = RandomTempPath::new();
+		let db = new_db(temp.as_str());
+		let mut config = Config::default();
+		config.enabled = Switch::On;
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(Extras::default())).unwrap();
+
+		let batch = DBTransaction::new(&*db);
+		tracedb.import(&batch, create_simple_import_request(1, H256::from(0xa2)));
+		db.write(batch).unwrap();
+
+		// side chain block #1 was imported before tracing was enabled.
+		let mut request = create_simple_import_request(2, H256::from(0xb3));
+		request.enacted = vec![H256::from(0xb2), H256::from(0xb3)];
+		request.retracted = 1;
+		let batch = DBTransaction::new(&*db);
+		tracedb.import(&batch, request);
+		db.write(batch).unwrap();
+		assert_eq!(tracedb.missing_traces(), Some((1, 1)));
+	}
+
+	#[test]
+	fn test_skipping_missing_traces() {
+		let temp = RandomTempPath::new();
+		let db = new_db(temp.as_str());
+		let mut config = Config::default();
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(0, H256::from(0xa1));
+		extras.block_hashes.insert(1, H256::from(0xa2));
+		extras.block_hashes.insert(2, H256::from(0xa3));
+
+		config.enabled = Switch::Off;
+		TraceDB::new(config.clone(), db.clone(), Arc::new(extras.clone())).unwrap();
+
+		config.enabled = Switch::On;
+		config.build_missing = true;
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras)).unwrap();
+
+		let batch = DBTransaction::new(&*db);
+		tracedb.skip_missing_traces(&batch, 2);
+		db.write(batch).unwrap();
+		assert_eq!(tracedb.missing_traces(), Some((2, 2)));
+
+		let batch = DBTransaction::new(&*db);
+		tracedb.skip_missing_traces(&batch, 3);
+		db.write(batch).unwrap();
+		assert_eq!(tracedb.missing_traces(), None);
+	}
 }
@@ -17,6 +17,7 @@
 //! `TraceDB` errors.
 
 use std::fmt::{Display, Formatter, Error as FmtError};
+use header::BlockNumber;
 
 const RESYNC_ERR: &'static str =
 "Your current parity installation has synced without transaction tracing.
@@ -24,7 +25,12 @@ To use Parity with transaction tracing, you'll need to resync with tracing.
 To do this, remove or move away your current database and restart parity. e.g.:
 
 > mv ~/.parity/906a34e69aec8c0d /tmp
-> parity";
+> parity
+
+Alternatively, if your database keeps the full state history (--pruning archive),
+build the traces of existing blocks with:
+
+> parity db build-traces";
 
 /// `TraceDB` errors.
 #[derive(Debug)]
@@ -32,10 +38,22 @@ pub enum Error {
 	/// Returned when tracing is enabled,
 	/// but database does not contain traces of old transactions.
 	ResyncRequired,
+	/// Returned when traces of a block cannot be built,
+	/// because the state of its parent is not available.
+	StatePruned(BlockNumber),
+	/// Returned when traces of a block cannot be built,
+	/// because its body has been pruned.
+	HistoryPruned(BlockNumber),
 }
 
 impl Display for Error {
 	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
-		write!(f, "{}", RESYNC_ERR)
+		match *self {
+			Error::ResyncRequired => write!(f, "{}", RESYNC_ERR),
+			Error::StatePruned(number) => write!(f, "Cannot build traces of block #{}: state of its parent has been pruned. \
+				Building traces requires a database with full state history (--pruning archive).", number),
+			Error::HistoryPruned(number) => write!(f, "Cannot build traces of block #{}: its body has been pruned. \
+				Building traces requires a database with full block history (no --history-blocks).", number),
+		}
 	}
 }
@@ -23,6 +23,7 @@ mod error;
 mod executive_tracer;
 mod import;
 mod noop_tracer;
+mod struct_logger;
 
 pub use types::trace_types::*;
 pub use self::config::{Config, Switch};
@@ -32,7 +33,9 @@ pub use types::trace_types::trace::{VMTrace, VMOperation, VMExecutedOperation, M
 pub use types::trace_types::flat::{FlatTrace, FlatTransactionTraces, FlatBlockTraces};
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
-pub use types::trace_types::filter::{Filter, AddressesFilter};
+pub use self::struct_logger::StructLogger;
+pub use types::trace_types::struct_log::{StructLog, StructLogOptions, StructLogTrace};
+pub use types::trace_types::filter::{Filter, AddressesFilter, ActionFilter, ActionType};
 pub use self::import::ImportRequest;
 pub use self::localized::LocalizedTrace;
 use util::{Bytes, Address, U256, H256, DBTransaction};
@@ -113,6 +116,9 @@ pub trait DatabaseExtras {
 
 	/// Returns hash of transaction at given position.
 	fn transaction_hash(&self, block_number: BlockNumber, tx_position: usize) -> Option<H256>;
+
+	/// Returns the number of the best block.
+	fn best_block_number(&self) -> BlockNumber;
 }
 
 /// Db provides an interface to query tracesdb.
@@ -133,5 +139,9 @@ pub trait Database {
 	fn block_traces(&self, block_number: BlockNumber) -> Option<Vec<LocalizedTrace>>;
 
 	/// Filter traces matching given filter.
-	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace>;
+	///
+	/// Blocks are visited in ascending order. The first `after` matching traces
+	/// are skipped and at most `count` traces are returned, so only the blocks
+	/// needed to fill the page are loaded.
+	fn filter(&self, filter: &Filter, after: usize, count: Option<usize>) -> Vec<LocalizedTrace>;
 }
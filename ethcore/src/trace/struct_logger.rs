@@ -0,0 +1,193 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Struct logger recording every executed instruction together with a snapshot of the VM state.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use util::{Bytes, H256, U256, Mutex};
+use evm::instruction_info;
+use trace::{VMTracer, VMTrace, StructLog, StructLogOptions};
+
+const SLOAD: u8 = 0x54;
+const SSTORE: u8 = 0x55;
+
+/// Instruction being executed.
+struct Step {
+	instruction: u8,
+	gas_cost: U256,
+	/// Position of the step in the log, if it was recorded.
+	index: Option<usize>,
+	/// Whether gas available before the step was known when it was recorded.
+	gas_known: bool,
+	/// Storage key read by `SLOAD`.
+	sload_key: Option<H256>,
+}
+
+/// VM tracer producing a flat, step by step log of the execution.
+///
+/// Stack, memory and storage of each call are reconstructed from the values reported
+/// by the interpreter. All subtracers append to the same log, so steps are kept
+/// in execution order.
+pub struct StructLogger {
+	options: Arc<StructLogOptions>,
+	logs: Arc<Mutex<Vec<StructLog>>>,
+	/// Size in bytes of stacks, memory and storage recorded by this tracer and all subtracers.
+	size: Arc<AtomicUsize>,
+	depth: usize,
+	stack: Vec<U256>,
+	memory: Bytes,
+	storage: BTreeMap<H256, H256>,
+	gas: Option<U256>,
+	step: Option<Step>,
+}
+
+impl StructLogger {
+	/// Create a new top-level instance.
+	pub fn toplevel(options: StructLogOptions) -> Self {
+		StructLogger {
+			options: Arc::new(options),
+			logs: Arc::new(Mutex::new(Vec::new())),
+			size: Arc::new(AtomicUsize::new(0)),
+			depth: 0,
+			stack: Vec::new(),
+			memory: Vec::new(),
+			storage: BTreeMap::new(),
+			gas: None,
+			step: None,
+		}
+	}
+
+	/// Returns the recorded steps. The handle stays valid after the tracer is consumed by the executive.
+	pub fn logs(&self) -> Arc<Mutex<Vec<StructLog>>> {
+		self.logs.clone()
+	}
+
+	fn should_record(&self, instruction: u8, recorded: usize) -> bool {
+		let opcode_matches = self.options.opcodes.is_empty() || self.options.opcodes.contains(&instruction);
+		let below_limit = self.options.max_steps.map_or(true, |max| recorded < max);
+		let below_size = self.options.max_size.map_or(true, |max| self.size.load(Ordering::Relaxed) < max);
+		opcode_matches && below_limit && below_size
+	}
+}
+
+impl VMTracer for StructLogger {
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		let len = self.stack.len();
+		if instruction == SSTORE && len >= 2 {
+			self.storage.insert(H256::from(&self.stack[len - 1]), H256::from(&self.stack[len - 2]));
+		}
+		let sload_key = match instruction {
+			SLOAD => self.stack.last().map(H256::from),
+			_ => None,
+		};
+
+		let index = {
+			let mut logs = self.logs.lock();
+			match self.should_record(instruction, logs.len()) {
+				true => {
+					let stack = if self.options.disable_stack { None } else { Some(self.stack.clone()) };
+					let memory = if self.options.disable_memory { None } else { Some(self.memory.clone()) };
+					let storage = if self.options.disable_storage { None } else { Some(self.storage.clone()) };
+					let size = stack.as_ref().map_or(0, |s| s.len() * 32)
+						+ memory.as_ref().map_or(0, |m| m.len())
+						+ storage.as_ref().map_or(0, |s| s.len() * 64);
+					self.size.fetch_add(size, Ordering::Relaxed);
+					logs.push(StructLog {
+						pc: pc,
+						instruction: instruction,
+						gas: self.gas.unwrap_or_else(U256::zero),
+						gas_cost: gas_cost.clone(),
+						depth: self.depth,
+						stack: stack,
+						memory: memory,
+						storage: storage,
+					});
+					Some(logs.len() - 1)
+				},
+				false => None,
+			}
+		};
+
+		self.step = Some(Step {
+			instruction: instruction,
+			gas_cost: gas_cost.clone(),
+			index: index,
+			gas_known: self.gas.is_some(),
+			sload_key: sload_key,
+		});
+		true
+	}
+
+	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		let step = self.step.take().expect("trace_executed is always called after a trace_prepare_execute");
+
+		let (_, args, _) = instruction_info(step.instruction);
+		let len = self.stack.len();
+		self.stack.truncate(len.saturating_sub(args));
+		self.stack.extend(stack_push.iter().cloned());
+
+		if let Some((offset, data)) = mem_diff {
+			let end = offset + data.len();
+			if self.memory.len() < end {
+				self.memory.resize(end, 0);
+			}
+			self.memory[offset..end].copy_from_slice(data);
+		}
+
+		if let Some((key, value)) = store_diff {
+			self.storage.insert(H256::from(key), H256::from(value));
+		}
+
+		if let (Some(key), Some(value)) = (step.sload_key, stack_push.first()) {
+			self.storage.insert(key, H256::from(value));
+		}
+
+		if let Some(index) = step.index {
+			let mut logs = self.logs.lock();
+			let log = &mut logs[index];
+			// gas available before the first instruction of a call is not reported by the interpreter,
+			// but the first instruction can't be a call, so its cost is exact.
+			if !step.gas_known {
+				log.gas = gas_used + step.gas_cost;
+			}
+			if step.instruction == SLOAD && !self.options.disable_storage {
+				log.storage = Some(self.storage.clone());
+			}
+		}
+
+		self.gas = Some(gas_used);
+	}
+
+	fn prepare_subtrace(&self, _code: &[u8]) -> Self {
+		StructLogger {
+			options: self.options.clone(),
+			logs: self.logs.clone(),
+			size: self.size.clone(),
+			depth: self.depth + 1,
+			stack: Vec::new(),
+			memory: Vec::new(),
+			storage: BTreeMap::new(),
+			gas: None,
+			step: None,
+		}
+	}
+
+	fn done_subtrace(&mut self, _sub: Self) {}
+
+	fn drain(self) -> Option<VMTrace> { None }
+}
@@ -0,0 +1,43 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State overrides applied before a call is executed.
+
+use util::numbers::*;
+use util::Bytes;
+use ipc::binary::BinaryConvertError;
+use std::mem;
+use std::collections::{VecDeque, BTreeMap};
+
+/// Overridden parts of a single account.
+#[derive(Debug, PartialEq, Default, Clone, Binary)]
+pub struct AccountOverride {
+	/// Balance of the account.
+	pub balance: Option<U256>,
+	/// Nonce of the account.
+	pub nonce: Option<U256>,
+	/// Code of the account.
+	pub code: Option<Bytes>,
+	/// Individual storage slots of the account.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// Overrides of accounts' state applied to a temporary state before a call is executed.
+#[derive(Debug, PartialEq, Default, Clone, Binary)]
+pub struct StateOverride {
+	/// Overridden accounts.
+	pub accounts: BTreeMap<Address, AccountOverride>,
+}
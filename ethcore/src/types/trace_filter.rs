@@ -22,6 +22,7 @@ use std::collections::VecDeque;
 use std::ops::Range;
 use util::{Address};
 use types::ids::BlockID;
+use types::trace_types::filter::ActionFilter;
 
 /// Easy to use trace filter.
 #[derive(Binary)]
@@ -32,4 +33,10 @@ pub struct Filter {
 	pub from_address: Vec<Address>,
 	/// To address.
 	pub to_address: Vec<Address>,
+	/// Action kind, value and result criteria.
+	pub action: ActionFilter,
+	/// Number of matching traces to skip.
+	pub after: Option<usize>,
+	/// Maximal number of traces to return.
+	pub count: Option<usize>,
 }
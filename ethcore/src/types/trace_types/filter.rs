@@ -18,11 +18,12 @@
 
 use std::ops::Range;
 use bloomchain::{Filter as BloomFilter, Bloom, Number};
-use util::{Address, FixedHash};
+use util::{Address, FixedHash, U256};
 use util::sha3::Hashable;
 use basic_types::LogBloom;
 use trace::flat::FlatTrace;
 use types::trace_types::trace::{Action, Res};
+use types::executed::CallType;
 use ipc::binary::BinaryConvertError;
 use std::mem;
 use std::collections::VecDeque;
@@ -76,6 +77,64 @@ impl AddressesFilter {
 	}
 }
 
+/// Kind of traced action.
+#[derive(Debug, PartialEq, Clone, Binary)]
+pub enum ActionType {
+	/// Call.
+	Call,
+	/// Contract creation.
+	Create,
+	/// Suicide.
+	Suicide,
+}
+
+/// Action filter.
+///
+/// Matches traces by the kind of action, transferred value and result.
+/// Empty lists and `None` values match all traces.
+#[derive(Debug, Default, Clone, Binary)]
+pub struct ActionFilter {
+	/// Kinds of action to match.
+	pub action_types: Vec<ActionType>,
+	/// Call types to match. Only calls can match a non-empty list.
+	pub call_types: Vec<CallType>,
+	/// Minimal value transferred by the action.
+	/// Suicides are matched by the balance sent to the refund address.
+	pub min_value: Option<U256>,
+	/// If `Some(true)` match only failed actions, if `Some(false)` only successful ones.
+	pub failed: Option<bool>,
+	/// Addresses of created contracts. Only successful creations can match a non-empty list.
+	pub created_address: Vec<Address>,
+}
+
+impl ActionFilter {
+	/// Returns true if given trace matches the filter.
+	pub fn matches(&self, trace: &FlatTrace) -> bool {
+		let (action_type, call_type, value) = match trace.action {
+			Action::Call(ref call) => (ActionType::Call, Some(&call.call_type), &call.value),
+			Action::Create(ref create) => (ActionType::Create, None, &create.value),
+			Action::Suicide(ref suicide) => (ActionType::Suicide, None, &suicide.balance),
+		};
+
+		let action_type_matches = self.action_types.is_empty() || self.action_types.contains(&action_type);
+		let call_type_matches = self.call_types.is_empty() || call_type.map_or(false, |t| self.call_types.contains(t));
+		let value_matches = self.min_value.as_ref().map_or(true, |min| value >= min);
+
+		let failed = match trace.result {
+			Res::FailedCall | Res::FailedCreate => true,
+			_ => false,
+		};
+		let failed_matches = self.failed.map_or(true, |f| f == failed);
+
+		let created_matches = self.created_address.is_empty() || match trace.result {
+			Res::Create(ref create) => self.created_address.contains(&create.address),
+			_ => false,
+		};
+
+		action_type_matches && call_type_matches && value_matches && failed_matches && created_matches
+	}
+}
+
 #[derive(Debug, Binary)]
 /// Traces filter.
 pub struct Filter {
@@ -87,6 +146,9 @@ pub struct Filter {
 
 	/// To address filter.
 	pub to_address: AddressesFilter,
+
+	/// Action filter.
+	pub action: ActionFilter,
 }
 
 impl BloomFilter for Filter {
@@ -110,6 +172,10 @@ impl Filter {
 
 	/// Returns true if given trace matches the filter.
 	pub fn matches(&self, trace: &FlatTrace) -> bool {
+		if !self.action.matches(trace) {
+			return false;
+		}
+
 		let action = match trace.action {
 			Action::Call(ref call) => {
 				let from_matches = self.from_address.matches(&call.from);
@@ -141,7 +207,7 @@ mod tests {
 	use util::sha3::Hashable;
 	use trace::trace::{Action, Call, Res, Create, CreateResult, Suicide};
 	use trace::flat::FlatTrace;
-	use trace::{Filter, AddressesFilter};
+	use trace::{Filter, AddressesFilter, ActionFilter, ActionType};
 	use basic_types::LogBloom;
 	use types::executed::CallType;
 
@@ -151,6 +217,7 @@ mod tests {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![]),
 			to_address: AddressesFilter::from(vec![]),
+			action: Default::default(),
 		};
 
 		let blooms = filter.bloom_possibilities();
@@ -163,6 +230,7 @@ mod tests {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![Address::from(1)]),
 			to_address: AddressesFilter::from(vec![Address::from(2)]),
+			action: Default::default(),
 		};
 
 		let blooms = filter.bloom_possibilities();
@@ -179,6 +247,7 @@ mod tests {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![Address::from(1)]),
 			to_address: AddressesFilter::from(vec![]),
+			action: Default::default(),
 		};
 
 		let blooms = filter.bloom_possibilities();
@@ -194,6 +263,7 @@ mod tests {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![]),
 			to_address: AddressesFilter::from(vec![Address::from(1)]),
+			action: Default::default(),
 		};
 
 		let blooms = filter.bloom_possibilities();
@@ -209,6 +279,7 @@ mod tests {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![Address::from(1), Address::from(3)]),
 			to_address: AddressesFilter::from(vec![Address::from(2), Address::from(4)]),
+			action: Default::default(),
 		};
 
 		let blooms = filter.bloom_possibilities();
@@ -241,42 +312,49 @@ mod tests {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![Address::from(1)]),
 			to_address: AddressesFilter::from(vec![]),
+			action: Default::default(),
 		};
 
 		let f1 = Filter {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![Address::from(3), Address::from(1)]),
 			to_address: AddressesFilter::from(vec![]),
+			action: Default::default(),
 		};
 
 		let f2 = Filter {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![]),
 			to_address: AddressesFilter::from(vec![]),
+			action: Default::default(),
 		};
 
 		let f3 = Filter {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![]),
 			to_address: AddressesFilter::from(vec![Address::from(2)]),
+			action: Default::default(),
 		};
 
 		let f4 = Filter {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![]),
 			to_address: AddressesFilter::from(vec![Address::from(2), Address::from(3)]),
+			action: Default::default(),
 		};
 
 		let f5 = Filter {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![Address::from(1)]),
 			to_address: AddressesFilter::from(vec![Address::from(2), Address::from(3)]),
+			action: Default::default(),
 		};
 
 		let f6 = Filter {
 			range: (0..0),
 			from_address: AddressesFilter::from(vec![Address::from(1)]),
 			to_address: AddressesFilter::from(vec![Address::from(4)]),
+			action: Default::default(),
 		};
 
 		let trace = FlatTrace {
@@ -344,4 +422,77 @@ mod tests {
 		assert!(f5.matches(&trace));
 		assert!(!f6.matches(&trace));
 	}
+
+	#[test]
+	fn action_filter_matches() {
+		let call = FlatTrace {
+			action: Action::Call(Call {
+				from: 1.into(),
+				to: 2.into(),
+				value: 100.into(),
+				gas: 4.into(),
+				input: vec![],
+				call_type: CallType::DelegateCall,
+			}),
+			result: Res::FailedCall,
+			trace_address: vec![0].into_iter().collect(),
+			subtraces: 0,
+		};
+
+		let create = FlatTrace {
+			action: Action::Create(Create {
+				from: 1.into(),
+				value: 3.into(),
+				gas: 4.into(),
+				init: vec![0x5],
+			}),
+			result: Res::Create(CreateResult {
+				gas_used: 10.into(),
+				code: vec![],
+				address: 2.into(),
+			}),
+			trace_address: vec![1].into_iter().collect(),
+			subtraces: 0,
+		};
+
+		let all = ActionFilter::default();
+		assert!(all.matches(&call));
+		assert!(all.matches(&create));
+
+		let calls = ActionFilter { action_types: vec![ActionType::Call], ..Default::default() };
+		assert!(calls.matches(&call));
+		assert!(!calls.matches(&create));
+
+		let plain_calls = ActionFilter { call_types: vec![CallType::Call], ..Default::default() };
+		assert!(!plain_calls.matches(&call));
+		assert!(!plain_calls.matches(&create));
+
+		let delegate_calls = ActionFilter { call_types: vec![CallType::DelegateCall], ..Default::default() };
+		assert!(delegate_calls.matches(&call));
+
+		let large_transfers = ActionFilter { min_value: Some(100.into()), ..Default::default() };
+		assert!(large_transfers.matches(&call));
+		assert!(!large_transfers.matches(&create));
+
+		let failed = ActionFilter { failed: Some(true), ..Default::default() };
+		assert!(failed.matches(&call));
+		assert!(!failed.matches(&create));
+
+		let succeeded = ActionFilter { failed: Some(false), ..Default::default() };
+		assert!(!succeeded.matches(&call));
+		assert!(succeeded.matches(&create));
+
+		let created = ActionFilter { created_address: vec![2.into()], ..Default::default() };
+		assert!(!created.matches(&call));
+		assert!(created.matches(&create));
+
+		let filter = Filter {
+			range: (0..0),
+			from_address: AddressesFilter::from(vec![Address::from(1)]),
+			to_address: AddressesFilter::from(vec![]),
+			action: large_transfers,
+		};
+		assert!(filter.matches(&call));
+		assert!(!filter.matches(&create));
+	}
 }
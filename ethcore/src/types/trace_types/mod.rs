@@ -20,3 +20,4 @@ pub mod filter;
 pub mod flat;
 pub mod trace;
 pub mod localized;
+pub mod struct_log;
@@ -0,0 +1,71 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Structured, step by step execution log types.
+
+use std::collections::BTreeMap;
+use util::{Bytes, H256, U256};
+use types::executed::Executed;
+use ipc::binary::BinaryConvertError;
+use std::mem;
+use std::collections::VecDeque;
+
+/// Options of the struct logger.
+#[derive(Debug, PartialEq, Default, Clone, Binary)]
+pub struct StructLogOptions {
+	/// Do not capture memory.
+	pub disable_memory: bool,
+	/// Do not capture stack.
+	pub disable_stack: bool,
+	/// Do not capture storage.
+	pub disable_storage: bool,
+	/// Maximal number of recorded steps.
+	pub max_steps: Option<usize>,
+	/// Maximal total size in bytes of recorded stacks, memory and storage.
+	pub max_size: Option<usize>,
+	/// Record only these instructions. Empty records all.
+	pub opcodes: Vec<u8>,
+}
+
+/// Single execution step.
+#[derive(Debug, PartialEq, Clone, Binary)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Executed instruction.
+	pub instruction: u8,
+	/// Gas available before executing the instruction.
+	pub gas: U256,
+	/// Gas cost of the instruction.
+	pub gas_cost: U256,
+	/// Call depth, starting at 1 for the transaction itself.
+	pub depth: usize,
+	/// Stack before executing the instruction, bottom first.
+	pub stack: Option<Vec<U256>>,
+	/// Memory written so far in the current call.
+	pub memory: Option<Bytes>,
+	/// Storage slots of the current contract read or written so far in the current call.
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// Execution result with its struct log.
+#[derive(Debug, PartialEq, Clone, Binary)]
+pub struct StructLogTrace {
+	/// Result of the execution.
+	pub executed: Executed,
+	/// Recorded steps in execution order.
+	pub logs: Vec<StructLog>,
+}
@@ -16,6 +16,7 @@
 
 //! Spec account deserialization.
 
+use std::collections::BTreeMap;
 use uint::Uint;
 use bytes::Bytes;
 use spec::builtin::Builtin;
@@ -30,7 +31,9 @@ pub struct Account {
 	/// Nonce.
 	pub nonce: Option<Uint>,
 	/// Code.
-	pub code: Option<Bytes>
+	pub code: Option<Bytes>,
+	/// Storage.
+	pub storage: Option<BTreeMap<Uint, Uint>>,
 }
 
 impl Account {
@@ -61,5 +64,21 @@ mod tests {
 		assert_eq!(deserialized.nonce.unwrap(), Uint(U256::from(0)));
 		assert_eq!(deserialized.code.unwrap(), Bytes::new(vec![0x12, 0x34]));
 		assert!(deserialized.builtin.is_some()); // Further tested in builtin.rs
+		assert!(deserialized.storage.is_none());
+	}
+
+	#[test]
+	fn account_with_storage_deserialization() {
+		let s = r#"{
+			"balance": "0x10",
+			"nonce": "1",
+			"storage": { "0x01": "0x2a", "2": "0x0100" }
+		}"#;
+		let deserialized: Account = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.balance.unwrap(), Uint(U256::from(0x10)));
+		let storage = deserialized.storage.unwrap();
+		assert_eq!(storage.len(), 2);
+		assert_eq!(storage[&Uint(U256::from(1))], Uint(U256::from(0x2a)));
+		assert_eq!(storage[&Uint(U256::from(2))], Uint(U256::from(0x100)));
 	}
 }
@@ -16,31 +16,71 @@
 
 //! Logger for parity executables
 
+#[macro_use]
 extern crate ethcore_util as util;
 #[macro_use]
 extern crate log as rlog;
 extern crate isatty;
 extern crate regex;
-extern crate env_logger;
 extern crate time;
+extern crate rustc_serialize;
 #[macro_use]
 extern crate lazy_static;
 
 use std::{env, thread};
 use std::sync::Arc;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::collections::BTreeMap;
 use isatty::{stderr_isatty, stdout_isatty};
-use env_logger::LogBuilder;
 use regex::Regex;
-use util::RotatingLogger;
-use util::log::Colour;
+use rustc_serialize::json::Json;
+use util::{RotatingLogger, LevelFilter};
+use util::log::{Colour, LOG_FIELDS_SEPARATOR};
+
+/// Format of log entries.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+	/// Human readable lines.
+	Text,
+	/// One JSON object per line.
+	Json,
+}
+
+impl Default for Format {
+	fn default() -> Self {
+		Format::Text
+	}
+}
+
+impl FromStr for Format {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"text" => Ok(Format::Text),
+			"json" => Ok(Format::Json),
+			other => Err(format!("Invalid log format: {}", other)),
+		}
+	}
+}
+
+impl Format {
+	pub fn as_str(&self) -> &'static str {
+		match *self {
+			Format::Text => "text",
+			Format::Json => "json",
+		}
+	}
+}
 
 #[derive(Debug, PartialEq)]
 pub struct Config {
 	pub mode: Option<String>,
 	pub color: bool,
 	pub file: Option<String>,
+	pub format: Format,
 }
 
 impl Default for Config {
@@ -49,6 +89,25 @@ impl Default for Config {
 			mode: None,
 			color: !cfg!(windows),
 			file: None,
+			format: Format::default(),
+		}
+	}
+}
+
+/// Logger filtering entries with levels of `RotatingLogger`, which can be changed at runtime.
+struct Logger<F> {
+	logs: Arc<RotatingLogger>,
+	format: F,
+}
+
+impl<F> rlog::Log for Logger<F> where F: Fn(&rlog::LogRecord) -> String + Send + Sync {
+	fn enabled(&self, metadata: &rlog::LogMetadata) -> bool {
+		self.logs.is_enabled(metadata.target(), metadata.level())
+	}
+
+	fn log(&self, record: &rlog::LogRecord) {
+		if self.enabled(record.metadata()) {
+			let _ = writeln!(&mut io::stderr(), "{}", (self.format)(record));
 		}
 	}
 }
@@ -58,26 +117,24 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 	use rlog::*;
 
 	let mut levels = String::new();
-	let mut builder = LogBuilder::new();
 	// Disable ws info logging by default.
-	builder.filter(Some("ws"), LogLevelFilter::Warn);
-	builder.filter(None, LogLevelFilter::Info);
+	let mut defaults = LevelFilter::new(LogLevelFilter::Info);
+	try!(defaults.parse("ws=warn"));
 
 	if env::var("RUST_LOG").is_ok() {
 		let lvl = &env::var("RUST_LOG").unwrap();
 		levels.push_str(lvl);
 		levels.push_str(",");
-		builder.parse(lvl);
 	}
 
 	if let Some(ref s) = config.mode {
 		levels.push_str(s);
-		builder.parse(s);
 	}
 
 	let isatty = stderr_isatty();
-	let enable_color = config.color && isatty;
-	let logs = Arc::new(RotatingLogger::new(levels));
+	let json = config.format == Format::Json;
+	let enable_color = config.color && isatty && !json;
+	let logs = Arc::new(RotatingLogger::with_defaults(defaults, levels));
 	let logger = logs.clone();
 
 	let maybe_file = match config.file.as_ref() {
@@ -86,13 +143,30 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 	};
 
 	let format = move |record: &LogRecord| {
+		if json {
+			let entry = json_entry(&time::now_utc(), record.level(), record.target(), thread::current().name(), &format!("{}", record.args()));
+			if let Some(mut file) = maybe_file.as_ref() {
+				// ignore errors - there's nothing we can do
+				let _ = file.write_all(entry.as_bytes());
+				let _ = file.write_all(b"\n");
+			}
+			logger.write_tees(record.target(), &entry);
+			logger.append(entry.clone());
+			if !isatty && record.level() <= LogLevel::Info && stdout_isatty() {
+				println!("{}", entry);
+			}
+			return entry;
+		}
+
 		let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
+		let message = format!("{}", record.args());
+		let (message, _) = split_fields(&message);
 
 		let with_color = if max_log_level() <= LogLevelFilter::Info {
-			format!("{}{}", Colour::Black.bold().paint(timestamp), record.args())
+			format!("{}{}", Colour::Black.bold().paint(timestamp), message)
 		} else {
 			let name = thread::current().name().map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
-			format!("{}{} {} {}  {}", Colour::Black.bold().paint(timestamp), name, record.level(), record.target(), record.args())
+			format!("{}{} {} {}  {}", Colour::Black.bold().paint(timestamp), name, record.level(), record.target(), message)
 		};
 
 		let removed_color = kill_color(with_color.as_ref());
@@ -107,6 +181,7 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 			let _ = file.write_all(removed_color.as_bytes());
 			let _ = file.write_all(b"\n");
 		}
+		logger.write_tees(record.target(), &removed_color);
 		logger.append(removed_color);
 		if !isatty && record.level() <= LogLevel::Info && stdout_isatty() {
 			// duplicate INFO/WARN output to console
@@ -116,12 +191,50 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		ret
     };
 
-	builder.format(format);
-	builder.init().unwrap();
+	let handle = logs.clone();
+	try!(set_logger(move |max_level| {
+		handle.set_max_level_handle(max_level);
+		Box::new(Logger {
+			logs: handle,
+			format: format,
+		})
+	}).map_err(|_| "Logger is already initialized.".to_owned()));
 
 	Ok(logs)
 }
 
+/// Serializes log entry as a single line JSON object.
+fn json_entry(tm: &time::Tm, level: rlog::LogLevel, target: &str, thread: Option<&str>, message: &str) -> String {
+	let message = kill_color(message);
+	let mut entry = BTreeMap::new();
+	entry.insert("timestamp".to_owned(), Json::String(format!("{}", tm.rfc3339())));
+	entry.insert("level".to_owned(), Json::String(format!("{}", level)));
+	entry.insert("target".to_owned(), Json::String(target.to_owned()));
+	if let Some(thread) = thread {
+		entry.insert("thread".to_owned(), Json::String(thread.to_owned()));
+	}
+	let (message, fields) = split_fields(&message);
+	for (key, value) in fields {
+		entry.insert(key.to_owned(), value);
+	}
+	entry.insert("message".to_owned(), Json::String(message.to_owned()));
+	Json::Object(entry).to_string()
+}
+
+/// Splits structured fields appended with `log_fields!` off the message.
+fn split_fields(message: &str) -> (&str, Vec<(&str, Json)>) {
+	let mut parts = message.split(LOG_FIELDS_SEPARATOR);
+	let message = parts.next().unwrap_or("");
+	let fields = parts.filter_map(|field| {
+		let mut pair = field.splitn(2, '=');
+		match (pair.next(), pair.next()) {
+			(Some(key), Some(value)) => Some((key, value.parse().map(Json::U64).unwrap_or_else(|_| Json::String(value.to_owned())))),
+			_ => None,
+		}
+	}).collect();
+	(message, fields)
+}
+
 fn kill_color(s: &str) -> String {
 	lazy_static! {
 		static ref RE: Regex = Regex::new("\x1b\\[[^m]+m").unwrap();
@@ -142,3 +255,25 @@ fn should_remove_multiple_colour() {
 	let after = kill_color(&t);
 	assert_eq!(after, "test again");
 }
+
+#[test]
+fn should_parse_format() {
+	assert_eq!("text".parse(), Ok(Format::Text));
+	assert_eq!("json".parse(), Ok(Format::Json));
+	assert!("xml".parse::<Format>().is_err());
+}
+
+#[test]
+fn should_format_json_entry() {
+	let tm = time::at_utc(time::Timespec::new(1470000000, 0));
+	let message = format!("Imported {} {} (3 txs){}", Colour::White.bold().paint("#1234"), "7cb4…6d92", log_fields!(block_number = 1234, hash = "7cb4"));
+	let entry = json_entry(&tm, rlog::LogLevel::Info, "import", None, &message);
+	assert_eq!(entry, r#"{"block_number":1234,"hash":"7cb4","level":"INFO","message":"Imported #1234 7cb4…6d92 (3 txs)","target":"import","timestamp":"2016-07-31T21:20:00Z"}"#);
+}
+
+#[test]
+fn should_split_fields() {
+	let message = format!("12: Confirmed peer{}", log_fields!(peer_id = 12));
+	assert_eq!(split_fields(&message), ("12: Confirmed peer", vec![("peer_id", Json::U64(12))]));
+	assert_eq!(split_fields("12: Confirmed peer"), ("12: Confirmed peer", vec![]));
+}
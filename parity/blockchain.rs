@@ -16,23 +16,34 @@
 
 use std::str::{FromStr, from_utf8};
 use std::{io, fs};
-use std::io::{BufReader, BufRead};
+use std::io::{BufReader, BufRead, Read, Write};
 use std::time::Duration;
 use std::thread::sleep;
 use std::path::Path;
-use std::sync::Arc;
-use rustc_serialize::hex::FromHex;
+use std::sync::{Arc, Weak};
+use std::collections::HashMap;
+use rustc_serialize::hex::{FromHex, ToHex};
+use rustc_serialize::json::{self, Json};
 use ethcore_logger::{setup_log, Config as LogConfig};
 use io::{PanicHandler, ForwardPanic};
-use util::{PayloadInfo, ToPretty};
+use util::{PayloadInfo, ToPretty, Address, U256, Bytes, UntrustedRlp, RlpStream, View, Stream};
+use util::snappy;
 use ethcore::service::ClientService;
-use ethcore::client::{Mode, DatabaseCompactionProfile, Switch, VMType, BlockImportError, BlockChainClient, BlockID};
+use util::journaldb::{self, Algorithm};
+use util::kvdb::{Database, DatabaseConfig};
+use ethcore::client::{Mode, DatabaseCompactionProfile, Switch, VMType, BlockImportError, BlockChainClient, BlockID, Client, DB_COL_STATE, DB_COL_HEADERS, DB_COL_BODIES, DB_COL_EXTRA, DB_COL_TRACE, DB_NO_OF_COLUMNS};
+use ethcore::{inspect, AncientStore};
+use ethcore::pod_account::PodAccount;
+use ethcore::receipt::Receipt;
+use ethcore::views::BlockView;
+use ethcore::header::BlockNumber;
 use ethcore::error::ImportError;
 use ethcore::miner::Miner;
 use cache::CacheConfig;
 use informant::Informant;
 use params::{SpecType, Pruning};
-use helpers::{to_client_config, execute_upgrades};
+use helpers::{to_client_config, to_trie_spec, execute_upgrades};
+use migration::{migrate_pruning, is_upgrade_needed};
 use dir::Directories;
 use fdlimit;
 
@@ -40,6 +51,8 @@ use fdlimit;
 pub enum DataFormat {
 	Hex,
 	Binary,
+	/// Snappy-compressed frames of binary blocks.
+	Framed,
 }
 
 impl Default for DataFormat {
@@ -55,6 +68,7 @@ impl FromStr for DataFormat {
 		match s {
 			"binary" | "bin" => Ok(DataFormat::Binary),
 			"hex" => Ok(DataFormat::Hex),
+			"framed" | "snappy" => Ok(DataFormat::Framed),
 			x => Err(format!("Invalid format: {}", x))
 		}
 	}
@@ -64,6 +78,13 @@ impl FromStr for DataFormat {
 pub enum BlockchainCmd {
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
+	BuildTraces(BuildTraces),
+	MigratePruning(MigratePruning),
+	Inspect(InspectDatabase),
+	Revert(RevertBlockchain),
+	Backup(BackupDatabase),
+	ExportState(ExportState),
+	ImportState(ImportState),
 }
 
 #[derive(Debug, PartialEq)]
@@ -75,11 +96,14 @@ pub struct ImportBlockchain {
 	pub file_path: Option<String>,
 	pub format: Option<DataFormat>,
 	pub pruning: Pruning,
+	pub pruning_history: u64,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
 	pub mode: Mode,
 	pub tracing: Switch,
 	pub vm_type: VMType,
+	pub fat_db: bool,
+	pub trusted: bool,
 }
 
 #[derive(Debug, PartialEq)]
@@ -91,18 +115,143 @@ pub struct ExportBlockchain {
 	pub file_path: Option<String>,
 	pub format: Option<DataFormat>,
 	pub pruning: Pruning,
+	pub pruning_history: u64,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
 	pub mode: Mode,
 	pub tracing: Switch,
 	pub from_block: BlockID,
 	pub to_block: BlockID,
+	pub receipts: bool,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct BuildTraces {
+	pub spec: SpecType,
+	pub logger_config: LogConfig,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub mode: Mode,
+	pub from_block: BlockID,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MigratePruning {
+	pub spec: SpecType,
+	pub logger_config: LogConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub compaction: DatabaseCompactionProfile,
+	pub to: Algorithm,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InspectKind {
+	/// Check consistency of the canonical chain.
+	Verify,
+	/// Rebuild block hashes, transaction addresses and blooms.
+	Repair,
+	/// Print sizes of database columns.
+	Stats,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InspectDatabase {
+	pub spec: SpecType,
+	pub logger_config: LogConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+	pub kind: InspectKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RevertBlockchain {
+	pub spec: SpecType,
+	pub logger_config: LogConfig,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub file_path: Option<String>,
+	pub format: Option<DataFormat>,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub mode: Mode,
+	pub tracing: Switch,
+	pub to_block: BlockID,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BackupDatabase {
+	pub spec: SpecType,
+	pub logger_config: LogConfig,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub mode: Mode,
+	pub tracing: Switch,
+	pub target: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportState {
+	pub spec: SpecType,
+	pub logger_config: LogConfig,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub file_path: Option<String>,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub mode: Mode,
+	pub tracing: Switch,
+	pub at: BlockID,
+	pub code: bool,
+	pub storage: bool,
+	pub min_balance: Option<U256>,
+	pub max_balance: Option<U256>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImportState {
+	pub spec: SpecType,
+	pub file_path: String,
+	pub output_path: Option<String>,
+}
+
+/// Number of bytes read to determine the format and size of the next item.
+const READAHEAD_BYTES: usize = 8;
+/// Opening bytes of a stream of snappy-compressed frames.
+const FRAMED_MAGIC: &'static [u8] = b"\0parity\x01";
+/// Size of uncompressed blocks after which a frame is written out.
+const FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+/// Number of blocks which traces are built at once.
+const BUILD_TRACES_BATCH: usize = 1000;
+/// Number of blocks which traces are built at once while the client is running.
+const BACKGROUND_BUILD_TRACES_BATCH: usize = 50;
+
 pub fn execute(cmd: BlockchainCmd) -> Result<String, String> {
 	match cmd {
 		BlockchainCmd::Import(import_cmd) => execute_import(import_cmd),
 		BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
+		BlockchainCmd::BuildTraces(build_traces_cmd) => execute_build_traces(build_traces_cmd),
+		BlockchainCmd::MigratePruning(migrate_cmd) => execute_migrate_pruning(migrate_cmd),
+		BlockchainCmd::Inspect(inspect_cmd) => execute_inspect(inspect_cmd),
+		BlockchainCmd::Revert(revert_cmd) => execute_revert(revert_cmd),
+		BlockchainCmd::Backup(backup_cmd) => execute_backup(backup_cmd),
+		BlockchainCmd::ExportState(export_cmd) => execute_export_state(export_cmd),
+		BlockchainCmd::ImportState(import_cmd) => execute_import_state(import_cmd),
 	}
 }
 
@@ -131,7 +280,8 @@ fn execute_import(cmd: ImportBlockchain) -> Result<String, String> {
 	try!(execute_upgrades(&cmd.dirs, genesis_hash, spec.fork_name.as_ref(), algorithm, cmd.compaction.compaction_profile()));
 
 	// prepare client config
-	let client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, cmd.tracing, cmd.pruning, cmd.compaction, cmd.wal, cmd.vm_type, "".into(), spec.fork_name.as_ref());
+	let mut client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, cmd.tracing, cmd.pruning, cmd.pruning_history, cmd.compaction, cmd.wal, cmd.vm_type, "".into(), spec.fork_name.as_ref());
+	client_config.trie_spec = try!(to_trie_spec(cmd.fat_db, algorithm));
 
 	// build client
 	let service = try!(ClientService::start(
@@ -149,8 +299,6 @@ fn execute_import(cmd: ImportBlockchain) -> Result<String, String> {
 		None => Box::new(io::stdin()),
 	};
 
-	const READAHEAD_BYTES: usize = 8;
-
 	let mut first_bytes: Vec<u8> = vec![0; READAHEAD_BYTES];
 	let mut first_read = 0;
 
@@ -159,6 +307,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<String, String> {
 		None => {
 			first_read = try!(instream.read(&mut first_bytes).map_err(|_| "Error reading from the file/stream."));
 			match first_bytes[0] {
+				_ if &first_bytes[..first_read] == FRAMED_MAGIC => DataFormat::Framed,
 				0xf9 => DataFormat::Binary,
 				_ => DataFormat::Hex,
 			}
@@ -166,10 +315,18 @@ fn execute_import(cmd: ImportBlockchain) -> Result<String, String> {
 	};
 
 	let informant = Informant::new(client.clone(), None, None, cmd.logger_config.color);
+	let trusted = cmd.trusted;
 
 	let do_import = |bytes| {
 		while client.queue_info().is_full() { sleep(Duration::from_secs(1)); }
-		match client.import_block(bytes) {
+		let (block, receipts) = try!(split_receipts(bytes));
+		// history below the best block may be filled in without execution.
+		let result = match receipts {
+			Some(receipts) if trusted && BlockView::new(&block).header_view().number() < client.chain_info().best_block_number =>
+				client.import_trusted_block(block, receipts),
+			_ => client.import_block(block),
+		};
+		match result {
 			Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {
 				trace!("Skipping block already in chain.");
 			}
@@ -209,6 +366,35 @@ fn execute_import(cmd: ImportBlockchain) -> Result<String, String> {
 				try!(do_import(bytes));
 			}
 		}
+		DataFormat::Framed => {
+			if first_read == 0 {
+				try!(instream.read_exact(&mut first_bytes).map_err(|_| "Error reading from the file/stream."));
+			}
+			if &first_bytes[..] != FRAMED_MAGIC {
+				return Err("Invalid framed file/stream.".into());
+			}
+			while let Some(frame) = try!(read_rlp(&mut instream)) {
+				let frame = UntrustedRlp::new(&frame);
+				let (first, last): (BlockNumber, BlockNumber) = try!(frame.val_at(0).and_then(|first| frame.val_at(1).map(|last| (first, last)))
+					.map_err(|e| format!("Invalid frame in the file/stream: {:?}", e)));
+				// frames imported before an interruption are skipped.
+				if client.block_hash(BlockID::Number(last)).is_some() {
+					debug!("Skipping blocks #{}..#{} already in chain.", first, last);
+					continue;
+				}
+				let compressed: Bytes = try!(frame.val_at(2).map_err(|e| format!("Invalid frame in the file/stream: {:?}", e)));
+				let blocks = try!(snappy::decompress(&compressed).map_err(|e| format!("Invalid frame in the file/stream: {}", e)));
+				let mut offset = 0;
+				while offset < blocks.len() {
+					let size = try!(PayloadInfo::from(&blocks[offset..]).map_err(|e| format!("Invalid RLP in the file/stream: {:?}", e))).total();
+					if offset + size > blocks.len() {
+						return Err("Invalid frame in the file/stream: block exceeds the frame.".into());
+					}
+					try!(do_import(blocks[offset..offset + size].to_vec()));
+					offset += size;
+				}
+			}
+		}
 	}
 	client.flush_queue();
 
@@ -242,7 +428,7 @@ fn execute_export(cmd: ExportBlockchain) -> Result<String, String> {
 	try!(execute_upgrades(&cmd.dirs, genesis_hash, spec.fork_name.as_ref(), algorithm, cmd.compaction.compaction_profile()));
 
 	// prepare client config
-	let client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, cmd.tracing, cmd.pruning, cmd.compaction, cmd.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
+	let client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, cmd.tracing, cmd.pruning, cmd.pruning_history, cmd.compaction, cmd.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
 
 	let service = try!(ClientService::start(
 		client_config,
@@ -254,25 +440,613 @@ fn execute_export(cmd: ExportBlockchain) -> Result<String, String> {
 	panic_handler.forward_from(&service);
 	let client = service.client();
 
-	let mut out: Box<io::Write> = match cmd.file_path {
+	let out: Box<io::Write> = match cmd.file_path {
 		Some(f) => Box::new(try!(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f)))),
 		None => Box::new(io::stdout()),
 	};
 
 	let from = try!(client.block_number(cmd.from_block).ok_or("From block could not be found"));
 	let to = try!(client.block_number(cmd.to_block).ok_or("From block could not be found"));
+	let history_start = client.history_start();
+	if from < history_start {
+		return Err(format!("Bodies of blocks before #{} have been pruned and cannot be exported.", history_start));
+	}
 
+	let mut out = BlockWriter::new(out, format).expect("Couldn't write to stream.");
 	for i in from..(to + 1) {
-		let b = client.block(BlockID::Number(i)).unwrap();
-		match format {
-			DataFormat::Binary => { out.write(&b).expect("Couldn't write to stream."); }
-			DataFormat::Hex => { out.write_fmt(format_args!("{}", b.pretty())).expect("Couldn't write to stream."); }
-		}
+		let b = try!(client.block(BlockID::Number(i)).ok_or_else(|| format!("Block #{} could not be found", i)));
+		let b = if cmd.receipts {
+			// genesis has no receipts.
+			let receipts = client.block_receipts(&BlockView::new(&b).header_view().sha3()).unwrap_or_else(|| vec![0xc0]);
+			let mut s = RlpStream::new_list(2);
+			s.append_raw(&b, 1);
+			s.append_raw(&receipts, 1);
+			s.out()
+		} else {
+			b
+		};
+		out.write(i, &b).expect("Couldn't write to stream.");
 	}
+	out.finish().expect("Couldn't write to stream.");
 
 	Ok("Export completed.".into())
 }
 
+fn execute_export_state(cmd: ExportState) -> Result<String, String> {
+	// Setup panic handler
+	let panic_handler = PanicHandler::new_in_arc();
+
+	// load spec file
+	let spec = try!(cmd.spec.spec());
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// Setup logging
+	let _logger = setup_log(&cmd.logger_config);
+
+	fdlimit::raise_fd_limit();
+
+	// select pruning algorithm
+	let algorithm = cmd.pruning.to_algorithm(&cmd.dirs, genesis_hash, spec.fork_name.as_ref());
+
+	// prepare client_path
+	let client_path = cmd.dirs.client_path(genesis_hash, spec.fork_name.as_ref(), algorithm);
+
+	// execute upgrades
+	try!(execute_upgrades(&cmd.dirs, genesis_hash, spec.fork_name.as_ref(), algorithm, cmd.compaction.compaction_profile()));
+
+	// prepare client config
+	let client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, cmd.tracing, cmd.pruning, cmd.pruning_history, cmd.compaction, cmd.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
+
+	let service = try!(ClientService::start(
+		client_config,
+		&spec,
+		Path::new(&client_path),
+		Arc::new(Miner::with_spec(&spec)),
+	).map_err(|e| format!("Client service error: {:?}", e)));
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let number = try!(client.block_number(cmd.at).ok_or("Block could not be found."));
+
+	let mut out: Box<io::Write> = match cmd.file_path {
+		Some(f) => Box::new(try!(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f)))),
+		None => Box::new(io::stdout()),
+	};
+
+	let mut exported = 0;
+	out.write_all(b"{").expect("Couldn't write to stream.");
+	try!(client.for_each_account(BlockID::Number(number), cmd.code, cmd.storage, |address, account| {
+		if cmd.min_balance.map_or(false, |min| account.balance < min) || cmd.max_balance.map_or(false, |max| account.balance > max) {
+			return;
+		}
+		let separator = if exported == 0 { "" } else { "," };
+		out.write_fmt(format_args!("{}\n\t\"0x{}\": {}", separator, address.hex(), account_json(&account))).expect("Couldn't write to stream.");
+		exported += 1;
+	}).map_err(|e| format!("Cannot export state of block #{}: {}", number, e)));
+	out.write_all(b"\n}\n").expect("Couldn't write to stream.");
+
+	Ok(format!("Exported {} accounts at block #{}.", exported, number))
+}
+
+/// Formats an account the way accounts are given in the chain specification.
+fn account_json(account: &PodAccount) -> String {
+	let mut fields = vec![
+		format!("\"balance\": \"{:x}\"", account.balance),
+		format!("\"nonce\": \"{:x}\"", account.nonce),
+	];
+	match account.code {
+		Some(ref code) if !code.is_empty() => fields.push(format!("\"code\": \"0x{}\"", code.to_hex())),
+		_ => {},
+	}
+	if !account.storage.is_empty() {
+		let storage: Vec<_> = account.storage.iter()
+			.map(|(key, value)| format!("\"0x{}\": \"0x{}\"", key.hex(), value.hex()))
+			.collect();
+		fields.push(format!("\"storage\": {{ {} }}", storage.join(", ")));
+	}
+	format!("{{ {} }}", fields.join(", "))
+}
+
+fn execute_import_state(cmd: ImportState) -> Result<String, String> {
+	let spec_path = match cmd.spec {
+		SpecType::Custom(ref path) => path.clone(),
+		_ => return Err("State can only be imported into a chain specification file given with --chain.".into()),
+	};
+
+	let mut spec = try!(read_json(&spec_path));
+	let state = match try!(read_json(&cmd.file_path)) {
+		Json::Object(state) => state,
+		_ => return Err(format!("Invalid state dump: {}", cmd.file_path)),
+	};
+
+	let imported = state.len();
+	{
+		let spec = try!(spec.as_object_mut().ok_or("Invalid chain specification."));
+		// root of the new genesis state is computed on load
+		if let Some(genesis) = spec.get_mut("genesis").and_then(Json::as_object_mut) {
+			genesis.remove("stateRoot");
+		}
+
+		let accounts = spec.entry("accounts".to_owned()).or_insert_with(|| Json::Object(json::Object::new()));
+		let accounts = try!(accounts.as_object_mut().ok_or("Invalid accounts in chain specification."));
+		let mut existing: HashMap<_, _> = accounts.keys().map(|key| (normalize_address(key), key.clone())).collect();
+		for (address, account) in state {
+			let mut account = match account {
+				Json::Object(account) => account,
+				_ => return Err(format!("Invalid account in state dump: {}", address)),
+			};
+			// builtin contracts of the chain are kept
+			let key = existing.remove(&normalize_address(&address));
+			if let Some(Json::Object(mut previous)) = key.and_then(|key| accounts.remove(&key)) {
+				if let Some(builtin) = previous.remove("builtin") {
+					account.insert("builtin".to_owned(), builtin);
+				}
+			}
+			accounts.insert(address, Json::Object(account));
+		}
+	}
+
+	let spec = format!("{}\n", spec.pretty());
+	match cmd.output_path {
+		Some(f) => try!(fs::File::create(&f).and_then(|mut file| file.write_all(spec.as_bytes())).map_err(|_| format!("Cannot write to file given: {}", f))),
+		None => io::stdout().write_all(spec.as_bytes()).expect("Couldn't write to stream."),
+	}
+
+	Ok(format!("Imported {} accounts into the genesis state.", imported))
+}
+
+fn read_json(path: &str) -> Result<Json, String> {
+	let mut file = try!(fs::File::open(path).map_err(|_| format!("Cannot open given file: {}", path)));
+	let mut content = String::new();
+	try!(file.read_to_string(&mut content).map_err(|_| format!("Cannot read given file: {}", path)));
+	Json::from_str(&content).map_err(|e| format!("Invalid JSON in {}: {}", path, e))
+}
+
+fn normalize_address(address: &str) -> String {
+	address.trim_left_matches("0x").to_lowercase()
+}
+
+fn execute_revert(cmd: RevertBlockchain) -> Result<String, String> {
+	// Setup panic handler
+	let panic_handler = PanicHandler::new_in_arc();
+
+	let format = cmd.format.unwrap_or_else(Default::default);
+
+	// load spec file
+	let spec = try!(cmd.spec.spec());
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// Setup logging
+	let _logger = setup_log(&cmd.logger_config);
+
+	fdlimit::raise_fd_limit();
+
+	// select pruning algorithm
+	let algorithm = cmd.pruning.to_algorithm(&cmd.dirs, genesis_hash, spec.fork_name.as_ref());
+
+	// prepare client_path
+	let client_path = cmd.dirs.client_path(genesis_hash, spec.fork_name.as_ref(), algorithm);
+
+	// execute upgrades
+	try!(execute_upgrades(&cmd.dirs, genesis_hash, spec.fork_name.as_ref(), algorithm, cmd.compaction.compaction_profile()));
+
+	// prepare client config
+	let client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, cmd.tracing, cmd.pruning, cmd.pruning_history, cmd.compaction, cmd.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
+
+	let service = try!(ClientService::start(
+		client_config,
+		&spec,
+		Path::new(&client_path),
+		Arc::new(Miner::with_spec(&spec)),
+	).map_err(|e| format!("Client service error: {:?}", e)));
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let number = try!(client.block_number(cmd.to_block).ok_or("Block to revert to could not be found."));
+	let best = client.chain_info().best_block_number;
+	if number >= best {
+		return Err(format!("Block #{} is not older than the best block #{}.", number, best));
+	}
+	let history_start = client.history_start();
+	if number < history_start {
+		return Err(format!("Bodies of blocks before #{} have been pruned, cannot revert to block #{}.", history_start, number));
+	}
+	if client.state_at(BlockID::Number(number)).is_none() {
+		return Err(format!("State of block #{} has already been pruned, cannot revert to it.", number));
+	}
+
+	// removed blocks may be imported again later
+	if let Some(f) = cmd.file_path {
+		let out: Box<io::Write> = Box::new(try!(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f))));
+		let mut out = try!(BlockWriter::new(out, format).map_err(|e| format!("Couldn't write to stream: {}", e)));
+		for i in (number + 1)..(best + 1) {
+			let b = try!(client.block(BlockID::Number(i)).ok_or_else(|| format!("Block #{} could not be found", i)));
+			try!(out.write(i, &b).map_err(|e| format!("Couldn't write to stream: {}", e)));
+		}
+		try!(out.finish().map_err(|e| format!("Couldn't write to stream: {}", e)));
+		info!("Blocks #{}..#{} written to {}", number + 1, best, f);
+	}
+
+	let removed = try!(client.revert(number).map_err(|e| format!("Cannot revert to block #{}: {}", number, e)));
+	Ok(format!("Reverted to block #{}, removed {} blocks.", number, removed.len()))
+}
+
+/// Backs up the database of a stopped node. A running node is backed up with `ethcore_backupDatabase`.
+fn execute_backup(cmd: BackupDatabase) -> Result<String, String> {
+	// Setup panic handler
+	let panic_handler = PanicHandler::new_in_arc();
+
+	// load spec file
+	let spec = try!(cmd.spec.spec());
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// Setup logging
+	let _logger = setup_log(&cmd.logger_config);
+
+	fdlimit::raise_fd_limit();
+
+	// select pruning algorithm
+	let algorithm = cmd.pruning.to_algorithm(&cmd.dirs, genesis_hash, spec.fork_name.as_ref());
+
+	// prepare client_path
+	let client_path = cmd.dirs.client_path(genesis_hash, spec.fork_name.as_ref(), algorithm);
+
+	// the source database is not upgraded, a backup has to be taken in its current state.
+	let version_path = cmd.dirs.db_version_path(genesis_hash, spec.fork_name.as_ref(), algorithm);
+	if try!(is_upgrade_needed(&version_path).map_err(|e| format!("{}", e))) {
+		return Err("Database has to be upgraded before it is backed up. Start parity once to upgrade it.".into());
+	}
+
+	// prepare client config
+	let client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, cmd.tracing, cmd.pruning, cmd.pruning_history, cmd.compaction, cmd.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
+
+	let service = try!(ClientService::start(
+		client_config,
+		&spec,
+		Path::new(&client_path),
+		Arc::new(Miner::with_spec(&spec)),
+	).map_err(|e| format!("Client service error: {:?}", e)));
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let target = Path::new(&cmd.target);
+	let backup = try!(client.backup(target).map_err(|e| format!("Cannot back up the database: {}", e)));
+
+	let nodes = Path::new(&cmd.dirs.db).join("network").join("nodes.json");
+	if nodes.exists() {
+		let target = target.join("nodes.json");
+		try!(fs::hard_link(&nodes, &target).or_else(|_| fs::copy(&nodes, &target).map(|_| ()))
+			.map_err(|e| format!("Cannot copy node table: {}", e)));
+	}
+
+	Ok(format!("Database at block #{} ({}) backed up to {}", backup.number, backup.hash, cmd.target))
+}
+
+fn execute_build_traces(cmd: BuildTraces) -> Result<String, String> {
+	// Setup panic handler
+	let panic_handler = PanicHandler::new_in_arc();
+
+	// load spec file
+	let spec = try!(cmd.spec.spec());
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// Setup logging
+	let _logger = setup_log(&cmd.logger_config);
+
+	fdlimit::raise_fd_limit();
+
+	// select pruning algorithm
+	let algorithm = cmd.pruning.to_algorithm(&cmd.dirs, genesis_hash, spec.fork_name.as_ref());
+
+	// prepare client_path
+	let client_path = cmd.dirs.client_path(genesis_hash, spec.fork_name.as_ref(), algorithm);
+
+	// execute upgrades
+	try!(execute_upgrades(&cmd.dirs, genesis_hash, spec.fork_name.as_ref(), algorithm, cmd.compaction.compaction_profile()));
+
+	// prepare client config, tracing is turned on even if the database was synced without it
+	let mut client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, Switch::On, cmd.pruning, cmd.pruning_history, cmd.compaction, cmd.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
+	client_config.tracing.build_missing = true;
+
+	let service = try!(ClientService::start(
+		client_config,
+		&spec,
+		Path::new(&client_path),
+		Arc::new(Miner::with_spec(&spec)),
+	).map_err(|e| format!("Client service error: {:?}", e)));
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let from = try!(client.block_number(cmd.from_block).ok_or("From block could not be found"));
+	client.skip_missing_traces(from);
+
+	let (first, last) = match client.missing_traces() {
+		Some(range) => range,
+		None => return Ok("Traces of all blocks are already built.".into()),
+	};
+	info!("Building traces of blocks #{}..#{}", first, last);
+
+	loop {
+		match try!(client.build_missing_traces(BUILD_TRACES_BATCH).map_err(|e| format!("{}", e))) {
+			Some((next, _)) => info!("Built traces of blocks #{}..#{}, {} blocks left", first, next - 1, last + 1 - next),
+			None => break,
+		}
+	}
+
+	Ok(format!("Built traces of {} blocks.", last + 1 - first))
+}
+
+fn execute_migrate_pruning(cmd: MigratePruning) -> Result<String, String> {
+	// load spec file
+	let spec = try!(cmd.spec.spec());
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// Setup logging
+	let _logger = setup_log(&cmd.logger_config);
+
+	fdlimit::raise_fd_limit();
+
+	// select pruning algorithm of the existing database
+	let from = cmd.pruning.to_algorithm(&cmd.dirs, genesis_hash, spec.fork_name.as_ref());
+	if from == cmd.to {
+		return Err(format!("Database is already using {} pruning.", from));
+	}
+	if !cmd.to.is_stable() {
+		return Err(format!("Migration to {} pruning is not supported. Use archive or fast.", cmd.to));
+	}
+
+	// prepare client paths
+	let from_path = cmd.dirs.client_path(genesis_hash, spec.fork_name.as_ref(), from);
+	let to_path = cmd.dirs.client_path(genesis_hash, spec.fork_name.as_ref(), cmd.to);
+	if fs::metadata(&from_path).is_err() {
+		return Err(format!("No database found at {}.", from_path.display()));
+	}
+	if fs::metadata(&to_path).is_ok() {
+		return Err(format!("Database using {} pruning already exists at {}. Remove it first.", cmd.to, to_path.display()));
+	}
+
+	// execute upgrades
+	try!(execute_upgrades(&cmd.dirs, genesis_hash, spec.fork_name.as_ref(), from, cmd.compaction.compaction_profile()));
+
+	let first = try!(migrate_pruning(
+		&from_path,
+		from,
+		&to_path,
+		cmd.to,
+		cmd.pruning_history,
+		&spec.genesis_block(),
+		cmd.compaction.compaction_profile()
+	).map_err(|e| format!("{}", e)));
+
+	// bodies and receipts of ancient blocks are kept outside of the database.
+	let fork_name = spec.fork_name.as_ref();
+	if let (Some(from_ancient), Some(to_ancient)) = (cmd.dirs.ancient_path(genesis_hash, fork_name, from), cmd.dirs.ancient_path(genesis_hash, fork_name, cmd.to)) {
+		if fs::metadata(&from_ancient).is_ok() {
+			try!(copy_dir(&from_ancient, &to_ancient).map_err(|e| format!("Cannot copy the ancient store: {}", e)));
+		}
+	}
+
+	let old_database = format!("The old database at {} was left untouched; remove it or run with --pruning {}.", from_path.display(), cmd.to);
+	if cmd.to == Algorithm::Archive && first > 0 {
+		Ok(format!("Database migrated to archive pruning. State of blocks before #{} was already pruned and is not available. {}", first, old_database))
+	} else {
+		Ok(format!("Database migrated to {} pruning, state is available from block #{}. {}", cmd.to, first, old_database))
+	}
+}
+
+/// Copies all files of the directory `from` to `to`.
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+	try!(fs::create_dir_all(to));
+	for entry in try!(fs::read_dir(from)) {
+		let entry = try!(entry);
+		try!(fs::copy(entry.path(), to.join(entry.file_name())));
+	}
+	Ok(())
+}
+
+fn execute_inspect(cmd: InspectDatabase) -> Result<String, String> {
+	// load spec file
+	let spec = try!(cmd.spec.spec());
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// Setup logging
+	let _logger = setup_log(&cmd.logger_config);
+
+	fdlimit::raise_fd_limit();
+
+	// select pruning algorithm
+	let algorithm = cmd.pruning.to_algorithm(&cmd.dirs, genesis_hash, spec.fork_name.as_ref());
+
+	// prepare client_path
+	let client_path = cmd.dirs.client_path(genesis_hash, spec.fork_name.as_ref(), algorithm);
+	if fs::metadata(&client_path).is_err() {
+		return Err(format!("No database found at {}.", client_path.display()));
+	}
+
+	// only repair writes to the database, verification and stats leave it in its current state.
+	if cmd.kind == InspectKind::Repair {
+		try!(execute_upgrades(&cmd.dirs, genesis_hash, spec.fork_name.as_ref(), algorithm, cmd.compaction.compaction_profile()));
+	} else {
+		let version_path = cmd.dirs.db_version_path(genesis_hash, spec.fork_name.as_ref(), algorithm);
+		if try!(is_upgrade_needed(&version_path).map_err(|e| format!("{}", e))) {
+			return Err("Database has to be upgraded before it is inspected. Start parity once to upgrade it.".into());
+		}
+	}
+
+	let mut db_config = DatabaseConfig::with_columns(DB_NO_OF_COLUMNS);
+	db_config.compaction = cmd.compaction.compaction_profile();
+	let client_path_str = try!(client_path.to_str().ok_or("Invalid database path."));
+	let db = Arc::new(try!(Database::open(&db_config, client_path_str).map_err(|e| format!("Cannot open database: {}", e))));
+	let ancient = match cmd.dirs.ancient_path(genesis_hash, spec.fork_name.as_ref(), algorithm) {
+		Some(path) => Some(try!(AncientStore::open(&path).map_err(|e| format!("Cannot open ancient store: {}", e)))),
+		None => None,
+	};
+
+	match cmd.kind {
+		InspectKind::Verify => {
+			info!("Verifying database at {}", client_path.display());
+			let state = journaldb::new(db.clone(), algorithm, DB_COL_STATE);
+			let report = inspect::verify(&db, ancient.as_ref(), state.as_hashdb());
+			for fault in &report.faults {
+				warn!("{}", fault);
+			}
+			if report.faults.is_empty() {
+				Ok(format!("Verified {} blocks, no problems found.", report.best_block + 1))
+			} else {
+				Err(format!("Found {} problems in the database. Run parity db repair to rebuild the indexes of the chain.", report.faults.len()))
+			}
+		},
+		InspectKind::Repair => {
+			info!("Repairing database at {}", client_path.display());
+			let best_block = try!(inspect::repair(&db, ancient.as_ref()).map_err(|fault| format!("Database cannot be repaired: {}", fault)));
+			Ok(format!("Rebuilt indexes of {} blocks.", best_block + 1))
+		},
+		InspectKind::Stats => {
+			let names = [(DB_COL_STATE, "state"), (DB_COL_HEADERS, "headers"), (DB_COL_BODIES, "bodies"), (DB_COL_EXTRA, "extras"), (DB_COL_TRACE, "traces")];
+			let lines: Vec<String> = inspect::stats(&db, DB_NO_OF_COLUMNS).into_iter().map(|(col, stats)| {
+				let name = names.iter().find(|&&(c, _)| c == col).map_or("default", |&(_, name)| name);
+				format!("{:<8} {:>12} keys {:>16} bytes", name, stats.keys, stats.key_bytes + stats.value_bytes)
+			}).collect();
+			Ok(lines.join("\n"))
+		},
+	}
+}
+
+/// Writes blocks to a stream in the given format.
+///
+/// Framed streams consist of RLP frames `[first, last, snappy(blocks)]` following `FRAMED_MAGIC`,
+/// so an interrupted import can skip the frames it has already imported.
+struct BlockWriter {
+	out: Box<io::Write>,
+	format: DataFormat,
+	frame: Bytes,
+	range: Option<(BlockNumber, BlockNumber)>,
+}
+
+impl BlockWriter {
+	fn new(mut out: Box<io::Write>, format: DataFormat) -> io::Result<Self> {
+		if format == DataFormat::Framed {
+			try!(out.write_all(FRAMED_MAGIC));
+		}
+
+		Ok(BlockWriter {
+			out: out,
+			format: format,
+			frame: Vec::new(),
+			range: None,
+		})
+	}
+
+	fn write(&mut self, number: BlockNumber, block: &[u8]) -> io::Result<()> {
+		match self.format {
+			DataFormat::Binary => self.out.write_all(block),
+			DataFormat::Hex => self.out.write_fmt(format_args!("{}", block.pretty())),
+			DataFormat::Framed => {
+				self.frame.extend_from_slice(block);
+				self.range = Some((self.range.map_or(number, |(first, _)| first), number));
+				if self.frame.len() >= FRAME_SIZE {
+					self.write_frame()
+				} else {
+					Ok(())
+				}
+			}
+		}
+	}
+
+	fn write_frame(&mut self) -> io::Result<()> {
+		if let Some((first, last)) = self.range.take() {
+			let mut s = RlpStream::new_list(3);
+			s.append(&first);
+			s.append(&last);
+			s.append(&snappy::compress(&self.frame));
+			self.frame.clear();
+			try!(self.out.write_all(&s.out()));
+		}
+		Ok(())
+	}
+
+	fn finish(mut self) -> io::Result<()> {
+		try!(self.write_frame());
+		self.out.flush()
+	}
+}
+
+/// Reads the next RLP item from the stream, `None` at its end.
+fn read_rlp(instream: &mut io::Read) -> Result<Option<Bytes>, String> {
+	let mut bytes = vec![0; READAHEAD_BYTES];
+	if try!(instream.read(&mut bytes[..1]).map_err(|_| "Error reading from the file/stream.")) == 0 {
+		return Ok(None);
+	}
+	try!(instream.read_exact(&mut bytes[1..]).map_err(|_| "Error reading from the file/stream."));
+	let size = try!(PayloadInfo::from(&bytes).map_err(|e| format!("Invalid RLP in the file/stream: {:?}", e))).total();
+	// no valid frame is shorter than the read ahead bytes.
+	if size < READAHEAD_BYTES {
+		return Err("Invalid frame in the file/stream.".into());
+	}
+	bytes.resize(size, 0);
+	try!(instream.read_exact(&mut bytes[READAHEAD_BYTES..]).map_err(|_| "Error reading from the file/stream."));
+	Ok(Some(bytes))
+}
+
+/// Splits a block bundled with its receipts as `[block, receipts]`.
+fn split_receipts(bytes: Bytes) -> Result<(Bytes, Option<Vec<Receipt>>), String> {
+	let (block, receipts) = {
+		let rlp = UntrustedRlp::new(&bytes);
+		let first = try!(rlp.at(0).map_err(|e| format!("Invalid RLP in the file/stream: {:?}", e)));
+		// plain blocks start with their header, which starts with the parent hash.
+		if !try!(first.at(0).map_err(|e| format!("Invalid RLP in the file/stream: {:?}", e))).is_list() {
+			(None, None)
+		} else {
+			let receipts = try!(rlp.val_at(1).map_err(|e| format!("Invalid receipts in the file/stream: {:?}", e)));
+			(Some(first.as_raw().to_vec()), Some(receipts))
+		}
+	};
+
+	Ok((block.unwrap_or(bytes), receipts))
+}
+
+/// Builds missing traces of the client in small batches, until all of them are built or the client is dropped.
+pub fn build_traces_in_background(client: Weak<Client>) {
+	loop {
+		let client = match client.upgrade() {
+			Some(client) => client,
+			None => return,
+		};
+
+		match client.build_missing_traces(BACKGROUND_BUILD_TRACES_BATCH) {
+			Ok(Some((next, last))) => debug!(target: "trace", "Building traces in background: #{} of #{}", next, last),
+			Ok(None) => {
+				info!("Traces of all blocks have been built.");
+				return;
+			},
+			Err(e) => {
+				warn!("Building traces in background failed: {}", e);
+				return;
+			},
+		}
+
+		// give way to block import
+		drop(client);
+		sleep(Duration::from_millis(100));
+	}
+}
+
 #[cfg(test)]
 mod test {
 	use super::DataFormat;
@@ -31,9 +31,18 @@ Usage:
   parity wallet import <path> --password FILE [options]
   parity import [ <file> ] [options]
   parity export [ <file> ] [options]
+  parity export-state [ <file> ] [options]
+  parity import-state <file> [ <output> ] [options]
   parity signer new-token [options]
+  parity rpc-keys new <name> [options]
+  parity rpc-keys list [options]
   parity snapshot <file> [options]
   parity restore <file> [options]
+  parity db build-traces [options]
+  parity db migrate-pruning <algorithm> [options]
+  parity db (verify | repair | stats) [options]
+  parity db revert <block> [ <file> ] [options]
+  parity db backup <dir> [options]
 
 Operating Options:
   --mode MODE              Set the operating mode. MODE can be one of:
@@ -112,13 +121,50 @@ API and Console Options:
   --jsonrpc-apis APIS      Specify the APIs available through the JSONRPC
                            interface. APIS is a comma-delimited list of API
                            name. Possible name are web3, eth, net, personal,
-                           ethcore, ethcore_set, traces, rpc.
+                           ethcore, ethcore_set, traces, debug, rpc.
                            [default: web3,eth,net,ethcore,personal,traces,rpc].
   --jsonrpc-hosts HOSTS    List of allowed Host header values. This option will
                            validate the Host header sent by the browser, it
                            is additional security against some attack
                            vectors. Special options: "all", "none",
                            [default: none].
+  --jsonrpc-max-payload KB  Maximal size of a JSON-RPC request body in
+                           kilobytes [default: 5120].
+  --jsonrpc-max-batch NUM  Maximal number of calls in a single JSON-RPC batch
+                           request. 0 means no limit [default: 0].
+  --jsonrpc-rate-limit NUM  Maximal number of JSON-RPC calls per minute
+                           accepted from a single client IP address. 0 means
+                           no limit [default: 0].
+  --jsonrpc-method-limits LIMITS  Comma-delimited list of per-method limits of
+                           calls per minute from a single client IP address,
+                           e.g. eth_getLogs=10,trace_filter=5.
+  --jsonrpc-call-timeout SECS  Fail eth_call, eth_estimateGas, eth_getLogs,
+                           trace_* and debug_* calls running longer than SECS
+                           seconds. Each call of a batch is timed separately.
+                           Limits apply to both HTTP and IPC. 0 means no
+                           timeout [default: 0].
+  --jsonrpc-max-timed-calls NUM  Maximal number of calls subject to
+                           --jsonrpc-call-timeout running at once. Calls which
+                           timed out keep running until they finish and count
+                           against the limit [default: 32].
+  --jsonrpc-max-results NUM  Maximal number of entries returned by a single
+                           eth_getLogs, eth_getFilterLogs,
+                           eth_getFilterChanges or trace_filter call. Larger
+                           queries fail and have to be paginated. 0 means no
+                           limit [default: 10000].
+  --jsonrpc-keys FILE      Specify a file with API keys. HTTP requests carrying
+                           a key in the X-Api-Key header and IPC requests
+                           carrying it in the apiKey member get access to the
+                           APIs and methods of that key. Keys are created with
+                           `parity rpc-keys new`. Defaults to rpc_keys.json in
+                           the --db-path directory.
+  --jsonrpc-anonymous-apis APIS  Restrict HTTP and IPC requests which carry no
+                           API key to a comma-delimited list of APIs, or none
+                           to reject them. By default they get --jsonrpc-apis
+                           over HTTP and --ipc-apis over IPC.
+  --key-methods METHODS    Restrict a key created with `parity rpc-keys new`
+                           to a comma-delimited list of methods within its
+                           --jsonrpc-apis.
 
   --no-ipc                 Disable JSON-RPC over IPC service.
   --ipc-path PATH          Specify custom path for JSON-RPC over IPC service
@@ -141,6 +187,14 @@ API and Console Options:
   --dapps-path PATH        Specify directory where dapps should be installed.
                            [default: $HOME/.parity/dapps]
 
+  --metrics                Enable the metrics server exposing node statistics
+                           in the Prometheus text format.
+  --metrics-port PORT      Specify the port portion of the metrics server
+                           [default: 8547].
+  --metrics-interface IP   Specify the hostname portion of the metrics server,
+                           IP should be an interface's IP address, or all
+                           (all interfaces) or local [default: local].
+
 Sealing/Mining Options:
   --author ADDRESS         Specify the block author (aka "coinbase") address
                            for sending block rewards from sealed blocks.
@@ -189,6 +243,9 @@ Sealing/Mining Options:
                            more than 32 characters.
   --tx-queue-size LIMIT    Maximum amount of transactions in the queue (waiting
                            to be included in next block) [default: 1024].
+  --tx-rebroadcast-blocks BLOCKS  Broadcast local transactions to all peers
+                           again when not mined within BLOCKS blocks
+                           [default: 10].
   --remove-solved          Move solved blocks from the work package queue
                            instead of cloning them. This gives a slightly
                            faster import speed, but means that extra solutions
@@ -202,12 +259,29 @@ Footprint Options:
                            with tracing enabled. BOOL may be one of auto, on,
                            off. auto uses last used value of this option (off
                            if it does not exist) [default: auto].
+  --build-traces           Enable tracing on a database synced without it and
+                           build traces of already imported blocks in the
+                           background. Requires full state history
+                           (--pruning archive).
   --pruning METHOD         Configure pruning of the state/storage trie. METHOD
                            may be one of auto, archive, fast:
                            archive - keep all state trie data. No pruning.
                            fast - maintain journal overlay. Fast but 50MB used.
                            auto - use the method most recently synced or
                            default to fast if none synced [default: auto].
+                           Existing database can be converted to another
+                           METHOD with parity db migrate-pruning.
+  --pruning-history NUM    Set a number of recent states to keep when pruning
+                           is active, at least 8 [default: 1200].
+  --ancient-path PATH      Move bodies and receipts of old blocks out of the
+                           database to an append-only store at PATH.
+  --ancient-depth BLOCKS   Keep bodies and receipts of the most recent BLOCKS
+                           blocks in the database when --ancient-path is
+                           used [default: 90000].
+  --history-blocks NUM     Keep bodies and receipts of only the NUM most recent
+                           blocks. Older ones are deleted and only their
+                           headers are kept. NUM must be at least 128.
+                           Cannot be used with --ancient-path.
   --cache-size-db MB       Override database cache size [default: 64].
   --cache-size-blocks MB   Specify the prefered size of the blockchain cache in
                            megabytes [default: 8].
@@ -221,15 +295,30 @@ Footprint Options:
   --db-compaction TYPE     Database compaction type. TYPE may be one of:
                            ssd - suitable for SSDs and fast HDDs;
                            hdd - suitable for slow HDDs [default: ssd].
-  --fat-db                 Fat database.
+  --fat-db                 Fat database. Keeps preimages of state trie keys,
+                           so the state can be exported with
+                           parity export-state. Requires --pruning archive.
 
 Import/Export Options:
-  --from BLOCK             Export from block BLOCK, which may be an index or
-                           hash [default: 1].
+  --from BLOCK             Export or build traces from block BLOCK, which may
+                           be an index or hash [default: 1].
   --to BLOCK               Export to (including) block BLOCK, which may be an
                            index, hash or 'latest' [default: latest].
   --format FORMAT          For import/export in given format. FORMAT must be
-                           one of 'hex' and 'binary'.
+                           one of 'hex', 'binary' and 'framed'. Framed files
+                           are compressed with snappy and an interrupted
+                           import of them resumes where it stopped. Also used
+                           for blocks removed by parity db revert.
+  --receipts               Export blocks together with their receipts.
+  --trusted                Import blocks bundled with receipts which fill
+                           the history below the best block without executing
+                           them. Use only with files from a trusted source.
+  --at BLOCK               Export state at block BLOCK, which may be an
+                           index, hash or 'latest' [default: latest].
+  --no-code                Don't export code of accounts.
+  --no-storage             Don't export storage of accounts.
+  --min-balance WEI        Export only accounts with balance of at least WEI.
+  --max-balance WEI        Export only accounts with balance of at most WEI.
 
 Virtual Machine Options:
   --jitvm                  Enable the JIT VM.
@@ -272,6 +361,9 @@ Miscellaneous Options:
                            format as RUST_LOG.
   --log-file FILENAME      Specify a filename into which logging should be
                            directed.
+  --log-format FORMAT      Specify the format of log entries. FORMAT must be
+                           either 'text' or 'json' - one JSON object per line
+                           [default: text].
   --no-color               Don't use terminal color codes in output.
   -v --version             Show information about version.
   -h --help                Show this screen.
@@ -286,14 +378,30 @@ pub struct Args {
 	pub cmd_list: bool,
 	pub cmd_export: bool,
 	pub cmd_import: bool,
+	pub cmd_export_state: bool,
+	pub cmd_import_state: bool,
 	pub cmd_signer: bool,
 	pub cmd_new_token: bool,
+	pub cmd_rpc_keys: bool,
 	pub cmd_snapshot: bool,
 	pub cmd_restore: bool,
+	pub cmd_db: bool,
+	pub cmd_build_traces: bool,
+	pub cmd_migrate_pruning: bool,
+	pub cmd_verify: bool,
+	pub cmd_repair: bool,
+	pub cmd_stats: bool,
+	pub cmd_revert: bool,
+	pub cmd_backup: bool,
 	pub cmd_ui: bool,
 	pub arg_pid_file: String,
 	pub arg_file: Option<String>,
 	pub arg_path: Vec<String>,
+	pub arg_name: String,
+	pub arg_algorithm: String,
+	pub arg_block: String,
+	pub arg_output: Option<String>,
+	pub arg_dir: String,
 	pub flag_mode: String,
 	pub flag_mode_timeout: u64,
 	pub flag_mode_alarm: u64,
@@ -308,7 +416,12 @@ pub struct Args {
 	pub flag_bootnodes: Option<String>,
 	pub flag_network_id: Option<String>,
 	pub flag_pruning: String,
+	pub flag_pruning_history: u64,
+	pub flag_ancient_path: Option<String>,
+	pub flag_ancient_depth: u64,
+	pub flag_history_blocks: Option<u64>,
 	pub flag_tracing: String,
+	pub flag_build_traces: bool,
 	pub flag_port: u16,
 	pub flag_min_peers: u16,
 	pub flag_max_peers: u16,
@@ -331,6 +444,16 @@ pub struct Args {
 	pub flag_jsonrpc_cors: Option<String>,
 	pub flag_jsonrpc_hosts: String,
 	pub flag_jsonrpc_apis: String,
+	pub flag_jsonrpc_max_payload: usize,
+	pub flag_jsonrpc_max_batch: usize,
+	pub flag_jsonrpc_rate_limit: u32,
+	pub flag_jsonrpc_method_limits: Option<String>,
+	pub flag_jsonrpc_call_timeout: u64,
+	pub flag_jsonrpc_max_timed_calls: usize,
+	pub flag_jsonrpc_max_results: usize,
+	pub flag_jsonrpc_keys: Option<String>,
+	pub flag_jsonrpc_anonymous_apis: Option<String>,
+	pub flag_key_methods: Option<String>,
 	pub flag_no_ipc: bool,
 	pub flag_ipc_path: String,
 	pub flag_ipc_apis: String,
@@ -340,6 +463,9 @@ pub struct Args {
 	pub flag_dapps_user: Option<String>,
 	pub flag_dapps_pass: Option<String>,
 	pub flag_dapps_path: String,
+	pub flag_metrics: bool,
+	pub flag_metrics_port: u16,
+	pub flag_metrics_interface: String,
 	pub flag_force_signer: bool,
 	pub flag_no_signer: bool,
 	pub flag_signer_port: u16,
@@ -360,14 +486,23 @@ pub struct Args {
 	pub flag_gas_cap: String,
 	pub flag_extra_data: Option<String>,
 	pub flag_tx_queue_size: usize,
+	pub flag_tx_rebroadcast_blocks: u64,
 	pub flag_notify_work: Option<String>,
 	pub flag_logging: Option<String>,
 	pub flag_version: bool,
 	pub flag_from: String,
 	pub flag_to: String,
 	pub flag_format: Option<String>,
+	pub flag_receipts: bool,
+	pub flag_trusted: bool,
+	pub flag_at: String,
+	pub flag_no_code: bool,
+	pub flag_no_storage: bool,
+	pub flag_min_balance: Option<String>,
+	pub flag_max_balance: Option<String>,
 	pub flag_jitvm: bool,
 	pub flag_log_file: Option<String>,
+	pub flag_log_format: String,
 	pub flag_no_color: bool,
 	pub flag_no_network: bool,
 	// legacy...
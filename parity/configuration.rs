@@ -17,31 +17,35 @@
 use std::time::Duration;
 use std::io::Read;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::collections::HashSet;
 use std::cmp::max;
 use cli::{USAGE, Args};
 use docopt::{Docopt, Error as DocoptError};
 use util::{Hashable, U256, Uint, Bytes, version_data, Secret, Address};
 use util::log::Colour;
 use ethsync::{NetworkConfiguration, is_valid_node_url};
-use ethcore::client::{VMType, Mode};
+use ethcore::client::{VMType, Mode, MIN_HISTORY, MIN_HISTORY_BLOCKS};
 use ethcore::miner::MinerOptions;
 
 use rpc::{IpcConfiguration, HttpConfiguration};
-use ethcore_rpc::NetworkSettings;
+use rpc_apis::ApiSet;
+use ethcore_rpc::{NetworkSettings, RequestLimits};
 use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, replace_home,
-geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address};
+geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_method_limits};
 use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras};
 use ethcore_logger::Config as LogConfig;
 use dir::Directories;
 use dapps::Configuration as DappsConfiguration;
 use signer::Configuration as SignerConfiguration;
+use metrics::Configuration as MetricsConfiguration;
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, BuildTraces, MigratePruning, InspectDatabase, InspectKind, RevertBlockchain, BackupDatabase, ExportState, ImportState, DataFormat};
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ImportAccounts};
 use snapshot::{self, SnapshotCommand};
+use rpc_keys::{RpcKeysCmd, NewKey};
 
 #[derive(Debug, PartialEq)]
 pub enum Cmd {
@@ -52,6 +56,7 @@ pub enum Cmd {
 	Blockchain(BlockchainCmd),
 	SignerToken(String),
 	Snapshot(SnapshotCommand),
+	RpcKeys(RpcKeysCmd),
 }
 
 #[derive(Debug, PartialEq)]
@@ -73,10 +78,14 @@ impl Configuration {
 	pub fn into_command(self) -> Result<Cmd, String> {
 		let dirs = self.directories();
 		let pruning = try!(self.args.flag_pruning.parse());
+		let pruning_history = self.args.flag_pruning_history;
+		if pruning_history < MIN_HISTORY {
+			return Err(format!("--pruning-history cannot be lower than {}.", MIN_HISTORY));
+		}
 		let vm_type = try!(self.vm_type());
 		let mode = try!(to_mode(&self.args.flag_mode, self.args.flag_mode_timeout, self.args.flag_mode_alarm));
 		let miner_options = try!(self.miner_options());
-		let logger_config = self.logger_config();
+		let logger_config = try!(self.logger_config());
 		let http_conf = try!(self.http_config());
 		let ipc_conf = try!(self.ipc_config());
 		let net_conf = try!(self.net_config());
@@ -91,12 +100,26 @@ impl Configuration {
 		let signer_port = self.signer_port();
 		let dapps_conf = self.dapps_config();
 		let signer_conf = self.signer_config();
+		let metrics_conf = self.metrics_config();
 		let format = try!(self.format());
 
 		let cmd = if self.args.flag_version {
 			Cmd::Version
 		} else if self.args.cmd_signer {
 			Cmd::SignerToken(dirs.signer)
+		} else if self.args.cmd_rpc_keys {
+			let keys_path = self.rpc_keys_path();
+			let keys_cmd = if self.args.cmd_new {
+				RpcKeysCmd::New(NewKey {
+					path: keys_path,
+					name: self.args.arg_name.clone(),
+					apis: self.rpc_apis(),
+					methods: self.args.flag_key_methods.as_ref().map(|m| m.split(',').map(Into::into).collect()),
+				})
+			} else {
+				RpcKeysCmd::List(keys_path)
+			};
+			Cmd::RpcKeys(keys_cmd)
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_new {
 				let new_acc = NewAccount {
@@ -134,11 +157,14 @@ impl Configuration {
 				file_path: self.args.arg_file.clone(),
 				format: format,
 				pruning: pruning,
+				pruning_history: pruning_history,
 				compaction: compaction,
 				wal: wal,
 				mode: mode,
 				tracing: tracing,
 				vm_type: vm_type,
+				fat_db: self.args.flag_fat_db,
+				trusted: self.args.flag_trusted,
 			};
 			Cmd::Blockchain(BlockchainCmd::Import(import_cmd))
 		} else if self.args.cmd_export {
@@ -150,20 +176,132 @@ impl Configuration {
 				file_path: self.args.arg_file.clone(),
 				format: format,
 				pruning: pruning,
+				pruning_history: pruning_history,
 				compaction: compaction,
 				wal: wal,
 				mode: mode,
 				tracing: tracing,
 				from_block: try!(to_block_id(&self.args.flag_from)),
 				to_block: try!(to_block_id(&self.args.flag_to)),
+				receipts: self.args.flag_receipts,
 			};
 			Cmd::Blockchain(BlockchainCmd::Export(export_cmd))
+		} else if self.args.cmd_export_state {
+			let min_balance = match self.args.flag_min_balance {
+				Some(ref balance) => Some(try!(to_u256(balance))),
+				None => None,
+			};
+			let max_balance = match self.args.flag_max_balance {
+				Some(ref balance) => Some(try!(to_u256(balance))),
+				None => None,
+			};
+			let export_cmd = ExportState {
+				spec: spec,
+				logger_config: logger_config,
+				cache_config: cache_config,
+				dirs: dirs,
+				file_path: self.args.arg_file.clone(),
+				pruning: pruning,
+				pruning_history: pruning_history,
+				compaction: compaction,
+				wal: wal,
+				mode: mode,
+				tracing: tracing,
+				at: try!(to_block_id(&self.args.flag_at)),
+				code: !self.args.flag_no_code,
+				storage: !self.args.flag_no_storage,
+				min_balance: min_balance,
+				max_balance: max_balance,
+			};
+			Cmd::Blockchain(BlockchainCmd::ExportState(export_cmd))
+		} else if self.args.cmd_import_state {
+			let import_cmd = ImportState {
+				spec: spec,
+				file_path: self.args.arg_file.clone().expect("<file> is a required argument of import-state; qed"),
+				output_path: self.args.arg_output.clone(),
+			};
+			Cmd::Blockchain(BlockchainCmd::ImportState(import_cmd))
+		} else if self.args.cmd_db && self.args.cmd_build_traces {
+			let build_traces_cmd = BuildTraces {
+				spec: spec,
+				logger_config: logger_config,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				compaction: compaction,
+				wal: wal,
+				mode: mode,
+				from_block: try!(to_block_id(&self.args.flag_from)),
+			};
+			Cmd::Blockchain(BlockchainCmd::BuildTraces(build_traces_cmd))
+		} else if self.args.cmd_db && self.args.cmd_migrate_pruning {
+			let migrate_cmd = MigratePruning {
+				spec: spec,
+				logger_config: logger_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				compaction: compaction,
+				to: try!(self.args.arg_algorithm.parse()),
+			};
+			Cmd::Blockchain(BlockchainCmd::MigratePruning(migrate_cmd))
+		} else if self.args.cmd_db && (self.args.cmd_verify || self.args.cmd_repair || self.args.cmd_stats) {
+			let kind = if self.args.cmd_verify {
+				InspectKind::Verify
+			} else if self.args.cmd_repair {
+				InspectKind::Repair
+			} else {
+				InspectKind::Stats
+			};
+			let inspect_cmd = InspectDatabase {
+				spec: spec,
+				logger_config: logger_config,
+				dirs: dirs,
+				pruning: pruning,
+				compaction: compaction,
+				kind: kind,
+			};
+			Cmd::Blockchain(BlockchainCmd::Inspect(inspect_cmd))
+		} else if self.args.cmd_db && self.args.cmd_revert {
+			let revert_cmd = RevertBlockchain {
+				spec: spec,
+				logger_config: logger_config,
+				cache_config: cache_config,
+				dirs: dirs,
+				file_path: self.args.arg_file.clone(),
+				format: format,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				compaction: compaction,
+				wal: wal,
+				mode: mode,
+				tracing: tracing,
+				to_block: try!(to_block_id(&self.args.arg_block)),
+			};
+			Cmd::Blockchain(BlockchainCmd::Revert(revert_cmd))
+		} else if self.args.cmd_db && self.args.cmd_backup {
+			let backup_cmd = BackupDatabase {
+				spec: spec,
+				logger_config: logger_config,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				compaction: compaction,
+				wal: wal,
+				mode: mode,
+				tracing: tracing,
+				target: self.args.arg_dir.clone(),
+			};
+			Cmd::Blockchain(BlockchainCmd::Backup(backup_cmd))
 		} else if self.args.cmd_snapshot {
 			let snapshot_cmd = SnapshotCommand {
 				cache_config: cache_config,
 				dirs: dirs,
 				spec: spec,
 				pruning: pruning,
+				pruning_history: pruning_history,
 				logger_config: logger_config,
 				mode: mode,
 				tracing: tracing,
@@ -179,6 +317,7 @@ impl Configuration {
 				dirs: dirs,
 				spec: spec,
 				pruning: pruning,
+				pruning_history: pruning_history,
 				logger_config: logger_config,
 				mode: mode,
 				tracing: tracing,
@@ -195,11 +334,19 @@ impl Configuration {
 				None
 			};
 
+			if self.args.flag_history_blocks.is_some() && self.args.flag_ancient_path.is_some() {
+				return Err("--history-blocks cannot be used with --ancient-path.".into());
+			}
+			if self.args.flag_history_blocks.map_or(false, |history| history < MIN_HISTORY_BLOCKS) {
+				return Err(format!("--history-blocks cannot be lower than {}.", MIN_HISTORY_BLOCKS));
+			}
+
 			let run_cmd = RunCmd {
 				cache_config: cache_config,
 				dirs: dirs,
 				spec: spec,
 				pruning: pruning,
+				pruning_history: pruning_history,
 				daemon: daemon,
 				logger_config: logger_config,
 				miner_options: miner_options,
@@ -212,18 +359,24 @@ impl Configuration {
 				miner_extras: try!(self.miner_extras()),
 				mode: mode,
 				tracing: tracing,
+				build_traces: self.args.flag_build_traces,
 				compaction: compaction,
 				wal: wal,
 				vm_type: vm_type,
 				enable_network: enable_network,
 				geth_compatibility: geth_compatibility,
+				rpc_max_results: self.rpc_max_results(),
 				signer_port: signer_port,
 				net_settings: self.network_settings(),
 				dapps_conf: dapps_conf,
 				signer_conf: signer_conf,
+				metrics_conf: metrics_conf,
 				ui: self.args.cmd_ui,
 				name: self.args.flag_identity,
 				custom_bootnodes: self.args.flag_bootnodes.is_some(),
+				fat_db: self.args.flag_fat_db,
+				ancient_depth: self.args.flag_ancient_depth,
+				history_blocks: self.args.flag_history_blocks,
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -276,12 +429,15 @@ impl Configuration {
 		}
 	}
 
-	fn logger_config(&self) -> LogConfig {
-		LogConfig {
+	fn logger_config(&self) -> Result<LogConfig, String> {
+		let config = LogConfig {
 			mode: self.args.flag_logging.clone(),
 			color: !self.args.flag_no_color && !cfg!(windows),
 			file: self.args.flag_log_file.clone(),
-		}
+			format: try!(self.args.flag_log_format.parse()),
+		};
+
+		Ok(config)
 	}
 
 	fn chain(&self) -> String {
@@ -334,6 +490,7 @@ impl Configuration {
 			reseal_min_period: Duration::from_millis(self.args.flag_reseal_min_period),
 			work_queue_size: self.args.flag_work_queue_size,
 			enable_resubmission: !self.args.flag_remove_solved,
+			tx_rebroadcast_blocks: self.args.flag_tx_rebroadcast_blocks,
 		};
 
 		Ok(options)
@@ -359,6 +516,18 @@ impl Configuration {
 		}
 	}
 
+	fn metrics_config(&self) -> MetricsConfiguration {
+		MetricsConfiguration {
+			enabled: self.args.flag_metrics,
+			interface: match self.args.flag_metrics_interface.as_str() {
+				"all" => "0.0.0.0",
+				"local" => "127.0.0.1",
+				x => x,
+			}.into(),
+			port: self.args.flag_metrics_port,
+		}
+	}
+
 	fn gas_pricer_config(&self) -> Result<GasPricerConfig, String> {
 		if let Some(d) = self.args.flag_gasprice.as_ref() {
 			return Ok(GasPricerConfig::Fixed(try!(to_u256(d))));
@@ -477,6 +646,9 @@ impl Configuration {
 			enabled: !(self.args.flag_ipcdisable || self.args.flag_ipc_off || self.args.flag_no_ipc),
 			socket_addr: self.ipc_path(),
 			apis: try!(self.args.flag_ipcapi.clone().unwrap_or(self.args.flag_ipc_apis.clone()).parse()),
+			limits: try!(self.rpc_limits()),
+			anonymous_apis: try!(self.rpc_anonymous_apis()),
+			keys_path: self.rpc_keys_path(),
 		};
 
 		Ok(conf)
@@ -490,11 +662,52 @@ impl Configuration {
 			apis: try!(self.rpc_apis().parse()),
 			hosts: self.rpc_hosts(),
 			cors: self.rpc_cors(),
+			limits: try!(self.rpc_limits()),
+			anonymous_apis: try!(self.rpc_anonymous_apis()),
+			keys_path: self.rpc_keys_path(),
 		};
 
 		Ok(conf)
 	}
 
+	fn rpc_keys_path(&self) -> String {
+		match self.args.flag_jsonrpc_keys {
+			Some(ref path) => replace_home(path),
+			None => Path::new(&self.directories().db).join("rpc_keys.json").to_string_lossy().into_owned(),
+		}
+	}
+
+	fn rpc_anonymous_apis(&self) -> Result<Option<ApiSet>, String> {
+		match self.args.flag_jsonrpc_anonymous_apis {
+			Some(ref apis) if apis == "none" => Ok(Some(ApiSet::List(HashSet::new()))),
+			Some(ref apis) => apis.parse().map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn rpc_limits(&self) -> Result<RequestLimits, String> {
+		let limits = RequestLimits {
+			max_request_size: self.args.flag_jsonrpc_max_payload * 1024,
+			max_batch_size: self.args.flag_jsonrpc_max_batch,
+			client_rate: self.args.flag_jsonrpc_rate_limit,
+			method_rates: try!(to_method_limits(&self.args.flag_jsonrpc_method_limits)),
+			call_timeout: match self.args.flag_jsonrpc_call_timeout {
+				0 => None,
+				secs => Some(Duration::from_secs(secs)),
+			},
+			max_timed_calls: self.args.flag_jsonrpc_max_timed_calls,
+		};
+
+		Ok(limits)
+	}
+
+	fn rpc_max_results(&self) -> Option<usize> {
+		match self.args.flag_jsonrpc_max_results {
+			0 => None,
+			max => Some(max),
+		}
+	}
+
 	fn network_settings(&self) -> NetworkSettings {
 		NetworkSettings {
 			name: self.args.flag_identity.clone(),
@@ -527,6 +740,7 @@ impl Configuration {
 			db: db_path,
 			dapps: dapps_path,
 			signer: signer_path,
+			ancient: self.args.flag_ancient_path.as_ref().map(|path| replace_home(path)),
 		}
 	}
 
@@ -583,13 +797,21 @@ mod tests {
 	use super::*;
 	use cli::USAGE;
 	use docopt::Docopt;
-	use ethcore_rpc::NetworkSettings;
+	use std::time::Duration;
+	use ethcore_rpc::{NetworkSettings, RequestLimits};
 	use ethcore::client::{VMType, BlockID};
 	use helpers::{replace_home, default_network_config};
 	use run::RunCmd;
-	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat};
+	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, BuildTraces, MigratePruning, InspectDatabase, InspectKind, RevertBlockchain, BackupDatabase, ExportState, ImportState, DataFormat};
 	use presale::ImportWallet;
 	use account::{AccountCmd, NewAccount, ImportAccounts};
+	use rpc_keys::{RpcKeysCmd, NewKey};
+	use rpc_apis::ApiSet;
+	use params::{Pruning, SpecType};
+	use util::U256;
+	use util::journaldb::Algorithm;
+	use metrics::Configuration as MetricsConfiguration;
+	use ethcore_logger::Format as LogFormat;
 	use devtools::{RandomTempPath};
 	use std::io::Write;
 	use std::fs::{File, create_dir};
@@ -664,11 +886,14 @@ mod tests {
 			file_path: Some("blockchain.json".into()),
 			format: Default::default(),
 			pruning: Default::default(),
+			pruning_history: 1200,
 			compaction: Default::default(),
 			wal: true,
 			mode: Default::default(),
 			tracing: Default::default(),
 			vm_type: VMType::Interpreter,
+			fat_db: false,
+			trusted: false,
 		})));
 	}
 
@@ -683,6 +908,7 @@ mod tests {
 			dirs: Default::default(),
 			file_path: Some("blockchain.json".into()),
 			pruning: Default::default(),
+			pruning_history: 1200,
 			format: Default::default(),
 			compaction: Default::default(),
 			wal: true,
@@ -690,6 +916,7 @@ mod tests {
 			tracing: Default::default(),
 			from_block: BlockID::Number(1),
 			to_block: BlockID::Latest,
+			receipts: false,
 		})));
 	}
 
@@ -704,6 +931,7 @@ mod tests {
 			dirs: Default::default(),
 			file_path: Some("blockchain.json".into()),
 			pruning: Default::default(),
+			pruning_history: 1200,
 			format: Some(DataFormat::Hex),
 			compaction: Default::default(),
 			wal: true,
@@ -711,6 +939,166 @@ mod tests {
 			tracing: Default::default(),
 			from_block: BlockID::Number(1),
 			to_block: BlockID::Latest,
+			receipts: false,
+		})));
+	}
+
+	#[test]
+	fn test_command_blockchain_export_framed_with_receipts() {
+		let args = vec!["parity", "export", "--format", "framed", "--receipts", "blockchain.bin"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::Export(ExportBlockchain {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			file_path: Some("blockchain.bin".into()),
+			pruning: Default::default(),
+			pruning_history: 1200,
+			format: Some(DataFormat::Framed),
+			compaction: Default::default(),
+			wal: true,
+			mode: Default::default(),
+			tracing: Default::default(),
+			from_block: BlockID::Number(1),
+			to_block: BlockID::Latest,
+			receipts: true,
+		})));
+	}
+
+	#[test]
+	fn test_command_export_state() {
+		let args = vec!["parity", "export-state", "state.json", "--at", "1000", "--no-code", "--min-balance", "0x10"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::ExportState(ExportState {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			file_path: Some("state.json".into()),
+			pruning: Default::default(),
+			pruning_history: 1200,
+			compaction: Default::default(),
+			wal: true,
+			mode: Default::default(),
+			tracing: Default::default(),
+			at: BlockID::Number(1000),
+			code: false,
+			storage: true,
+			min_balance: Some(U256::from(16)),
+			max_balance: None,
+		})));
+	}
+
+	#[test]
+	fn test_command_import_state() {
+		let args = vec!["parity", "import-state", "state.json", "spec.json", "--chain", "private.json"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::ImportState(ImportState {
+			spec: SpecType::Custom("private.json".into()),
+			file_path: "state.json".into(),
+			output_path: Some("spec.json".into()),
+		})));
+	}
+
+	#[test]
+	fn test_command_db_build_traces() {
+		let args = vec!["parity", "db", "build-traces", "--from", "1000"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::BuildTraces(BuildTraces {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 1200,
+			compaction: Default::default(),
+			wal: true,
+			mode: Default::default(),
+			from_block: BlockID::Number(1000),
+		})));
+	}
+
+	#[test]
+	fn test_command_db_migrate_pruning() {
+		let args = vec!["parity", "db", "migrate-pruning", "archive", "--pruning", "fast"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::MigratePruning(MigratePruning {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Pruning::Specific(Algorithm::OverlayRecent),
+			pruning_history: 1200,
+			compaction: Default::default(),
+			to: Algorithm::Archive,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_verify() {
+		let args = vec!["parity", "db", "verify"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::Inspect(InspectDatabase {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			compaction: Default::default(),
+			kind: InspectKind::Verify,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_revert() {
+		let args = vec!["parity", "db", "revert", "1000", "reverted.bin"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::Revert(RevertBlockchain {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			file_path: Some("reverted.bin".into()),
+			format: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 1200,
+			compaction: Default::default(),
+			wal: true,
+			mode: Default::default(),
+			tracing: Default::default(),
+			to_block: BlockID::Number(1000),
+		})));
+	}
+
+	#[test]
+	fn test_command_db_backup() {
+		let args = vec!["parity", "db", "backup", "/var/backups/parity"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::Backup(BackupDatabase {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 1200,
+			compaction: Default::default(),
+			wal: true,
+			mode: Default::default(),
+			tracing: Default::default(),
+			target: "/var/backups/parity".into(),
+		})));
+	}
+
+	#[test]
+	fn test_command_db_stats() {
+		let args = vec!["parity", "db", "stats", "--pruning", "archive"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::Blockchain(BlockchainCmd::Inspect(InspectDatabase {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Pruning::Specific(Algorithm::Archive),
+			compaction: Default::default(),
+			kind: InspectKind::Stats,
 		})));
 	}
 
@@ -722,6 +1110,43 @@ mod tests {
 		assert_eq!(conf.into_command().unwrap(), Cmd::SignerToken(expected));
 	}
 
+	#[test]
+	fn test_command_rpc_keys_new() {
+		let args = vec!["parity", "rpc-keys", "new", "billing", "--jsonrpc-apis", "eth,net", "--key-methods", "eth_call,net_version"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::RpcKeys(RpcKeysCmd::New(NewKey {
+			path: replace_home("$HOME/.parity/rpc_keys.json"),
+			name: "billing".into(),
+			apis: "eth,net".into(),
+			methods: Some(vec!["eth_call".into(), "net_version".into()]),
+		})));
+	}
+
+	#[test]
+	fn test_command_rpc_keys_list() {
+		let args = vec!["parity", "rpc-keys", "list"];
+		let conf = Configuration::parse(args).unwrap();
+		assert_eq!(conf.into_command().unwrap(), Cmd::RpcKeys(RpcKeysCmd::List(replace_home("$HOME/.parity/rpc_keys.json"))));
+	}
+
+	#[test]
+	fn should_parse_rpc_keys_config() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity", "--db-path", "/tmp/parity"]);
+		let conf1 = parse(&["parity", "--jsonrpc-anonymous-apis", "web3,net"]);
+		let conf2 = parse(&["parity", "--jsonrpc-anonymous-apis", "none"]);
+
+		// then
+		assert_eq!(conf0.http_config().unwrap().keys_path, "/tmp/parity/rpc_keys.json");
+		assert_eq!(conf0.ipc_config().unwrap().keys_path, "/tmp/parity/rpc_keys.json");
+		assert_eq!(conf0.http_config().unwrap().anonymous_apis, None);
+		assert_eq!(conf1.http_config().unwrap().anonymous_apis, Some("web3,net".parse().unwrap()));
+		assert_eq!(conf1.ipc_config().unwrap().anonymous_apis, Some("web3,net".parse().unwrap()));
+		assert_eq!(conf2.ipc_config().unwrap().anonymous_apis, Some(ApiSet::List(Default::default())));
+	}
+
 	#[test]
 	fn test_run_cmd() {
 		let args = vec!["parity"];
@@ -731,6 +1156,7 @@ mod tests {
 			dirs: Default::default(),
 			spec: Default::default(),
 			pruning: Default::default(),
+			pruning_history: 1200,
 			daemon: None,
 			logger_config: Default::default(),
 			miner_options: Default::default(),
@@ -743,18 +1169,24 @@ mod tests {
 			miner_extras: Default::default(),
 			mode: Default::default(),
 			tracing: Default::default(),
+			build_traces: false,
 			compaction: Default::default(),
 			wal: true,
 			vm_type: Default::default(),
 			enable_network: true,
 			geth_compatibility: false,
+			rpc_max_results: Some(10000),
 			signer_port: Some(8180),
 			net_settings: Default::default(),
 			dapps_conf: Default::default(),
 			signer_conf: Default::default(),
+			metrics_conf: Default::default(),
 			ui: false,
 			name: "".into(),
 			custom_bootnodes: false,
+			fat_db: false,
+			ancient_depth: 90000,
+			history_blocks: None,
 		}));
 	}
 
@@ -826,6 +1258,118 @@ mod tests {
 		assert_eq!(conf3.rpc_hosts(), Some(vec!["ethcore.io".into(), "something.io".into()]));
 	}
 
+	#[test]
+	fn should_parse_rpc_limits() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--jsonrpc-max-payload", "64", "--jsonrpc-max-batch", "10",
+			"--jsonrpc-rate-limit", "600", "--jsonrpc-method-limits", "eth_getLogs=10", "--jsonrpc-call-timeout", "30",
+			"--jsonrpc-max-timed-calls", "8"]);
+
+		// then
+		assert_eq!(conf0.rpc_limits().unwrap(), RequestLimits::default());
+		let limits = conf1.rpc_limits().unwrap();
+		assert_eq!(limits.max_request_size, 64 * 1024);
+		assert_eq!(limits.max_batch_size, 10);
+		assert_eq!(limits.client_rate, 600);
+		assert_eq!(limits.method_rates.get("eth_getLogs"), Some(&10));
+		assert_eq!(limits.call_timeout, Some(Duration::from_secs(30)));
+		assert_eq!(limits.max_timed_calls, 8);
+		assert_eq!(conf1.ipc_config().unwrap().limits, limits);
+	}
+
+	#[test]
+	fn should_parse_log_format() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--log-format", "json"]);
+		let conf2 = parse(&["parity", "--log-format", "xml"]);
+
+		// then
+		assert_eq!(conf0.logger_config().unwrap().format, LogFormat::Text);
+		assert_eq!(conf1.logger_config().unwrap().format, LogFormat::Json);
+		assert!(conf2.logger_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_pruning_history() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--pruning-history", "1000"]);
+		let conf2 = parse(&["parity", "--pruning-history", "0"]);
+
+		// then
+		match (conf0.into_command().unwrap(), conf1.into_command().unwrap()) {
+			(Cmd::Run(cmd0), Cmd::Run(cmd1)) => {
+				assert_eq!(cmd0.pruning_history, 1200);
+				assert_eq!(cmd1.pruning_history, 1000);
+			},
+			_ => panic!("Should parse run command."),
+		}
+		assert!(conf2.into_command().is_err());
+	}
+
+	#[test]
+	fn should_parse_ancient_store() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--ancient-path", "/mnt/ancient", "--ancient-depth", "1000"]);
+
+		// then
+		match (conf0.into_command().unwrap(), conf1.into_command().unwrap()) {
+			(Cmd::Run(cmd0), Cmd::Run(cmd1)) => {
+				assert_eq!(cmd0.dirs.ancient, None);
+				assert_eq!(cmd0.ancient_depth, 90000);
+				assert_eq!(cmd1.dirs.ancient, Some("/mnt/ancient".into()));
+				assert_eq!(cmd1.ancient_depth, 1000);
+			},
+			_ => panic!("Should parse run command."),
+		}
+	}
+
+	#[test]
+	fn should_parse_history_blocks() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity", "--history-blocks", "1000"]);
+		let conf1 = parse(&["parity", "--history-blocks", "1000", "--ancient-path", "/mnt/ancient"]);
+		let conf2 = parse(&["parity", "--history-blocks", "0"]);
+
+		// then
+		match conf0.into_command().unwrap() {
+			Cmd::Run(cmd) => assert_eq!(cmd.history_blocks, Some(1000)),
+			_ => panic!("Should parse run command."),
+		}
+		assert!(conf1.into_command().is_err());
+		assert!(conf2.into_command().is_err());
+	}
+
+	#[test]
+	fn should_parse_metrics_config() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--metrics", "--metrics-interface", "all", "--metrics-port", "9000"]);
+
+		// then
+		assert_eq!(conf0.metrics_config(), Default::default());
+		assert_eq!(conf1.metrics_config(), MetricsConfiguration {
+			enabled: true,
+			interface: "0.0.0.0".into(),
+			port: 9000,
+		});
+	}
+
 	#[test]
 	fn should_disable_signer_in_geth_compat() {
 		// given
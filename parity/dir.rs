@@ -30,6 +30,7 @@ pub struct Directories {
 	pub keys: String,
 	pub signer: String,
 	pub dapps: String,
+	pub ancient: Option<String>,
 }
 
 impl Default for Directories {
@@ -39,6 +40,7 @@ impl Default for Directories {
 			keys: replace_home("$HOME/.parity/keys"),
 			signer: replace_home("$HOME/.parity/signer"),
 			dapps: replace_home("$HOME/.parity/dapps"),
+			ancient: None,
 		}
 	}
 }
@@ -54,7 +56,16 @@ impl Directories {
 
 	/// Get the root path for database
 	pub fn db_version_path(&self, genesis_hash: H256, fork_name: Option<&String>, pruning: Algorithm) -> PathBuf {
-		let mut dir = Path::new(&self.db).to_path_buf();
+		Self::version_path(&self.db, genesis_hash, fork_name, pruning)
+	}
+
+	/// Get the path for the ancient block store of the database, if configured.
+	pub fn ancient_path(&self, genesis_hash: H256, fork_name: Option<&String>, pruning: Algorithm) -> Option<PathBuf> {
+		self.ancient.as_ref().map(|ancient| Self::version_path(ancient, genesis_hash, fork_name, pruning))
+	}
+
+	fn version_path(root: &str, genesis_hash: H256, fork_name: Option<&String>, pruning: Algorithm) -> PathBuf {
+		let mut dir = Path::new(root).to_path_buf();
 		dir.push(format!("{:?}{}", H64::from(genesis_hash), fork_name.map(|f| format!("-{}", f)).unwrap_or_default()));
 		dir.push(format!("v{}-sec-{}", LEGACY_CLIENT_DB_VER_STR, pruning.as_internal_name_str()));
 		dir
@@ -80,6 +91,7 @@ mod tests {
 			keys: replace_home("$HOME/.parity/keys"),
 			signer: replace_home("$HOME/.parity/signer"),
 			dapps: replace_home("$HOME/.parity/dapps"),
+			ancient: None,
 		};
 		assert_eq!(expected, Directories::default());
 	}
@@ -17,9 +17,11 @@
 use std::{io, env};
 use std::io::{Write, Read, BufReader, BufRead};
 use std::time::Duration;
+use std::collections::HashMap;
 use std::path::Path;
 use std::fs::File;
 use util::{clean_0x, U256, Uint, Address, path, H256, CompactionProfile};
+use util::trie::TrieSpec;
 use util::journaldb::Algorithm;
 use ethcore::client::{Mode, BlockID, Switch, VMType, DatabaseCompactionProfile, ClientConfig};
 use ethcore::miner::PendingSet;
@@ -167,6 +169,20 @@ pub fn to_bootnodes(bootnodes: &Option<String>) -> Result<Vec<String>, String> {
 	}
 }
 
+/// Parses per-method limits given as `method=limit` pairs delimited by commas.
+pub fn to_method_limits(limits: &Option<String>) -> Result<HashMap<String, u32>, String> {
+	match *limits {
+		Some(ref x) if !x.is_empty() => x.split(',').map(|s| {
+			let mut parts = s.splitn(2, '=');
+			match (parts.next(), parts.next().and_then(|limit| limit.parse().ok())) {
+				(Some(method), Some(limit)) if !method.is_empty() => Ok((method.to_owned(), limit)),
+				_ => Err(format!("Invalid method limit given: {}. Expected method=NUM.", s)),
+			}
+		}).collect(),
+		_ => Ok(HashMap::new()),
+	}
+}
+
 #[cfg(test)]
 pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 	use ethsync::NetworkConfiguration;
@@ -194,6 +210,7 @@ pub fn to_client_config(
 		mode: Mode,
 		tracing: Switch,
 		pruning: Pruning,
+		pruning_history: u64,
 		compaction: DatabaseCompactionProfile,
 		wal: bool,
 		vm_type: VMType,
@@ -221,6 +238,8 @@ pub fn to_client_config(
 	client_config.mode = mode;
 	client_config.tracing.enabled = tracing;
 	client_config.pruning = pruning.to_algorithm(dirs, genesis_hash, fork_name);
+	client_config.ancient_path = dirs.ancient_path(genesis_hash, fork_name, client_config.pruning);
+	client_config.history = pruning_history;
 	client_config.db_compaction = compaction;
 	client_config.db_wal = wal;
 	client_config.vm_type = vm_type;
@@ -228,6 +247,14 @@ pub fn to_client_config(
 	client_config
 }
 
+pub fn to_trie_spec(fat_db: bool, pruning: Algorithm) -> Result<TrieSpec, String> {
+	match (fat_db, pruning) {
+		(false, _) => Ok(TrieSpec::Secure),
+		(true, Algorithm::Archive) => Ok(TrieSpec::Fat),
+		(true, _) => Err("Fat database requires --pruning archive.".into()),
+	}
+}
+
 pub fn execute_upgrades(
 	dirs: &Directories,
 	genesis_hash: H256,
@@ -297,10 +324,13 @@ pub fn passwords_from_files(files: Vec<String>) -> Result<Vec<String>, String> {
 #[cfg(test)]
 mod tests {
 	use std::time::Duration;
+	use std::collections::HashMap;
 	use util::{U256};
 	use ethcore::client::{Mode, BlockID};
 	use ethcore::miner::PendingSet;
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes};
+	use util::trie::TrieSpec;
+	use util::journaldb::Algorithm;
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, to_method_limits, to_trie_spec};
 
 	#[test]
 	fn test_to_duration() {
@@ -350,6 +380,13 @@ mod tests {
 		assert!(to_u256("u").is_err())
 	}
 
+	#[test]
+	fn test_to_trie_spec() {
+		assert_eq!(to_trie_spec(false, Algorithm::OverlayRecent).unwrap(), TrieSpec::Secure);
+		assert_eq!(to_trie_spec(true, Algorithm::Archive).unwrap(), TrieSpec::Fat);
+		assert!(to_trie_spec(true, Algorithm::OverlayRecent).is_err());
+	}
+
 	#[test]
 	fn test_pending_set() {
 		assert_eq!(to_pending_set("cheap").unwrap(), PendingSet::AlwaysQueue);
@@ -416,5 +453,17 @@ mod tests {
 		assert_eq!(to_bootnodes(&Some(one_bootnode.into())), Ok(vec![one_bootnode.into()]));
 		assert_eq!(to_bootnodes(&Some(two_bootnodes.into())), Ok(vec![one_bootnode.into(), one_bootnode.into()]));
 	}
+
+	#[test]
+	fn test_to_method_limits() {
+		let mut expected = HashMap::new();
+		expected.insert("eth_getLogs".to_owned(), 10);
+		expected.insert("trace_filter".to_owned(), 5);
+
+		assert_eq!(to_method_limits(&None), Ok(HashMap::new()));
+		assert_eq!(to_method_limits(&Some("eth_getLogs=10,trace_filter=5".into())), Ok(expected));
+		assert!(to_method_limits(&Some("eth_getLogs".into())).is_err());
+		assert!(to_method_limits(&Some("=5".into())).is_err());
+	}
 }
 
@@ -166,14 +166,15 @@ impl ChainNotify for Informant {
 				let tx_count = view.transactions_count();
 				let size = block.len();
 				let skipped = self.skipped.load(AtomicOrdering::Relaxed);
-				info!(target: "import", "Imported {} {} ({} txs, {} Mgas, {} ms, {} KiB){}",
+				info!(target: "import", "Imported {} {} ({} txs, {} Mgas, {} ms, {} KiB){}{}",
 					Colour::White.bold().paint(format!("#{}", header.number())),
 					Colour::White.bold().paint(format!("{}", header.hash())),
 					Colour::Yellow.bold().paint(format!("{}", tx_count)),
 					Colour::Yellow.bold().paint(format!("{:.2}", header.gas_used.low_u64() as f32 / 1000000f32)),
 					Colour::Purple.bold().paint(format!("{:.2}", duration as f32 / 1000000f32)),
 					Colour::Blue.bold().paint(format!("{:.2}", size as f32 / 1024f32)),
-					if skipped > 0 { format!(" + another {} block(s)", Colour::Red.bold().paint(format!("{}", skipped))) } else { String::new() }
+					if skipped > 0 { format!(" + another {} block(s)", Colour::Red.bold().paint(format!("{}", skipped))) } else { String::new() },
+					log_fields!(block_number = header.number(), hash = header.hash().hex())
 				);
 				*last_import = Instant::now();
 			}
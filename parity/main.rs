@@ -45,7 +45,6 @@ extern crate ethcore_ipc as ipc;
 extern crate ethcore_ipc_nano as nanoipc;
 #[macro_use]
 extern crate hyper; // for price_info.rs
-extern crate json_ipc_server as jsonipc;
 
 extern crate ethcore_ipc_hypervisor as hypervisor;
 extern crate ethcore_rpc;
@@ -83,6 +82,8 @@ mod presale;
 mod run;
 mod sync;
 mod snapshot;
+mod rpc_keys;
+mod metrics;
 
 use std::{process, env};
 use cli::print_version;
@@ -101,6 +102,7 @@ fn execute(command: Cmd) -> Result<String, String> {
 		Cmd::Blockchain(blockchain_cmd) => blockchain::execute(blockchain_cmd),
 		Cmd::SignerToken(path) => signer::new_token(path),
 		Cmd::Snapshot(snapshot_cmd) => snapshot::execute(snapshot_cmd),
+		Cmd::RpcKeys(keys_cmd) => rpc_keys::execute(keys_cmd),
 	}
 }
 
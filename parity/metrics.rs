@@ -0,0 +1,213 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node statistics exposed over HTTP in the Prometheus text format.
+
+use std::fmt::{Display, Write};
+use std::sync::Arc;
+use hyper::server::{self, Listening};
+use hyper::method::Method;
+use hyper::status::StatusCode;
+use util::Uint;
+use ethcore::client::{Client, BlockChainClient};
+use ethcore::miner::{Miner, MinerService};
+use ethsync::{SyncProvider, ManageNetwork};
+use ethcore_rpc::RpcMetrics;
+
+const CONTENT_TYPE: &'static str = "text/plain; version=0.0.4";
+
+#[derive(Debug, PartialEq)]
+pub struct Configuration {
+	pub enabled: bool,
+	pub interface: String,
+	pub port: u16,
+}
+
+impl Default for Configuration {
+	fn default() -> Self {
+		Configuration {
+			enabled: false,
+			interface: "127.0.0.1".into(),
+			port: 8547,
+		}
+	}
+}
+
+pub struct Dependencies {
+	pub client: Arc<Client>,
+	pub sync: Arc<SyncProvider>,
+	pub net: Arc<ManageNetwork>,
+	pub miner: Arc<Miner>,
+	pub rpc: Arc<RpcMetrics>,
+}
+
+/// Metrics server handle. The server is closed when dropped.
+pub struct MetricsServer {
+	listening: Listening,
+}
+
+impl Drop for MetricsServer {
+	fn drop(&mut self) {
+		let _ = self.listening.close();
+	}
+}
+
+pub fn start(conf: Configuration, deps: Dependencies) -> Result<Option<MetricsServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let server = try!(server::Server::http(url.as_str()).map_err(|e| format!("Metrics server error: {}", e)));
+	let listening = try!(server.handle(MetricsHandler { deps: deps }).map_err(|e| format!("Metrics server error: {}", e)));
+	info!("Metrics server listening on {}", url);
+	Ok(Some(MetricsServer { listening: listening }))
+}
+
+struct MetricsHandler {
+	deps: Dependencies,
+}
+
+impl server::Handler for MetricsHandler {
+	fn handle<'a, 'k>(&'a self, req: server::Request<'a, 'k>, mut res: server::Response<'a>) {
+		if req.method != Method::Get {
+			*res.status_mut() = StatusCode::MethodNotAllowed;
+			let _ = res.send(b"");
+			return;
+		}
+
+		let body = gather(&self.deps);
+		res.headers_mut().set_raw("Content-Type", vec![CONTENT_TYPE.as_bytes().to_vec()]);
+		if let Err(e) = res.send(body.as_bytes()) {
+			debug!(target: "metrics", "Error sending metrics: {}", e);
+		}
+	}
+}
+
+/// Collects current values of all metrics.
+fn gather(deps: &Dependencies) -> String {
+	let mut e = Exposition::default();
+
+	let chain_info = deps.client.chain_info();
+	let report = deps.client.report();
+	e.gauge("parity_chain_best_block", "Number of the best block.", chain_info.best_block_number);
+	e.counter("parity_import_blocks_total", "Number of imported blocks.", report.blocks_imported);
+	e.counter("parity_import_transactions_total", "Number of applied transactions.", report.transactions_applied);
+	e.counter("parity_import_gas_total", "Amount of processed gas.", report.gas_processed.low_u64());
+
+	let queue_info = deps.client.queue_info();
+	e.header("parity_block_queue_size", "gauge", "Number of blocks in the verification queue.");
+	e.value("parity_block_queue_size", &[("stage", "unverified")], queue_info.unverified_queue_size);
+	e.value("parity_block_queue_size", &[("stage", "verifying")], queue_info.verifying_queue_size);
+	e.value("parity_block_queue_size", &[("stage", "verified")], queue_info.verified_queue_size);
+	e.gauge("parity_block_queue_memory_bytes", "Memory used by the verification queue.", queue_info.mem_used);
+
+	e.gauge("parity_blockchain_cache_bytes", "Memory used by blockchain caches.", deps.client.blockchain_cache_info().total());
+	e.gauge("parity_state_db_memory_bytes", "Memory used by the state database.", report.state_db_mem);
+
+	let sync_status = deps.sync.status();
+	e.gauge("parity_sync_peers", "Number of peers known to sync.", sync_status.num_peers);
+	e.gauge("parity_sync_active_peers", "Number of peers actively used by sync.", sync_status.num_active_peers);
+	e.gauge("parity_sync_memory_bytes", "Memory used by sync.", sync_status.mem_used);
+
+	let net = deps.net.network_metrics();
+	e.header("parity_network_peers", "gauge", "Number of connected peers by protocol.");
+	for (protocol, peers) in &net.protocol_peers {
+		e.value("parity_network_peers", &[("protocol", protocol.as_str())], peers);
+	}
+	e.counter("parity_network_received_bytes_total", "Number of bytes received from peers.", net.bytes_received);
+	e.counter("parity_network_sent_bytes_total", "Number of bytes sent to peers.", net.bytes_sent);
+	e.counter("parity_network_sessions_total", "Number of created peer sessions.", net.sessions_created);
+
+	let miner_status = deps.miner.status();
+	e.header("parity_transaction_queue_size", "gauge", "Number of transactions in the queue.");
+	e.value("parity_transaction_queue_size", &[("state", "pending")], miner_status.transactions_in_pending_queue);
+	e.value("parity_transaction_queue_size", &[("state", "future")], miner_status.transactions_in_future_queue);
+
+	let methods = deps.rpc.methods();
+	e.header("parity_rpc_request_duration_seconds", "summary", "Time spent handling RPC calls by method.");
+	for (method, metrics) in &methods {
+		let secs = metrics.time.as_secs() as f64 + metrics.time.subsec_nanos() as f64 / 1_000_000_000f64;
+		e.value("parity_rpc_request_duration_seconds_sum", &[("method", method.as_str())], secs);
+		e.value("parity_rpc_request_duration_seconds_count", &[("method", method.as_str())], metrics.calls);
+	}
+
+	e.out
+}
+
+/// Writer of the Prometheus text exposition format.
+#[derive(Default)]
+struct Exposition {
+	out: String,
+}
+
+impl Exposition {
+	fn header(&mut self, name: &str, kind: &str, help: &str) {
+		let _ = writeln!(self.out, "# HELP {} {}", name, help);
+		let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+	}
+
+	fn value<V: Display>(&mut self, name: &str, labels: &[(&str, &str)], value: V) {
+		let labels = labels.iter()
+			.map(|&(label, value)| format!("{}=\"{}\"", label, escape(value)))
+			.collect::<Vec<_>>();
+		let _ = if labels.is_empty() {
+			writeln!(self.out, "{} {}", name, value)
+		} else {
+			writeln!(self.out, "{}{{{}}} {}", name, labels.join(","), value)
+		};
+	}
+
+	fn gauge<V: Display>(&mut self, name: &str, help: &str, value: V) {
+		self.header(name, "gauge", help);
+		self.value(name, &[], value);
+	}
+
+	fn counter<V: Display>(&mut self, name: &str, help: &str, value: V) {
+		self.header(name, "counter", help);
+		self.value(name, &[], value);
+	}
+}
+
+fn escape(value: &str) -> String {
+	value.replace("\\", "\\\\").replace("\"", "\\\"").replace("\n", "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Exposition;
+
+	#[test]
+	fn should_write_text_format() {
+		// given
+		let mut e = Exposition::default();
+
+		// when
+		e.counter("parity_import_blocks_total", "Number of imported blocks.", 5);
+		e.header("parity_network_peers", "gauge", "Number of connected peers by protocol.");
+		e.value("parity_network_peers", &[("protocol", "eth")], 3);
+		e.value("parity_network_peers", &[("protocol", "a\"b")], 0);
+
+		// then
+		assert_eq!(e.out, "# HELP parity_import_blocks_total Number of imported blocks.\n\
+			# TYPE parity_import_blocks_total counter\n\
+			parity_import_blocks_total 5\n\
+			# HELP parity_network_peers Number of connected peers by protocol.\n\
+			# TYPE parity_network_peers gauge\n\
+			parity_network_peers{protocol=\"eth\"} 3\n\
+			parity_network_peers{protocol=\"a\\\"b\"} 0\n");
+	}
+}
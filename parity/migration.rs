@@ -18,6 +18,7 @@ use std::fs;
 use std::fs::File;
 use std::io::{Read, Write, Error as IoError, ErrorKind};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::fmt::{Display, Formatter, Error as FmtError};
 use util::journaldb::Algorithm;
 use util::migration::{Manager as MigrationManager, Config as MigrationConfig, Error as MigrationError, Migration};
@@ -50,6 +51,8 @@ pub enum Error {
 	MigrationImpossible,
 	/// Migration unexpectadly failed.
 	MigrationFailed,
+	/// Migration failed for the given reason.
+	Custom(String),
 	/// Migration was completed succesfully,
 	/// but there was a problem with io.
 	Io(IoError),
@@ -63,6 +66,7 @@ impl Display for Error {
 			Error::FutureDBVersion => "Database was created with newer client version. Upgrade your client or delete DB and resync.".into(),
 			Error::MigrationImpossible => format!("Database migration to version {} is not possible.", CURRENT_VERSION),
 			Error::MigrationFailed => "Database migration unexpectedly failed".into(),
+			Error::Custom(ref reason) => format!("Database migration failed: {}", reason),
 			Error::Io(ref err) => format!("Unexpected io error on DB migration: {}.", err),
 		};
 
@@ -106,6 +110,11 @@ fn current_version(path: &Path) -> Result<u32, Error> {
 	}
 }
 
+/// Returns true if the database at given path has to be upgraded before it is opened.
+pub fn is_upgrade_needed(path: &Path) -> Result<bool, Error> {
+	current_version(path).map(|version| version != CURRENT_VERSION)
+}
+
 /// Writes current database version to the file.
 /// Creates a new file if the version file does not exist yet.
 fn update_version(path: &Path) -> Result<(), Error> {
@@ -130,6 +139,14 @@ fn backup_database_path(path: &Path) -> PathBuf {
 	backup_path
 }
 
+/// Database being migrated to another pruning algorithm.
+fn pruning_migration_path(path: &Path, to: Algorithm) -> PathBuf {
+	let mut temp_path = path.to_owned();
+	temp_path.pop();
+	temp_path.push(format!("temp_pruning_migration_{}", to.as_internal_name_str()));
+	temp_path
+}
+
 /// Default migration settings.
 pub fn default_migration_settings(compaction_profile: &CompactionProfile) -> MigrationConfig {
 	MigrationConfig {
@@ -169,13 +186,13 @@ fn consolidate_database(
 	let old_path_str = try!(old_db_path.to_str().ok_or(Error::MigrationImpossible));
 	let new_path_str = try!(new_db_path.to_str().ok_or(Error::MigrationImpossible));
 
-	let cur_db = try!(Database::open(&db_config, old_path_str).map_err(db_error));
+	let cur_db = Arc::new(try!(Database::open(&db_config, old_path_str).map_err(db_error)));
 	// open new DB with proper number of columns
 	db_config.columns = migration.columns();
-	let mut new_db = try!(Database::open(&db_config, new_path_str).map_err(db_error));
+	let new_db = Arc::new(try!(Database::open(&db_config, new_path_str).map_err(db_error)));
 
 	// Migrate to new database (default column only)
-	try!(migration.migrate(&cur_db, &config, &mut new_db, None));
+	try!(migration.migrate(cur_db, &config, new_db, None));
 
 	Ok(())
 }
@@ -263,6 +280,47 @@ pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionPr
 	update_version(path)
 }
 
+/// Converts the database at `from_path` into a new database at `to_path` using another pruning algorithm.
+/// Pruned databases keep the state of `history` recent blocks.
+/// Interrupted conversion is resumed when started again.
+/// Returns the number of the first block with state in the new database.
+pub fn migrate_pruning(
+	from_path: &Path,
+	from: Algorithm,
+	to_path: &Path,
+	to: Algorithm,
+	history: u64,
+	genesis: &[u8],
+	compaction_profile: CompactionProfile) -> Result<u64, Error> {
+	let migration = try!(migrations::ToPruning::new(from, to, history, genesis.to_vec()).map_err(|_| Error::UnsuportedPruningMethod));
+	let first = migration.first_block();
+	let mut manager = MigrationManager::new(default_migration_settings(&compaction_profile));
+	try!(manager.add_migration(migration));
+
+	let temp_path = pruning_migration_path(to_path, to);
+	if exists(&temp_path) {
+		println!("Resuming interrupted migration from {} to {}", from, to);
+	} else {
+		println!("Migrating database from {} to {}", from, to);
+		try!(fs::create_dir_all(&temp_path));
+	}
+
+	try!(manager.execute_into(from_path, client::DB_NO_OF_COLUMNS, &temp_path).map_err(|e| match e {
+		MigrationError::Custom(reason) => Error::Custom(reason),
+		e => e.into(),
+	}));
+	let first = *first.lock();
+	let first = try!(first.ok_or(Error::MigrationFailed));
+
+	try!(fs::rename(&temp_path, to_path));
+	let mut version_path = to_path.to_owned();
+	version_path.pop();
+	try!(update_version(&version_path));
+	println!("Migration finished");
+
+	Ok(first)
+}
+
 /// Old migrations utilities
 mod legacy {
 	use super::*;
@@ -89,6 +89,8 @@ fn sync_arguments(sync_cfg: SyncConfig, net_cfg: NetworkConfiguration, log_setti
 		cli_args.push("--log-file".to_owned());
 		cli_args.push(file.to_owned());
 	}
+	cli_args.push("--log-format".to_owned());
+	cli_args.push(log_settings.format.as_str().to_owned());
 
 	BootArgs::new().stdin(service_payload).cli(cli_args)
 }
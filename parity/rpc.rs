@@ -18,13 +18,13 @@ use std::fmt;
 use std::sync::Arc;
 use std::net::SocketAddr;
 use io::PanicHandler;
-use ethcore_rpc::{RpcServerError, RpcServer as Server};
-use jsonipc;
+use ethcore_rpc::{RpcServerError, RpcServer as Server, RequestLimits, ApiKeys, RpcMetrics};
 use rpc_apis;
 use rpc_apis::ApiSet;
-use helpers::parity_ipc_path;
+use helpers::{parity_ipc_path, replace_home};
+use rpc_keys;
 
-pub use jsonipc::Server as IpcServer;
+pub use ethcore_rpc::IpcServer;
 pub use ethcore_rpc::Server as HttpServer;
 
 #[derive(Debug, PartialEq)]
@@ -35,6 +35,9 @@ pub struct HttpConfiguration {
 	pub apis: ApiSet,
 	pub cors: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
+	pub limits: RequestLimits,
+	pub anonymous_apis: Option<ApiSet>,
+	pub keys_path: String,
 }
 
 impl Default for HttpConfiguration {
@@ -46,6 +49,9 @@ impl Default for HttpConfiguration {
 			apis: ApiSet::UnsafeContext,
 			cors: None,
 			hosts: Some(Vec::new()),
+			limits: Default::default(),
+			anonymous_apis: None,
+			keys_path: replace_home("$HOME/.parity/rpc_keys.json"),
 		}
 	}
 }
@@ -55,6 +61,9 @@ pub struct IpcConfiguration {
 	pub enabled: bool,
 	pub socket_addr: String,
 	pub apis: ApiSet,
+	pub limits: RequestLimits,
+	pub anonymous_apis: Option<ApiSet>,
+	pub keys_path: String,
 }
 
 impl Default for IpcConfiguration {
@@ -63,6 +72,9 @@ impl Default for IpcConfiguration {
 			enabled: true,
 			socket_addr: parity_ipc_path("$HOME/.parity/jsonrpc.ipc"),
 			apis: ApiSet::UnsafeContext,
+			limits: Default::default(),
+			anonymous_apis: None,
+			keys_path: replace_home("$HOME/.parity/rpc_keys.json"),
 		}
 	}
 }
@@ -80,6 +92,7 @@ impl fmt::Display for IpcConfiguration {
 pub struct Dependencies {
 	pub panic_handler: Arc<PanicHandler>,
 	pub apis: Arc<rpc_apis::Dependencies>,
+	pub metrics: Arc<RpcMetrics>,
 }
 
 pub fn new_http(conf: HttpConfiguration, deps: &Dependencies) -> Result<Option<HttpServer>, String> {
@@ -89,7 +102,17 @@ pub fn new_http(conf: HttpConfiguration, deps: &Dependencies) -> Result<Option<H
 
 	let url = format!("{}:{}", conf.interface, conf.port);
 	let addr = try!(url.parse().map_err(|_| format!("Invalid JSONRPC listen host/port given: {}", url)));
-	Ok(Some(try!(setup_http_rpc_server(deps, &addr, conf.cors, conf.hosts, conf.apis))))
+	let keys = try!(load_keys(&conf.keys_path, deps));
+	let apis = conf.anonymous_apis.unwrap_or(conf.apis);
+	Ok(Some(try!(setup_http_rpc_server(deps, &addr, conf.cors, conf.hosts, conf.limits, keys, apis))))
+}
+
+fn load_keys(path: &str, deps: &Dependencies) -> Result<ApiKeys, String> {
+	let keys = try!(rpc_keys::load_keys(path, |apis| setup_rpc_server(apis, deps)));
+	if !keys.is_empty() {
+		info!("Loaded RPC API keys from {}", path);
+	}
+	Ok(keys)
 }
 
 fn setup_rpc_server(apis: ApiSet, deps: &Dependencies) -> Result<Server, String> {
@@ -102,11 +125,14 @@ pub fn setup_http_rpc_server(
 	url: &SocketAddr,
 	cors_domains: Option<Vec<String>>,
 	allowed_hosts: Option<Vec<String>>,
+	limits: RequestLimits,
+	keys: ApiKeys,
 	apis: ApiSet
 ) -> Result<HttpServer, String> {
 	let server = try!(setup_rpc_server(apis, dependencies));
 	let ph = dependencies.panic_handler.clone();
-	let start_result = server.start_http(url, cors_domains, allowed_hosts, ph);
+	let metrics = dependencies.metrics.clone();
+	let start_result = server.start_http(url, cors_domains, allowed_hosts, limits, keys, metrics, ph);
 	match start_result {
 		Err(RpcServerError::IoError(err)) => Err(format!("RPC io error: {}", err)),
 		Err(e) => Err(format!("RPC error: {:?}", e)),
@@ -116,14 +142,15 @@ pub fn setup_http_rpc_server(
 
 pub fn new_ipc(conf: IpcConfiguration, deps: &Dependencies) -> Result<Option<IpcServer>, String> {
 	if !conf.enabled { return Ok(None); }
-	Ok(Some(try!(setup_ipc_rpc_server(deps, &conf.socket_addr, conf.apis))))
+	let keys = try!(load_keys(&conf.keys_path, deps));
+	let apis = conf.anonymous_apis.unwrap_or(conf.apis);
+	Ok(Some(try!(setup_ipc_rpc_server(deps, &conf.socket_addr, conf.limits, keys, apis))))
 }
 
-pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, apis: ApiSet) -> Result<IpcServer, String> {
+pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, limits: RequestLimits, keys: ApiKeys, apis: ApiSet) -> Result<IpcServer, String> {
 	let server = try!(setup_rpc_server(apis, dependencies));
-	match server.start_ipc(addr) {
-		Err(jsonipc::Error::Io(io_error)) => Err(format!("RPC io error: {}", io_error)),
-		Err(any_error) => Err(format!("Rpc error: {:?}", any_error)),
-		Ok(server) => Ok(server)
-	}
+	let ph = dependencies.panic_handler.clone();
+	let metrics = dependencies.metrics.clone();
+	server.start_ipc(addr, limits, keys, metrics, ph)
+		.map_err(|io_error| format!("RPC io error: {}", io_error))
 }
@@ -19,6 +19,7 @@ use std::collections::HashSet;
 use std::cmp::PartialEq;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::path::PathBuf;
 use util::RotatingLogger;
 use ethcore::miner::{Miner, ExternalMiner};
 use ethcore::client::Client;
@@ -38,6 +39,7 @@ pub enum Api {
 	Ethcore,
 	EthcoreSet,
 	Traces,
+	Debug,
 	Rpc,
 }
 
@@ -56,6 +58,7 @@ impl FromStr for Api {
 			"ethcore" => Ok(Ethcore),
 			"ethcore_set" => Ok(EthcoreSet),
 			"traces" => Ok(Traces),
+			"debug" => Ok(Debug),
 			"rpc" => Ok(Rpc),
 			api => Err(format!("Unknown api: {}", api))
 		}
@@ -102,9 +105,12 @@ pub struct Dependencies {
 	pub miner: Arc<Miner>,
 	pub external_miner: Arc<ExternalMiner>,
 	pub logger: Arc<RotatingLogger>,
+	pub logs_path: PathBuf,
+	pub backups_path: PathBuf,
 	pub settings: Arc<NetworkSettings>,
 	pub net_service: Arc<ManageNetwork>,
 	pub geth_compatibility: bool,
+	pub max_results: Option<usize>,
 }
 
 fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
@@ -119,6 +125,7 @@ fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
 			Api::Ethcore => ("ethcore", "1.0"),
 			Api::EthcoreSet => ("ethcore_set", "1.0"),
 			Api::Traces => ("traces", "1.0"),
+			Api::Debug => ("debug", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
 		};
 		modules.insert(name.into(), version.into());
@@ -135,7 +142,7 @@ impl ApiSet {
 					.into_iter().collect()
 			},
 			_ => {
-				vec![Api::Web3, Api::Net, Api::Eth, Api::Personal, Api::Signer, Api::Ethcore, Api::EthcoreSet, Api::Traces, Api::Rpc]
+				vec![Api::Web3, Api::Net, Api::Eth, Api::Personal, Api::Signer, Api::Ethcore, Api::EthcoreSet, Api::Traces, Api::Debug, Api::Rpc]
 					.into_iter().collect()
 			},
 		}
@@ -165,11 +172,12 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 					EthClientOptions {
 						allow_pending_receipt_query: !deps.geth_compatibility,
 						send_block_number_in_get_work: !deps.geth_compatibility,
+						max_results: deps.max_results,
 					}
 				);
 				server.add_delegate(client.to_delegate());
 
-				let filter_client = EthFilterClient::new(&deps.client, &deps.miner);
+				let filter_client = EthFilterClient::new(&deps.client, &deps.miner, deps.max_results);
 				server.add_delegate(filter_client.to_delegate());
 
 				if deps.signer_port.is_some() {
@@ -186,13 +194,16 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 			},
 			Api::Ethcore => {
 				let queue = deps.signer_port.map(|_| deps.signer_queue.clone());
-				server.add_delegate(EthcoreClient::new(&deps.client, &deps.miner, deps.logger.clone(), deps.settings.clone(), queue).to_delegate())
+				server.add_delegate(EthcoreClient::new(&deps.client, &deps.miner, &deps.sync, deps.logger.clone(), deps.settings.clone(), queue).to_delegate())
 			},
 			Api::EthcoreSet => {
-				server.add_delegate(EthcoreSetClient::new(&deps.client, &deps.miner, &deps.net_service).to_delegate())
+				server.add_delegate(EthcoreSetClient::new(&deps.client, &deps.miner, &deps.net_service, deps.logger.clone(), deps.logs_path.clone(), deps.backups_path.clone()).to_delegate())
 			},
 			Api::Traces => {
-				server.add_delegate(TracesClient::new(&deps.client, &deps.miner).to_delegate())
+				server.add_delegate(TracesClient::new(&deps.client, &deps.miner, deps.max_results).to_delegate())
+			},
+			Api::Debug => {
+				server.add_delegate(DebugClient::new(&deps.client, &deps.miner).to_delegate())
 			},
 			Api::Rpc => {
 				let modules = to_modules(&apis);
@@ -217,6 +228,7 @@ mod test {
 		assert_eq!(Api::Ethcore, "ethcore".parse().unwrap());
 		assert_eq!(Api::EthcoreSet, "ethcore_set".parse().unwrap());
 		assert_eq!(Api::Traces, "traces".parse().unwrap());
+		assert_eq!(Api::Debug, "debug".parse().unwrap());
 		assert_eq!(Api::Rpc, "rpc".parse().unwrap());
 		assert!("rp".parse::<Api>().is_err());
 	}
@@ -240,7 +252,7 @@ mod test {
 
 	#[test]
 	fn test_api_set_safe_context() {
-		let expected = vec![Api::Web3, Api::Net, Api::Eth, Api::Personal, Api::Signer, Api::Ethcore, Api::EthcoreSet, Api::Traces, Api::Rpc]
+		let expected = vec![Api::Web3, Api::Net, Api::Eth, Api::Personal, Api::Signer, Api::Ethcore, Api::EthcoreSet, Api::Traces, Api::Debug, Api::Rpc]
 			.into_iter().collect();
 		assert_eq!(ApiSet::SafeContext.list_apis(), expected);
 	}
@@ -0,0 +1,163 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC API keys file and management commands.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use rustc_serialize::json;
+use ansi_term::Colour;
+use util::{H256, FixedHash, Hashable};
+use util::path::restrict_permissions_owner;
+use ethcore_rpc::{Access, ApiKeys, RpcServer, API_KEY_HEADER, API_KEY_MEMBER};
+use rpc_apis::ApiSet;
+
+/// Single API key entry. Only hash of the key is stored.
+#[derive(Debug, PartialEq, Clone, RustcEncodable, RustcDecodable)]
+pub struct ApiKey {
+	/// Name of the key.
+	pub name: String,
+	/// Hex-encoded sha3 of the key.
+	pub key_hash: String,
+	/// Comma-delimited list of permitted API namespaces.
+	pub apis: String,
+	/// Optional whitelist of methods within permitted namespaces.
+	pub methods: Option<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RpcKeysCmd {
+	New(NewKey),
+	List(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NewKey {
+	pub path: String,
+	pub name: String,
+	pub apis: String,
+	pub methods: Option<Vec<String>>,
+}
+
+pub fn execute(cmd: RpcKeysCmd) -> Result<String, String> {
+	match cmd {
+		RpcKeysCmd::New(new_cmd) => new(new_cmd),
+		RpcKeysCmd::List(path) => list(path),
+	}
+}
+
+/// Reads keys from file. Missing file means no keys.
+pub fn read_keys<P: AsRef<Path>>(path: P) -> Result<Vec<ApiKey>, String> {
+	let mut file = match File::open(path.as_ref()) {
+		Ok(file) => file,
+		Err(_) => return Ok(Vec::new()),
+	};
+	let mut content = String::new();
+	try!(file.read_to_string(&mut content).map_err(|e| format!("Could not read RPC keys file: {}", e)));
+	if content.trim().is_empty() {
+		return Ok(Vec::new());
+	}
+	json::decode(&content).map_err(|e| format!("Invalid RPC keys file {}: {}", path.as_ref().display(), e))
+}
+
+fn write_keys<P: AsRef<Path>>(path: P, keys: &[ApiKey]) -> Result<(), String> {
+	let content = try!(json::encode(&keys).map_err(|e| format!("Could not serialize RPC keys: {}", e)));
+	let mut file = try!(File::create(path.as_ref()).map_err(|e| format!("Could not create RPC keys file: {}", e)));
+	try!(file.write_all(content.as_bytes()).map_err(|e| format!("Could not write RPC keys file: {}", e)));
+	let _ = restrict_permissions_owner(path.as_ref());
+	Ok(())
+}
+
+/// Builds `ApiKeys` from keys in file, using `setup` to create a server for each set of namespaces.
+pub fn load_keys<P, F>(path: P, mut setup: F) -> Result<ApiKeys, String> where
+	P: AsRef<Path>,
+	F: FnMut(ApiSet) -> Result<RpcServer, String>,
+{
+	let mut api_keys = ApiKeys::new();
+	for key in try!(read_keys(path)) {
+		let hash: H256 = try!(key.key_hash.parse().map_err(|_| format!("Invalid hash of RPC key {}", key.name)));
+		let server = try!(setup(try!(key.apis.parse())));
+		let methods = key.methods.map(|m| m.into_iter().collect());
+		api_keys.insert(hash, Access::new(key.name, server.handler(), methods));
+	}
+	Ok(api_keys)
+}
+
+fn new(n: NewKey) -> Result<String, String> {
+	// validate namespaces before storing anything
+	try!(n.apis.parse::<ApiSet>());
+
+	let mut keys = try!(read_keys(&n.path));
+	if keys.iter().any(|k| k.name == n.name) {
+		return Err(format!("RPC key named {} already exists.", n.name));
+	}
+
+	let key = H256::random().hex();
+	keys.push(ApiKey {
+		name: n.name.clone(),
+		key_hash: key.sha3().hex(),
+		apis: n.apis,
+		methods: n.methods,
+	});
+	try!(write_keys(&n.path, &keys));
+
+	Ok(format!("New RPC key {} created. Pass it in {} HTTP header or {} member of IPC requests: {}", n.name, API_KEY_HEADER, API_KEY_MEMBER, Colour::White.bold().paint(key)))
+}
+
+fn list(path: String) -> Result<String, String> {
+	let keys = try!(read_keys(path));
+	let result = keys.into_iter()
+		.map(|k| match k.methods {
+			Some(methods) => format!("{}: {} (methods: {})", k.name, k.apis, methods.join(",")),
+			None => format!("{}: {}", k.name, k.apis),
+		})
+		.collect::<Vec<String>>()
+		.join("\n");
+
+	Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use devtools::RandomTempPath;
+	use ethcore_rpc::RpcServer;
+	use super::{execute, read_keys, load_keys, RpcKeysCmd, NewKey};
+
+	#[test]
+	fn should_create_and_load_keys() {
+		// given
+		let path = RandomTempPath::new();
+		let file = path.as_str().to_owned();
+		let new_key = |name: &str| RpcKeysCmd::New(NewKey {
+			path: file.clone(),
+			name: name.into(),
+			apis: "web3,eth".into(),
+			methods: Some(vec!["eth_call".into()]),
+		});
+
+		// when
+		execute(new_key("first")).unwrap();
+		execute(new_key("second")).unwrap();
+
+		// then
+		assert!(execute(new_key("first")).is_err());
+		assert_eq!(read_keys(&file).unwrap().len(), 2);
+		let keys = load_keys(&file, |_| Ok(RpcServer::new())).unwrap();
+		assert!(!keys.is_empty());
+		assert!(keys.get("invalid").is_none());
+	}
+}
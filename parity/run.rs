@@ -15,16 +15,17 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::{Arc, Mutex, Condvar};
+use std::thread;
 use std::path::Path;
 use std::io::ErrorKind;
 use ctrlc::CtrlC;
 use fdlimit::raise_fd_limit;
 use ethcore_logger::{Config as LogConfig, setup_log};
-use ethcore_rpc::NetworkSettings;
+use ethcore_rpc::{NetworkSettings, RpcMetrics};
 use ethsync::NetworkConfiguration;
 use util::{Colour, version, U256};
 use io::{MayPanic, ForwardPanic, PanicHandler};
-use ethcore::client::{Mode, Switch, DatabaseCompactionProfile, VMType, ChainNotify};
+use ethcore::client::{Mode, Switch, DatabaseCompactionProfile, VMType, ChainNotify, BlockChainClient};
 use ethcore::service::ClientService;
 use ethcore::account_provider::AccountProvider;
 use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
@@ -36,13 +37,16 @@ use ethcore::client::ChainNotify;
 use rpc::{HttpServer, IpcServer, HttpConfiguration, IpcConfiguration};
 use signer::SignerServer;
 use dapps::WebappServer;
+use metrics::MetricsServer;
 use io_handler::ClientIoHandler;
 use params::{SpecType, Pruning, AccountsConfig, GasPricerConfig, MinerExtras};
-use helpers::{to_client_config, execute_upgrades, passwords_from_files};
+use helpers::{to_client_config, to_trie_spec, execute_upgrades, passwords_from_files};
+use blockchain::build_traces_in_background;
 use dir::Directories;
 use cache::CacheConfig;
 use dapps;
 use signer;
+use metrics;
 use modules;
 use rpc_apis;
 use rpc;
@@ -54,6 +58,7 @@ pub struct RunCmd {
 	pub dirs: Directories,
 	pub spec: SpecType,
 	pub pruning: Pruning,
+	pub pruning_history: u64,
 	/// Some if execution should be daemonized. Contains pid_file path.
 	pub daemon: Option<String>,
 	pub logger_config: LogConfig,
@@ -67,18 +72,24 @@ pub struct RunCmd {
 	pub miner_extras: MinerExtras,
 	pub mode: Mode,
 	pub tracing: Switch,
+	pub build_traces: bool,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
 	pub vm_type: VMType,
 	pub enable_network: bool,
 	pub geth_compatibility: bool,
+	pub rpc_max_results: Option<usize>,
 	pub signer_port: Option<u16>,
 	pub net_settings: NetworkSettings,
 	pub dapps_conf: dapps::Configuration,
 	pub signer_conf: signer::Configuration,
+	pub metrics_conf: metrics::Configuration,
 	pub ui: bool,
 	pub name: String,
 	pub custom_bootnodes: bool,
+	pub fat_db: bool,
+	pub ancient_depth: u64,
+	pub history_blocks: Option<u64>,
 }
 
 pub fn execute(cmd: RunCmd) -> Result<(), String> {
@@ -146,20 +157,28 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	miner.set_extra_data(cmd.miner_extras.extra_data);
 	miner.set_transactions_limit(cmd.miner_extras.transactions_limit);
 
+	// turn on tracing when traces of existing blocks are going to be built
+	let tracing = if cmd.build_traces { Switch::On } else { cmd.tracing };
+
 	// create client config
-	let client_config = to_client_config(
+	let mut client_config = to_client_config(
 		&cmd.cache_config,
 		&cmd.dirs,
 		genesis_hash,
 		cmd.mode,
-		cmd.tracing,
+		tracing,
 		cmd.pruning,
+		cmd.pruning_history,
 		cmd.compaction,
 		cmd.wal,
 		cmd.vm_type,
 		cmd.name,
 		fork_name.as_ref(),
 	);
+	client_config.tracing.build_missing = cmd.build_traces;
+	client_config.trie_spec = try!(to_trie_spec(cmd.fat_db, algorithm));
+	client_config.blockchain.ancient_depth = cmd.ancient_depth;
+	client_config.blockchain.history_blocks = cmd.history_blocks;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -181,6 +200,16 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	// take handle to client
 	let client = service.client();
 
+	// build missing traces in background
+	if let Some((from, to)) = client.missing_traces() {
+		info!("Building traces of blocks #{}..#{} in background", from, to);
+		let weak_client = Arc::downgrade(&client);
+		try!(thread::Builder::new()
+			.name("Trace builder".into())
+			.spawn(move || build_traces_in_background(weak_client))
+			.map_err(|e| format!("Failed to spawn trace builder: {}", e)));
+	}
+
 	// create external miner
 	let external_miner = Arc::new(ExternalMiner::default());
 
@@ -207,14 +236,20 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 		miner: miner.clone(),
 		external_miner: external_miner.clone(),
 		logger: logger.clone(),
+		logs_path: Path::new(&cmd.dirs.db).join("logs"),
+		backups_path: Path::new(&cmd.dirs.db).join("backups"),
 		settings: Arc::new(cmd.net_settings.clone()),
 		net_service: manage_network.clone(),
 		geth_compatibility: cmd.geth_compatibility,
+		max_results: cmd.rpc_max_results,
 	});
 
+	let rpc_metrics = Arc::new(RpcMetrics::default());
+
 	let dependencies = rpc::Dependencies {
 		panic_handler: panic_handler.clone(),
 		apis: deps_for_rpc_apis.clone(),
+		metrics: rpc_metrics.clone(),
 	};
 
 	// start rpc servers
@@ -237,6 +272,17 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	// start signer server
 	let signer_server = try!(signer::start(cmd.signer_conf, signer_deps));
 
+	let metrics_deps = metrics::Dependencies {
+		client: client.clone(),
+		sync: sync_provider.clone(),
+		net: manage_network.clone(),
+		miner: miner.clone(),
+		rpc: rpc_metrics.clone(),
+	};
+
+	// start metrics server
+	let metrics_server = try!(metrics::start(cmd.metrics_conf, metrics_deps));
+
 	let informant = Arc::new(Informant::new(service.client(), Some(sync_provider.clone()), Some(manage_network.clone()), cmd.logger_config.color));
 	let info_notify: Arc<ChainNotify> = informant.clone();
 	service.add_notify(info_notify);
@@ -258,7 +304,7 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	}
 
 	// Handle exit
-	wait_for_exit(panic_handler, http_server, ipc_server, dapps_server, signer_server);
+	wait_for_exit(panic_handler, http_server, ipc_server, dapps_server, signer_server, metrics_server);
 
 	Ok(())
 }
@@ -322,7 +368,8 @@ fn wait_for_exit(
 	_http_server: Option<HttpServer>,
 	_ipc_server: Option<IpcServer>,
 	_dapps_server: Option<WebappServer>,
-	_signer_server: Option<SignerServer>
+	_signer_server: Option<SignerServer>,
+	_metrics_server: Option<MetricsServer>
 	) {
 	let exit = Arc::new(Condvar::new());
 
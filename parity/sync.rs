@@ -41,6 +41,7 @@ Usage:
                            format as RUST_LOG.
   --log-file FILENAME      Specify a filename into which logging should be
                            directed.
+  --log-format FORMAT      Specify the format of log entries [default: text].
   --no-color               Don't use terminal color codes in output.
 ";
 
@@ -48,16 +49,20 @@ Usage:
 struct Args {
 	flag_logging: Option<String>,
 	flag_log_file: Option<String>,
+	flag_log_format: String,
 	flag_no_color: bool,
 }
 
 impl Args {
-	pub fn log_settings(&self) -> LogConfig {
-		LogConfig {
+	pub fn log_settings(&self) -> Result<LogConfig, String> {
+		let config = LogConfig {
 			color: self.flag_no_color || cfg!(windows),
 			mode: self.flag_logging.clone(),
 			file: self.flag_log_file.clone(),
-		}
+			format: try!(self.flag_log_format.parse()),
+		};
+
+		Ok(config)
 	}
 }
 
@@ -80,7 +85,14 @@ pub fn main() {
 		.and_then(|d| d.decode())
 		.unwrap_or_else(|e| e.exit());
 
-	setup_log(&args.log_settings()).expect("Log initialization failure");
+	let log_settings = match args.log_settings() {
+		Ok(settings) => settings,
+		Err(err) => {
+			println!("{}", err);
+			std::process::exit(1);
+		}
+	};
+	setup_log(&log_settings).expect("Log initialization failure");
 
 	let mut buffer = Vec::new();
 	io::stdin().read_to_end(&mut buffer).expect("Failed to read initialisation payload");
@@ -124,7 +124,7 @@ impl Configuration {
 			let accounts = Arc::new(TestAccountProvider::new(accs));
 			let server = rpc::RpcServer::new();
 			server.add_delegate(EthClient::new(&client, &sync, &accounts, &miner, true).to_delegate());
-			server.add_delegate(EthFilterClient::new(&client, &miner).to_delegate());
+			server.add_delegate(EthFilterClient::new(&client, &miner, None).to_delegate());
 
 			let url = format!("{}:{}", self.args.flag_jsonrpc_addr, self.args.flag_jsonrpc_port);
 			let panic_handler = server.start_http(url.as_ref(), "*", 1);
@@ -0,0 +1,280 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JSON-RPC over HTTP transport.
+
+use std::{io, thread};
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::net::{SocketAddr, IpAddr};
+use hyper::{self, header, server, Decoder, Encoder, Next};
+use hyper::method::Method;
+use hyper::net::HttpStream;
+use hyper::status::StatusCode;
+use unicase::UniCase;
+use jsonrpc_core::{IoHandler, Value};
+use jsonrpc_http_server::is_host_header_valid;
+use io::PanicHandler;
+use limits::{Limiter, LimitError};
+use keys::{Access, ApiKeys, API_KEY_HEADER};
+
+/// Allowed value of the `Origin` header.
+pub use hyper::header::AccessControlAllowOrigin;
+
+/// Size of a single read from the request body.
+const READ_CHUNK: usize = 4096;
+
+/// Handles a single HTTP request and dispatches its body to the `IoHandler`.
+pub struct RpcHandler {
+	access: Arc<Access>,
+	keys: Arc<ApiKeys>,
+	limiter: Arc<Limiter>,
+	cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+	allowed_hosts: Option<Vec<String>>,
+	remote: Option<IpAddr>,
+	cors: Option<AccessControlAllowOrigin>,
+	request: Vec<u8>,
+	status: StatusCode,
+	response: String,
+	write_pos: usize,
+}
+
+impl RpcHandler {
+	fn new(
+		access: Arc<Access>,
+		keys: Arc<ApiKeys>,
+		limiter: Arc<Limiter>,
+		cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+		allowed_hosts: Option<Vec<String>>,
+	) -> Self {
+		RpcHandler {
+			access: access,
+			keys: keys,
+			limiter: limiter,
+			cors_domains: cors_domains,
+			allowed_hosts: allowed_hosts,
+			remote: None,
+			cors: None,
+			request: Vec::new(),
+			status: StatusCode::Ok,
+			response: String::new(),
+			write_pos: 0,
+		}
+	}
+
+	fn cors_header(&self, origin: Option<String>) -> Option<AccessControlAllowOrigin> {
+		let domains = match self.cors_domains {
+			Some(ref domains) => domains,
+			None => return None,
+		};
+
+		match origin {
+			Some(ref origin) => domains.iter().find(|domain| match **domain {
+				AccessControlAllowOrigin::Any => true,
+				AccessControlAllowOrigin::Null => origin == "null",
+				AccessControlAllowOrigin::Value(ref value) => value == origin,
+			}).cloned(),
+			None => None,
+		}
+	}
+
+	fn respond(&mut self, status: StatusCode, response: String) -> Next {
+		self.status = status;
+		self.response = response;
+		Next::write()
+	}
+
+	fn reject(&mut self, error: LimitError) -> Next {
+		debug!(target: "rpc", "Rejecting HTTP request from {:?}: {:?}", self.remote, error);
+		let response = error.to_response(Value::Null);
+		self.respond(StatusCode::Ok, response)
+	}
+}
+
+impl server::Handler<HttpStream> for RpcHandler {
+	fn on_request(&mut self, request: server::Request<HttpStream>) -> Next {
+		if let Some(ref hosts) = self.allowed_hosts {
+			if !is_host_header_valid(&request, hosts) {
+				return self.respond(StatusCode::Forbidden, "Provided Host header is not whitelisted.\n".into());
+			}
+		}
+
+		self.remote = request.remote_addr().map(|addr| addr.ip());
+		let origin = request.headers().get_raw("origin")
+			.and_then(|list| list.get(0))
+			.and_then(|origin| String::from_utf8(origin.clone()).ok());
+		self.cors = self.cors_header(origin);
+
+		match *request.method() {
+			Method::Options => return self.respond(StatusCode::Ok, String::new()),
+			Method::Post => {},
+			_ => return self.respond(StatusCode::MethodNotAllowed, "Used HTTP Method is not allowed. POST or OPTIONS is required\n".into()),
+		}
+
+		let key = request.headers().get_raw(API_KEY_HEADER)
+			.and_then(|list| list.get(0))
+			.and_then(|key| String::from_utf8(key.clone()).ok());
+		if let Some(key) = key {
+			match self.keys.get(&key) {
+				Some(access) => self.access = access,
+				None => return self.reject(LimitError::InvalidApiKey),
+			}
+		}
+
+		if let Some(&header::ContentLength(length)) = request.headers().get::<header::ContentLength>() {
+			if !self.limiter.is_size_allowed(length as usize) {
+				let max = self.limiter.limits().max_request_size;
+				return self.reject(LimitError::RequestTooLarge(max));
+			}
+		}
+
+		Next::read()
+	}
+
+	fn on_request_readable(&mut self, decoder: &mut Decoder<HttpStream>) -> Next {
+		let mut chunk = [0u8; READ_CHUNK];
+		loop {
+			match decoder.read(&mut chunk) {
+				Ok(0) => break,
+				Ok(read) => {
+					self.request.extend_from_slice(&chunk[..read]);
+					if !self.limiter.is_size_allowed(self.request.len()) {
+						let max = self.limiter.limits().max_request_size;
+						return self.reject(LimitError::RequestTooLarge(max));
+					}
+				},
+				Err(e) => return match e.kind() {
+					io::ErrorKind::WouldBlock => Next::read(),
+					_ => Next::end(),
+				},
+			}
+		}
+
+		let request = match String::from_utf8(::std::mem::replace(&mut self.request, Vec::new())) {
+			Ok(request) => request,
+			Err(_) => return self.reject(LimitError::Parse),
+		};
+		let response = self.limiter.handle_request(&self.access, &request, self.remote).unwrap_or_else(String::new);
+		self.respond(StatusCode::Ok, response)
+	}
+
+	fn on_response(&mut self, res: &mut server::Response) -> Next {
+		res.set_status(self.status);
+		let mut headers = res.headers_mut();
+		headers.set(header::ContentType::json());
+		headers.set(header::ContentLength(self.response.len() as u64));
+		headers.set(header::Allow(vec![Method::Options, Method::Post]));
+		headers.set(header::AccessControlAllowHeaders(vec![
+			UniCase("origin".to_owned()),
+			UniCase("content-type".to_owned()),
+			UniCase("accept".to_owned()),
+			UniCase(API_KEY_HEADER.to_lowercase()),
+		]));
+		if let Some(ref cors) = self.cors {
+			headers.set(cors.clone());
+		}
+		Next::write()
+	}
+
+	fn on_response_writable(&mut self, encoder: &mut Encoder<HttpStream>) -> Next {
+		let bytes = self.response.as_bytes();
+		if self.write_pos == bytes.len() {
+			return Next::end();
+		}
+
+		match encoder.write(&bytes[self.write_pos..]) {
+			Ok(bytes) => {
+				self.write_pos += bytes;
+				Next::write()
+			},
+			Err(e) => match e.kind() {
+				io::ErrorKind::WouldBlock => Next::write(),
+				_ => Next::end(),
+			},
+		}
+	}
+}
+
+/// RPC HTTP server handle. The server is closed when dropped.
+pub struct Server {
+	server: Option<server::Listening>,
+}
+
+impl Server {
+	/// Starts the server on given address.
+	pub fn start(
+		addr: &SocketAddr,
+		handler: Arc<IoHandler>,
+		keys: ApiKeys,
+		limiter: Arc<Limiter>,
+		cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+		allowed_hosts: Option<Vec<String>>,
+		panic_handler: Arc<PanicHandler>,
+	) -> Result<Server, RpcServerError> {
+		// Always allow the address we are bound to.
+		let allowed_hosts = allowed_hosts.map(|mut hosts| {
+			let bind_address = format!("{}", addr);
+			hosts.push(bind_address.replace("127.0.0.1", "localhost"));
+			hosts.push(bind_address);
+			hosts
+		});
+
+		let access = Arc::new(Access::new("anonymous".into(), handler, None));
+		let keys = Arc::new(keys);
+		try!(hyper::Server::http(addr))
+			.handle(move |_| RpcHandler::new(
+				access.clone(),
+				keys.clone(),
+				limiter.clone(),
+				cors_domains.clone(),
+				allowed_hosts.clone(),
+			))
+			.map(|(l, srv)| {
+				thread::spawn(move || {
+					let _ = panic_handler.catch_panic(move || srv.run());
+				});
+
+				Server {
+					server: Some(l),
+				}
+			})
+			.map_err(RpcServerError::from)
+	}
+}
+
+impl Drop for Server {
+	fn drop(&mut self) {
+		self.server.take().unwrap().close()
+	}
+}
+
+/// RPC server startup error.
+#[derive(Debug)]
+pub enum RpcServerError {
+	/// Wrapped `std::io::Error`
+	IoError(io::Error),
+	/// Other `hyper` error
+	Other(hyper::error::Error),
+}
+
+impl From<hyper::error::Error> for RpcServerError {
+	fn from(err: hyper::error::Error) -> Self {
+		match err {
+			hyper::error::Error::Io(e) => RpcServerError::IoError(e),
+			e => RpcServerError::Other(e),
+		}
+	}
+}
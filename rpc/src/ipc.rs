@@ -0,0 +1,324 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JSON-RPC over IPC transport.
+//!
+//! Requests are JSON values written one after another to the socket. A request may carry
+//! an API key in its `apiKey` member; in a batch all calls carrying a key have to use the same one.
+
+use std::sync::Arc;
+use serde_json;
+use jsonrpc_core::Value;
+use limits::{Limiter, LimitError};
+use keys::{Access, ApiKeys};
+
+/// Name of the request member carrying the API key.
+pub const API_KEY_MEMBER: &'static str = "apiKey";
+
+/// Size of a single read from the socket.
+#[cfg(unix)]
+const READ_CHUNK: usize = 4096;
+
+/// Removes API keys from the request. Returns the request to dispatch and the key, if any.
+/// Requests which cannot be parsed are returned untouched.
+fn take_api_key(request: &str) -> Result<(String, Option<String>), LimitError> {
+	fn take(call: &mut Value) -> Result<Option<String>, LimitError> {
+		match *call {
+			Value::Object(ref mut members) => match members.remove(API_KEY_MEMBER) {
+				Some(Value::String(key)) => Ok(Some(key)),
+				Some(_) => Err(LimitError::InvalidApiKey),
+				None => Ok(None),
+			},
+			_ => Ok(None),
+		}
+	}
+
+	let mut value: Value = match serde_json::from_str(request) {
+		Ok(value) => value,
+		Err(_) => return Ok((request.to_owned(), None)),
+	};
+
+	let key = match value {
+		Value::Array(ref mut calls) => {
+			let mut key: Option<String> = None;
+			for call in calls.iter_mut() {
+				if let Some(k) = try!(take(call)) {
+					if key.as_ref().map_or(false, |previous| previous != &k) {
+						return Err(LimitError::InvalidApiKey);
+					}
+					key = Some(k);
+				}
+			}
+			key
+		},
+		ref mut call => try!(take(call)),
+	};
+
+	match key {
+		Some(key) => Ok((serde_json::to_string(&value).expect("Serialization of Value never fails; qed"), Some(key))),
+		None => Ok((request.to_owned(), None)),
+	}
+}
+
+/// Returns the length of the first complete JSON value in `buffer`, including leading whitespace.
+/// If the buffer does not start with an object or an array everything is returned,
+/// so that the garbage is rejected as a parse error.
+fn request_end(buffer: &[u8]) -> Option<usize> {
+	let start = match buffer.iter().position(|b| !(*b as char).is_whitespace()) {
+		Some(start) => start,
+		None => return None,
+	};
+	if buffer[start] != b'{' && buffer[start] != b'[' {
+		return Some(buffer.len());
+	}
+
+	let mut depth = 0usize;
+	let mut in_string = false;
+	let mut escaped = false;
+	for (i, b) in buffer.iter().enumerate().skip(start) {
+		if in_string {
+			match *b {
+				_ if escaped => escaped = false,
+				b'\\' => escaped = true,
+				b'"' => in_string = false,
+				_ => {},
+			}
+			continue;
+		}
+
+		match *b {
+			b'"' => in_string = true,
+			b'{' | b'[' => depth += 1,
+			b'}' | b']' => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(i + 1);
+				}
+			},
+			_ => {},
+		}
+	}
+	None
+}
+
+/// Handles a single request using the access granted by its key, or `access` if there is none.
+fn handle_request(request: &str, access: &Arc<Access>, keys: &ApiKeys, limiter: &Limiter) -> Option<String> {
+	let (request, key) = match take_api_key(request) {
+		Ok(result) => result,
+		Err(err) => return Some(err.to_response(Value::Null)),
+	};
+
+	let access = match key {
+		Some(key) => match keys.get(&key) {
+			Some(access) => access,
+			None => return Some(LimitError::InvalidApiKey.to_response(Value::Null)),
+		},
+		None => access.clone(),
+	};
+
+	limiter.handle_request(&access, &request, None)
+}
+
+#[cfg(unix)]
+mod unix {
+	use std::{fs, io, thread};
+	use std::io::{Read, Write};
+	use std::path::PathBuf;
+	use std::sync::Arc;
+	use std::sync::atomic::{AtomicBool, Ordering};
+	use std::os::unix::net::{UnixListener, UnixStream};
+	use jsonrpc_core::Value;
+	use io::PanicHandler;
+	use limits::{Limiter, LimitError};
+	use keys::{Access, ApiKeys};
+	use super::{READ_CHUNK, request_end, handle_request};
+
+	/// RPC IPC server handle. The server is closed when dropped.
+	pub struct Server {
+		path: PathBuf,
+		closed: Arc<AtomicBool>,
+	}
+
+	impl Server {
+		/// Starts the server listening on a unix socket at given path.
+		pub fn start(
+			path: &str,
+			access: Arc<Access>,
+			keys: ApiKeys,
+			limiter: Arc<Limiter>,
+			panic_handler: Arc<PanicHandler>,
+		) -> io::Result<Server> {
+			// remove a socket left behind by a previous run
+			let _ = fs::remove_file(path);
+			let listener = try!(UnixListener::bind(path));
+			let closed = Arc::new(AtomicBool::new(false));
+			let keys = Arc::new(keys);
+
+			let is_closed = closed.clone();
+			thread::spawn(move || {
+				let _ = panic_handler.catch_panic(move || {
+					for stream in listener.incoming() {
+						if is_closed.load(Ordering::SeqCst) {
+							break;
+						}
+						match stream {
+							Ok(stream) => {
+								let access = access.clone();
+								let keys = keys.clone();
+								let limiter = limiter.clone();
+								thread::spawn(move || handle_connection(stream, access, keys, limiter));
+							},
+							Err(e) => warn!(target: "rpc", "Error accepting IPC connection: {}", e),
+						}
+					}
+				});
+			});
+
+			Ok(Server {
+				path: PathBuf::from(path),
+				closed: closed,
+			})
+		}
+	}
+
+	impl Drop for Server {
+		fn drop(&mut self) {
+			self.closed.store(true, Ordering::SeqCst);
+			// wake up the listener so that it notices the server is closed.
+			let _ = UnixStream::connect(&self.path);
+			let _ = fs::remove_file(&self.path);
+		}
+	}
+
+	fn handle_connection(mut stream: UnixStream, access: Arc<Access>, keys: Arc<ApiKeys>, limiter: Arc<Limiter>) {
+		let mut buffer = Vec::new();
+		let mut chunk = [0u8; READ_CHUNK];
+		loop {
+			let read = match stream.read(&mut chunk) {
+				Ok(0) | Err(_) => return,
+				Ok(read) => read,
+			};
+			buffer.extend_from_slice(&chunk[..read]);
+
+			while let Some(end) = request_end(&buffer) {
+				let request: Vec<u8> = buffer.drain(..end).collect();
+				let response = match String::from_utf8(request) {
+					Ok(request) => handle_request(&request, &access, &keys, &limiter),
+					Err(_) => Some(LimitError::Parse.to_response(Value::Null)),
+				};
+				if let Some(response) = response {
+					if stream.write_all(response.as_bytes()).and_then(|_| stream.write_all(b"\n")).is_err() {
+						return;
+					}
+				}
+			}
+
+			if !limiter.is_size_allowed(buffer.len()) {
+				let max = limiter.limits().max_request_size;
+				let _ = stream.write_all(LimitError::RequestTooLarge(max).to_response(Value::Null).as_bytes());
+				return;
+			}
+		}
+	}
+}
+
+#[cfg(not(unix))]
+mod fallback {
+	use std::io;
+	use std::sync::Arc;
+	use jsonipc;
+	use io::PanicHandler;
+	use limits::Limiter;
+	use keys::{Access, ApiKeys};
+
+	/// RPC IPC server handle. The server is closed when dropped.
+	///
+	/// Only requests without an API key are supported on this platform.
+	/// Request limits are not applied.
+	pub struct Server {
+		_server: jsonipc::Server,
+	}
+
+	impl Server {
+		/// Starts the server listening on a named pipe at given path.
+		pub fn start(
+			path: &str,
+			access: Arc<Access>,
+			keys: ApiKeys,
+			_limiter: Arc<Limiter>,
+			_panic_handler: Arc<PanicHandler>,
+		) -> io::Result<Server> {
+			if !keys.is_empty() {
+				warn!(target: "rpc", "API keys are not supported over IPC on this platform.");
+			}
+			let to_io = |e| io::Error::new(io::ErrorKind::Other, format!("{:?}", e));
+			let server = try!(jsonipc::Server::new(path, access.handler()).map_err(&to_io));
+			try!(server.run_async().map_err(&to_io));
+			Ok(Server {
+				_server: server,
+			})
+		}
+	}
+}
+
+#[cfg(unix)]
+pub use self::unix::Server;
+#[cfg(not(unix))]
+pub use self::fallback::Server;
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use jsonrpc_core::{IoHandler, Value};
+	use util::Hashable;
+	use limits::{Limiter, LimitError};
+	use keys::{Access, ApiKeys};
+	use super::{take_api_key, request_end, handle_request};
+
+	#[test]
+	fn should_find_end_of_request() {
+		assert_eq!(request_end(b" {\"a\":\"}\\\"\"}{"), Some(12));
+		assert_eq!(request_end(b"[{}, {}]"), Some(8));
+		assert_eq!(request_end(b"{\"a\":[1,"), None);
+		assert_eq!(request_end(b"  "), None);
+		assert_eq!(request_end(b"garbage"), Some(7));
+	}
+
+	#[test]
+	fn should_take_api_key() {
+		let request = r#"{"apiKey":"secret","id":1,"jsonrpc":"2.0","method":"eth_call"}"#;
+		let batch = r#"[{"apiKey":"secret","id":1,"method":"eth_call"},{"apiKey":"other","id":2,"method":"eth_call"}]"#;
+
+		assert_eq!(take_api_key(request), Ok((r#"{"id":1,"jsonrpc":"2.0","method":"eth_call"}"#.to_owned(), Some("secret".to_owned()))));
+		assert_eq!(take_api_key(batch), Err(LimitError::InvalidApiKey));
+		assert_eq!(take_api_key("garbage"), Ok(("garbage".to_owned(), None)));
+	}
+
+	#[test]
+	fn should_reject_unknown_keys_and_methods() {
+		let mut keys = ApiKeys::new();
+		let methods = vec!["web3_sha3".to_owned()].into_iter().collect();
+		keys.insert("secret".sha3(), Access::new("partner".into(), Arc::new(IoHandler::new()), Some(methods)));
+		let anonymous = Arc::new(Access::new("anonymous".into(), Arc::new(IoHandler::new()), None));
+		let limiter = Limiter::new(Default::default(), Default::default());
+
+		let unknown = r#"{"apiKey":"unknown","id":1,"jsonrpc":"2.0","method":"web3_sha3","params":[]}"#;
+		let not_allowed = r#"{"apiKey":"secret","id":1,"jsonrpc":"2.0","method":"eth_call","params":[]}"#;
+
+		assert_eq!(handle_request(unknown, &anonymous, &keys, &limiter), Some(LimitError::InvalidApiKey.to_response(Value::Null)));
+		assert_eq!(handle_request(not_allowed, &anonymous, &keys, &limiter), Some(LimitError::MethodNotAllowed("eth_call".into()).to_response(Value::Null)));
+	}
+}
@@ -0,0 +1,113 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! API keys granting access to a subset of RPC methods.
+
+use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use jsonrpc_core::IoHandler;
+use util::{H256, Hashable};
+
+/// Name of HTTP header carrying the API key.
+pub const API_KEY_HEADER: &'static str = "X-Api-Key";
+
+/// Set of methods available to a client.
+pub struct Access {
+	name: String,
+	handler: Arc<IoHandler>,
+	methods: Option<HashSet<String>>,
+}
+
+impl Access {
+	/// Creates new `Access` dispatching to `handler`.
+	/// If `methods` are given only those methods may be called.
+	pub fn new(name: String, handler: Arc<IoHandler>, methods: Option<HashSet<String>>) -> Self {
+		Access {
+			name: name,
+			handler: handler,
+			methods: methods,
+		}
+	}
+
+	/// Name of the key (or client class) this access is granted to.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Handler with delegates of all permitted namespaces.
+	pub fn handler(&self) -> &Arc<IoHandler> {
+		&self.handler
+	}
+
+	/// Returns `true` if method is not excluded by the method whitelist.
+	/// Methods outside of permitted namespaces are not registered in the `handler` at all.
+	pub fn is_allowed(&self, method: &str) -> bool {
+		self.methods.as_ref().map_or(true, |methods| methods.contains(method))
+	}
+}
+
+/// Collection of API keys indexed by their hashes.
+#[derive(Default)]
+pub struct ApiKeys {
+	keys: HashMap<H256, Arc<Access>>,
+}
+
+impl ApiKeys {
+	/// Creates empty collection.
+	pub fn new() -> Self {
+		ApiKeys::default()
+	}
+
+	/// Adds a key given by its hash.
+	pub fn insert(&mut self, key_hash: H256, access: Access) {
+		self.keys.insert(key_hash, Arc::new(access));
+	}
+
+	/// Returns access granted to given key.
+	pub fn get(&self, key: &str) -> Option<Arc<Access>> {
+		self.keys.get(&key.trim().sha3()).cloned()
+	}
+
+	/// Returns `true` if there are no keys configured.
+	pub fn is_empty(&self) -> bool {
+		self.keys.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use jsonrpc_core::IoHandler;
+	use util::Hashable;
+	use super::{Access, ApiKeys};
+
+	#[test]
+	fn should_find_access_by_key() {
+		// given
+		let mut keys = ApiKeys::new();
+		let methods = vec!["eth_call".to_owned()].into_iter().collect();
+		keys.insert("secret".sha3(), Access::new("partner".into(), Arc::new(IoHandler::new()), Some(methods)));
+
+		// when
+		let access = keys.get("secret").unwrap();
+
+		// then
+		assert_eq!(access.name(), "partner");
+		assert!(access.is_allowed("eth_call"));
+		assert!(!access.is_allowed("eth_sendTransaction"));
+		assert!(keys.get("other").is_none());
+	}
+}
@@ -26,13 +26,16 @@ extern crate serde;
 extern crate serde_json;
 extern crate jsonrpc_core;
 extern crate jsonrpc_http_server;
+extern crate hyper;
+extern crate unicase;
 #[macro_use]
 extern crate ethcore_util as util;
 extern crate ethcore_io as io;
 extern crate ethcore;
 extern crate ethsync;
 extern crate transient_hashmap;
-extern crate json_ipc_server as ipc;
+#[cfg(not(unix))]
+extern crate json_ipc_server as jsonipc;
 extern crate ethcore_ipc;
 
 #[cfg(test)]
@@ -45,7 +48,17 @@ use std::net::SocketAddr;
 use io::PanicHandler;
 use self::jsonrpc_core::{IoHandler, IoDelegate};
 
-pub use jsonrpc_http_server::{ServerBuilder, Server, RpcServerError};
+mod http;
+mod ipc;
+mod keys;
+mod limits;
+mod metrics;
+
+pub use http::{Server, RpcServerError, AccessControlAllowOrigin};
+pub use ipc::{Server as IpcServer, API_KEY_MEMBER};
+pub use keys::{Access, ApiKeys, API_KEY_HEADER};
+pub use limits::{RequestLimits, Limiter};
+pub use metrics::{RpcMetrics, MethodMetrics};
 pub mod v1;
 pub use v1::{SigningQueue, ConfirmationsQueue, NetworkSettings};
 
@@ -75,38 +88,51 @@ impl RpcServer {
 		}
 	}
 
+	/// Returns handler with all registered delegates.
+	pub fn handler(&self) -> Arc<IoHandler> {
+		self.handler.clone()
+	}
+
 	/// Start http server asynchronously and returns result with `Server` handle on success or an error.
+	/// Requests carrying one of `keys` are dispatched to handler of that key instead of this server.
+	/// Handled calls are recorded in `metrics`.
 	pub fn start_http(
 		&self,
 		addr: &SocketAddr,
 		cors_domains: Option<Vec<String>>,
 		allowed_hosts: Option<Vec<String>>,
+		limits: RequestLimits,
+		keys: ApiKeys,
+		metrics: Arc<RpcMetrics>,
 		panic_handler: Arc<PanicHandler>,
 		) -> Result<Server, RpcServerError> {
 
 		let cors_domains = cors_domains.map(|domains| {
 			domains.into_iter()
 				.map(|v| match v.as_str() {
-					"*" => jsonrpc_http_server::AccessControlAllowOrigin::Any,
-					"null" => jsonrpc_http_server::AccessControlAllowOrigin::Null,
-					v => jsonrpc_http_server::AccessControlAllowOrigin::Value(v.into()),
+					"*" => AccessControlAllowOrigin::Any,
+					"null" => AccessControlAllowOrigin::Null,
+					v => AccessControlAllowOrigin::Value(v.into()),
 				})
 				.collect()
 		});
 
-		ServerBuilder::new(self.handler.clone())
-			.cors(cors_domains.into())
-			.allowed_hosts(allowed_hosts.into())
-			.panic_handler(move || {
-				panic_handler.notify_all("Panic in RPC thread.".to_owned());
-			})
-			.start_http(addr)
+		Server::start(addr, self.handler.clone(), keys, Arc::new(Limiter::new(limits, metrics)), cors_domains, allowed_hosts, panic_handler)
 	}
 
-	/// Start ipc server asynchronously and returns result with `Server` handle on success or an error.
-	pub fn start_ipc(&self, addr: &str) -> Result<ipc::Server, ipc::Error> {
-		let server = try!(ipc::Server::new(addr, &self.handler));
-		try!(server.run_async());
-		Ok(server)
+	/// Start ipc server asynchronously and returns result with `IpcServer` handle on success or an error.
+	/// Requests carrying one of `keys` are dispatched to handler of that key instead of this server.
+	/// Handled calls are recorded in `metrics`.
+	pub fn start_ipc(
+		&self,
+		addr: &str,
+		limits: RequestLimits,
+		keys: ApiKeys,
+		metrics: Arc<RpcMetrics>,
+		panic_handler: Arc<PanicHandler>,
+		) -> ::std::io::Result<IpcServer> {
+
+		let access = Arc::new(Access::new("anonymous".into(), self.handler.clone(), None));
+		IpcServer::start(addr, access, keys, Arc::new(Limiter::new(limits, metrics)), panic_handler)
 	}
 }
@@ -0,0 +1,485 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Request limits enforced by RPC transports before dispatching calls.
+
+use std::thread;
+use std::sync::{Arc, Mutex, Condvar};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap};
+use serde_json;
+use jsonrpc_core::Value;
+use keys::Access;
+use metrics::RpcMetrics;
+
+/// Methods that are subject to `call_timeout`.
+const TIMED_METHODS: &'static [&'static str] = &["eth_call", "eth_estimateGas", "eth_getLogs"];
+/// Prefixes of methods that are subject to `call_timeout`.
+const TIMED_PREFIXES: &'static [&'static str] = &["trace_", "debug_"];
+/// Length of a rate limiting window.
+const RATE_WINDOW_SECS: u64 = 60;
+/// Number of tracked rate counters after which stale ones are collected.
+const RATE_GC_THRESHOLD: usize = 4096;
+
+mod error_codes {
+	// NOTE Codes from [-32099, -32000], see `v1::impls::error_codes`.
+	pub const RATE_LIMITED: i64 = -32050;
+	pub const BATCH_TOO_LARGE: i64 = -32051;
+	pub const REQUEST_TOO_LARGE: i64 = -32052;
+	pub const CALL_TIMEOUT: i64 = -32053;
+	pub const SERVER_BUSY: i64 = -32054;
+	pub const INVALID_API_KEY: i64 = -32060;
+	pub const METHOD_NOT_ALLOWED: i64 = -32061;
+	pub const PARSE_ERROR: i64 = -32700;
+}
+
+/// Limits applied to incoming requests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestLimits {
+	/// Maximal size of request body in bytes.
+	pub max_request_size: usize,
+	/// Maximal number of calls in a single batch. `0` means no limit.
+	pub max_batch_size: usize,
+	/// Maximal number of calls per minute coming from a single client address. `0` means no limit.
+	pub client_rate: u32,
+	/// Maximal number of calls per minute of particular method coming from a single client address.
+	pub method_rates: HashMap<String, u32>,
+	/// Execution timeout of expensive calls (`eth_call`, `eth_estimateGas`, `eth_getLogs`, `trace_*`).
+	pub call_timeout: Option<Duration>,
+	/// Maximal number of timed calls running at once, including the ones which already timed out.
+	pub max_timed_calls: usize,
+}
+
+impl Default for RequestLimits {
+	fn default() -> Self {
+		RequestLimits {
+			max_request_size: 5 * 1024 * 1024,
+			max_batch_size: 0,
+			client_rate: 0,
+			method_rates: HashMap::new(),
+			call_timeout: None,
+			max_timed_calls: 32,
+		}
+	}
+}
+
+/// Reason for rejecting a request.
+#[derive(Debug, PartialEq)]
+pub enum LimitError {
+	/// Request body is larger than allowed.
+	RequestTooLarge(usize),
+	/// Too many calls in a batch.
+	BatchTooLarge(usize),
+	/// Client exceeded the rate for given method (or all methods if `None`).
+	RateLimited(Option<String>),
+	/// Request could not be parsed.
+	Parse,
+	/// Call did not finish in time.
+	Timeout,
+	/// Too many timed calls are running.
+	Busy,
+	/// Provided API key is unknown.
+	InvalidApiKey,
+	/// Method is not available with provided API key.
+	MethodNotAllowed(String),
+}
+
+impl LimitError {
+	fn code(&self) -> i64 {
+		match *self {
+			LimitError::RequestTooLarge(_) => error_codes::REQUEST_TOO_LARGE,
+			LimitError::BatchTooLarge(_) => error_codes::BATCH_TOO_LARGE,
+			LimitError::RateLimited(_) => error_codes::RATE_LIMITED,
+			LimitError::Parse => error_codes::PARSE_ERROR,
+			LimitError::Timeout => error_codes::CALL_TIMEOUT,
+			LimitError::Busy => error_codes::SERVER_BUSY,
+			LimitError::InvalidApiKey => error_codes::INVALID_API_KEY,
+			LimitError::MethodNotAllowed(_) => error_codes::METHOD_NOT_ALLOWED,
+		}
+	}
+
+	fn message(&self) -> String {
+		match *self {
+			LimitError::RequestTooLarge(max) => format!("Request exceeds the maximal size of {} bytes.", max),
+			LimitError::BatchTooLarge(max) => format!("Batch exceeds the maximal number of {} calls.", max),
+			LimitError::RateLimited(Some(ref method)) => format!("Rate limit exceeded for method {}. Try again later.", method),
+			LimitError::RateLimited(None) => "Rate limit exceeded. Try again later.".into(),
+			LimitError::Parse => "Parse error".into(),
+			LimitError::Timeout => "Request timed out. Try narrowing down the query.".into(),
+			LimitError::Busy => "Too many expensive calls in progress. Try again later.".into(),
+			LimitError::InvalidApiKey => "Invalid API key.".into(),
+			LimitError::MethodNotAllowed(ref method) => format!("Method {} is not allowed with provided API key.", method),
+		}
+	}
+
+	/// Returns serialized JSON-RPC failure response with given id.
+	pub fn to_response(&self, id: Value) -> String {
+		let mut error = BTreeMap::new();
+		error.insert("code".to_owned(), Value::I64(self.code()));
+		error.insert("message".to_owned(), Value::String(self.message()));
+
+		let mut response = BTreeMap::new();
+		response.insert("jsonrpc".to_owned(), Value::String("2.0".into()));
+		response.insert("error".to_owned(), Value::Object(error));
+		response.insert("id".to_owned(), id);
+		serde_json::to_string(&Value::Object(response)).expect("Serialization of BTreeMap of Values never fails; qed")
+	}
+}
+
+/// Fixed window rate counters keyed by client address and method.
+#[derive(Default)]
+struct RateCounters {
+	counters: HashMap<(IpAddr, Option<String>), (Instant, u32)>,
+}
+
+impl RateCounters {
+	/// Registers a call and returns `true` if it's within the limit.
+	fn hit(&mut self, ip: IpAddr, method: Option<&str>, limit: u32) -> bool {
+		let window = Duration::from_secs(RATE_WINDOW_SECS);
+		let now = Instant::now();
+
+		if self.counters.len() > RATE_GC_THRESHOLD {
+			self.counters.retain(|_, &mut (start, _)| now.duration_since(start) < window);
+		}
+
+		let entry = self.counters.entry((ip, method.map(Into::into))).or_insert((now, 0));
+		if now.duration_since(entry.0) >= window {
+			*entry = (now, 0);
+		}
+		entry.1 += 1;
+		entry.1 <= limit
+	}
+}
+
+/// Slot of a running timed call, released when dropped even if the call panics.
+struct TimedCallSlot(Arc<AtomicUsize>);
+
+impl Drop for TimedCallSlot {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+/// Validates incoming requests against `RequestLimits` and dispatches them.
+pub struct Limiter {
+	limits: RequestLimits,
+	rates: Mutex<RateCounters>,
+	metrics: Arc<RpcMetrics>,
+	/// Number of running timed calls.
+	running: Arc<AtomicUsize>,
+}
+
+impl Limiter {
+	/// Creates new limiter. Dispatched calls are recorded in `metrics`.
+	pub fn new(limits: RequestLimits, metrics: Arc<RpcMetrics>) -> Self {
+		Limiter {
+			limits: limits,
+			rates: Mutex::new(RateCounters::default()),
+			metrics: metrics,
+			running: Arc::new(AtomicUsize::new(0)),
+		}
+	}
+
+	/// Returns configured limits.
+	pub fn limits(&self) -> &RequestLimits {
+		&self.limits
+	}
+
+	/// Returns `true` if body of given size is acceptable.
+	pub fn is_size_allowed(&self, size: usize) -> bool {
+		size <= self.limits.max_request_size
+	}
+
+	/// Checks request and returns names of called methods.
+	pub fn check(&self, request: &str, remote: Option<IpAddr>) -> Result<Vec<String>, (LimitError, Value)> {
+		if !self.is_size_allowed(request.len()) {
+			return Err((LimitError::RequestTooLarge(self.limits.max_request_size), Value::Null));
+		}
+
+		let request: Value = try!(serde_json::from_str(request).map_err(|_| (LimitError::Parse, Value::Null)));
+		let (calls, id) = match request {
+			Value::Array(calls) => (calls, Value::Null),
+			call => {
+				let id = call.find("id").cloned().unwrap_or(Value::Null);
+				(vec![call], id)
+			},
+		};
+
+		if self.limits.max_batch_size > 0 && calls.len() > self.limits.max_batch_size {
+			return Err((LimitError::BatchTooLarge(self.limits.max_batch_size), id));
+		}
+
+		let methods = calls.iter()
+			.filter_map(|call| call.find("method").and_then(Value::as_string).map(Into::into))
+			.collect::<Vec<String>>();
+
+		if let Some(ip) = remote {
+			let mut rates = self.rates.lock().unwrap();
+			for method in &methods {
+				if self.limits.client_rate > 0 && !rates.hit(ip, None, self.limits.client_rate) {
+					return Err((LimitError::RateLimited(None), id));
+				}
+				if let Some(limit) = self.limits.method_rates.get(method) {
+					if !rates.hit(ip, Some(method), *limit) {
+						return Err((LimitError::RateLimited(Some(method.clone())), id));
+					}
+				}
+			}
+		}
+
+		Ok(methods)
+	}
+
+	/// Validates the request and dispatches it to handler of given `access`, applying the call timeout if needed.
+	pub fn handle_request(&self, access: &Access, request: &str, remote: Option<IpAddr>) -> Option<String> {
+		let methods = match self.check(request, remote) {
+			Ok(methods) => methods,
+			Err((err, id)) => {
+				debug!(target: "rpc", "Rejecting request from {:?}: {:?}", remote, err);
+				return Some(err.to_response(id));
+			},
+		};
+
+		if let Some(method) = methods.iter().find(|method| !access.is_allowed(method)) {
+			debug!(target: "rpc", "Rejecting call to {} from {:?} using key {}", method, remote, access.name());
+			return Some(LimitError::MethodNotAllowed(method.clone()).to_response(Value::Null));
+		}
+
+		self.dispatch(access, request)
+	}
+
+	fn dispatch(&self, access: &Access, request: &str) -> Option<String> {
+		// calls of a batch are dispatched one by one, so that each of them is timed and recorded separately.
+		let calls = match serde_json::from_str::<Value>(request) {
+			Ok(Value::Array(ref calls)) if calls.is_empty() => return access.handler().handle_request(request),
+			Ok(Value::Array(calls)) => calls,
+			Ok(call) => return self.dispatch_call(access, &call),
+			Err(_) => return access.handler().handle_request(request),
+		};
+
+		let responses = calls.iter()
+			.filter_map(|call| self.dispatch_call(access, call))
+			.filter_map(|response| serde_json::from_str::<Value>(&response).ok())
+			.collect::<Vec<_>>();
+		if responses.is_empty() {
+			None
+		} else {
+			Some(serde_json::to_string(&Value::Array(responses)).expect("Serialization of Values never fails; qed"))
+		}
+	}
+
+	/// Dispatches a single call, applying the timeout if it's expensive, and records the time it took.
+	fn dispatch_call(&self, access: &Access, call: &Value) -> Option<String> {
+		let request = serde_json::to_string(call).expect("Serialization of Values never fails; qed");
+		let method = call.find("method").and_then(Value::as_string).map(|method| method.to_owned());
+
+		let start = Instant::now();
+		let response = match (self.limits.call_timeout, method.as_ref()) {
+			(Some(timeout), Some(method)) if is_timed(method) => self.dispatch_timed(access, call, request, method, timeout),
+			_ => access.handler().handle_request(&request),
+		};
+		if let Some(ref method) = method {
+			self.metrics.record(method, start.elapsed());
+		}
+		response
+	}
+
+	/// Dispatches an expensive call with a timeout.
+	/// Fails if too many timed calls are already running.
+	fn dispatch_timed(&self, access: &Access, call: &Value, request: String, method: &str, timeout: Duration) -> Option<String> {
+		let id = call.find("id").cloned().unwrap_or(Value::Null);
+		if self.running.fetch_add(1, Ordering::SeqCst) >= self.limits.max_timed_calls {
+			self.running.fetch_sub(1, Ordering::SeqCst);
+			debug!(target: "rpc", "Rejecting call to {}: too many expensive calls in progress.", method);
+			return Some(LimitError::Busy.to_response(id));
+		}
+
+		let handler = access.handler().clone();
+		let slot = TimedCallSlot(self.running.clone());
+		let result = with_timeout(timeout, move || {
+			let _slot = slot;
+			handler.handle_request(&request)
+		});
+		match result {
+			Some(response) => response,
+			None => {
+				warn!(target: "rpc", "Call to {} exceeded the timeout of {}s.", method, timeout.as_secs());
+				Some(LimitError::Timeout.to_response(id))
+			}
+		}
+	}
+}
+
+fn is_timed(method: &str) -> bool {
+	TIMED_METHODS.contains(&method) || TIMED_PREFIXES.iter().any(|prefix| method.starts_with(prefix))
+}
+
+/// Runs `f` on a separate thread and waits for the result at most `timeout`.
+/// The thread is not stopped when the timeout is exceeded.
+fn with_timeout<F, T>(timeout: Duration, f: F) -> Option<T> where
+	F: FnOnce() -> T + Send + 'static,
+	T: Send + 'static,
+{
+	let pair = Arc::new((Mutex::new(None), Condvar::new()));
+	let worker_pair = pair.clone();
+	thread::spawn(move || {
+		let result = f();
+		let &(ref lock, ref cvar) = &*worker_pair;
+		*lock.lock().unwrap() = Some(result);
+		cvar.notify_one();
+	});
+
+	let &(ref lock, ref cvar) = &*pair;
+	let start = Instant::now();
+	let mut result = lock.lock().unwrap();
+	while result.is_none() {
+		let elapsed = start.elapsed();
+		if elapsed >= timeout {
+			return None;
+		}
+		result = cvar.wait_timeout(result, timeout - elapsed).unwrap().0;
+	}
+	result.take()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::iter::repeat;
+	use std::thread;
+	use std::sync::Arc;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::time::Duration;
+	use std::collections::HashMap;
+	use serde_json;
+	use jsonrpc_core::{IoHandler, Value};
+	use keys::Access;
+	use super::{Limiter, RequestLimits, LimitError, TimedCallSlot, with_timeout};
+
+	fn limiter(max_batch_size: usize, client_rate: u32, method_rates: HashMap<String, u32>) -> Limiter {
+		Limiter::new(RequestLimits {
+			max_request_size: 128,
+			max_batch_size: max_batch_size,
+			client_rate: client_rate,
+			method_rates: method_rates,
+			call_timeout: None,
+			max_timed_calls: 32,
+		}, Default::default())
+	}
+
+	#[test]
+	fn should_reject_too_large_requests() {
+		let limiter = limiter(0, 0, HashMap::new());
+		let request = format!(r#"{{"jsonrpc":"2.0","method":"web3_sha3","params":["{}"],"id":1}}"#, repeat("a").take(128).collect::<String>());
+
+		assert_eq!(limiter.check(&request, None), Err((LimitError::RequestTooLarge(128), Value::Null)));
+	}
+
+	#[test]
+	fn should_reject_too_large_batches() {
+		let limiter = limiter(1, 0, HashMap::new());
+		let request = r#"[{"jsonrpc":"2.0","method":"eth_blockNumber","id":1},{"jsonrpc":"2.0","method":"eth_blockNumber","id":2}]"#;
+
+		assert_eq!(limiter.check(request, None), Err((LimitError::BatchTooLarge(1), Value::Null)));
+	}
+
+	#[test]
+	fn should_rate_limit_per_method_and_client() {
+		let mut rates = HashMap::new();
+		rates.insert("eth_getLogs".to_owned(), 1);
+		let limiter = limiter(0, 0, rates);
+		let request = r#"{"jsonrpc":"2.0","method":"eth_getLogs","params":[{}],"id":1}"#;
+		let client1 = "10.0.0.1".parse().unwrap();
+		let client2 = "10.0.0.2".parse().unwrap();
+
+		assert!(limiter.check(request, Some(client1)).is_ok());
+		assert_eq!(limiter.check(request, Some(client1)), Err((LimitError::RateLimited(Some("eth_getLogs".into())), Value::U64(1))));
+		assert!(limiter.check(request, Some(client2)).is_ok());
+	}
+
+	#[test]
+	fn should_rate_limit_all_methods() {
+		let limiter = limiter(0, 2, HashMap::new());
+		let request = r#"[{"jsonrpc":"2.0","method":"eth_blockNumber","id":1},{"jsonrpc":"2.0","method":"net_version","id":2}]"#;
+		let client = "10.0.0.1".parse().unwrap();
+
+		assert_eq!(limiter.check(request, Some(client)), Ok(vec!["eth_blockNumber".into(), "net_version".into()]));
+		assert_eq!(limiter.check(request, Some(client)), Err((LimitError::RateLimited(None), Value::Null)));
+	}
+
+	#[test]
+	fn should_serialize_limit_error() {
+		assert_eq!(
+			LimitError::Timeout.to_response(Value::U64(1)),
+			r#"{"error":{"code":-32053,"message":"Request timed out. Try narrowing down the query."},"id":1,"jsonrpc":"2.0"}"#
+		);
+	}
+
+	#[test]
+	fn should_return_none_on_timeout() {
+		assert_eq!(with_timeout(Duration::from_millis(500), || 5), Some(5));
+		assert_eq!(with_timeout(Duration::from_millis(10), || ::std::thread::sleep(Duration::from_millis(200))), None);
+	}
+
+	#[test]
+	fn should_reject_timed_calls_when_busy() {
+		let limiter = Limiter::new(RequestLimits {
+			call_timeout: Some(Duration::from_secs(1)),
+			..Default::default()
+		}, Default::default());
+		let access = Access::new("anonymous".into(), Arc::new(IoHandler::new()), None);
+		limiter.running.store(limiter.limits.max_timed_calls, Ordering::SeqCst);
+		let request = r#"[{"jsonrpc":"2.0","method":"eth_call","params":[],"id":1},{"jsonrpc":"2.0","method":"web3_sha3","params":[],"id":2}]"#;
+
+		let response: Value = serde_json::from_str(&limiter.handle_request(&access, request, None).unwrap()).unwrap();
+
+		// only the expensive call is rejected
+		let responses = response.as_array().unwrap();
+		assert_eq!(responses.len(), 2);
+		assert_eq!(responses[0].find_path(&["error", "code"]), Some(&Value::I64(-32054)));
+		assert_eq!(responses[0].find("id"), Some(&Value::U64(1)));
+		assert_eq!(responses[1].find_path(&["error", "code"]), Some(&Value::I64(-32601)));
+	}
+
+	#[test]
+	fn should_release_slot_of_panicking_call() {
+		let running = Arc::new(AtomicUsize::new(1));
+		let slot = TimedCallSlot(running.clone());
+
+		let result = thread::spawn(move || {
+			let _slot = slot;
+			panic!("call failed");
+		}).join();
+
+		assert!(result.is_err());
+		assert_eq!(running.load(Ordering::SeqCst), 0);
+	}
+
+	#[test]
+	fn should_record_each_call_of_batch() {
+		let limiter = Limiter::new(Default::default(), Default::default());
+		let access = Access::new("anonymous".into(), Arc::new(IoHandler::new()), None);
+		let request = r#"[{"jsonrpc":"2.0","method":"eth_call","params":[],"id":1},{"jsonrpc":"2.0","method":"web3_sha3","params":[],"id":2},{"jsonrpc":"2.0","method":"web3_sha3","params":[],"id":3}]"#;
+
+		limiter.handle_request(&access, request, None).unwrap();
+
+		let methods = limiter.metrics.methods();
+		assert_eq!(methods.len(), 2);
+		assert_eq!(methods["eth_call"].calls, 1);
+		assert_eq!(methods["web3_sha3"].calls, 2);
+	}
+}
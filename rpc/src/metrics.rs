@@ -0,0 +1,74 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-method statistics of handled RPC calls.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use std::collections::BTreeMap;
+
+/// Statistics of a single method.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MethodMetrics {
+	/// Number of handled calls.
+	pub calls: u64,
+	/// Total time spent handling the calls.
+	pub time: Duration,
+}
+
+/// Collects number of calls and time spent in each RPC method.
+#[derive(Default)]
+pub struct RpcMetrics {
+	methods: Mutex<BTreeMap<String, MethodMetrics>>,
+}
+
+impl RpcMetrics {
+	/// Registers a handled call.
+	pub fn record(&self, method: &str, time: Duration) {
+		let mut methods = self.methods.lock().unwrap();
+		let entry = methods.entry(method.to_owned()).or_insert_with(Default::default);
+		entry.calls += 1;
+		entry.time = entry.time + time;
+	}
+
+	/// Returns statistics of all methods called so far.
+	pub fn methods(&self) -> BTreeMap<String, MethodMetrics> {
+		self.methods.lock().unwrap().clone()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::RpcMetrics;
+
+	#[test]
+	fn should_accumulate_calls() {
+		// given
+		let metrics = RpcMetrics::default();
+
+		// when
+		metrics.record("eth_call", Duration::from_millis(10));
+		metrics.record("eth_call", Duration::from_millis(20));
+		metrics.record("eth_blockNumber", Duration::from_millis(1));
+
+		// then
+		let methods = metrics.methods();
+		assert_eq!(methods.len(), 2);
+		assert_eq!(methods["eth_call"].calls, 2);
+		assert_eq!(methods["eth_call"].time, Duration::from_millis(30));
+	}
+}
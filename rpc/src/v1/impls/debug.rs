@@ -0,0 +1,105 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug api implementation.
+
+use std::cmp;
+use std::sync::{Weak, Arc};
+use jsonrpc_core::*;
+use ethcore::client::{BlockChainClient, TransactionID};
+use ethcore::evm::instruction_info;
+use ethcore::miner::MinerService;
+use ethcore::trace::StructLogOptions as EthStructLogOptions;
+use v1::traits::Debug;
+use v1::types::{BlockNumber, CallRequest, H256, StructLogOptions, StructLogTrace};
+use v1::impls::{params_len, sign_call};
+
+/// Maximal number of steps recorded by a single call, also used when no limit is requested.
+const MAX_STRUCT_LOG_STEPS: usize = 10_000;
+/// Maximal total size of stacks, memory and storage recorded by a single call.
+const MAX_STRUCT_LOG_SIZE: usize = 64 * 1024 * 1024;
+
+fn to_struct_log_options(options: StructLogOptions) -> Result<EthStructLogOptions, Error> {
+	let mut opcodes = Vec::new();
+	for name in options.opcodes.unwrap_or_else(Vec::new) {
+		let name = name.to_uppercase();
+		match (0..256usize).map(|i| i as u8).find(|i| instruction_info(*i).0 == name) {
+			Some(instruction) => opcodes.push(instruction),
+			None => return Err(Error::invalid_params()),
+		}
+	}
+
+	Ok(EthStructLogOptions {
+		disable_memory: options.disable_memory.unwrap_or(false),
+		disable_stack: options.disable_stack.unwrap_or(false),
+		disable_storage: options.disable_storage.unwrap_or(false),
+		max_steps: Some(options.limit.map_or(MAX_STRUCT_LOG_STEPS, |limit| cmp::min(limit, MAX_STRUCT_LOG_STEPS))),
+		max_size: Some(MAX_STRUCT_LOG_SIZE),
+		opcodes: opcodes,
+	})
+}
+
+/// Debug api implementation.
+pub struct DebugClient<C, M> where C: BlockChainClient, M: MinerService {
+	client: Weak<C>,
+	miner: Weak<M>,
+}
+
+impl<C, M> DebugClient<C, M> where C: BlockChainClient, M: MinerService {
+	/// Creates new Debug client.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+		DebugClient {
+			client: Arc::downgrade(client),
+			miner: Arc::downgrade(miner),
+		}
+	}
+
+	fn active(&self) -> Result<(), Error> {
+		// TODO: only call every 30s at most.
+		take_weak!(self.client).keep_alive();
+		Ok(())
+	}
+}
+
+impl<C, M> Debug for DebugClient<C, M> where C: BlockChainClient + 'static, M: MinerService + 'static {
+	fn trace_transaction(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let (transaction_hash, options) = match params_len(&params) {
+			1 => try!(from_params::<(H256,)>(params).map(|(hash,)| (hash, StructLogOptions::default()))),
+			_ => try!(from_params::<(H256, StructLogOptions)>(params)),
+		};
+		let options = try!(to_struct_log_options(options));
+		match take_weak!(self.client).debug_replay(TransactionID::Hash(transaction_hash.into()), options) {
+			Ok(t) => to_value(&StructLogTrace::from(t)),
+			_ => Ok(Value::Null),
+		}
+	}
+
+	fn trace_call(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let (request, block, options) = match params_len(&params) {
+			1 => try!(from_params::<(CallRequest,)>(params).map(|(request,)| (request, BlockNumber::Latest, StructLogOptions::default()))),
+			2 => try!(from_params::<(CallRequest, BlockNumber)>(params).map(|(request, block)| (request, block, StructLogOptions::default()))),
+			_ => try!(from_params::<(CallRequest, BlockNumber, StructLogOptions)>(params)),
+		};
+		let options = try!(to_struct_log_options(options));
+		let signed = sign_call(&*take_weak!(self.client), &*take_weak!(self.miner), CallRequest::into(request));
+		match take_weak!(self.client).debug_call(&signed, block.into(), options) {
+			Ok(t) => to_value(&StructLogTrace::from(t)),
+			_ => Ok(Value::Null),
+		}
+	}
+}
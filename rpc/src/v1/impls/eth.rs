@@ -37,14 +37,12 @@ use ethcore::header::Header as BlockHeader;
 use ethcore::block::IsBlock;
 use ethcore::views::*;
 use ethcore::ethereum::Ethash;
-use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
 use ethcore::log_entry::LogEntry;
 use ethcore::filter::Filter as EthcoreFilter;
 use self::ethash::SeedHashCompute;
 use v1::traits::Eth;
-use v1::types::{Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo, Transaction, CallRequest, Index, Filter, Log, Receipt, H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256};
-use v1::helpers::CallRequest as CRequest;
-use v1::impls::{default_gas_price, dispatch_transaction, error_codes, from_params_default_second, from_params_default_third};
+use v1::types::{Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo, Transaction, CallRequest, StateOverride, Index, Filter, Log, Receipt, H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256};
+use v1::impls::{default_gas_price, dispatch_transaction, error_codes, from_params_default_second, from_params_default_second_and_third, from_params_default_third, limited_count, check_result_limit, check_history, sign_call};
 
 /// Eth RPC options
 pub struct EthClientOptions {
@@ -52,6 +50,8 @@ pub struct EthClientOptions {
 	pub allow_pending_receipt_query: bool,
 	/// Send additional block number when asking for work
 	pub send_block_number_in_get_work: bool,
+	/// Maximal number of logs returned by a single query
+	pub max_results: Option<usize>,
 }
 
 impl Default for EthClientOptions {
@@ -59,6 +59,7 @@ impl Default for EthClientOptions {
 		EthClientOptions {
 			allow_pending_receipt_query: true,
 			send_block_number_in_get_work: true,
+			max_results: None,
 		}
 	}
 }
@@ -101,7 +102,7 @@ impl<C, S: ?Sized, M, EM> EthClient<C, S, M, EM> where
 
 	fn block(&self, id: BlockID, include_txs: bool) -> Result<Value, Error> {
 		let client = take_weak!(self.client);
-		match (client.block(id.clone()), client.block_total_difficulty(id)) {
+		match (client.block(id.clone()), client.block_total_difficulty(id.clone())) {
 			(Some(bytes), Some(total_difficulty)) => {
 				let block_view = BlockView::new(&bytes);
 				let view = block_view.header_view();
@@ -132,14 +133,24 @@ impl<C, S: ?Sized, M, EM> EthClient<C, S, M, EM> where
 				};
 				to_value(&block)
 			},
+			(None, _) => check_history(&*client, id).map(|_| Value::Null),
 			_ => Ok(Value::Null)
 		}
 	}
 
 	fn transaction(&self, id: TransactionID) -> Result<Value, Error> {
-		match take_weak!(self.client).transaction(id) {
+		let client = take_weak!(self.client);
+		match client.transaction(id.clone()) {
 			Some(t) => to_value(&Transaction::from(t)),
-			None => Ok(Value::Null)
+			None => Self::check_transaction_history(&*client, id).map(|_| Value::Null),
+		}
+	}
+
+	/// Fails if the given transaction is known, but the body of its block has been pruned.
+	fn check_transaction_history(client: &C, id: TransactionID) -> Result<(), Error> {
+		match id {
+			TransactionID::Location(block, _) => check_history(client, block),
+			id => client.transaction_block(id).map_or(Ok(()), |hash| check_history(client, BlockID::Hash(hash))),
 		}
 	}
 
@@ -178,19 +189,6 @@ impl<C, S: ?Sized, M, EM> EthClient<C, S, M, EM> where
 		};
 		to_value(&block)
 	}
-
-	fn sign_call(&self, request: CRequest) -> Result<SignedTransaction, Error> {
-		let (client, miner) = (take_weak!(self.client), take_weak!(self.miner));
-		let from = request.from.unwrap_or(Address::zero());
-		Ok(EthTransaction {
-			nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
-			action: request.to.map_or(Action::Create, Action::Call),
-			gas: request.gas.unwrap_or(U256::from(50_000_000)),
-			gas_price: request.gas_price.unwrap_or_else(|| default_gas_price(&*client, &*miner)),
-			value: request.value.unwrap_or_else(U256::zero),
-			data: request.data.map_or_else(Vec::new, |d| d.to_vec())
-		}.fake_sign(from))
-	}
 }
 
 pub fn pending_logs<M>(miner: &M, filter: &EthcoreFilter) -> Vec<Log> where M: MinerService {
@@ -494,8 +492,10 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 					Some(receipt) if self.options.allow_pending_receipt_query => to_value(&Receipt::from(receipt.clone())),
 					_ => {
 						let client = take_weak!(self.client);
-						let receipt = client.transaction_receipt(TransactionID::Hash(hash));
-						to_value(&receipt.map(Receipt::from))
+						match client.transaction_receipt(TransactionID::Hash(hash)) {
+							Some(receipt) => to_value(&Receipt::from(receipt)),
+							None => Self::check_transaction_history(&*client, TransactionID::Hash(hash)).map(|_| Value::Null),
+						}
 					}
 				}
 			})
@@ -532,7 +532,8 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 		from_params::<(Filter,)>(params)
 			.and_then(|(filter,)| {
 				let include_pending = filter.to_block == Some(BlockNumber::Pending);
-				let filter: EthcoreFilter = filter.into();
+				let mut filter: EthcoreFilter = filter.into();
+				filter.limit = limited_count(filter.limit, self.options.max_results);
 				let mut logs = take_weak!(self.client).logs(filter.clone())
 					.into_iter()
 					.map(From::from)
@@ -543,6 +544,11 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 					logs.extend(pending);
 				}
 
+				if let Some(limit) = filter.limit {
+					logs.truncate(limit);
+				}
+				try!(check_result_limit(logs.len(), self.options.max_results));
+
 				to_value(&logs)
 			})
 	}
@@ -628,13 +634,14 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 	fn call(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		trace!(target: "jsonrpc", "call: {:?}", params);
-		from_params_default_second(params)
-			.and_then(|(request, block_number,)| {
+		from_params_default_second_and_third(params)
+			.and_then(|(request, block_number, state_override)| {
 				let request = CallRequest::into(request);
-				let signed = try!(self.sign_call(request));
+				let state_override = StateOverride::into(state_override);
+				let signed = sign_call(&*take_weak!(self.client), &*take_weak!(self.miner), request);
 				let r = match block_number {
-					BlockNumber::Pending => take_weak!(self.miner).call(take_weak!(self.client).deref(), &signed, Default::default()),
-					block_number => take_weak!(self.client).call(&signed, block_number.into(), Default::default()),
+					BlockNumber::Pending => take_weak!(self.miner).call(take_weak!(self.client).deref(), &signed, Default::default(), &state_override),
+					block_number => take_weak!(self.client).call(&signed, block_number.into(), Default::default(), &state_override),
 				};
 				to_value(&r.map(|e| Bytes(e.output)).unwrap_or(Bytes::new(vec![])))
 			})
@@ -645,10 +652,10 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 		from_params_default_second(params)
 			.and_then(|(request, block_number,)| {
 				let request = CallRequest::into(request);
-				let signed = try!(self.sign_call(request));
+				let signed = sign_call(&*take_weak!(self.client), &*take_weak!(self.miner), request);
 				let r = match block_number {
-					BlockNumber::Pending => take_weak!(self.miner).call(take_weak!(self.client).deref(), &signed, Default::default()),
-					block => take_weak!(self.client).call(&signed, block.into(), Default::default()),
+					BlockNumber::Pending => take_weak!(self.miner).call(take_weak!(self.client).deref(), &signed, Default::default(), &Default::default()),
+					block => take_weak!(self.client).call(&signed, block.into(), Default::default(), &Default::default()),
 				};
 				to_value(&RpcU256::from(r.map(|res| res.gas_used + res.refunded).unwrap_or(From::from(0))))
 			})
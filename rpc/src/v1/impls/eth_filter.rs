@@ -28,6 +28,7 @@ use v1::traits::EthFilter;
 use v1::types::{BlockNumber, Index, Filter, Log, H256 as RpcH256, U256 as RpcU256};
 use v1::helpers::{PollFilter, PollManager};
 use v1::impls::eth::pending_logs;
+use v1::impls::{limited_count, check_result_limit};
 
 /// Eth filter rpc implementation.
 pub struct EthFilterClient<C, M> where
@@ -37,6 +38,7 @@ pub struct EthFilterClient<C, M> where
 	client: Weak<C>,
 	miner: Weak<M>,
 	polls: Mutex<PollManager<PollFilter>>,
+	max_results: Option<usize>,
 }
 
 impl<C, M> EthFilterClient<C, M> where
@@ -44,11 +46,13 @@ impl<C, M> EthFilterClient<C, M> where
 	M: MinerService {
 
 	/// Creates new Eth filter client.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+	/// Queries returning more than `max_results` logs are rejected.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, max_results: Option<usize>) -> Self {
 		EthFilterClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
 			polls: Mutex::new(PollManager::new()),
+			max_results: max_results,
 		}
 	}
 
@@ -156,12 +160,14 @@ impl<C, M> EthFilter for EthFilterClient<C, M> where
 							let mut filter: EthcoreFilter = filter.clone().into();
 							filter.from_block = BlockID::Number(*block_number);
 							filter.to_block = BlockID::Latest;
+							filter.limit = limited_count(filter.limit, self.max_results);
 
 							// retrieve logs in range from_block..min(BlockID::Latest..to_block)
 							let mut logs = client.logs(filter.clone())
 								.into_iter()
 								.map(From::from)
 								.collect::<Vec<Log>>();
+							try!(check_result_limit(logs.len(), self.max_results));
 
 							// additionally retrieve pending logs
 							if include_pending {
@@ -178,6 +184,7 @@ impl<C, M> EthFilter for EthFilterClient<C, M> where
 
 								// append logs array with new pending logs
 								logs.extend(new_pending_logs);
+								try!(check_result_limit(logs.len(), self.max_results));
 							}
 
 							// save the number of the next block as a first block from which
@@ -199,7 +206,8 @@ impl<C, M> EthFilter for EthFilterClient<C, M> where
 				match polls.poll(&index.value()) {
 					Some(&PollFilter::Logs(ref _block_number, ref _previous_log, ref filter)) => {
 						let include_pending = filter.to_block == Some(BlockNumber::Pending);
-						let filter: EthcoreFilter = filter.clone().into();
+						let mut filter: EthcoreFilter = filter.clone().into();
+						filter.limit = limited_count(filter.limit, self.max_results);
 						let mut logs = take_weak!(self.client).logs(filter.clone())
 							.into_iter()
 							.map(From::from)
@@ -209,6 +217,11 @@ impl<C, M> EthFilter for EthFilterClient<C, M> where
 							logs.extend(pending_logs(take_weak!(self.miner).deref(), &filter));
 						}
 
+						if let Some(limit) = filter.limit {
+							logs.truncate(limit);
+						}
+						try!(check_result_limit(logs.len(), self.max_results));
+
 						to_value(&logs)
 					},
 					// just empty array
@@ -23,29 +23,33 @@ use std::collections::{BTreeMap};
 use ethcore::client::{MiningBlockChainClient};
 use jsonrpc_core::*;
 use ethcore::miner::MinerService;
+use ethsync::SyncProvider;
 use v1::traits::Ethcore;
-use v1::types::{Bytes, U256};
+use v1::types::{Bytes, U256, H256, Peer, QueuedTransaction, LocalTransactionStatus};
 use v1::helpers::{SigningQueue, ConfirmationsQueue, NetworkSettings};
 use v1::impls::signer_disabled_error;
 
 /// Ethcore implementation.
-pub struct EthcoreClient<C, M> where
+pub struct EthcoreClient<C, M, S: ?Sized> where
 	C: MiningBlockChainClient,
-	M: MinerService {
+	M: MinerService,
+	S: SyncProvider {
 
 	client: Weak<C>,
 	miner: Weak<M>,
+	sync: Weak<S>,
 	logger: Arc<RotatingLogger>,
 	settings: Arc<NetworkSettings>,
 	confirmations_queue: Option<Arc<ConfirmationsQueue>>,
 }
 
-impl<C, M> EthcoreClient<C, M> where C: MiningBlockChainClient, M: MinerService {
+impl<C, M, S: ?Sized> EthcoreClient<C, M, S> where C: MiningBlockChainClient, M: MinerService, S: SyncProvider {
 	/// Creates new `EthcoreClient`.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>, logger: Arc<RotatingLogger>, settings: Arc<NetworkSettings>, queue: Option<Arc<ConfirmationsQueue>>) -> Self {
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, sync: &Arc<S>, logger: Arc<RotatingLogger>, settings: Arc<NetworkSettings>, queue: Option<Arc<ConfirmationsQueue>>) -> Self {
 		EthcoreClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
+			sync: Arc::downgrade(sync),
 			logger: logger,
 			settings: settings,
 			confirmations_queue: queue,
@@ -59,7 +63,10 @@ impl<C, M> EthcoreClient<C, M> where C: MiningBlockChainClient, M: MinerService
 	}
 }
 
-impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: MiningBlockChainClient + 'static {
+impl<C, M, S: ?Sized> Ethcore for EthcoreClient<C, M, S> where
+	M: MinerService + 'static,
+	C: MiningBlockChainClient + 'static,
+	S: SyncProvider + 'static {
 
 	fn transactions_limit(&self, _: Params) -> Result<Value, Error> {
 		try!(self.active());
@@ -107,6 +114,12 @@ impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: M
 		to_value(&self.settings.max_peers)
 	}
 
+	fn net_peers(&self, _params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let peers = take_weak!(self.sync).peers().into_iter().map(Peer::from).collect::<Vec<_>>();
+		to_value(&peers)
+	}
+
 	fn net_port(&self, _params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		to_value(&self.settings.network_port)
@@ -155,4 +168,50 @@ impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: M
 			Some(ref queue) => to_value(&queue.len()),
 		}
 	}
+
+	fn queued_transactions(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		match params {
+			Params::None => {
+				let mut by_sender = BTreeMap::new();
+				for tx in take_weak!(self.miner).queued_transactions() {
+					let sender = tx.transaction.sender().expect("Transactions in the queue have verified signatures; qed");
+					by_sender.entry(format!("0x{}", sender.hex())).or_insert_with(Vec::new).push(tx);
+				}
+				let by_sender = by_sender.into_iter()
+					.map(|(sender, mut txs)| {
+						txs.sort_by(|a, b| a.transaction.nonce.cmp(&b.transaction.nonce));
+						(sender, txs.into_iter().map(QueuedTransaction::from).collect::<Vec<_>>())
+					})
+					.collect::<BTreeMap<_, _>>();
+				to_value(&by_sender)
+			},
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn transaction_rejection(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(H256,)>(params).and_then(|(hash,)| {
+			to_value(&take_weak!(self.miner).transaction_rejection(&hash.into()))
+		})
+	}
+
+	fn local_transactions(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		match params {
+			Params::None => {
+				let transactions = take_weak!(self.miner).local_transactions().into_iter()
+					.map(|(hash, status)| (format!("0x{}", hash.hex()), LocalTransactionStatus::from(status)))
+					.collect::<BTreeMap<_, _>>();
+				to_value(&transactions)
+			},
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn oldest_state(&self, _: Params) -> Result<Value, Error> {
+		try!(self.active());
+		to_value(&take_weak!(self.client).oldest_state().map(U256::from))
+	}
 }
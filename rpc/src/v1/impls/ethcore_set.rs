@@ -15,13 +15,18 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 /// Ethcore-specific rpc interface for operations altering the settings.
+use std::{fs, thread};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use jsonrpc_core::*;
+use util::{RotatingLogger, Mutex};
+use util::log::MAX_TEE_DURATION;
 use ethcore::miner::MinerService;
 use ethcore::client::MiningBlockChainClient;
 use ethsync::ManageNetwork;
 use v1::traits::EthcoreSet;
-use v1::types::{Bytes, H160, U256};
+use v1::types::{BackupStatus, Bytes, H160, H256, U256};
 
 /// Ethcore-specific rpc interface for operations altering the settings.
 pub struct EthcoreSetClient<C, M> where
@@ -31,17 +36,27 @@ pub struct EthcoreSetClient<C, M> where
 	client: Weak<C>,
 	miner: Weak<M>,
 	net: Weak<ManageNetwork>,
+	logger: Arc<RotatingLogger>,
+	logs_path: PathBuf,
+	backups_path: PathBuf,
+	backup: Arc<Mutex<BackupStatus>>,
 }
 
 impl<C, M> EthcoreSetClient<C, M> where
 	C: MiningBlockChainClient,
 	M: MinerService {
 	/// Creates new `EthcoreSetClient`.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>, net: &Arc<ManageNetwork>) -> Self {
+	/// Files requested by `ethcore_teeLog` are created in `logs_path`
+	/// and backups requested by `ethcore_backupDatabase` in `backups_path`.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, net: &Arc<ManageNetwork>, logger: Arc<RotatingLogger>, logs_path: PathBuf, backups_path: PathBuf) -> Self {
 		EthcoreSetClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
 			net: Arc::downgrade(net),
+			logger: logger,
+			logs_path: logs_path,
+			backups_path: backups_path,
+			backup: Arc::new(Mutex::new(BackupStatus::default())),
 		}
 	}
 
@@ -153,4 +168,107 @@ impl<C, M> EthcoreSet for EthcoreSetClient<C, M> where
 		take_weak!(self.net).stop_network();
 		Ok(Value::Bool(true))
 	}
+
+	fn set_log_levels(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String,)>(params).and_then(|(directives,)| {
+			match self.logger.set_levels(&directives) {
+				Ok(()) => to_value(&true),
+				Err(e) => Err(invalid_params(e)),
+			}
+		})
+	}
+
+	fn tee_log(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(String, String, u64)>(params).and_then(|(target, name, seconds)| {
+			// only plain file names, tees must not escape the logs directory
+			if Path::new(&name).file_name().map_or(true, |n| n != name.as_str()) {
+				return Err(invalid_params(format!("Invalid log file name: {}", name)));
+			}
+			if seconds > MAX_TEE_DURATION {
+				return Err(invalid_params(format!("Tee duration cannot exceed {} seconds", MAX_TEE_DURATION)));
+			}
+			try!(fs::create_dir_all(&self.logs_path).map_err(|e| invalid_params(format!("Cannot create logs directory: {}", e))));
+			match self.logger.tee(&target, &self.logs_path.join(&name), Duration::from_secs(seconds)) {
+				Ok(()) => to_value(&true),
+				Err(e) => Err(invalid_params(e)),
+			}
+		})
+	}
+
+	fn remove_transaction(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(H256,)>(params).and_then(|(hash,)| {
+			let client = take_weak!(self.client);
+			to_value(&take_weak!(self.miner).remove_local_transaction(&*client, &hash.into()))
+		})
+	}
+
+	fn prioritise_transaction(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(H256,)>(params).and_then(|(hash,)| {
+			to_value(&take_weak!(self.miner).prioritise_local_transaction(&hash.into()))
+		})
+	}
+
+	fn backup_database(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(String,)>(params).and_then(|(name,)| {
+			// only plain directory names, backups must not escape the backups directory
+			if Path::new(&name).file_name().map_or(true, |n| n != name.as_str()) {
+				return Err(invalid_params(format!("Invalid backup name: {}", name)));
+			}
+			let path = self.backups_path.join(&name);
+			if path.exists() {
+				return Err(invalid_params(format!("Backup {} already exists", name)));
+			}
+			let mut status = self.backup.lock();
+			if status.running {
+				return Err(invalid_params("Another backup is running".into()));
+			}
+
+			let client = self.client.clone();
+			let nodes = take_weak!(self.net).network_config().config_path.map(|p| Path::new(&p).join("nodes.json"));
+			let backup = self.backup.clone();
+			let spawned = thread::Builder::new().name("Database backup".into()).spawn(move || {
+				let result = match client.upgrade() {
+					Some(client) => client.backup_database(path.to_string_lossy().into_owned()),
+					None => Err("Client is shut down".into()),
+				};
+				// the node table is saved periodically, the last saved version is good enough.
+				let result = result.and_then(|backup| match nodes {
+					Some(ref nodes) if nodes.exists() => {
+						let target = path.join("nodes.json");
+						fs::hard_link(nodes, &target).or_else(|_| fs::copy(nodes, &target).map(|_| ()))
+							.map(|_| backup)
+							.map_err(|e| format!("Cannot copy node table: {}", e))
+					},
+					_ => Ok(backup),
+				});
+				if let Err(ref e) = result {
+					warn!(target: "rpc", "Database backup to {} failed: {}", path.display(), e);
+				}
+				backup.lock().finish(result);
+			});
+
+			if let Err(e) = spawned {
+				return Err(invalid_params(format!("Cannot start backup: {}", e)));
+			}
+			*status = BackupStatus::started(name);
+			to_value(&true)
+		})
+	}
+
+	fn backup_status(&self, _: Params) -> Result<Value, Error> {
+		try!(self.active());
+		to_value(&*self.backup.lock())
+	}
+}
+
+fn invalid_params(message: String) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: message,
+		data: None,
+	}
 }
@@ -39,6 +39,7 @@ mod personal_signer;
 mod ethcore;
 mod ethcore_set;
 mod traces;
+mod debug;
 mod rpc;
 
 pub use self::web3::Web3Client;
@@ -51,14 +52,16 @@ pub use self::personal_signer::SignerClient;
 pub use self::ethcore::EthcoreClient;
 pub use self::ethcore_set::EthcoreSetClient;
 pub use self::traces::TracesClient;
+pub use self::debug::DebugClient;
 pub use self::rpc::RpcClient;
 
 use serde;
-use v1::helpers::TransactionRequest;
+use v1::helpers::{CallRequest, TransactionRequest};
 use v1::types::{H256 as RpcH256, H520 as RpcH520, BlockNumber};
 use ethcore::error::Error as EthcoreError;
 use ethcore::miner::MinerService;
-use ethcore::client::MiningBlockChainClient;
+use ethcore::client::{BlockChainClient, MiningBlockChainClient, BlockID};
+use ethcore::views::HeaderView;
 use ethcore::transaction::{Action, SignedTransaction, Transaction};
 use ethcore::account_provider::{AccountProvider, Error as AccountError};
 use util::numbers::*;
@@ -71,6 +74,9 @@ mod error_codes {
 	pub const UNSUPPORTED_REQUEST_CODE: i64 = -32000;
 	pub const NO_WORK_CODE: i64 = -32001;
 	pub const NO_AUTHOR_CODE: i64 = -32002;
+	pub const RESULT_LIMIT_EXCEEDED: i64 = -32005;
+	pub const HISTORY_PRUNED: i64 = -32006;
+	pub const TRACES_MISSING: i64 = -32007;
 	pub const UNKNOWN_ERROR: i64 = -32009;
 	pub const TRANSACTION_ERROR: i64 = -32010;
 	pub const ACCOUNT_LOCKED: i64 = -32020;
@@ -103,6 +109,26 @@ pub fn from_params_default_third<F1, F2>(params: Params) -> Result<(F1, F2, Bloc
 	}
 }
 
+/// Deserialize request parameters with optional second parameter `BlockNumber` defaulting to `BlockNumber::Latest`
+/// and optional third parameter defaulting to its default value.
+pub fn from_params_default_second_and_third<F, T>(params: Params) -> Result<(F, BlockNumber, T), Error> where F: serde::de::Deserialize, T: serde::de::Deserialize + Default {
+	match params_len(&params) {
+		1 => from_params::<(F, )>(params).map(|(f,)| (f, BlockNumber::Latest, T::default())),
+		2 => from_params::<(F, BlockNumber)>(params).map(|(f, block)| (f, block, T::default())),
+		_ => from_params::<(F, BlockNumber, T)>(params),
+	}
+}
+
+/// Deserialize request parameters with optional third parameter `BlockNumber` defaulting to `BlockNumber::Latest`
+/// and optional fourth parameter defaulting to its default value.
+pub fn from_params_default_third_and_fourth<F1, F2, T>(params: Params) -> Result<(F1, F2, BlockNumber, T), Error> where F1: serde::de::Deserialize, F2: serde::de::Deserialize, T: serde::de::Deserialize + Default {
+	match params_len(&params) {
+		2 => from_params::<(F1, F2, )>(params).map(|(f1, f2)| (f1, f2, BlockNumber::Latest, T::default())),
+		3 => from_params::<(F1, F2, BlockNumber)>(params).map(|(f1, f2, block)| (f1, f2, block, T::default())),
+		_ => from_params::<(F1, F2, BlockNumber, T)>(params),
+	}
+}
+
 
 fn dispatch_transaction<C, M>(client: &C, miner: &M, signed_transaction: SignedTransaction) -> Result<Value, Error>
 	where C: MiningBlockChainClient, M: MinerService {
@@ -166,13 +192,75 @@ fn sign_and_dispatch<C, M>(client: &C, miner: &M, request: TransactionRequest, a
 	dispatch_transaction(&*client, &*miner, signed_transaction)
 }
 
-fn default_gas_price<C, M>(client: &C, miner: &M) -> U256 where C: MiningBlockChainClient, M: MinerService {
+fn default_gas_price<C, M>(client: &C, miner: &M) -> U256 where C: BlockChainClient, M: MinerService {
 	client
 		.gas_price_statistics(100, 8)
 		.map(|x| x[4])
 		.unwrap_or_else(|_| miner.sensible_gas_price())
 }
 
+/// Fills in defaults for the missing fields of a call and signs it with a fake signature of the sender.
+fn sign_call<C, M>(client: &C, miner: &M, request: CallRequest) -> SignedTransaction where C: BlockChainClient, M: MinerService {
+	let from = request.from.unwrap_or(Address::zero());
+	Transaction {
+		nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
+		action: request.to.map_or(Action::Create, Action::Call),
+		gas: request.gas.unwrap_or(U256::from(50_000_000)),
+		gas_price: request.gas_price.unwrap_or_else(|| default_gas_price(client, miner)),
+		value: request.value.unwrap_or_else(U256::zero),
+		data: request.data.map_or_else(Vec::new, |d| d.to_vec())
+	}.fake_sign(from)
+}
+
+/// Returns the number of results to request from the client.
+///
+/// When a server-side maximum is set, one result more than the maximum is requested
+/// so that `check_result_limit` can tell whether the maximum was exceeded.
+fn limited_count(requested: Option<usize>, max: Option<usize>) -> Option<usize> {
+	match (requested, max) {
+		(Some(requested), Some(max)) if requested <= max => Some(requested),
+		(_, Some(max)) => Some(max + 1),
+		(requested, None) => requested,
+	}
+}
+
+fn check_result_limit(len: usize, max: Option<usize>) -> Result<(), Error> {
+	match max {
+		Some(max) if len > max => Err(Error {
+			code: ErrorCode::ServerError(error_codes::RESULT_LIMIT_EXCEEDED),
+			message: format!("Query returned more than {} results. Narrow the block range or use pagination.", max),
+			data: None,
+		}),
+		_ => Ok(()),
+	}
+}
+
+/// Fails if the given block is known, but its body and receipts have been pruned.
+fn check_history<C>(client: &C, id: BlockID) -> Result<(), Error> where C: MiningBlockChainClient {
+	let start = client.history_start();
+	match client.block_header(id).map(|header| HeaderView::new(&header).number()) {
+		Some(number) if number < start => Err(Error {
+			code: ErrorCode::ServerError(error_codes::HISTORY_PRUNED),
+			message: format!("History of block #{} has been pruned. Only headers of blocks before #{} are available.", number, start),
+			data: None,
+		}),
+		_ => Ok(()),
+	}
+}
+
+/// Fails if traces of any block between the given ones have not been built yet.
+fn check_traces<C>(client: &C, from: BlockID, to: BlockID) -> Result<(), Error> where C: BlockChainClient {
+	let number = |id| client.block_header(id).map(|header| HeaderView::new(&header).number());
+	match (client.missing_traces(), number(from), number(to)) {
+		(Some((start, end)), Some(from), Some(to)) if start <= to && from <= end => Err(Error {
+			code: ErrorCode::ServerError(error_codes::TRACES_MISSING),
+			message: format!("Traces of blocks #{}..#{} have not been built yet.", start, end),
+			data: None,
+		}),
+		_ => Ok(()),
+	}
+}
+
 fn signer_disabled_error() -> Error {
 	Error {
 		code: ErrorCode::ServerError(error_codes::SIGNER_DISABLED),
@@ -19,13 +19,12 @@
 use std::sync::{Weak, Arc};
 use jsonrpc_core::*;
 use util::rlp::{UntrustedRlp, View};
-use ethcore::client::{BlockChainClient, CallAnalytics, TransactionID, TraceId};
+use ethcore::client::{BlockChainClient, CallAnalytics, BlockID, TransactionID, TraceId, TraceFilter as TraceFilterType};
+use ethcore::views::BodyView;
 use ethcore::miner::MinerService;
-use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
 use v1::traits::Traces;
-use v1::helpers::CallRequest as CRequest;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
-use v1::impls::from_params_default_third;
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, StateOverride, Bytes, TraceResults, TraceResultsWithTransactionHash, StateDiff, H160, H256};
+use v1::impls::{from_params_default_third, from_params_default_third_and_fourth, limited_count, check_result_limit, check_traces, sign_call};
 
 fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
 	CallAnalytics {
@@ -39,32 +38,20 @@ fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
 pub struct TracesClient<C, M> where C: BlockChainClient, M: MinerService {
 	client: Weak<C>,
 	miner: Weak<M>,
+	max_results: Option<usize>,
 }
 
 impl<C, M> TracesClient<C, M> where C: BlockChainClient, M: MinerService {
 	/// Creates new Traces client.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+	/// `max_results` limits the number of traces returned by a single `trace_filter` call.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, max_results: Option<usize>) -> Self {
 		TracesClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
+			max_results: max_results,
 		}
 	}
 
-	// TODO: share with eth.rs
-	fn sign_call(&self, request: CRequest) -> Result<SignedTransaction, Error> {
-		let client = take_weak!(self.client);
-		let miner = take_weak!(self.miner);
-		let from = request.from.unwrap_or(0.into());
-		Ok(EthTransaction {
-			nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
-			action: request.to.map_or(Action::Create, Action::Call),
-			gas: request.gas.unwrap_or(50_000_000.into()),
-			gas_price: request.gas_price.unwrap_or_else(|| miner.sensible_gas_price()),
-			value: request.value.unwrap_or(0.into()),
-			data: request.data.map_or_else(Vec::new, |d| d.to_vec())
-		}.fake_sign(from))
-	}
-
 	fn active(&self) -> Result<(), Error> {
 		// TODO: only call every 30s at most.
 		take_weak!(self.client).keep_alive();
@@ -78,8 +65,12 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 		from_params::<(TraceFilter,)>(params)
 			.and_then(|(filter, )| {
 				let client = take_weak!(self.client);
-				let traces = client.filter_traces(filter.into());
-				let traces = traces.map_or_else(Vec::new, |traces| traces.into_iter().map(LocalizedTrace::from).collect());
+				let mut filter: TraceFilterType = filter.into();
+				try!(check_traces(&*client, filter.range.start, filter.range.end));
+				filter.count = limited_count(filter.count, self.max_results);
+				let traces = client.filter_traces(filter);
+				let traces: Vec<LocalizedTrace> = traces.map_or_else(Vec::new, |traces| traces.into_iter().map(LocalizedTrace::from).collect());
+				try!(check_result_limit(traces.len(), self.max_results));
 				to_value(&traces)
 			})
 	}
@@ -89,7 +80,9 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 		from_params::<(BlockNumber,)>(params)
 			.and_then(|(block_number,)| {
 				let client = take_weak!(self.client);
-				let traces = client.block_traces(block_number.into());
+				let id: BlockID = block_number.into();
+				try!(check_traces(&*client, id, id));
+				let traces = client.block_traces(id);
 				let traces = traces.map_or_else(Vec::new, |traces| traces.into_iter().map(LocalizedTrace::from).collect());
 				to_value(&traces)
 			})
@@ -100,7 +93,11 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 		from_params::<(H256,)>(params)
 			.and_then(|(transaction_hash,)| {
 				let client = take_weak!(self.client);
-				let traces = client.transaction_traces(TransactionID::Hash(transaction_hash.into()));
+				let id = TransactionID::Hash(transaction_hash.into());
+				if let Some(hash) = client.transaction_block(id.clone()) {
+					try!(check_traces(&*client, BlockID::Hash(hash), BlockID::Hash(hash)));
+				}
+				let traces = client.transaction_traces(id);
 				let traces = traces.map_or_else(Vec::new, |traces| traces.into_iter().map(LocalizedTrace::from).collect());
 				to_value(&traces)
 			})
@@ -115,6 +112,9 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 					transaction: TransactionID::Hash(transaction_hash.into()),
 					address: address.into_iter().map(|i| i.value()).collect()
 				};
+				if let Some(hash) = client.transaction_block(id.transaction.clone()) {
+					try!(check_traces(&*client, BlockID::Hash(hash), BlockID::Hash(hash)));
+				}
 				let trace = client.trace(id);
 				let trace = trace.map(LocalizedTrace::from);
 				to_value(&trace)
@@ -123,11 +123,12 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 
 	fn call(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
-		from_params_default_third(params)
-			.and_then(|(request, flags, block)| {
+		from_params_default_third_and_fourth(params)
+			.and_then(|(request, flags, block, state_override)| {
 				let request = CallRequest::into(request);
-				let signed = try!(self.sign_call(request));
-				match take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags)) {
+				let state_override = StateOverride::into(state_override);
+				let signed = sign_call(&*take_weak!(self.client), &*take_weak!(self.miner), request);
+				match take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags), &state_override) {
 					Ok(e) => to_value(&TraceResults::from(e)),
 					_ => Ok(Value::Null),
 				}
@@ -140,7 +141,7 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 			.and_then(|(raw_transaction, flags, block)| {
 				let raw_transaction = Bytes::to_vec(raw_transaction);
 				match UntrustedRlp::new(&raw_transaction).as_val() {
-					Ok(signed) => match take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags)) {
+					Ok(signed) => match take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags), &Default::default()) {
 						Ok(e) => to_value(&TraceResults::from(e)),
 						_ => Ok(Value::Null),
 					},
@@ -159,4 +160,42 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 				}
 			})
 	}
+
+	fn replay_block_transactions(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(BlockNumber, _)>(params)
+			.and_then(|(block_number, flags)| {
+				let client = take_weak!(self.client);
+				let hash = match client.block_hash(block_number.into()) {
+					Some(hash) => hash,
+					None => return Ok(Value::Null),
+				};
+				let hashes = match client.block_body(BlockID::Hash(hash.clone())) {
+					Some(body) => BodyView::new(&body).transaction_hashes(),
+					None => return Ok(Value::Null),
+				};
+				match client.replay_block_transactions(BlockID::Hash(hash), to_call_analytics(flags)) {
+					Ok(executed) => {
+						let results = hashes.into_iter()
+							.zip(executed.into_iter())
+							.map(TraceResultsWithTransactionHash::from)
+							.collect::<Vec<_>>();
+						to_value(&results)
+					},
+					_ => Ok(Value::Null),
+				}
+			})
+	}
+
+	fn state_diff(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(BlockNumber, BlockNumber, Vec<H160>)>(params)
+			.and_then(|(from, to, addresses)| {
+				let addresses = addresses.into_iter().map(Into::into).collect();
+				match take_weak!(self.client).state_diff(from.into(), to.into(), addresses) {
+					Some(diff) => to_value(&StateDiff::from(diff)),
+					None => Ok(Value::Null),
+				}
+			})
+	}
 }
@@ -19,11 +19,12 @@
 use util::{Address, H256, Bytes, U256, FixedHash, Uint};
 use util::standard::*;
 use ethcore::error::{Error, CallError};
-use ethcore::client::{MiningBlockChainClient, Executed, CallAnalytics};
+use ethcore::client::{MiningBlockChainClient, Executed, CallAnalytics, StateOverride};
 use ethcore::block::{ClosedBlock, IsBlock};
 use ethcore::transaction::SignedTransaction;
 use ethcore::receipt::Receipt;
-use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult};
+use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, TransactionOrigin, QueuedTransaction, QueuedTransactionState,
+	LocalTransactionStatus};
 
 /// Test miner service.
 pub struct TestMinerService {
@@ -37,6 +38,10 @@ pub struct TestMinerService {
 	pub pending_receipts: Mutex<BTreeMap<H256, Receipt>>,
 	/// Last nonces.
 	pub last_nonces: RwLock<HashMap<Address, U256>>,
+	/// Reasons of rejected transactions.
+	pub rejections: RwLock<HashMap<H256, String>>,
+	/// Status of local transactions.
+	pub local_transactions: Mutex<BTreeMap<H256, LocalTransactionStatus>>,
 
 	min_gas_price: RwLock<U256>,
 	gas_range_target: RwLock<(U256, U256)>,
@@ -54,6 +59,8 @@ impl Default for TestMinerService {
 			pending_transactions: Mutex::new(HashMap::new()),
 			pending_receipts: Mutex::new(BTreeMap::new()),
 			last_nonces: RwLock::new(HashMap::new()),
+			rejections: RwLock::new(HashMap::new()),
+			local_transactions: Mutex::new(BTreeMap::new()),
 			min_gas_price: RwLock::new(U256::from(20_000_000)),
 			gas_range_target: RwLock::new((U256::from(12345), U256::from(54321))),
 			author: RwLock::new(Address::zero()),
@@ -206,6 +213,32 @@ impl MinerService for TestMinerService {
 		self.last_nonces.read().get(address).cloned()
 	}
 
+	fn queued_transactions(&self) -> Vec<QueuedTransaction> {
+		self.pending_transactions.lock().values().cloned().map(|tx| QueuedTransaction {
+			transaction: tx,
+			origin: TransactionOrigin::Local,
+			insertion_time: 0,
+			prioritised: false,
+			state: QueuedTransactionState::Current,
+		}).collect()
+	}
+
+	fn transaction_rejection(&self, hash: &H256) -> Option<String> {
+		self.rejections.read().get(hash).cloned()
+	}
+
+	fn remove_local_transaction(&self, _chain: &MiningBlockChainClient, hash: &H256) -> bool {
+		self.pending_transactions.lock().remove(hash).is_some()
+	}
+
+	fn prioritise_local_transaction(&self, hash: &H256) -> bool {
+		self.pending_transactions.lock().contains_key(hash)
+	}
+
+	fn local_transactions(&self) -> BTreeMap<H256, LocalTransactionStatus> {
+		self.local_transactions.lock().clone()
+	}
+
 	fn is_sealing(&self) -> bool {
 		false
 	}
@@ -220,7 +253,7 @@ impl MinerService for TestMinerService {
 		self.latest_closed_block.lock().as_ref().map_or_else(U256::zero, |b| b.block().fields().state.balance(address).clone())
 	}
 
-	fn call(&self, _chain: &MiningBlockChainClient, _t: &SignedTransaction, _analytics: CallAnalytics) -> Result<Executed, CallError> {
+	fn call(&self, _chain: &MiningBlockChainClient, _t: &SignedTransaction, _analytics: CallAnalytics, _state_override: &StateOverride) -> Result<Executed, CallError> {
 		unimplemented!();
 	}
 
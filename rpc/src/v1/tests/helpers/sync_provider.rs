@@ -17,7 +17,7 @@
 //! Test implementation of SyncProvider.
 
 use util::{RwLock, U256};
-use ethsync::{SyncProvider, SyncStatus, SyncState};
+use ethsync::{SyncProvider, SyncStatus, SyncState, PeerInfo};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -58,5 +58,24 @@ impl SyncProvider for TestSyncProvider {
 	fn status(&self) -> SyncStatus {
 		self.status.read().clone()
 	}
+
+	fn peers(&self) -> Vec<PeerInfo> {
+		vec![PeerInfo {
+			id: Some("node1".into()),
+			remote_address: "127.0.0.1:7777".into(),
+			client_version: "Parity/1".into(),
+			capabilities: vec!["eth/63".into()],
+			protocol_version: 4,
+			eth_version: Some(63),
+			head_hash: Some(5.into()),
+			head_number: Some(10),
+			difficulty: Some(40.into()),
+			asking: Some("Nothing".into()),
+			bytes_received: 100,
+			bytes_sent: 200,
+			connected_secs: 60,
+			ping_ms: Some(15),
+		}]
+	}
 }
 
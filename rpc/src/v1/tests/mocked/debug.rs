@@ -0,0 +1,89 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use jsonrpc_core::IoHandler;
+use v1::{Debug, DebugClient};
+use ethcore::client::{TestBlockChainClient, Executed};
+use v1::tests::helpers::TestMinerService;
+use util::numbers::*;
+
+fn debug_tester() -> (Arc<TestBlockChainClient>, IoHandler) {
+	let client = Arc::new(TestBlockChainClient::default());
+	let miner = Arc::new(TestMinerService::default());
+	let io = IoHandler::new();
+	io.add_delegate(DebugClient::new(&client, &miner).to_delegate());
+	(client, io)
+}
+
+fn executed() -> Executed {
+	Executed {
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}
+}
+
+#[test]
+fn rpc_debug_trace_call() {
+	let (client, io) = debug_tester();
+	client.set_execution_result(Ok(executed()));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "debug_traceCall",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd67c5d32be"
+		},
+		"latest",
+		{"disableMemory": true, "opcodes": ["SSTORE", "call"]}],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"gas":"0xff30","returnValue":"0x1234ff","structLogs":[]},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let (client, io) = debug_tester();
+	client.set_execution_result(Ok(executed()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransaction", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"gas":"0xff30","returnValue":"0x1234ff","structLogs":[]},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_transaction_unknown_opcode() {
+	let (client, io) = debug_tester();
+	client.set_execution_result(Ok(executed()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransaction", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001", {"opcodes": ["JUMPAROUND"]}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
@@ -23,7 +23,8 @@ use util::hash::{Address, H256, FixedHash};
 use util::numbers::{Uint, U256};
 use util::Mutex;
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{TestBlockChainClient, EachBlockWith, Executed, TransactionID};
+use ethcore::client::{BlockChainClient, TestBlockChainClient, EachBlockWith, Executed, BlockID, TransactionID};
+use ethcore::views::BlockView;
 use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
 use ethcore::receipt::LocalizedReceipt;
 use ethcore::transaction::{Transaction, Action};
@@ -254,6 +255,21 @@ fn rpc_eth_block_number() {
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_block_with_pruned_history() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(10, EachBlockWith::Nothing);
+	tester.client.set_history_start(5);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["0x02", false], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32006,"message":"History of block #2 has been pruned. Only headers of blocks before #5 are available.","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["0x20", false], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_balance() {
 	let tester = EthTester::default();
@@ -526,6 +542,46 @@ fn rpc_eth_call_default_block() {
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_with_state_override() {
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Ok(Executed {
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd6"
+		},
+		"latest",
+		{
+			"0xd46e8dd67c5d32be8058bb8eb970870f07244567": {
+				"code": "0x6000",
+				"storage": {
+					"0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+				}
+			}
+		}],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_estimate_gas() {
 	let tester = EthTester::default();
@@ -764,6 +820,21 @@ fn rpc_eth_transaction_receipt() {
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_transaction_receipt_with_pruned_history() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(10, EachBlockWith::Transaction);
+	let block = tester.client.block(BlockID::Number(2)).unwrap();
+	let hash = BlockView::new(&block).transaction_hashes()[0];
+	tester.client.set_history_start(5);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": [""#.to_owned()
+		+ &format!("0x{}", hash.hex()) + r#""], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32006,"message":"History of block #2 has been pruned. Only headers of blocks before #5 are available.","data":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_transaction_receipt_null() {
 	let tester = EthTester::default();
@@ -846,6 +917,7 @@ fn should_not_return_block_number() {
 	let eth_tester = EthTester::new_with_options(EthClientOptions {
 		allow_pending_receipt_query: true,
 		send_block_number_in_get_work: false,
+		max_results: None,
 	});
 	eth_tester.miner.set_author(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap());
 
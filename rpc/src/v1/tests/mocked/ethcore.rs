@@ -17,9 +17,11 @@
 use std::sync::Arc;
 use jsonrpc_core::IoHandler;
 use v1::{Ethcore, EthcoreClient};
-use v1::tests::helpers::TestMinerService;
+use v1::tests::helpers::{TestMinerService, TestSyncProvider, Config};
 use v1::helpers::ConfirmationsQueue;
 use ethcore::client::{TestBlockChainClient};
+use ethcore::miner::LocalTransactionStatus;
+use util::H256;
 use util::log::RotatingLogger;
 use v1::helpers::NetworkSettings;
 
@@ -31,6 +33,13 @@ fn client_service() -> Arc<TestBlockChainClient> {
 	Arc::new(TestBlockChainClient::default())
 }
 
+fn sync_provider() -> Arc<TestSyncProvider> {
+	Arc::new(TestSyncProvider::new(Config {
+		network_id: 3.into(),
+		num_peers: 1,
+	}))
+}
+
 fn logger() -> Arc<RotatingLogger> {
 	Arc::new(RotatingLogger::new("rpc=trace".to_owned()))
 }
@@ -48,8 +57,8 @@ fn settings() -> Arc<NetworkSettings> {
 	})
 }
 
-fn ethcore_client(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>) -> EthcoreClient<TestBlockChainClient, TestMinerService> {
-	EthcoreClient::new(client, miner, logger(), settings(), None)
+fn ethcore_client(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>) -> EthcoreClient<TestBlockChainClient, TestMinerService, TestSyncProvider> {
+	EthcoreClient::new(client, miner, &sync_provider(), logger(), settings(), None)
 }
 
 #[test]
@@ -114,7 +123,7 @@ fn rpc_ethcore_dev_logs() {
 	let logger = logger();
 	logger.append("a".to_owned());
 	logger.append("b".to_owned());
-	let ethcore = EthcoreClient::new(&client, &miner, logger.clone(), settings(), None).to_delegate();
+	let ethcore = EthcoreClient::new(&client, &miner, &sync_provider(), logger.clone(), settings(), None).to_delegate();
 	let io = IoHandler::new();
 	io.add_delegate(ethcore);
 
@@ -132,7 +141,7 @@ fn rpc_ethcore_dev_logs_levels() {
 	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_devLogsLevels", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"rpc=trace","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"info,rpc=trace","id":1}"#;
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
@@ -150,6 +159,19 @@ fn rpc_ethcore_transactions_limit() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_ethcore_oldest_state() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_oldestState", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_ethcore_net_chain() {
 	let miner = miner_service();
@@ -189,6 +211,20 @@ fn rpc_ethcore_net_port() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_ethcore_net_peers() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let io = IoHandler::new();
+	io.add_delegate(EthcoreClient::new(&client, &miner, &sync, logger(), settings(), None).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_netPeers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"asking":"Nothing","bytesReceived":"0x64","bytesSent":"0xc8","capabilities":["eth/63"],"clientVersion":"Parity/1","connectedSecs":"0x3c","difficulty":"0x28","ethVersion":63,"headHash":"0x0000000000000000000000000000000000000000000000000000000000000005","headNumber":"0x0a","id":"node1","pingMs":"0x0f","protocolVersion":4,"remoteAddress":"127.0.0.1:7777"}],"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_ethcore_rpc_settings() {
 	let miner = miner_service();
@@ -221,7 +257,7 @@ fn rpc_ethcore_unsigned_transactions_count() {
 	let client = client_service();
 	let io = IoHandler::new();
 	let queue = Arc::new(ConfirmationsQueue::default());
-	let ethcore = EthcoreClient::new(&client, &miner, logger(), settings(), Some(queue)).to_delegate();
+	let ethcore = EthcoreClient::new(&client, &miner, &sync_provider(), logger(), settings(), Some(queue)).to_delegate();
 	io.add_delegate(ethcore);
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_unsignedTransactionsCount", "params":[], "id": 1}"#;
@@ -242,3 +278,66 @@ fn rpc_ethcore_unsigned_transactions_count_when_signer_disabled() {
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_ethcore_transaction_rejection() {
+	let miner = miner_service();
+	let client = client_service();
+	miner.rejections.write().insert(H256::from(1), "Transaction nonce is too low.".to_owned());
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_transactionRejection", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"Transaction nonce is too low.","id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_transactionRejection", "params":["0x0000000000000000000000000000000000000000000000000000000000000002"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_queued_transactions_when_empty() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_queuedTransactions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_queued_transactions() {
+	use util::*;
+	use ethcore::transaction::*;
+
+	let miner = miner_service();
+	let client = client_service();
+	let tx: SignedTransaction = decode(&FromHex::from_hex("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap());
+	miner.pending_transactions.lock().insert(tx.hash(), tx);
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_queuedTransactions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x0f65fe9276bc9a24ae7083ae28e2660ef72df99e":[{"insertionTime":0,"origin":"local","prioritised":false,"state":"current","transaction":{"blockHash":null,"blockNumber":null,"creates":null,"from":"0x0f65fe9276bc9a24ae7083ae28e2660ef72df99e","gas":"0x5208","gasPrice":"0x01","hash":"0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31","input":"0x","nonce":"0x00","raw":"0xf85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804","to":"0x095e7baea6a6c7c4c2dfeb977efac326af552d87","transactionIndex":null,"value":"0x0a"}}]},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_local_transactions() {
+	let miner = miner_service();
+	let client = client_service();
+	miner.local_transactions.lock().insert(H256::from(10), LocalTransactionStatus::Future);
+	miner.local_transactions.lock().insert(H256::from(15), LocalTransactionStatus::Dropped);
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_localTransactions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x000000000000000000000000000000000000000000000000000000000000000a":{"blockHash":null,"blockNumber":null,"reason":null,"replacedBy":null,"status":"future"},"0x000000000000000000000000000000000000000000000000000000000000000f":{"blockHash":null,"blockNumber":null,"reason":null,"replacedBy":null,"status":"dropped"}},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
@@ -19,9 +19,12 @@ use std::str::FromStr;
 use jsonrpc_core::IoHandler;
 use v1::{EthcoreSet, EthcoreSetClient};
 use ethcore::miner::MinerService;
-use ethcore::client::TestBlockChainClient;
+use ethcore::client::{BlockChainClient, TestBlockChainClient};
 use v1::tests::helpers::TestMinerService;
 use util::numbers::*;
+use util::log::RotatingLogger;
+use log::LogLevel;
+use devtools::RandomTempPath;
 use rustc_serialize::hex::FromHex;
 use super::manage_network::TestManageNetwork;
 use ethsync::ManageNetwork;
@@ -38,8 +41,13 @@ fn network_service() -> Arc<TestManageNetwork> {
 	Arc::new(TestManageNetwork)
 }
 
-fn ethcore_set_client(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>, net: &Arc<TestManageNetwork>) -> EthcoreSetClient<TestBlockChainClient, TestMinerService> {
-	EthcoreSetClient::new(client, miner, &(net.clone() as Arc<ManageNetwork>))
+fn logger() -> Arc<RotatingLogger> {
+	Arc::new(RotatingLogger::new("rpc=trace".to_owned()))
+}
+
+/// Creates the client with logs and backups kept in `dir`, which must outlive it.
+fn ethcore_set_client(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>, net: &Arc<TestManageNetwork>, dir: &RandomTempPath) -> EthcoreSetClient<TestBlockChainClient, TestMinerService> {
+	EthcoreSetClient::new(client, miner, &(net.clone() as Arc<ManageNetwork>), logger(), dir.as_path().join("logs"), dir.as_path().join("backups"))
 }
 
 #[test]
@@ -47,8 +55,9 @@ fn rpc_ethcore_set_min_gas_price() {
 	let miner = miner_service();
 	let client = client_service();
 	let network = network_service();
+	let dir = RandomTempPath::create_dir();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_set_client(&client, &miner, &network).to_delegate());
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setMinGasPrice", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681"], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
@@ -62,8 +71,9 @@ fn rpc_ethcore_set_gas_floor_target() {
 	let miner = miner_service();
 	let client = client_service();
 	let network = network_service();
+	let dir = RandomTempPath::create_dir();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_set_client(&client, &miner, &network).to_delegate());
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setGasFloorTarget", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681"], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
@@ -77,8 +87,9 @@ fn rpc_ethcore_set_extra_data() {
 	let miner = miner_service();
 	let client = client_service();
 	let network = network_service();
+	let dir = RandomTempPath::create_dir();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_set_client(&client, &miner, &network).to_delegate());
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setExtraData", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681"], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
@@ -92,8 +103,9 @@ fn rpc_ethcore_set_author() {
 	let miner = miner_service();
 	let client = client_service();
 	let network = network_service();
+	let dir = RandomTempPath::create_dir();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_set_client(&client, &miner, &network).to_delegate());
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setAuthor", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681"], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
@@ -107,8 +119,9 @@ fn rpc_ethcore_set_transactions_limit() {
 	let miner = miner_service();
 	let client = client_service();
 	let network = network_service();
+	let dir = RandomTempPath::create_dir();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_set_client(&client, &miner, &network).to_delegate());
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setTransactionsLimit", "params":[10240240], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
@@ -116,3 +129,112 @@ fn rpc_ethcore_set_transactions_limit() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 	assert_eq!(miner.transactions_limit(), 10_240_240);
 }
+
+#[test]
+fn rpc_ethcore_set_log_levels() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let logger = logger();
+	let dir = RandomTempPath::create_dir();
+	let io = IoHandler::new();
+	io.add_delegate(EthcoreSetClient::new(&client, &miner, &(network as Arc<ManageNetwork>), logger.clone(), dir.as_path().join("logs"), dir.as_path().join("backups")).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setLogLevels", "params":["sync=debug"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	assert!(logger.is_enabled("sync", LogLevel::Debug));
+	assert_eq!(logger.levels(), "info,rpc=trace,sync=debug");
+}
+
+#[test]
+fn rpc_ethcore_tee_log_rejects_paths() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let dir = RandomTempPath::create_dir();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_teeLog", "params":["sync", "../sync.log", 60], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid log file name: ../sync.log","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_tee_log_rejects_long_durations() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let dir = RandomTempPath::create_dir();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_teeLog", "params":["sync", "sync.log", 18446744073709551615], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Tee duration cannot exceed 86400 seconds","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_remove_unknown_transaction() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let dir = RandomTempPath::create_dir();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_removeTransaction", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_backup_database() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let dir = RandomTempPath::create_dir();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_backupDatabase", "params":["backup"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_backupDatabase", "params":["../backup"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid backup name: ../backup","data":null},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_backup_status() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let dir = RandomTempPath::create_dir();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&client, &miner, &network, &dir).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_backupStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":null,"blockNumber":null,"error":null,"name":null,"running":false},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_backupDatabase", "params":["backup"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_backupStatus", "params":[], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":{{"blockHash":"0x{}","blockNumber":"0x00","error":null,"name":"backup","running":false}},"id":1}}"#, client.chain_info().best_block_hash.hex());
+	for _ in 0..100 {
+		if !io.handle_request(request).unwrap().contains(r#""running":true"#) {
+			break;
+		}
+		::std::thread::sleep(::std::time::Duration::from_millis(10));
+	}
+	assert_eq!(io.handle_request(request), Some(response));
+}
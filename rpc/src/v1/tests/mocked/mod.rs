@@ -25,5 +25,6 @@ mod personal;
 mod personal_signer;
 mod ethcore;
 mod ethcore_set;
+mod debug;
 mod rpc;
 mod manage_network;
@@ -0,0 +1,37 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug rpc interface.
+use std::sync::Arc;
+use jsonrpc_core::*;
+
+/// Debug rpc interface.
+pub trait Debug: Sized + Send + Sync + 'static {
+	/// Replays the transaction with the given hash and returns its step by step execution log.
+	fn trace_transaction(&self, _: Params) -> Result<Value, Error>;
+
+	/// Executes the given call and returns its step by step execution log.
+	fn trace_call(&self, _: Params) -> Result<Value, Error>;
+
+	/// Should be used to convert object to io delegate.
+	fn to_delegate(self) -> IoDelegate<Self> {
+		let mut delegate = IoDelegate::new(Arc::new(self));
+		delegate.add_method("debug_traceTransaction", Debug::trace_transaction);
+		delegate.add_method("debug_traceCall", Debug::trace_call);
+
+		delegate
+	}
+}
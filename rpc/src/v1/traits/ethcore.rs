@@ -48,6 +48,9 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns max peers
 	fn net_max_peers(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns details of connected peers
+	fn net_peers(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns network port
 	fn net_port(&self, _: Params) -> Result<Value, Error>;
 
@@ -67,6 +70,18 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns error when signer is disabled
 	fn unsigned_transactions_count(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns transactions in the queue (both current and future) grouped by sender.
+	fn queued_transactions(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns reason of recent rejection or removal of given transaction from the queue.
+	fn transaction_rejection(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns status of transactions submitted through this node.
+	fn local_transactions(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the number of the oldest block whose state can be queried.
+	fn oldest_state(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -80,12 +95,17 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_devLogsLevels", Ethcore::dev_logs_levels);
 		delegate.add_method("ethcore_netChain", Ethcore::net_chain);
 		delegate.add_method("ethcore_netMaxPeers", Ethcore::net_max_peers);
+		delegate.add_method("ethcore_netPeers", Ethcore::net_peers);
 		delegate.add_method("ethcore_netPort", Ethcore::net_port);
 		delegate.add_method("ethcore_rpcSettings", Ethcore::rpc_settings);
 		delegate.add_method("ethcore_nodeName", Ethcore::node_name);
 		delegate.add_method("ethcore_defaultExtraData", Ethcore::default_extra_data);
 		delegate.add_method("ethcore_gasPriceStatistics", Ethcore::gas_price_statistics);
 		delegate.add_method("ethcore_unsignedTransactionsCount", Ethcore::unsigned_transactions_count);
+		delegate.add_method("ethcore_queuedTransactions", Ethcore::queued_transactions);
+		delegate.add_method("ethcore_transactionRejection", Ethcore::transaction_rejection);
+		delegate.add_method("ethcore_localTransactions", Ethcore::local_transactions);
+		delegate.add_method("ethcore_oldestState", Ethcore::oldest_state);
 
 		delegate
 	}
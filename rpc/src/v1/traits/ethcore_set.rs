@@ -61,6 +61,25 @@ pub trait EthcoreSet: Sized + Send + Sync + 'static {
 	/// Stop the network.
 	fn stop_network(&self, _: Params) -> Result<Value, Error>;
 
+	/// Changes log levels with directives in the `RUST_LOG` format, e.g. `sync=trace`.
+	fn set_log_levels(&self, _: Params) -> Result<Value, Error>;
+
+	/// Copies log entries of given target to a separate file for given number of seconds.
+	fn tee_log(&self, _: Params) -> Result<Value, Error>;
+
+	/// Removes local transaction from the queue.
+	fn remove_transaction(&self, _: Params) -> Result<Value, Error>;
+
+	/// Moves local transaction ahead of other transactions with the same nonce height.
+	fn prioritise_transaction(&self, _: Params) -> Result<Value, Error>;
+
+	/// Starts copying the database and the node table into the backups directory under given name,
+	/// without stopping block import. Returns immediately, the outcome is reported by `ethcore_backupStatus`.
+	fn backup_database(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns status of the last backup started with `ethcore_backupDatabase`.
+	fn backup_status(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -75,6 +94,12 @@ pub trait EthcoreSet: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_removeReservedPeer", EthcoreSet::remove_reserved_peer);
 		delegate.add_method("ethcore_dropNonReservedPeers", EthcoreSet::drop_non_reserved_peers);
 		delegate.add_method("ethcore_acceptNonReservedPeers", EthcoreSet::accept_non_reserved_peers);
+		delegate.add_method("ethcore_setLogLevels", EthcoreSet::set_log_levels);
+		delegate.add_method("ethcore_teeLog", EthcoreSet::tee_log);
+		delegate.add_method("ethcore_removeTransaction", EthcoreSet::remove_transaction);
+		delegate.add_method("ethcore_prioritiseTransaction", EthcoreSet::prioritise_transaction);
+		delegate.add_method("ethcore_backupDatabase", EthcoreSet::backup_database);
+		delegate.add_method("ethcore_backupStatus", EthcoreSet::backup_status);
 
 		delegate
 	}
@@ -41,6 +41,12 @@ pub trait Traces: Sized + Send + Sync + 'static {
 	/// Executes the transaction with the given hash and returns a number of possible traces for it.
 	fn replay_transaction(&self, _: Params) -> Result<Value, Error>;
 
+	/// Executes all transactions of the given block and returns a number of possible traces for each of them.
+	fn replay_block_transactions(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns differences of given accounts between the states at the end of two blocks.
+	fn state_diff(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -51,6 +57,8 @@ pub trait Traces: Sized + Send + Sync + 'static {
 		delegate.add_method("trace_call", Traces::call);
 		delegate.add_method("trace_rawTransaction", Traces::raw_transaction);
 		delegate.add_method("trace_replayTransaction", Traces::replay_transaction);
+		delegate.add_method("trace_replayBlockTransactions", Traces::replay_block_transactions);
+		delegate.add_method("trace_stateDiff", Traces::state_diff);
 
 		delegate
 	}
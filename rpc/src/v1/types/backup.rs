@@ -0,0 +1,73 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::client::DatabaseBackup;
+use v1::types::{H256, U256};
+
+/// Status of the last database backup started through this node
+#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+pub struct BackupStatus {
+	/// Name of the backup, `None` if no backup was started yet
+	pub name: Option<String>,
+	/// Whether the backup is still being written
+	pub running: bool,
+	/// Number of the best block the finished backup corresponds to
+	#[serde(rename="blockNumber")]
+	pub block_number: Option<U256>,
+	/// Hash of the best block the finished backup corresponds to
+	#[serde(rename="blockHash")]
+	pub block_hash: Option<H256>,
+	/// Reason the backup failed
+	pub error: Option<String>,
+}
+
+impl BackupStatus {
+	/// Status of a backup which has just been started.
+	pub fn started(name: String) -> Self {
+		BackupStatus {
+			name: Some(name),
+			running: true,
+			..Default::default()
+		}
+	}
+
+	/// Marks the backup as finished with given result.
+	pub fn finish(&mut self, result: Result<DatabaseBackup, String>) {
+		self.running = false;
+		match result {
+			Ok(backup) => {
+				self.block_number = Some(backup.number.into());
+				self.block_hash = Some(backup.hash.into());
+			},
+			Err(e) => self.error = Some(e),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::client::DatabaseBackup;
+	use super::BackupStatus;
+
+	#[test]
+	fn should_serialize_finished_backup() {
+		let mut status = BackupStatus::started("backup".into());
+		status.finish(Ok(DatabaseBackup { number: 10, hash: 5.into() }));
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"name":"backup","running":false,"blockNumber":"0x0a","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000005","error":null}"#);
+	}
+}
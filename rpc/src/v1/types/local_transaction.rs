@@ -0,0 +1,85 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::miner::LocalTransactionStatus as EthLocalTransactionStatus;
+use v1::types::{H256, U256};
+
+/// Status of transaction submitted through this node
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LocalTransactionStatus {
+	/// One of `pending`, `future`, `mined`, `replaced`, `dropped` or `invalid`
+	pub status: String,
+	/// Number of block the transaction was mined in
+	#[serde(rename="blockNumber")]
+	pub block_number: Option<U256>,
+	/// Hash of block the transaction was mined in
+	#[serde(rename="blockHash")]
+	pub block_hash: Option<H256>,
+	/// Hash of transaction which replaced this one
+	#[serde(rename="replacedBy")]
+	pub replaced_by: Option<H256>,
+	/// Reason the transaction is invalid
+	pub reason: Option<String>,
+}
+
+impl LocalTransactionStatus {
+	fn new(status: &str) -> Self {
+		LocalTransactionStatus {
+			status: status.into(),
+			block_number: None,
+			block_hash: None,
+			replaced_by: None,
+			reason: None,
+		}
+	}
+}
+
+impl From<EthLocalTransactionStatus> for LocalTransactionStatus {
+	fn from(s: EthLocalTransactionStatus) -> Self {
+		match s {
+			EthLocalTransactionStatus::Pending => LocalTransactionStatus::new("pending"),
+			EthLocalTransactionStatus::Future => LocalTransactionStatus::new("future"),
+			EthLocalTransactionStatus::Mined { block_number, block_hash } => LocalTransactionStatus {
+				block_number: Some(block_number.into()),
+				block_hash: Some(block_hash.into()),
+				..LocalTransactionStatus::new("mined")
+			},
+			EthLocalTransactionStatus::Replaced(by) => LocalTransactionStatus {
+				replaced_by: Some(by.into()),
+				..LocalTransactionStatus::new("replaced")
+			},
+			EthLocalTransactionStatus::Dropped => LocalTransactionStatus::new("dropped"),
+			EthLocalTransactionStatus::Invalid(reason) => LocalTransactionStatus {
+				reason: Some(reason),
+				..LocalTransactionStatus::new("invalid")
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::miner::LocalTransactionStatus as EthLocalTransactionStatus;
+	use super::LocalTransactionStatus;
+
+	#[test]
+	fn should_serialize_mined_status() {
+		let status: LocalTransactionStatus = EthLocalTransactionStatus::Mined { block_number: 10, block_hash: 5.into() }.into();
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"status":"mined","blockNumber":"0x0a","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000005","replacedBy":null,"reason":null}"#);
+	}
+}
@@ -0,0 +1,82 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethsync::PeerInfo as SyncPeerInfo;
+use v1::types::{H256, U256};
+
+/// Connected peer
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Peer {
+	/// Peer node id
+	pub id: Option<String>,
+	/// Peer socket address
+	#[serde(rename="remoteAddress")]
+	pub remote_address: String,
+	/// Peer client version
+	#[serde(rename="clientVersion")]
+	pub client_version: String,
+	/// Negotiated capabilities
+	pub capabilities: Vec<String>,
+	/// RLPx protocol version
+	#[serde(rename="protocolVersion")]
+	pub protocol_version: u32,
+	/// eth protocol version
+	#[serde(rename="ethVersion")]
+	pub eth_version: Option<u32>,
+	/// Peer best block hash
+	#[serde(rename="headHash")]
+	pub head_hash: Option<H256>,
+	/// Peer best block number
+	#[serde(rename="headNumber")]
+	pub head_number: Option<U256>,
+	/// Peer total difficulty
+	pub difficulty: Option<U256>,
+	/// Data currently requested from the peer
+	pub asking: Option<String>,
+	/// Bytes received from the peer
+	#[serde(rename="bytesReceived")]
+	pub bytes_received: U256,
+	/// Bytes sent to the peer
+	#[serde(rename="bytesSent")]
+	pub bytes_sent: U256,
+	/// Seconds since the connection was established
+	#[serde(rename="connectedSecs")]
+	pub connected_secs: U256,
+	/// Ping delay in milliseconds
+	#[serde(rename="pingMs")]
+	pub ping_ms: Option<U256>,
+}
+
+impl From<SyncPeerInfo> for Peer {
+	fn from(p: SyncPeerInfo) -> Self {
+		Peer {
+			id: p.id,
+			remote_address: p.remote_address,
+			client_version: p.client_version,
+			capabilities: p.capabilities,
+			protocol_version: p.protocol_version,
+			eth_version: p.eth_version,
+			head_hash: p.head_hash.map(Into::into),
+			head_number: p.head_number.map(Into::into),
+			difficulty: p.difficulty.map(Into::into),
+			asking: p.asking,
+			bytes_received: p.bytes_received.into(),
+			bytes_sent: p.bytes_sent.into(),
+			connected_secs: p.connected_secs.into(),
+			ping_ms: p.ping_ms.map(Into::into),
+		}
+	}
+}
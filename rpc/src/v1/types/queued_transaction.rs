@@ -0,0 +1,52 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::miner::{QueuedTransaction as EthQueuedTransaction, QueuedTransactionState, TransactionOrigin};
+use v1::types::Transaction;
+
+/// Transaction waiting in the queue
+#[derive(Debug, Serialize)]
+pub struct QueuedTransaction {
+	/// Transaction
+	pub transaction: Transaction,
+	/// Transaction origin: `local` or `external`
+	pub origin: String,
+	/// Unix timestamp of insertion to the queue
+	#[serde(rename="insertionTime")]
+	pub insertion_time: u64,
+	/// Transaction was prioritised by the node operator
+	pub prioritised: bool,
+	/// Queue state: `current` (ready to be mined) or `future` (waiting for lower nonces)
+	pub state: String,
+}
+
+impl From<EthQueuedTransaction> for QueuedTransaction {
+	fn from(t: EthQueuedTransaction) -> QueuedTransaction {
+		QueuedTransaction {
+			transaction: t.transaction.into(),
+			origin: match t.origin {
+				TransactionOrigin::Local => "local",
+				TransactionOrigin::External => "external",
+			}.into(),
+			insertion_time: t.insertion_time,
+			prioritised: t.prioritised,
+			state: match t.state {
+				QueuedTransactionState::Current => "current",
+				QueuedTransactionState::Future => "future",
+			}.into(),
+		}
+	}
+}
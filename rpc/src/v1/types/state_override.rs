@@ -0,0 +1,104 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Deserializer};
+use ethcore::client::{StateOverride as EthStateOverride, AccountOverride as EthAccountOverride};
+use v1::types::{Bytes, H160, H256, U256};
+
+/// Overridden parts of a single account.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct AccountOverride {
+	/// Balance
+	pub balance: Option<U256>,
+	/// Nonce
+	pub nonce: Option<U256>,
+	/// Code
+	pub code: Option<Bytes>,
+	/// Individual storage slots
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+impl Into<EthAccountOverride> for AccountOverride {
+	fn into(self) -> EthAccountOverride {
+		EthAccountOverride {
+			balance: self.balance.map(Into::into),
+			nonce: self.nonce.map(Into::into),
+			code: self.code.map(Into::into),
+			storage: self.storage.unwrap_or_else(BTreeMap::new).into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+		}
+	}
+}
+
+/// Accounts overridden before a call is executed.
+#[derive(Debug, Default, PartialEq)]
+pub struct StateOverride(BTreeMap<H160, AccountOverride>);
+
+impl Deserialize for StateOverride {
+	fn deserialize<D>(deserializer: &mut D) -> Result<StateOverride, D::Error>
+	where D: Deserializer {
+		BTreeMap::deserialize(deserializer).map(StateOverride)
+	}
+}
+
+impl Into<EthStateOverride> for StateOverride {
+	fn into(self) -> EthStateOverride {
+		EthStateOverride {
+			accounts: self.0.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use serde_json;
+	use v1::types::{U256, H160, H256};
+	use super::{StateOverride, AccountOverride};
+
+	#[test]
+	fn state_override_deserialize() {
+		let s = r#"{
+			"0x0000000000000000000000000000000000000001": {
+				"balance": "0x1",
+				"nonce": "0x2",
+				"code": "0x6000",
+				"storage": {
+					"0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+				}
+			},
+			"0x0000000000000000000000000000000000000002": {
+				"balance": "0x3"
+			}
+		}"#;
+		let deserialized: StateOverride = serde_json::from_str(s).unwrap();
+
+		let mut storage = BTreeMap::new();
+		storage.insert(H256::from(1), H256::from(2));
+		let mut accounts = BTreeMap::new();
+		accounts.insert(H160::from(1), AccountOverride {
+			balance: Some(U256::from(1)),
+			nonce: Some(U256::from(2)),
+			code: Some(vec![0x60, 0x00].into()),
+			storage: Some(storage),
+		});
+		accounts.insert(H160::from(2), AccountOverride {
+			balance: Some(U256::from(3)),
+			..Default::default()
+		});
+		assert_eq!(deserialized, StateOverride(accounts));
+	}
+}
@@ -0,0 +1,141 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Struct log serialization.
+
+use std::collections::BTreeMap;
+use ethcore::trace::{StructLog as EthStructLog, StructLogTrace as EthStructLogTrace};
+use ethcore::evm::instruction_info;
+use v1::types::{Bytes, H256, U256};
+
+/// Options of the struct logger
+#[derive(Debug, PartialEq, Default, Deserialize)]
+pub struct StructLogOptions {
+	/// Do not capture memory
+	#[serde(rename="disableMemory")]
+	pub disable_memory: Option<bool>,
+	/// Do not capture stack
+	#[serde(rename="disableStack")]
+	pub disable_stack: Option<bool>,
+	/// Do not capture storage
+	#[serde(rename="disableStorage")]
+	pub disable_storage: Option<bool>,
+	/// Maximal number of recorded steps, at most 10000
+	pub limit: Option<usize>,
+	/// Record only these instructions, e.g. `["SSTORE", "CALL"]`
+	pub opcodes: Option<Vec<String>>,
+}
+
+/// Single execution step
+#[derive(Debug, Serialize)]
+pub struct StructLog {
+	/// Program counter
+	pub pc: usize,
+	/// Instruction name
+	pub op: String,
+	/// Gas available before the step
+	pub gas: U256,
+	/// Gas cost of the step
+	#[serde(rename="gasCost")]
+	pub gas_cost: U256,
+	/// Call depth
+	pub depth: usize,
+	/// Stack, bottom first
+	pub stack: Option<Vec<U256>>,
+	/// Memory split into 32-byte words
+	pub memory: Option<Vec<Bytes>>,
+	/// Storage of the current contract
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+impl From<EthStructLog> for StructLog {
+	fn from(l: EthStructLog) -> Self {
+		let (name, _, _) = instruction_info(l.instruction);
+		StructLog {
+			pc: l.pc,
+			op: name.into(),
+			gas: l.gas.into(),
+			gas_cost: l.gas_cost.into(),
+			depth: l.depth,
+			stack: l.stack.map(|s| s.into_iter().map(Into::into).collect()),
+			memory: l.memory.map(|m| m.chunks(32).map(|c| Bytes::new(c.to_vec())).collect()),
+			storage: l.storage.map(|s| s.into_iter().map(|(k, v)| (k.into(), v.into())).collect()),
+		}
+	}
+}
+
+/// Execution result with its struct log
+#[derive(Debug, Serialize)]
+pub struct StructLogTrace {
+	/// Gas used
+	pub gas: U256,
+	/// Output of the execution
+	#[serde(rename="returnValue")]
+	pub return_value: Bytes,
+	/// Recorded steps
+	#[serde(rename="structLogs")]
+	pub struct_logs: Vec<StructLog>,
+}
+
+impl From<EthStructLogTrace> for StructLogTrace {
+	fn from(t: EthStructLogTrace) -> Self {
+		StructLogTrace {
+			gas: t.executed.gas_used.into(),
+			return_value: t.executed.output.into(),
+			struct_logs: t.logs.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use std::collections::BTreeMap;
+	use ethcore::trace::StructLog as EthStructLog;
+	use super::*;
+
+	#[test]
+	fn struct_log_options_deserialization() {
+		let s = r#"{"disableStorage":true,"limit":100,"opcodes":["SSTORE"]}"#;
+		let deserialized: StructLogOptions = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, StructLogOptions {
+			disable_memory: None,
+			disable_stack: None,
+			disable_storage: Some(true),
+			limit: Some(100),
+			opcodes: Some(vec!["SSTORE".into()]),
+		});
+	}
+
+	#[test]
+	fn struct_log_serialization() {
+		let mut storage = BTreeMap::new();
+		storage.insert(1.into(), 5.into());
+		let log = StructLog::from(EthStructLog {
+			pc: 12,
+			instruction: 0x55,
+			gas: 99976.into(),
+			gas_cost: 20000.into(),
+			depth: 1,
+			stack: Some(vec![5.into(), 1.into()]),
+			memory: Some(vec![0; 33]),
+			storage: Some(storage),
+		});
+
+		let serialized = serde_json::to_string(&log).unwrap();
+		assert_eq!(serialized, r#"{"pc":12,"op":"SSTORE","gas":"0x018688","gasCost":"0x4e20","depth":1,"stack":["0x05","0x01"],"memory":["0x0000000000000000000000000000000000000000000000000000000000000000","0x00"],"storage":{"0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000005"}}"#);
+	}
+}
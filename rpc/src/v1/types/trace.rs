@@ -15,7 +15,8 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
-use serde::{Serialize, Serializer};
+use serde::{Serialize, Serializer, Deserialize, Deserializer, Error};
+use serde::de::Visitor;
 use ethcore::trace::trace;
 use ethcore::trace::{FlatTrace, LocalizedTrace as EthLocalizedTrace};
 use ethcore::trace as et;
@@ -23,7 +24,7 @@ use ethcore::state_diff;
 use ethcore::account_diff;
 use ethcore::executed;
 use ethcore::client::Executed;
-use util::Uint;
+use util::{Uint, H256 as UtilH256};
 use v1::types::{Bytes, H160, H256, U256};
 
 #[derive(Debug, Serialize)]
@@ -237,7 +238,7 @@ impl From<trace::Create> for Create {
 }
 
 /// Call type.
-#[derive(Debug, Serialize)]
+#[derive(Debug, PartialEq, Serialize)]
 pub enum CallType {
 	/// None
 	#[serde(rename="none")]
@@ -264,6 +265,44 @@ impl From<executed::CallType> for CallType {
 	}
 }
 
+impl Into<executed::CallType> for CallType {
+	fn into(self) -> executed::CallType {
+		match self {
+			CallType::None => executed::CallType::None,
+			CallType::Call => executed::CallType::Call,
+			CallType::CallCode => executed::CallType::CallCode,
+			CallType::DelegateCall => executed::CallType::DelegateCall,
+		}
+	}
+}
+
+impl Deserialize for CallType {
+	fn deserialize<D>(deserializer: &mut D) -> Result<CallType, D::Error>
+	where D: Deserializer {
+		deserializer.deserialize(CallTypeVisitor)
+	}
+}
+
+struct CallTypeVisitor;
+
+impl Visitor for CallTypeVisitor {
+	type Value = CallType;
+
+	fn visit_str<E>(&mut self, value: &str) -> Result<Self::Value, E> where E: Error {
+		match value {
+			"none" => Ok(CallType::None),
+			"call" => Ok(CallType::Call),
+			"callcode" => Ok(CallType::CallCode),
+			"delegatecall" => Ok(CallType::DelegateCall),
+			_ => Err(Error::custom("invalid call type")),
+		}
+	}
+
+	fn visit_string<E>(&mut self, value: String) -> Result<Self::Value, E> where E: Error {
+		self.visit_str(value.as_ref())
+	}
+}
+
 /// Call response
 #[derive(Debug, Serialize)]
 pub struct Call {
@@ -506,6 +545,36 @@ impl From<Executed> for TraceResults {
 	}
 }
 
+#[derive(Debug, Serialize)]
+/// Results of a replayed block transaction.
+pub struct TraceResultsWithTransactionHash {
+	/// The output of the call/create
+	pub output: Vec<u8>,
+	/// The transaction trace.
+	pub trace: Vec<Trace>,
+	/// The VM trace.
+	#[serde(rename="vmTrace")]
+	pub vm_trace: Option<VMTrace>,
+	/// The state diff.
+	#[serde(rename="stateDiff")]
+	pub state_diff: Option<StateDiff>,
+	/// The transaction hash.
+	#[serde(rename="transactionHash")]
+	pub transaction_hash: H256,
+}
+
+impl From<(UtilH256, Executed)> for TraceResultsWithTransactionHash {
+	fn from(t: (UtilH256, Executed)) -> Self {
+		TraceResultsWithTransactionHash {
+			output: t.1.output.into(),
+			trace: t.1.trace.into_iter().map(Into::into).collect(),
+			vm_trace: t.1.vm_trace.map(Into::into),
+			state_diff: t.1.state_diff.map(Into::into),
+			transaction_hash: t.0.into(),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
@@ -16,9 +16,60 @@
 
 //! Trace filter deserialization.
 
+use serde::{Deserialize, Deserializer, Error};
+use serde::de::Visitor;
 use ethcore::client::BlockID;
 use ethcore::client;
-use v1::types::{BlockNumber, H160};
+use ethcore::trace;
+use v1::types::{BlockNumber, H160, U256};
+use super::trace::CallType;
+
+/// Kind of traced action.
+#[derive(Debug, PartialEq)]
+pub enum ActionType {
+	/// Call
+	Call,
+	/// Contract creation
+	Create,
+	/// Suicide
+	Suicide,
+}
+
+impl Deserialize for ActionType {
+	fn deserialize<D>(deserializer: &mut D) -> Result<ActionType, D::Error>
+	where D: Deserializer {
+		deserializer.deserialize(ActionTypeVisitor)
+	}
+}
+
+struct ActionTypeVisitor;
+
+impl Visitor for ActionTypeVisitor {
+	type Value = ActionType;
+
+	fn visit_str<E>(&mut self, value: &str) -> Result<Self::Value, E> where E: Error {
+		match value {
+			"call" => Ok(ActionType::Call),
+			"create" => Ok(ActionType::Create),
+			"suicide" => Ok(ActionType::Suicide),
+			_ => Err(Error::custom("invalid action type")),
+		}
+	}
+
+	fn visit_string<E>(&mut self, value: String) -> Result<Self::Value, E> where E: Error {
+		self.visit_str(value.as_ref())
+	}
+}
+
+impl Into<trace::ActionType> for ActionType {
+	fn into(self) -> trace::ActionType {
+		match self {
+			ActionType::Call => trace::ActionType::Call,
+			ActionType::Create => trace::ActionType::Create,
+			ActionType::Suicide => trace::ActionType::Suicide,
+		}
+	}
+}
 
 /// Trace filter
 #[derive(Debug, PartialEq, Deserialize)]
@@ -35,6 +86,24 @@ pub struct TraceFilter {
 	/// To address
 	#[serde(rename="toAddress")]
 	pub to_address: Option<Vec<H160>>,
+	/// Kinds of action
+	#[serde(rename="actionType")]
+	pub action_type: Option<Vec<ActionType>>,
+	/// Call types
+	#[serde(rename="callType")]
+	pub call_type: Option<Vec<CallType>>,
+	/// Minimal transferred value
+	#[serde(rename="minValue")]
+	pub min_value: Option<U256>,
+	/// Match only failed or only successful actions
+	pub failed: Option<bool>,
+	/// Addresses of created contracts
+	#[serde(rename="createdAddress")]
+	pub created_address: Option<Vec<H160>>,
+	/// Number of matching traces to skip
+	pub after: Option<usize>,
+	/// Maximal number of traces to return
+	pub count: Option<usize>,
 }
 
 impl Into<client::TraceFilter> for TraceFilter {
@@ -45,6 +114,15 @@ impl Into<client::TraceFilter> for TraceFilter {
 			range: start..end,
 			from_address: self.from_address.map_or_else(Vec::new, |x| x.into_iter().map(Into::into).collect()),
 			to_address: self.to_address.map_or_else(Vec::new, |x| x.into_iter().map(Into::into).collect()),
+			action: trace::ActionFilter {
+				action_types: self.action_type.map_or_else(Vec::new, |x| x.into_iter().map(Into::into).collect()),
+				call_types: self.call_type.map_or_else(Vec::new, |x| x.into_iter().map(Into::into).collect()),
+				min_value: self.min_value.map(Into::into),
+				failed: self.failed,
+				created_address: self.created_address.map_or_else(Vec::new, |x| x.into_iter().map(Into::into).collect()),
+			},
+			after: self.after,
+			count: self.count,
 		}
 	}
 }
@@ -52,8 +130,9 @@ impl Into<client::TraceFilter> for TraceFilter {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use util::Address;
-	use v1::types::{BlockNumber, TraceFilter};
+	use util::{Address, U256};
+	use v1::types::{BlockNumber, TraceFilter, ActionType};
+	use v1::types::trace::CallType;
 
 	#[test]
 	fn test_empty_trace_filter_deserialize() {
@@ -63,7 +142,14 @@ mod tests {
 			from_block: None,
 			to_block: None,
 			from_address: None,
-			to_address: None
+			to_address: None,
+			action_type: None,
+			call_type: None,
+			min_value: None,
+			failed: None,
+			created_address: None,
+			after: None,
+			count: None,
 		});
 	}
 
@@ -73,7 +159,14 @@ mod tests {
 			"fromBlock": "latest",
 			"toBlock": "latest",
 			"fromAddress": ["0x0000000000000000000000000000000000000003"],
-			"toAddress": ["0x0000000000000000000000000000000000000005"]
+			"toAddress": ["0x0000000000000000000000000000000000000005"],
+			"actionType": ["call", "suicide"],
+			"callType": ["call", "delegatecall"],
+			"minValue": "0x0de0b6b3a7640000",
+			"failed": false,
+			"createdAddress": ["0x0000000000000000000000000000000000000007"],
+			"after": 10,
+			"count": 20
 		}"#;
 		let deserialized: TraceFilter = serde_json::from_str(s).unwrap();
 		assert_eq!(deserialized, TraceFilter {
@@ -81,6 +174,13 @@ mod tests {
 			to_block: Some(BlockNumber::Latest),
 			from_address: Some(vec![Address::from(3).into()]),
 			to_address: Some(vec![Address::from(5).into()]),
+			action_type: Some(vec![ActionType::Call, ActionType::Suicide]),
+			call_type: Some(vec![CallType::Call, CallType::DelegateCall]),
+			min_value: Some(U256::from(1_000_000_000_000_000_000u64).into()),
+			failed: Some(false),
+			created_address: Some(vec![Address::from(7).into()]),
+			after: Some(10),
+			count: Some(20),
 		});
 	}
 }
@@ -27,7 +27,7 @@ use chain::{ChainSync, SyncStatus};
 use std::net::{SocketAddr, AddrParseError};
 use ipc::{BinaryConvertable, BinaryConvertError, IpcConfig};
 use std::mem;
-use std::collections::VecDeque;
+use std::collections::{VecDeque, BTreeMap};
 use std::str::FromStr;
 use parking_lot::RwLock;
 
@@ -62,6 +62,42 @@ binary_fixed_size!(SyncStatus);
 pub trait SyncProvider: Send + Sync {
 	/// Get sync status
 	fn status(&self) -> SyncStatus;
+
+	/// Get details of connected peers
+	fn peers(&self) -> Vec<PeerInfo>;
+}
+
+#[derive(Binary, Debug, Clone, PartialEq, Eq)]
+/// Connected peer details
+pub struct PeerInfo {
+	/// Peer public key, hex-encoded
+	pub id: Option<String>,
+	/// Peer socket address
+	pub remote_address: String,
+	/// Peer client ID
+	pub client_version: String,
+	/// Negotiated protocols, e.g. `eth/63`
+	pub capabilities: Vec<String>,
+	/// Peer RLPx protocol version
+	pub protocol_version: u32,
+	/// eth protocol version, if sync handshake was completed
+	pub eth_version: Option<u32>,
+	/// Peer best block hash
+	pub head_hash: Option<H256>,
+	/// Peer best block number if known
+	pub head_number: Option<u64>,
+	/// Peer total difficulty if known
+	pub difficulty: Option<U256>,
+	/// Type of data currently being requested from peer
+	pub asking: Option<String>,
+	/// Bytes received from the peer
+	pub bytes_received: u64,
+	/// Bytes sent to the peer
+	pub bytes_sent: u64,
+	/// Seconds since the connection was established
+	pub connected_secs: u64,
+	/// Peer ping delay in milliseconds
+	pub ping_ms: Option<u64>,
 }
 
 /// Ethereum network protocol handler
@@ -93,6 +129,32 @@ impl SyncProvider for EthSync {
 	fn status(&self) -> SyncStatus {
 		self.handler.sync.write().status()
 	}
+
+	/// Get details of connected peers
+	fn peers(&self) -> Vec<PeerInfo> {
+		let sync = self.handler.sync.read();
+		self.network.sessions_info().into_iter()
+			.map(|(peer_id, session)| {
+				let peer_sync = sync.peer_info(&peer_id);
+				PeerInfo {
+					id: session.id.map(|id| id.hex()),
+					remote_address: session.remote_address,
+					client_version: session.client_version,
+					capabilities: session.capabilities.iter().map(|&(protocol, version)| format!("{}/{}", protocol, version)).collect(),
+					protocol_version: session.protocol_version,
+					eth_version: peer_sync.as_ref().map(|p| p.protocol_version),
+					head_hash: peer_sync.as_ref().map(|p| p.latest_hash),
+					head_number: peer_sync.as_ref().and_then(|p| p.latest_number),
+					difficulty: peer_sync.as_ref().and_then(|p| p.difficulty),
+					asking: peer_sync.map(|p| p.asking),
+					bytes_received: session.bytes_received as u64,
+					bytes_sent: session.bytes_sent as u64,
+					connected_secs: session.duration.as_secs(),
+					ping_ms: session.ping_ms,
+				}
+			})
+			.collect()
+	}
 }
 
 struct SyncProtocolHandler {
@@ -176,6 +238,8 @@ pub trait ManageNetwork : Send + Sync {
 	fn stop_network(&self);
 	/// Query the current configuration of the network
 	fn network_config(&self) -> NetworkConfiguration;
+	/// Query network traffic and peer statistics
+	fn network_metrics(&self) -> NetworkMetrics;
 }
 
 
@@ -213,6 +277,31 @@ impl ManageNetwork for EthSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn network_metrics(&self) -> NetworkMetrics {
+		let stats = self.network.stats();
+		NetworkMetrics {
+			bytes_received: stats.recv() as u64,
+			bytes_sent: stats.send() as u64,
+			sessions_created: stats.sessions() as u64,
+			protocol_peers: self.network.protocol_peers().into_iter()
+				.map(|(protocol, peers)| (protocol.to_owned(), peers as u64))
+				.collect(),
+		}
+	}
+}
+
+#[derive(Binary, Debug, Clone, PartialEq, Eq, Default)]
+/// Network traffic and peer statistics
+pub struct NetworkMetrics {
+	/// Total number of bytes received
+	pub bytes_received: u64,
+	/// Total number of bytes sent
+	pub bytes_sent: u64,
+	/// Total number of sessions created
+	pub sessions_created: u64,
+	/// Number of connected peers for each protocol
+	pub protocol_peers: BTreeMap<String, u64>,
 }
 
 #[derive(Binary, Debug, Clone, PartialEq, Eq)]
@@ -189,6 +189,21 @@ impl SyncStatus {
 	}
 }
 
+/// Sync details of a single peer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerSyncInfo {
+	/// eth protocol version
+	pub protocol_version: u32,
+	/// Peer best block hash
+	pub latest_hash: H256,
+	/// Peer best block number if known
+	pub latest_number: Option<BlockNumber>,
+	/// Peer total difficulty if known
+	pub difficulty: Option<U256>,
+	/// Type of data currently being requested from peer.
+	pub asking: String,
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 /// Peer data type requested
 enum PeerAsking {
@@ -316,6 +331,17 @@ impl ChainSync {
 		}
 	}
 
+	/// Returns sync details of given peer.
+	pub fn peer_info(&self, peer_id: &PeerId) -> Option<PeerSyncInfo> {
+		self.peers.get(peer_id).map(|p| PeerSyncInfo {
+			protocol_version: p.protocol_version,
+			latest_hash: p.latest_hash,
+			latest_number: p.latest_number,
+			difficulty: p.difficulty,
+			asking: format!("{:?}", p.asking),
+		})
+	}
+
 	/// Abort all sync activity
 	pub fn abort(&mut self, io: &mut SyncIo) {
 		self.restart(io);
@@ -349,7 +375,7 @@ impl ChainSync {
 
 	/// Remove peer from active peer set
 	fn deactivate_peer(&mut self, io: &mut SyncIo, peer_id: PeerId) {
-		trace!(target: "sync", "Deactivating peer {}", peer_id);
+		trace!(target: "sync", "Deactivating peer {}{}", peer_id, log_fields!(peer_id = peer_id));
 		self.active_peers.remove(&peer_id);
 		if self.active_peers.is_empty() {
 			trace!(target: "sync", "No more active peers");
@@ -427,7 +453,7 @@ impl ChainSync {
 			Some(ref mut peer) if peer.asking == PeerAsking::ForkHeader => {
 				let item_count = r.item_count();
 				if item_count == 0 || (item_count == 1 && try!(r.at(0)).as_raw().sha3() == self.fork_block.unwrap().1) {
-					trace!(target: "sync", "{}: Confirmed peer", peer_id);
+					trace!(target: "sync", "{}: Confirmed peer{}", peer_id, log_fields!(peer_id = peer_id));
 					peer.asking = PeerAsking::Nothing;
 					peer.confirmed = true;
 					true
@@ -621,7 +647,7 @@ impl ChainSync {
 					self.last_imported_block = header.number;
 					self.last_imported_hash = header.hash();
 				}
-				trace!(target: "sync", "New block queued {:?} ({})", h, header.number);
+				trace!(target: "sync", "New block queued {:?} ({}){}", h, header.number, log_fields!(peer_id = peer_id, block_number = header.number, hash = h.hex()));
 			},
 			Err(BlockImportError::Block(BlockError::UnknownParent(p))) => {
 				unknown = true;
@@ -1073,6 +1099,8 @@ impl ChainSync {
 		let skip: usize = try!(r.val_at(2));
 		let reverse: bool = try!(r.val_at(3));
 		let last = io.chain().chain_info().best_block_number;
+		// peers would ask for bodies of the returned headers, so blocks with pruned history are not advertised.
+		let first = io.chain().history_start();
 		let number = if try!(r.at(0)).size() == 32 {
 			// id is a hash
 			let hash: H256 = try!(r.val_at(0));
@@ -1081,6 +1109,9 @@ impl ChainSync {
 				Some(hdr) => {
 					let number = From::from(HeaderView::new(&hdr).number());
 					debug_assert_eq!(HeaderView::new(&hdr).sha3(), hash);
+					if number < first {
+						return Ok(Some((BLOCK_HEADERS_PACKET, RlpStream::new_list(0))));
+					}
 					if max_headers == 1 || io.chain().block_hash(BlockID::Number(number)) != Some(hash) {
 						// Non canonical header or single header requested
 						// TODO: handle single-step reverse hashchains of non-canon hashes
@@ -1107,7 +1138,7 @@ impl ChainSync {
 		let mut count = 0;
 		let mut data = Bytes::new();
 		let inc = (skip + 1) as BlockNumber;
-		while number <= last && count < max_count {
+		while number <= last && number >= first && count < max_count {
 			if let Some(mut hdr) = io.chain().block_header(BlockID::Number(number)) {
 				data.append(&mut hdr);
 				count += 1;
@@ -1431,6 +1462,21 @@ impl ChainSync {
 			return 0;
 		}
 
+		// Local transactions which are not getting mined are sent to everyone.
+		let stale = io.chain().local_transactions_to_rebroadcast();
+		if !stale.is_empty() {
+			let mut packet = RlpStream::new_list(stale.len());
+			for tx in &stale {
+				packet.append(tx);
+			}
+			let rlp = packet.out();
+			let peers = self.peers.keys().cloned().collect::<Vec<_>>();
+			for peer_id in peers {
+				self.send_packet(io, peer_id, TRANSACTIONS_PACKET, rlp.clone());
+			}
+			trace!(target: "sync", "Rebroadcast {} local transactions to all peers.", stale.len());
+		}
+
 		let mut transactions = io.chain().pending_transactions();
 		if transactions.is_empty() {
 			return 0;
@@ -1649,6 +1695,39 @@ mod tests {
 		assert_eq!(to_header_vec(result), vec![headers[50].clone(), headers[44].clone(), headers[38].clone()]);
 	}
 
+	#[test]
+	fn does_not_return_pruned_history() {
+		fn make_req<E: Encodable>(start: &E, count: usize, reverse: bool) -> Bytes {
+			let mut rlp = RlpStream::new_list(4);
+			rlp.append(start);
+			rlp.append(&count);
+			rlp.append(&0usize);
+			rlp.append(&if reverse {1u32} else {0u32});
+			rlp.out()
+		}
+		fn count(rlp: ::chain::RlpResponseResult) -> usize {
+			Rlp::new(&rlp.unwrap().unwrap().1.out()).item_count()
+		}
+
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Nothing);
+		client.set_history_start(50);
+		let hashes: Vec<_> = (0 .. 100).map(|i| (&client as &BlockChainClient).block_hash(BlockID::Number(i as BlockNumber)).unwrap()).collect();
+
+		let mut queue = VecDeque::new();
+		let io = TestIo::new(&mut client, &mut queue, None);
+
+		assert_eq!(count(ChainSync::return_block_headers(&io, &UntrustedRlp::new(&make_req(&40usize, 3, false)), 0)), 0);
+		assert_eq!(count(ChainSync::return_block_headers(&io, &UntrustedRlp::new(&make_req(&hashes[40], 1, false)), 0)), 0);
+		assert_eq!(count(ChainSync::return_block_headers(&io, &UntrustedRlp::new(&make_req(&52usize, 5, true)), 0)), 3);
+		assert_eq!(count(ChainSync::return_block_headers(&io, &UntrustedRlp::new(&make_req(&hashes[60], 3, false)), 0)), 3);
+
+		let mut bodies = RlpStream::new_list(2);
+		bodies.append(&hashes[40]);
+		bodies.append(&hashes[60]);
+		assert_eq!(count(ChainSync::return_block_bodies(&io, &UntrustedRlp::new(&bodies.out()), 0)), 1);
+	}
+
 	#[test]
 	fn return_nodes() {
 		let mut client = TestBlockChainClient::new();
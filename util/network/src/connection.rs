@@ -60,6 +60,10 @@ pub struct GenericConnection<Socket: GenericSocket> {
 	interest: EventSet,
 	/// Shared network statistics
 	stats: Arc<NetworkStats>,
+	/// Bytes received over this connection
+	bytes_received: usize,
+	/// Bytes sent over this connection
+	bytes_sent: usize,
 	/// Registered flag
 	registered: AtomicBool,
 }
@@ -84,6 +88,7 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 			match sock_ref.take(max as u64).try_read_buf(&mut self.rec_buf) {
 				Ok(Some(size)) if size != 0  => {
 					self.stats.inc_recv(size);
+					self.bytes_received += size;
 					trace!(target:"network", "{}: Read {} of {} bytes", self.token, self.rec_buf.len(), self.rec_size);
 					if self.rec_size != 0 && self.rec_buf.len() == self.rec_size {
 						self.rec_size = 0;
@@ -119,6 +124,16 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		self.interest.is_writable()
 	}
 
+	/// Number of bytes received over this connection.
+	pub fn bytes_received(&self) -> usize {
+		self.bytes_received
+	}
+
+	/// Number of bytes sent over this connection.
+	pub fn bytes_sent(&self) -> usize {
+		self.bytes_sent
+	}
+
 	/// Writable IO handler. Called when the socket is ready to send.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>) -> Result<WriteStatus, NetworkError> where Message: Send + Clone {
 		if self.send_queue.is_empty() {
@@ -134,10 +149,12 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 			match self.socket.try_write_buf(buf) {
 				Ok(Some(size)) if (buf.position() as usize) < send_size => {
 					self.stats.inc_send(size);
+					self.bytes_sent += size;
 					Ok(WriteStatus::Ongoing)
 				},
 				Ok(Some(size)) if (buf.position() as usize) == send_size => {
 					self.stats.inc_send(size);
+					self.bytes_sent += size;
 					trace!(target:"network", "{}: Wrote {} bytes", self.token, send_size);
 					Ok(WriteStatus::Complete)
 				},
@@ -172,6 +189,8 @@ impl Connection {
 			rec_size: 0,
 			interest: EventSet::hup() | EventSet::readable(),
 			stats: stats,
+			bytes_received: 0,
+			bytes_sent: 0,
 			registered: AtomicBool::new(false),
 		}
 	}
@@ -201,6 +220,8 @@ impl Connection {
 			send_queue: self.send_queue.clone(),
 			interest: EventSet::hup(),
 			stats: self.stats.clone(),
+			bytes_received: self.bytes_received,
+			bytes_sent: self.bytes_sent,
 			registered: AtomicBool::new(false),
 		})
 	}
@@ -541,6 +562,8 @@ mod tests {
 				rec_size: 0,
 				interest: EventSet::hup() | EventSet::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
+				bytes_received: 0,
+				bytes_sent: 0,
 				registered: AtomicBool::new(false),
 			}
 		}
@@ -564,6 +587,8 @@ mod tests {
 				rec_size: 0,
 				interest: EventSet::hup() | EventSet::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
+				bytes_received: 0,
+				bytes_sent: 0,
 				registered: AtomicBool::new(false),
 			}
 		}
@@ -32,7 +32,7 @@ use util::crypto::*;
 use util::Hashable;
 use util::rlp::*;
 use util::version;
-use session::{Session, SessionData};
+use session::{Session, SessionData, PeerSessionInfo};
 use error::*;
 use io::*;
 use {NetworkProtocolHandler, NonReservedPeerMode, PROTOCOL_VERSION};
@@ -492,6 +492,30 @@ impl Host {
 		r
 	}
 
+	/// Returns details of all sessions which completed the handshake.
+	pub fn sessions_info(&self) -> Vec<(PeerId, PeerSessionInfo)> {
+		self.sessions.read().iter()
+			.map(|s| s.lock())
+			.filter(|s| s.is_ready())
+			.map(|s| (s.token(), s.peer_session_info()))
+			.collect()
+	}
+
+	/// Returns number of connected peers for each protocol.
+	pub fn protocol_peers(&self) -> HashMap<ProtocolId, usize> {
+		let mut peers = HashMap::new();
+		for s in self.sessions.read().iter() {
+			let session = s.lock();
+			if !session.is_ready() {
+				continue;
+			}
+			for protocol in session.protocols() {
+				*peers.entry(protocol).or_insert(0) += 1;
+			}
+		}
+		peers
+	}
+
 	pub fn stop(&self, io: &IoContext<NetworkIoMessage>) -> Result<(), NetworkError> {
 		self.stopping.store(true, AtomicOrdering::Release);
 		let mut to_kill = Vec::new();
@@ -738,7 +762,7 @@ impl Host {
 	}
 
 	fn connection_closed(&self, token: TimerToken, io: &IoContext<NetworkIoMessage>) {
-		trace!(target: "network", "Connection closed: {}", token);
+		trace!(target: "network", "Connection closed: {}{}", token, log_fields!(peer_id = token));
 		self.kill_connection(token, io, true);
 	}
 
@@ -1023,7 +1047,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 				if let Some(session) = session {
 					session.lock().disconnect(io, DisconnectReason::DisconnectRequested);
 				}
-				trace!(target: "network", "Disconnect requested {}", peer);
+				trace!(target: "network", "Disconnect requested {}{}", peer, log_fields!(peer_id = peer));
 				self.kill_connection(*peer, io, false);
 			},
 			NetworkIoMessage::DisablePeer(ref peer) => {
@@ -1034,7 +1058,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 						self.nodes.write().mark_as_useless(id)
 					}
 				}
-				trace!(target: "network", "Disabling peer {}", peer);
+				trace!(target: "network", "Disabling peer {}{}", peer, log_fields!(peer_id = peer));
 				self.kill_connection(*peer, io, false);
 			},
 			NetworkIoMessage::InitPublicInterface =>
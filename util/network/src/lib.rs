@@ -58,6 +58,7 @@
 #[macro_use]
 extern crate log;
 extern crate ethcore_io as io;
+#[macro_use]
 extern crate ethcore_util as util;
 extern crate parking_lot;
 extern crate mio;
@@ -95,6 +96,7 @@ pub use host::NetworkIoMessage;
 pub use error::NetworkError;
 pub use host::NetworkConfiguration;
 pub use stats::NetworkStats;
+pub use session::PeerSessionInfo;
 
 use io::TimerToken;
 pub use node_table::is_valid_node_url;
@@ -16,11 +16,13 @@
 
 use {NetworkProtocolHandler, NetworkConfiguration, NonReservedPeerMode};
 use error::NetworkError;
-use host::{Host, NetworkContext, NetworkIoMessage, ProtocolId};
+use host::{Host, NetworkContext, NetworkIoMessage, ProtocolId, PeerId};
+use session::PeerSessionInfo;
 use stats::NetworkStats;
 use io::*;
 use parking_lot::RwLock;
 use std::sync::Arc;
+use std::collections::HashMap;
 use ansi_term::Colour;
 
 struct HostHandler {
@@ -114,6 +116,18 @@ impl NetworkService {
 		host.as_ref().map(|h| h.local_url())
 	}
 
+	/// Returns details of all connected peers.
+	pub fn sessions_info(&self) -> Vec<(PeerId, PeerSessionInfo)> {
+		let host = self.host.read();
+		host.as_ref().map_or_else(Vec::new, |h| h.sessions_info())
+	}
+
+	/// Returns number of connected peers for each protocol.
+	pub fn protocol_peers(&self) -> HashMap<ProtocolId, usize> {
+		let host = self.host.read();
+		host.as_ref().map_or_else(HashMap::new, |h| h.protocol_peers())
+	}
+
 	/// Start network IO
 	pub fn start(&self) -> Result<(), NetworkError> {
 		let mut host = self.host.write();
@@ -17,6 +17,7 @@
 use std::net::SocketAddr;
 use std::io;
 use std::sync::*;
+use std::time::{Duration, Instant};
 use mio::*;
 use mio::tcp::*;
 use util::rlp::*;
@@ -46,6 +47,8 @@ pub struct Session {
 	ping_time_ns: u64,
 	pong_time_ns: Option<u64>,
 	state: State,
+	/// Time the connection was established.
+	connected: Instant,
 }
 
 enum State {
@@ -87,6 +90,31 @@ pub struct SessionInfo {
 	pub originated: bool,
 }
 
+/// Details of a connected peer session.
+#[derive(Debug, Clone)]
+pub struct PeerSessionInfo {
+	/// Peer public key
+	pub id: Option<NodeId>,
+	/// Peer socket address
+	pub remote_address: String,
+	/// Peer client ID
+	pub client_version: String,
+	/// Peer RLPx protocol version
+	pub protocol_version: u32,
+	/// Negotiated protocols and their versions
+	pub capabilities: Vec<(ProtocolId, u8)>,
+	/// Peer ping delay in milliseconds
+	pub ping_ms: Option<u64>,
+	/// True if this session was originated by us.
+	pub originated: bool,
+	/// Bytes received from the peer
+	pub bytes_received: usize,
+	/// Bytes sent to the peer
+	pub bytes_sent: usize,
+	/// Time elapsed since the connection was established
+	pub duration: Duration,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct PeerCapabilityInfo {
 	pub protocol: String,
@@ -143,6 +171,7 @@ impl Session {
 			ping_time_ns: 0,
 			pong_time_ns: None,
 			expired: false,
+			connected: Instant::now(),
 		})
 	}
 
@@ -243,6 +272,28 @@ impl Session {
 		self.info.capabilities.iter().any(|c| c.protocol == protocol)
 	}
 
+	/// Returns protocols negotiated with the peer.
+	pub fn protocols(&self) -> Vec<ProtocolId> {
+		self.info.capabilities.iter().map(|c| c.protocol).collect()
+	}
+
+	/// Returns details of this session.
+	pub fn peer_session_info(&self) -> PeerSessionInfo {
+		let connection = self.connection();
+		PeerSessionInfo {
+			id: self.info.id.clone(),
+			remote_address: connection.remote_addr_str(),
+			client_version: self.info.client_version.clone(),
+			protocol_version: self.info.protocol_version,
+			capabilities: self.info.capabilities.iter().map(|c| (c.protocol, c.version)).collect(),
+			ping_ms: self.info.ping_ms,
+			originated: self.info.originated,
+			bytes_received: connection.bytes_received(),
+			bytes_sent: connection.bytes_sent(),
+			duration: self.connected.elapsed(),
+		}
+	}
+
 	/// Register the session socket with the event loop
 	pub fn register_socket<Host:Handler<Timeout = Token>>(&self, reg: Token, event_loop: &mut EventLoop<Host>) -> Result<(), NetworkError> {
 		if self.expired() {
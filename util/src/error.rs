@@ -28,6 +28,10 @@ pub enum BaseDataError {
 	NegativelyReferencedHash(H256),
 	/// A committed value was inserted more than once.
 	AlreadyExists(H256),
+	/// State of the given era has already been pruned.
+	PrunedEra(u64),
+	/// Preimage of a trie key is not known to the database.
+	MissingPreimage(H256),
 }
 
 impl fmt::Display for BaseDataError {
@@ -37,6 +41,10 @@ impl fmt::Display for BaseDataError {
 				write!(f, "Entry {} removed from database more times than it was added.", hash),
 			BaseDataError::AlreadyExists(hash) =>
 				write!(f, "Committed key already exists in database: {}", hash),
+			BaseDataError::PrunedEra(era) =>
+				write!(f, "State of era {} has already been pruned.", era),
+			BaseDataError::MissingPreimage(hash) =>
+				write!(f, "Preimage of trie key {} is missing. Is the database fat?", hash),
 		}
 	}
 }
@@ -22,9 +22,7 @@ use hashdb::*;
 use memorydb::*;
 use super::{DB_PREFIX_LEN, LATEST_ERA_KEY};
 use super::traits::JournalDB;
-use kvdb::{Database, DBTransaction};
-#[cfg(test)]
-use std::env;
+use kvdb::{DBTransaction, KeyValueDB};
 
 /// Suffix appended to auxiliary keys to distinguish them from normal keys.
 /// Would be nich to use rocksdb columns for this eventually.
@@ -39,14 +37,14 @@ const AUX_FLAG: u8 = 255;
 /// the removals actually take effect.
 pub struct ArchiveDB {
 	overlay: MemoryDB,
-	backing: Arc<Database>,
+	backing: Arc<KeyValueDB>,
 	latest_era: Option<u64>,
 	column: Option<u32>,
 }
 
 impl ArchiveDB {
 	/// Create a new instance from file
-	pub fn new(backing: Arc<Database>, col: Option<u32>) -> ArchiveDB {
+	pub fn new(backing: Arc<KeyValueDB>, col: Option<u32>) -> ArchiveDB {
 		let latest_era = backing.get(col, &LATEST_ERA_KEY).expect("Low-level database error.").map(|val| decode::<u64>(&val));
 		ArchiveDB {
 			overlay: MemoryDB::new(),
@@ -56,13 +54,10 @@ impl ArchiveDB {
 		}
 	}
 
-	/// Create a new instance with an anonymous temporary database.
+	/// Create a new instance with an in-memory database.
 	#[cfg(test)]
 	fn new_temp() -> ArchiveDB {
-		let mut dir = env::temp_dir();
-		dir.push(H32::random().hex());
-		let backing = Arc::new(Database::open_default(dir.to_str().unwrap()).unwrap());
-		Self::new(backing, None)
+		Self::new(Arc::new(::kvdb::in_memory(None)), None)
 	}
 
 	fn payload(&self, key: &H256) -> Option<Bytes> {
@@ -185,6 +180,15 @@ impl JournalDB for ArchiveDB {
 		Ok((inserts + deletes) as u32)
 	}
 
+	fn revert(&mut self, batch: &DBTransaction, era: u64) -> Result<(), UtilError> {
+		// nodes inserted by later eras are kept, just like all other historical data.
+		if self.latest_era.map_or(false, |e| e > era) {
+			try!(batch.put(self.column, &LATEST_ERA_KEY, &encode(&era)));
+			self.latest_era = Some(era);
+		}
+		Ok(())
+	}
+
 	fn inject(&mut self, batch: &DBTransaction) -> Result<u32, UtilError> {
 		let mut inserts = 0usize;
 		let mut deletes = 0usize;
@@ -225,7 +229,7 @@ impl JournalDB for ArchiveDB {
 
 	fn is_pruned(&self) -> bool { false }
 
-	fn backing(&self) -> &Arc<Database> {
+	fn backing(&self) -> &Arc<KeyValueDB> {
 		&self.backing
 	}
 }
@@ -17,7 +17,7 @@
 //! `JournalDB` interface and implementation.
 
 use common::*;
-use kvdb::Database;
+use kvdb::KeyValueDB;
 
 /// Export the journaldb module.
 pub mod traits;
@@ -116,7 +116,7 @@ impl fmt::Display for Algorithm {
 }
 
 /// Create a new `JournalDB` trait object.
-pub fn new(backing: Arc<Database>, algorithm: Algorithm, col: Option<u32>) -> Box<JournalDB> {
+pub fn new(backing: Arc<KeyValueDB>, algorithm: Algorithm, col: Option<u32>) -> Box<JournalDB> {
 	match algorithm {
 		Algorithm::Archive => Box::new(archivedb::ArchiveDB::new(backing, col)),
 		Algorithm::EarlyMerge => Box::new(earlymergedb::EarlyMergeDB::new(backing, col)),
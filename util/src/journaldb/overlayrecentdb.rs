@@ -21,9 +21,7 @@ use rlp::*;
 use hashdb::*;
 use memorydb::*;
 use super::{DB_PREFIX_LEN, LATEST_ERA_KEY};
-use kvdb::{Database, DBTransaction};
-#[cfg(test)]
-use std::env;
+use kvdb::{DBTransaction, KeyValueDB};
 use super::JournalDB;
 
 /// Implementation of the `JournalDB` trait for a disk-backed database with a memory overlay
@@ -59,7 +57,7 @@ use super::JournalDB;
 
 pub struct OverlayRecentDB {
 	transaction_overlay: MemoryDB,
-	backing: Arc<Database>,
+	backing: Arc<KeyValueDB>,
 	journal_overlay: Arc<RwLock<JournalOverlay>>,
 	column: Option<u32>,
 }
@@ -99,7 +97,7 @@ const PADDING : [u8; 10] = [ 0u8; 10 ];
 
 impl OverlayRecentDB {
 	/// Create a new instance.
-	pub fn new(backing: Arc<Database>, col: Option<u32>) -> OverlayRecentDB {
+	pub fn new(backing: Arc<KeyValueDB>, col: Option<u32>) -> OverlayRecentDB {
 		let journal_overlay = Arc::new(RwLock::new(OverlayRecentDB::read_overlay(&backing, col)));
 		OverlayRecentDB {
 			transaction_overlay: MemoryDB::new(),
@@ -109,13 +107,10 @@ impl OverlayRecentDB {
 		}
 	}
 
-	/// Create a new instance with an anonymous temporary database.
+	/// Create a new instance with an in-memory database.
 	#[cfg(test)]
 	pub fn new_temp() -> OverlayRecentDB {
-		let mut dir = env::temp_dir();
-		dir.push(H32::random().hex());
-		let backing = Arc::new(Database::open_default(dir.to_str().unwrap()).unwrap());
-		Self::new(backing, None)
+		Self::new(Arc::new(::kvdb::in_memory(None)), None)
 	}
 
 	#[cfg(test)]
@@ -129,7 +124,7 @@ impl OverlayRecentDB {
 		self.backing.get(self.column, key).expect("Low-level database error. Some issue with your hard disk?")
 	}
 
-	fn read_overlay(db: &Database, col: Option<u32>) -> JournalOverlay {
+	fn read_overlay(db: &KeyValueDB, col: Option<u32>) -> JournalOverlay {
 		let mut journal = HashMap::new();
 		let mut overlay = MemoryDB::new();
 		let mut count = 0;
@@ -202,7 +197,7 @@ impl JournalDB for OverlayRecentDB {
 		self.backing.get(self.column, &LATEST_ERA_KEY).expect("Low level database error").is_none()
 	}
 
-	fn backing(&self) -> &Arc<Database> {
+	fn backing(&self) -> &Arc<KeyValueDB> {
 		&self.backing
 	}
 
@@ -298,6 +293,40 @@ impl JournalDB for OverlayRecentDB {
 		Ok(0)
 	}
 
+	fn revert(&mut self, batch: &DBTransaction, era: u64) -> Result<(), UtilError> {
+		let mut journal_overlay = self.journal_overlay.write();
+		let journal_overlay = &mut *journal_overlay;
+		let latest_era = match journal_overlay.latest_era {
+			Some(latest_era) if latest_era > era => latest_era,
+			_ => return Ok(()),
+		};
+
+		// removals of an era are applied once it becomes ancient and its journal is deleted,
+		// so the state of `era` is complete only if all later eras are still journalled.
+		if (era + 1..latest_era + 1).any(|e| !journal_overlay.journal.contains_key(&e)) {
+			return Err(BaseDataError::PrunedEra(era).into());
+		}
+
+		// insertions of recent eras live only in the journal, dropping it reverts them.
+		for e in era + 1..latest_era + 1 {
+			let records = journal_overlay.journal.remove(&e).unwrap_or_else(Vec::new);
+			for (index, journal) in records.into_iter().enumerate() {
+				let mut r = RlpStream::new_list(3);
+				r.append(&e);
+				r.append(&index);
+				r.append(&&PADDING[..]);
+				try!(batch.delete(self.column, &r.drain()));
+				for k in &journal.insertions {
+					journal_overlay.backing_overlay.remove_and_purge(&to_short_key(k));
+				}
+			}
+		}
+
+		try!(batch.put_vec(self.column, &LATEST_ERA_KEY, encode(&era).to_vec()));
+		journal_overlay.latest_era = Some(era);
+		Ok(())
+	}
+
 	fn inject(&mut self, batch: &DBTransaction) -> Result<u32, UtilError> {
 		let mut ops = 0;
 		for (key, (value, rc)) in self.transaction_overlay.drain() {
@@ -906,6 +935,32 @@ mod tests {
 		assert!(jdb.contains(&bar));
 	}
 
+	#[test]
+	fn revert() {
+		let mut jdb = OverlayRecentDB::new_temp();
+		// history is 1
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &b"0".sha3(), None).unwrap();
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &b"1".sha3(), None).unwrap();
+		jdb.remove(&foo);
+		let baz = jdb.insert(b"baz");
+		jdb.commit_batch(2, &b"2".sha3(), Some((0, b"0".sha3()))).unwrap();
+		jdb.commit_batch(3, &b"3".sha3(), Some((1, b"1".sha3()))).unwrap();
+
+		// era 1 is ancient already.
+		let batch = jdb.backing().transaction();
+		assert!(jdb.revert(&batch, 0).is_err());
+
+		jdb.revert(&batch, 1).unwrap();
+		jdb.backing().write(batch).unwrap();
+		assert!(jdb.can_reconstruct_refs());
+		assert_eq!(jdb.latest_era(), Some(1));
+		assert!(jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+		assert!(!jdb.contains(&baz));
+	}
+
 	#[test]
 	fn inject() {
 		let temp = ::devtools::RandomTempPath::new();
@@ -18,7 +18,7 @@
 
 use common::*;
 use hashdb::*;
-use kvdb::{Database, DBTransaction};
+use kvdb::{DBTransaction, KeyValueDB};
 
 /// A `HashDB` which can manage a short-term journal potentially containing many forks of mutually
 /// exclusive actions.
@@ -51,11 +51,17 @@ pub trait JournalDB: HashDB {
 	/// State data query
 	fn state(&self, _id: &H256) -> Option<Bytes>;
 
+	/// Drop the journal of all eras after `era`, making it the latest one.
+	/// Fails if the state of `era` has already been pruned.
+	fn revert(&mut self, _batch: &DBTransaction, _era: u64) -> Result<(), UtilError> {
+		Err(UtilError::SimpleString("Reverting is not supported by this pruning method.".into()))
+	}
+
 	/// Whether this database is pruned.
 	fn is_pruned(&self) -> bool { true }
 
 	/// Get backing database.
-	fn backing(&self) -> &Arc<Database>;
+	fn backing(&self) -> &Arc<KeyValueDB>;
 
 	/// Commit all changes in a single batch
 	#[cfg(test)]
@@ -25,6 +25,7 @@ use rocksdb::{DB, Writable, WriteBatch, WriteOptions, IteratorMode, DBIterator,
 
 const DB_BACKGROUND_FLUSHES: i32 = 2;
 const DB_BACKGROUND_COMPACTIONS: i32 = 2;
+const BACKUP_BATCH_SIZE: usize = 4096;
 
 /// Write transaction. Batches a sequence of put/delete operations for efficiency.
 pub struct DBTransaction {
@@ -48,9 +49,17 @@ enum DBOp {
 	}
 }
 
+impl DBOp {
+	fn col(&self) -> Option<u32> {
+		match *self {
+			DBOp::Insert { col, .. } | DBOp::InsertCompressed { col, .. } | DBOp::Delete { col, .. } => col,
+		}
+	}
+}
+
 impl DBTransaction {
 	/// Create new transaction.
-	pub fn new(_db: &Database) -> DBTransaction {
+	pub fn new(_db: &KeyValueDB) -> DBTransaction {
 		DBTransaction {
 			ops: Mutex::new(Vec::with_capacity(256)),
 		}
@@ -193,6 +202,171 @@ impl<'a> Iterator for DatabaseIterator {
 	}
 }
 
+/// Generic key-value database.
+///
+/// Changes are applied in transactions, each of which is written atomically.
+pub trait KeyValueDB: Sync + Send {
+	/// Creates new transaction for this database.
+	fn transaction(&self) -> DBTransaction;
+
+	/// Get value by key.
+	fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<Bytes>, String>;
+
+	/// Get value by partial key. Prefix size should match configured prefix size.
+	fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>>;
+
+	/// Commit transaction to the write buffer. Changes become durable after `flush`.
+	fn write_buffered(&self, transaction: DBTransaction) -> Result<(), String>;
+
+	/// Commit buffered changes to the database.
+	fn flush(&self) -> Result<(), String>;
+
+	/// Commit transaction to the database.
+	fn write(&self, transaction: DBTransaction) -> Result<(), String>;
+
+	/// Iterate over flushed data of given column, ordered by key.
+	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>;
+
+	/// Number of columns besides the default one, `None` if there are none.
+	fn columns(&self) -> Option<u32>;
+
+	/// Check if there is anything in the column.
+	fn is_empty(&self, col: Option<u32>) -> bool {
+		self.iter(col).next().is_none()
+	}
+
+	/// Get a snapshot of all columns. Iterators are only consistent with each other
+	/// if there are no concurrent writes while this is called. Buffered changes are not included.
+	fn snapshot(&self) -> DatabaseSnapshot {
+		let columns = self.columns();
+		let cols = Some(None).into_iter().chain((0..columns.unwrap_or(0)).map(Some));
+		DatabaseSnapshot {
+			columns: columns,
+			iters: cols.map(|col| (col, self.iter(col))).collect(),
+		}
+	}
+}
+
+/// Point-in-time view of all columns of a database.
+pub struct DatabaseSnapshot<'a> {
+	columns: Option<u32>,
+	iters: Vec<(Option<u32>, Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>)>,
+}
+
+impl<'a> DatabaseSnapshot<'a> {
+	/// Copies all entries of the snapshot into a new database at given path.
+	/// Values are copied as stored, compressed ones are not recompressed.
+	pub fn write_to(self, path: &str) -> Result<(), String> {
+		let target = try!(Database::open(&DatabaseConfig::with_columns(self.columns), path));
+		if self.iters.iter().any(|&(col, _)| !target.is_empty(col)) {
+			return Err(format!("Backup target {} is not empty", path));
+		}
+
+		for (col, iter) in self.iters {
+			let mut batch = target.transaction();
+			let mut count = 0;
+			for (key, value) in iter {
+				try!(batch.put(col, &key, &value));
+				count += 1;
+				if count == BACKUP_BATCH_SIZE {
+					try!(target.write(batch));
+					batch = target.transaction();
+					count = 0;
+				}
+			}
+			try!(target.write(batch));
+		}
+		Ok(())
+	}
+}
+
+/// Key-value database kept in memory.
+///
+/// Writes are never buffered, so `flush` has nothing to do. Nothing survives dropping the database.
+pub struct InMemory {
+	columns: Option<u32>,
+	data: RwLock<HashMap<Option<u32>, BTreeMap<Bytes, Bytes>>>,
+}
+
+/// Create an in-memory database with given number of columns.
+pub fn in_memory(columns: Option<u32>) -> InMemory {
+	let cols = Some(None).into_iter().chain((0..columns.unwrap_or(0)).map(Some));
+	InMemory {
+		columns: columns,
+		data: RwLock::new(cols.map(|col| (col, BTreeMap::new())).collect()),
+	}
+}
+
+impl KeyValueDB for InMemory {
+	fn transaction(&self) -> DBTransaction {
+		DBTransaction::new(self)
+	}
+
+	fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<Bytes>, String> {
+		match self.data.read().get(&col) {
+			Some(map) => Ok(map.get(key).cloned()),
+			None => Err(format!("No such column family: {:?}", col)),
+		}
+	}
+
+	fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		let data = self.data.read();
+		let map = match data.get(&col) {
+			Some(map) => map,
+			None => return None,
+		};
+		// keys are sorted, so the first key not lower than the prefix is the only candidate.
+		match map.range(prefix.to_vec()..).next() {
+			Some((k, v)) if k.starts_with(prefix) => Some(v.clone().into_boxed_slice()),
+			_ => None,
+		}
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) -> Result<(), String> {
+		// all operations are checked first, so that a failing transaction changes nothing.
+		let ops = transaction.ops.into_inner();
+		let mut data = self.data.write();
+		if let Some(col) = ops.iter().map(DBOp::col).find(|col| !data.contains_key(col)) {
+			return Err(format!("No such column family: {:?}", col));
+		}
+
+		for op in ops {
+			match op {
+				DBOp::Insert { col, key, value } => {
+					data.get_mut(&col).expect("columns checked above; qed").insert(key.to_vec(), value);
+				},
+				DBOp::InsertCompressed { col, key, value } => {
+					let compressed = UntrustedRlp::new(&value).compress(RlpType::Blocks).to_vec();
+					data.get_mut(&col).expect("columns checked above; qed").insert(key.to_vec(), compressed);
+				},
+				DBOp::Delete { col, key } => {
+					data.get_mut(&col).expect("columns checked above; qed").remove(&*key);
+				},
+			}
+		}
+		Ok(())
+	}
+
+	fn flush(&self) -> Result<(), String> {
+		Ok(())
+	}
+
+	fn write(&self, transaction: DBTransaction) -> Result<(), String> {
+		self.write_buffered(transaction)
+	}
+
+	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		// entries are copied out, so later writes do not affect the iterator.
+		let entries: Vec<_> = self.data.read().get(&col)
+			.map_or_else(Vec::new, |map| map.iter().map(|(k, v)| (k.clone().into_boxed_slice(), v.clone().into_boxed_slice())).collect());
+		Box::new(entries.into_iter())
+	}
+
+	fn columns(&self) -> Option<u32> {
+		self.columns
+	}
+}
+
 /// Key-Value database.
 pub struct Database {
 	db: DB,
@@ -415,6 +589,43 @@ impl Database {
 	}
 }
 
+impl KeyValueDB for Database {
+	fn transaction(&self) -> DBTransaction {
+		DBTransaction::new(self)
+	}
+
+	fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<Bytes>, String> {
+		Database::get(self, col, key)
+	}
+
+	fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		Database::get_by_prefix(self, col, prefix)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) -> Result<(), String> {
+		Database::write_buffered(self, transaction)
+	}
+
+	fn flush(&self) -> Result<(), String> {
+		Database::flush(self)
+	}
+
+	fn write(&self, transaction: DBTransaction) -> Result<(), String> {
+		Database::write(self, transaction)
+	}
+
+	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		Box::new(Database::iter(self, col))
+	}
+
+	fn columns(&self) -> Option<u32> {
+		match self.cfs.len() {
+			0 => None,
+			n => Some(n as u32),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use hash::*;
@@ -472,4 +683,51 @@ mod tests {
 		assert!(smoke.is_empty(None));
 		test_db(&DatabaseConfig::default());
 	}
+
+	#[test]
+	fn snapshot_ignores_later_writes() {
+		let path = RandomTempPath::create_dir();
+		let db = Database::open(&DatabaseConfig::with_columns(Some(2)), path.as_path().to_str().unwrap()).unwrap();
+		let batch = db.transaction();
+		batch.put(Some(0), b"cat", b"meow").unwrap();
+		batch.put(Some(1), b"dog", b"woof").unwrap();
+		db.write(batch).unwrap();
+
+		let snapshot = db.snapshot();
+		let batch = db.transaction();
+		batch.put(Some(0), b"cow", b"moo").unwrap();
+		batch.delete(Some(1), b"dog").unwrap();
+		db.write(batch).unwrap();
+
+		let backup_path = RandomTempPath::create_dir();
+		snapshot.write_to(backup_path.as_path().to_str().unwrap()).unwrap();
+		let backup = Database::open(&DatabaseConfig::with_columns(Some(2)), backup_path.as_path().to_str().unwrap()).unwrap();
+		assert_eq!(backup.get(Some(0), b"cat").unwrap().unwrap().deref(), b"meow");
+		assert_eq!(backup.get(Some(1), b"dog").unwrap().unwrap().deref(), b"woof");
+		assert!(backup.get(Some(0), b"cow").unwrap().is_none());
+	}
+
+	#[test]
+	fn in_memory_database() {
+		let db = in_memory(Some(2));
+		let batch = db.transaction();
+		batch.put(Some(0), b"cat", b"meow").unwrap();
+		batch.put(Some(0), b"cow", b"moo").unwrap();
+		batch.put(Some(1), b"dog", b"woof").unwrap();
+		db.write(batch).unwrap();
+
+		assert_eq!(db.get(Some(0), b"cat").unwrap().unwrap(), b"meow".to_vec());
+		assert!(db.get(Some(1), b"cat").unwrap().is_none());
+		assert_eq!(db.get_by_prefix(Some(0), b"co").unwrap().deref(), b"moo");
+		let keys: Vec<_> = db.iter(Some(0)).map(|(k, _)| k.to_vec()).collect();
+		assert_eq!(keys, vec![b"cat".to_vec(), b"cow".to_vec()]);
+		assert!(db.is_empty(None));
+
+		// transactions touching unknown columns are rejected as a whole.
+		let batch = db.transaction();
+		batch.delete(Some(0), b"cat").unwrap();
+		batch.put(Some(2), b"eel", b"zap").unwrap();
+		assert!(db.write(batch).is_err());
+		assert!(db.get(Some(0), b"cat").unwrap().is_some());
+	}
 }
@@ -16,13 +16,18 @@
 
 //! Common log helper functions
 
-use std::env;
-use rlog::LogLevelFilter;
+use std::{env, fmt};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::collections::BTreeMap;
+use rlog::{LogLevel, LogLevelFilter, MaxLogLevelFilter};
 use env_logger::LogBuilder;
 use arrayvec::ArrayVec;
 pub use ansi_term::{Colour, Style};
 
-use parking_lot::{RwLock, RwLockReadGuard};
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 
 lazy_static! {
 	static ref LOG_DUMMY: bool = {
@@ -47,33 +52,204 @@ pub fn init_log() {
 
 const LOG_SIZE : usize = 128;
 
+/// Separates a log message from the structured fields appended to it with `log_fields!`.
+pub const LOG_FIELDS_SEPARATOR: char = '\u{1e}';
+
+/// Renders structured fields to be appended to a log message, e.g.
+/// `trace!(target: "sync", "{}: Confirmed peer{}", peer_id, log_fields!(peer_id = peer_id))`.
+/// Text logs leave the fields out, JSON logs report them as separate members.
+#[macro_export]
+macro_rules! log_fields {
+	( $( $key:ident = $value:expr ),+ ) => {{
+		let mut fields = String::new();
+		$(
+			fields.push($crate::log::LOG_FIELDS_SEPARATOR);
+			fields.push_str(&format!("{}={}", stringify!($key), $value));
+		)+
+		fields
+	}}
+}
+
+/// Maximal number of tees active at once.
+pub const MAX_TEES: usize = 16;
+
+/// Maximal duration of a single tee in seconds (one day).
+pub const MAX_TEE_DURATION: u64 = 24 * 60 * 60;
+
+/// Log level directives in the `RUST_LOG` format which can be altered at runtime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelFilter {
+	default: LogLevelFilter,
+	targets: BTreeMap<String, LogLevelFilter>,
+}
+
+impl LevelFilter {
+	/// Creates filter with given default level and no target-specific directives.
+	pub fn new(default: LogLevelFilter) -> Self {
+		LevelFilter {
+			default: default,
+			targets: BTreeMap::new(),
+		}
+	}
+
+	/// Applies comma-delimited directives (`level`, `target` or `target=level`) on top of current ones.
+	pub fn parse(&mut self, directives: &str) -> Result<(), String> {
+		for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+			let mut parts = directive.splitn(2, '=');
+			match (parts.next(), parts.next()) {
+				(Some(level), None) if level.parse::<LogLevelFilter>().is_ok() => {
+					self.default = level.parse().expect("Checked in guard; qed");
+				},
+				(Some(target), None) => {
+					self.targets.insert(target.to_owned(), LogLevelFilter::Trace);
+				},
+				(Some(target), Some(level)) => {
+					let level = try!(level.parse().map_err(|_| format!("Invalid log level: {}", level)));
+					self.targets.insert(target.to_owned(), level);
+				},
+				_ => return Err(format!("Invalid log directive: {}", directive)),
+			}
+		}
+		Ok(())
+	}
+
+	/// Returns `true` if entry of given target and level should be logged.
+	/// The most specific (longest) matching target prefix wins.
+	pub fn enabled(&self, target: &str, level: LogLevel) -> bool {
+		let filter = self.targets.iter()
+			.filter(|&(name, _)| target.starts_with(name.as_str()))
+			.max_by_key(|&(name, _)| name.len())
+			.map_or(self.default, |(_, level)| *level);
+		level <= filter
+	}
+
+	/// Returns the most verbose level enabled for any target.
+	pub fn max_level(&self) -> LogLevelFilter {
+		self.targets.values().fold(self.default, |max, level| ::std::cmp::max(max, *level))
+	}
+}
+
+impl fmt::Display for LevelFilter {
+	/// Renders the directives in the `RUST_LOG` format, the default level first.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		try!(write!(f, "{}", format!("{}", self.default).to_lowercase()));
+		for (target, level) in &self.targets {
+			try!(write!(f, ",{}={}", target, format!("{}", level).to_lowercase()));
+		}
+		Ok(())
+	}
+}
+
+/// Copy of the log entries of a single target written to a separate file.
+struct Tee {
+	target: String,
+	file: File,
+	until: Instant,
+}
+
 /// Logger implementation that keeps up to `LOG_SIZE` log elements.
 pub struct RotatingLogger {
-	/// Defined logger levels
-	levels: String,
+	/// Levels filter
+	filter: RwLock<LevelFilter>,
+	/// Handle to the global max log level
+	max_level: Mutex<Option<MaxLogLevelFilter>>,
+	/// Active tees
+	tees: Mutex<Vec<Tee>>,
 	/// Logs array. Latest log is always at index 0
 	logs: RwLock<ArrayVec<[String; LOG_SIZE]>>,
 }
 
 impl RotatingLogger {
 
-	/// Creates new `RotatingLogger` with given levels.
-	/// It does not enforce levels - it's just read only.
+	/// Creates new `RotatingLogger` with given levels applied on top of the `info` default.
+	/// Invalid directives are ignored.
 	pub fn new(levels: String) -> Self {
+		Self::with_defaults(LevelFilter::new(LogLevelFilter::Info), levels)
+	}
+
+	/// Creates new `RotatingLogger` with given levels applied on top of `defaults`.
+	/// Invalid directives are ignored.
+	pub fn with_defaults(mut defaults: LevelFilter, levels: String) -> Self {
+		let _ = defaults.parse(&levels);
 		RotatingLogger {
-			levels: levels,
+			filter: RwLock::new(defaults),
+			max_level: Mutex::new(None),
+			tees: Mutex::new(Vec::new()),
 			logs: RwLock::new(ArrayVec::<[_; LOG_SIZE]>::new()),
 		}
 	}
 
+	/// Sets the handle used to lift the global max log level when levels are changed.
+	pub fn set_max_level_handle(&self, handle: MaxLogLevelFilter) {
+		handle.set(self.filter.read().max_level());
+		*self.max_level.lock() = Some(handle);
+	}
+
 	/// Append new log entry
 	pub fn append(&self, log: String) {
 		self.logs.write().insert(0, log);
 	}
 
-	/// Return levels
-	pub fn levels(&self) -> &str {
-		&self.levels
+	/// Returns levels currently in effect in the `RUST_LOG` format.
+	pub fn levels(&self) -> String {
+		self.filter.read().to_string()
+	}
+
+	/// Returns `true` if entry of given target and level should be logged.
+	pub fn is_enabled(&self, target: &str, level: LogLevel) -> bool {
+		self.filter.read().enabled(target, level)
+	}
+
+	/// Changes levels with directives in the `RUST_LOG` format, e.g. `sync=trace,network=debug`.
+	pub fn set_levels(&self, directives: &str) -> Result<(), String> {
+		let mut filter = self.filter.write();
+		let mut updated = filter.clone();
+		try!(updated.parse(directives));
+		*filter = updated;
+
+		if let Some(ref handle) = *self.max_level.lock() {
+			handle.set(filter.max_level());
+		}
+		Ok(())
+	}
+
+	/// Copies entries of given target to a file at `path` for given `duration`.
+	/// At most `MAX_TEES` tees can be active, each lasting up to `MAX_TEE_DURATION` seconds.
+	pub fn tee(&self, target: &str, path: &Path, duration: Duration) -> Result<(), String> {
+		if duration > Duration::from_secs(MAX_TEE_DURATION) {
+			return Err(format!("Tee duration cannot exceed {} seconds", MAX_TEE_DURATION));
+		}
+
+		let mut tees = self.tees.lock();
+		let now = Instant::now();
+		tees.retain(|tee| tee.until > now);
+		if tees.len() >= MAX_TEES {
+			return Err(format!("Too many active tees (at most {})", MAX_TEES));
+		}
+
+		let file = try!(OpenOptions::new().create(true).append(true).open(path)
+			.map_err(|e| format!("Cannot open log file {}: {}", path.display(), e)));
+		tees.push(Tee {
+			target: target.to_owned(),
+			file: file,
+			until: now + duration,
+		});
+		Ok(())
+	}
+
+	/// Writes entry to files of active tees of given target.
+	pub fn write_tees(&self, target: &str, log: &str) {
+		let mut tees = self.tees.lock();
+		if tees.is_empty() {
+			return;
+		}
+		let now = Instant::now();
+		tees.retain(|tee| tee.until > now);
+		for tee in tees.iter_mut().filter(|tee| target.starts_with(tee.target.as_str())) {
+			// ignore errors - there's nothing we can do
+			let _ = tee.file.write_all(log.as_bytes());
+			let _ = tee.file.write_all(b"\n");
+		}
 	}
 
 	/// Return logs
@@ -85,7 +261,12 @@ impl RotatingLogger {
 
 #[cfg(test)]
 mod test {
-	use super::RotatingLogger;
+	use std::fs::File;
+	use std::io::Read;
+	use std::time::Duration;
+	use rlog::{LogLevel, LogLevelFilter};
+	use devtools::RandomTempPath;
+	use super::{RotatingLogger, LevelFilter, MAX_TEES, MAX_TEE_DURATION};
 
 	fn logger() -> RotatingLogger {
 		RotatingLogger::new("test".to_owned())
@@ -100,7 +281,7 @@ mod test {
 		let levels = logger.levels();
 
 		// then
-		assert_eq!(levels, "test");
+		assert_eq!(levels, "info,test=trace");
 	}
 
 	#[test]
@@ -118,5 +299,90 @@ mod test {
 		assert_eq!(logs[1], "a".to_owned());
 		assert_eq!(logs.len(), 2);
 	}
-}
 
+	#[test]
+	fn should_match_most_specific_target() {
+		// given
+		let mut filter = LevelFilter::new(LogLevelFilter::Info);
+
+		// when
+		filter.parse("sync=trace,sync::blocks=warn,network").unwrap();
+
+		// then
+		assert!(filter.enabled("sync", LogLevel::Trace));
+		assert!(!filter.enabled("sync::blocks", LogLevel::Info));
+		assert!(filter.enabled("network", LogLevel::Trace));
+		assert!(!filter.enabled("miner", LogLevel::Debug));
+		assert_eq!(filter.max_level(), LogLevelFilter::Trace);
+		assert!(filter.parse("sync=loud").is_err());
+	}
+
+	#[test]
+	fn should_change_levels_at_runtime() {
+		// given
+		let logger = logger();
+		assert!(!logger.is_enabled("sync", LogLevel::Debug));
+
+		// when
+		logger.set_levels("sync=debug").unwrap();
+
+		// then
+		assert!(logger.is_enabled("sync", LogLevel::Debug));
+		assert!(!logger.is_enabled("sync", LogLevel::Trace));
+		assert_eq!(logger.levels(), "info,sync=debug,test=trace");
+	}
+
+	#[test]
+	fn should_not_repeat_changed_levels() {
+		// given
+		let logger = logger();
+
+		// when
+		logger.set_levels("sync=debug").unwrap();
+		logger.set_levels("sync=trace,warn").unwrap();
+
+		// then
+		assert_eq!(logger.levels(), "warn,sync=trace,test=trace");
+	}
+
+	#[test]
+	fn should_limit_tees() {
+		// given
+		let logger = logger();
+		let dir = RandomTempPath::create_dir();
+		let path = dir.as_path().join("sync.log");
+
+		// when
+		let too_long = logger.tee("sync", &path, Duration::from_secs(MAX_TEE_DURATION + 1));
+		let max = Duration::from_secs(::std::u64::MAX);
+		let overflow = logger.tee("sync", &path, max);
+		for _ in 0..MAX_TEES {
+			logger.tee("sync", &path, Duration::from_secs(60)).unwrap();
+		}
+		let too_many = logger.tee("sync", &path, Duration::from_secs(60));
+
+		// then
+		assert!(too_long.is_err());
+		assert!(overflow.is_err());
+		assert!(too_many.is_err());
+	}
+
+	#[test]
+	fn should_write_tees_of_target() {
+		// given
+		let logger = logger();
+		let dir = RandomTempPath::create_dir();
+		let path = dir.as_path().join("sync.log");
+		logger.tee("sync", &path, Duration::from_secs(60)).unwrap();
+
+		// when
+		logger.write_tees("sync::blocks", "imported");
+		logger.write_tees("network", "connected");
+		logger.write_tees("sync", "idle");
+
+		// then
+		let mut content = String::new();
+		File::open(&path).unwrap().read_to_string(&mut content).unwrap();
+		assert_eq!(content, "imported\nidle\n");
+	}
+}
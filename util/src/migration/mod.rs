@@ -21,6 +21,7 @@ mod tests;
 use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use ::kvdb::{CompactionProfile, Database, DatabaseConfig, DBTransaction};
 
@@ -60,7 +61,7 @@ impl Batch {
 	}
 
 	/// Insert a value into the batch, committing if necessary.
-	pub fn insert(&mut self, key: Vec<u8>, value: Vec<u8>, dest: &mut Database) -> Result<(), Error> {
+	pub fn insert(&mut self, key: Vec<u8>, value: Vec<u8>, dest: &Database) -> Result<(), Error> {
 		self.inner.insert(key, value);
 		if self.inner.len() == self.batch_size {
 			try!(self.commit(dest));
@@ -69,7 +70,7 @@ impl Batch {
 	}
 
 	/// Commit all the items in the batch to the given database.
-	pub fn commit(&mut self, dest: &mut Database) -> Result<(), Error> {
+	pub fn commit(&mut self, dest: &Database) -> Result<(), Error> {
 		if self.inner.is_empty() { return Ok(()) }
 
 		let transaction = DBTransaction::new(dest);
@@ -109,7 +110,7 @@ pub trait Migration: 'static {
 	/// Version of the database after the migration.
 	fn version(&self) -> u32;
 	/// Migrate a source to a destination.
-	fn migrate(&mut self, source: &Database, config: &Config, destination: &mut Database, col: Option<u32>) -> Result<(), Error>;
+	fn migrate(&mut self, source: Arc<Database>, config: &Config, destination: Arc<Database>, col: Option<u32>) -> Result<(), Error>;
 }
 
 /// A simple migration over key-value pairs.
@@ -128,16 +129,16 @@ impl<T: SimpleMigration> Migration for T {
 
 	fn version(&self) -> u32 { SimpleMigration::version(self) }
 
-	fn migrate(&mut self, source: &Database, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+	fn migrate(&mut self, source: Arc<Database>, config: &Config, dest: Arc<Database>, col: Option<u32>) -> Result<(), Error> {
 		let mut batch = Batch::new(config, col);
 
 		for (key, value) in source.iter(col) {
 			if let Some((key, value)) = self.simple_migrate(key.to_vec(), value.to_vec()) {
-				try!(batch.insert(key, value, dest));
+				try!(batch.insert(key, value, &dest));
 			}
 		}
 
-		batch.commit(dest)
+		batch.commit(&dest)
 	}
 }
 
@@ -222,7 +223,7 @@ impl Manager {
 
 		// start with the old db.
 		let old_path_str = try!(old_path.to_str().ok_or(Error::MigrationImpossible));
-		let mut cur_db = try!(Database::open(&db_config, old_path_str).map_err(Error::Custom));
+		let mut cur_db = Arc::new(try!(Database::open(&db_config, old_path_str).map_err(Error::Custom)));
 
 		for migration in migrations {
 			// Change number of columns in new db
@@ -232,19 +233,10 @@ impl Manager {
 			// open the target temporary database.
 			temp_path = temp_idx.path(&db_root);
 			let temp_path_str = try!(temp_path.to_str().ok_or(Error::MigrationImpossible));
-			let mut new_db = try!(Database::open(&db_config, temp_path_str).map_err(Error::Custom));
+			let new_db = Arc::new(try!(Database::open(&db_config, temp_path_str).map_err(Error::Custom)));
 
 			// perform the migration from cur_db to new_db.
-			match current_columns {
-				// migrate only default column
-				None => try!(migration.migrate(&cur_db, &config, &mut new_db, None)),
-				Some(v) => {
-					// Migrate all columns in previous DB
-					for col in 0..v {
-						try!(migration.migrate(&cur_db, &config, &mut new_db, Some(col)))
-					}
-				}
-			}
+			try!(migrate_columns(migration, &cur_db, &config, &new_db, current_columns));
 			// next iteration, we will migrate from this db into the other temp.
 			cur_db = new_db;
 			temp_idx.swap();
@@ -255,6 +247,30 @@ impl Manager {
 		Ok(temp_path)
 	}
 
+	/// Performs the only registered migration from the database at `old_path` having `columns`
+	/// into the database at `new_path`, regardless of versions.
+	/// The destination is not removed beforehand, so that a migration can resume an interrupted run.
+	pub fn execute_into(&mut self, old_path: &Path, columns: Option<u32>, new_path: &Path) -> Result<(), Error> {
+		if self.migrations.len() != 1 { return Err(Error::MigrationImpossible) };
+		let config = self.config.clone();
+		let migration = &mut self.migrations[0];
+		let mut db_config = DatabaseConfig {
+			max_open_files: 64,
+			cache_size: None,
+			compaction: config.compaction_profile,
+			columns: columns,
+			wal: true,
+		};
+
+		let old_path_str = try!(old_path.to_str().ok_or(Error::MigrationImpossible));
+		let new_path_str = try!(new_path.to_str().ok_or(Error::MigrationImpossible));
+		let old_db = Arc::new(try!(Database::open(&db_config, old_path_str).map_err(Error::Custom)));
+		db_config.columns = migration.columns();
+		let new_db = Arc::new(try!(Database::open(&db_config, new_path_str).map_err(Error::Custom)));
+
+		migrate_columns(migration, &old_db, &config, &new_db, columns)
+	}
+
 	/// Returns true if migration is needed.
 	pub fn is_needed(&self, version: u32) -> bool {
 		match self.migrations.last() {
@@ -277,6 +293,21 @@ impl Manager {
 	}
 }
 
+// migrate all columns of the source database.
+fn migrate_columns(migration: &mut Box<Migration>, source: &Arc<Database>, config: &Config, dest: &Arc<Database>, columns: Option<u32>) -> Result<(), Error> {
+	match columns {
+		// migrate only default column
+		None => migration.migrate(source.clone(), config, dest.clone(), None),
+		Some(v) => {
+			// Migrate all columns in previous DB
+			for col in 0..v {
+				try!(migration.migrate(source.clone(), config, dest.clone(), Some(col)))
+			}
+			Ok(())
+		}
+	}
+}
+
 /// Prints a dot every `max` ticks
 pub struct Progress {
 	current: usize,
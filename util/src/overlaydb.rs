@@ -25,7 +25,7 @@ use memorydb::*;
 use std::ops::*;
 use std::sync::*;
 use std::collections::HashMap;
-use kvdb::{Database, DBTransaction};
+use kvdb::{DBTransaction, KeyValueDB};
 
 /// Implementation of the `HashDB` trait for a disk-backed database with a memory overlay.
 ///
@@ -38,22 +38,20 @@ use kvdb::{Database, DBTransaction};
 #[derive(Clone)]
 pub struct OverlayDB {
 	overlay: MemoryDB,
-	backing: Arc<Database>,
+	backing: Arc<KeyValueDB>,
 	column: Option<u32>,
 }
 
 impl OverlayDB {
 	/// Create a new instance of OverlayDB given a `backing` database.
-	pub fn new(backing: Arc<Database>, col: Option<u32>) -> OverlayDB {
+	pub fn new(backing: Arc<KeyValueDB>, col: Option<u32>) -> OverlayDB {
 		OverlayDB{ overlay: MemoryDB::new(), backing: backing, column: col }
 	}
 
-	/// Create a new instance of OverlayDB with an anonymous temporary database.
+	/// Create a new instance of OverlayDB with an in-memory database.
 	#[cfg(test)]
 	pub fn new_temp() -> OverlayDB {
-		let mut dir = ::std::env::temp_dir();
-		dir.push(H32::random().hex());
-		Self::new(Arc::new(Database::open_default(dir.to_str().unwrap()).unwrap()), None)
+		Self::new(Arc::new(::kvdb::in_memory(None)), None)
 	}
 
 	/// Commit all operations in a single batch.
@@ -299,6 +297,7 @@ fn overlaydb_complex() {
 #[test]
 fn playpen() {
 	use std::fs;
+	use kvdb::Database;
 	{
 		let db = Database::open_default("/tmp/test").unwrap();
 		let batch = db.transaction();